@@ -1,5 +1,7 @@
 export * from './FrequencyAccount';
+export * from './RootRegistryAccount';
 
 import { FrequencyAccount } from './FrequencyAccount';
+import { RootRegistryAccount } from './RootRegistryAccount';
 
-export const accountProviders = { FrequencyAccount };
+export const accountProviders = { FrequencyAccount, RootRegistryAccount };
@@ -856,6 +856,699 @@ export class DuplicatedOperationNameError extends Error {
 createErrorFromCodeLookup.set(0x27, () => new DuplicatedOperationNameError());
 createErrorFromNameLookup.set('DuplicatedOperationName', () => new DuplicatedOperationNameError());
 
+/**
+ * AlignmentError: 'Could not determine alignemnt'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class AlignmentErrorError extends Error {
+  readonly code: number = 0x28;
+  readonly name: string = 'AlignmentError';
+  constructor() {
+    super('Could not determine alignemnt');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, AlignmentErrorError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x28, () => new AlignmentErrorError());
+createErrorFromNameLookup.set('AlignmentError', () => new AlignmentErrorError());
+
+/**
+ * RuleDefinitionNotFound: 'Rule definition not found'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class RuleDefinitionNotFoundError extends Error {
+  readonly code: number = 0x29;
+  readonly name: string = 'RuleDefinitionNotFound';
+  constructor() {
+    super('Rule definition not found');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, RuleDefinitionNotFoundError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x29, () => new RuleDefinitionNotFoundError());
+createErrorFromNameLookup.set('RuleDefinitionNotFound', () => new RuleDefinitionNotFoundError());
+
+/**
+ * RuleDefinitionCycle: 'Rule definitions reference each other in a cycle'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class RuleDefinitionCycleError extends Error {
+  readonly code: number = 0x2a;
+  readonly name: string = 'RuleDefinitionCycle';
+  constructor() {
+    super('Rule definitions reference each other in a cycle');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, RuleDefinitionCycleError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x2a, () => new RuleDefinitionCycleError());
+createErrorFromNameLookup.set('RuleDefinitionCycle', () => new RuleDefinitionCycleError());
+
+/**
+ * DuplicatedDefinitionName: 'Duplicated rule definition name'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class DuplicatedDefinitionNameError extends Error {
+  readonly code: number = 0x2b;
+  readonly name: string = 'DuplicatedDefinitionName';
+  constructor() {
+    super('Duplicated rule definition name');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, DuplicatedDefinitionNameError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x2b, () => new DuplicatedDefinitionNameError());
+createErrorFromNameLookup.set(
+  'DuplicatedDefinitionName',
+  () => new DuplicatedDefinitionNameError(),
+);
+
+/**
+ * RuleSetDepthExceeded: 'Too many nested RuleSets'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class RuleSetDepthExceededError extends Error {
+  readonly code: number = 0x2c;
+  readonly name: string = 'RuleSetDepthExceeded';
+  constructor() {
+    super('Too many nested RuleSets');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, RuleSetDepthExceededError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x2c, () => new RuleSetDepthExceededError());
+createErrorFromNameLookup.set('RuleSetDepthExceeded', () => new RuleSetDepthExceededError());
+
+/**
+ * BytesMatchCheckFailed: 'Failed to match bytes'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class BytesMatchCheckFailedError extends Error {
+  readonly code: number = 0x2d;
+  readonly name: string = 'BytesMatchCheckFailed';
+  constructor() {
+    super('Failed to match bytes');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, BytesMatchCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x2d, () => new BytesMatchCheckFailedError());
+createErrorFromNameLookup.set('BytesMatchCheckFailed', () => new BytesMatchCheckFailedError());
+
+/**
+ * StrMatchCheckFailed: 'Failed to match string'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class StrMatchCheckFailedError extends Error {
+  readonly code: number = 0x2e;
+  readonly name: string = 'StrMatchCheckFailed';
+  constructor() {
+    super('Failed to match string');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, StrMatchCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x2e, () => new StrMatchCheckFailedError());
+createErrorFromNameLookup.set('StrMatchCheckFailed', () => new StrMatchCheckFailedError());
+
+/**
+ * BoolMatchCheckFailed: 'Failed to match boolean flag'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class BoolMatchCheckFailedError extends Error {
+  readonly code: number = 0x2f;
+  readonly name: string = 'BoolMatchCheckFailed';
+  constructor() {
+    super('Failed to match boolean flag');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, BoolMatchCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x2f, () => new BoolMatchCheckFailedError());
+createErrorFromNameLookup.set('BoolMatchCheckFailed', () => new BoolMatchCheckFailedError());
+
+/**
+ * PubkeyListContainsCheckFailed: 'Pubkey not found in list'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class PubkeyListContainsCheckFailedError extends Error {
+  readonly code: number = 0x30;
+  readonly name: string = 'PubkeyListContainsCheckFailed';
+  constructor() {
+    super('Pubkey not found in list');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, PubkeyListContainsCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x30, () => new PubkeyListContainsCheckFailedError());
+createErrorFromNameLookup.set(
+  'PubkeyListContainsCheckFailed',
+  () => new PubkeyListContainsCheckFailedError(),
+);
+
+/**
+ * InvalidMatchOp: 'Invalid match operator'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class InvalidMatchOpError extends Error {
+  readonly code: number = 0x31;
+  readonly name: string = 'InvalidMatchOp';
+  constructor() {
+    super('Invalid match operator');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, InvalidMatchOpError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x31, () => new InvalidMatchOpError());
+createErrorFromNameLookup.set('InvalidMatchOp', () => new InvalidMatchOpError());
+
+/**
+ * FieldTypeMismatch: 'Payload fields have different types'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class FieldTypeMismatchError extends Error {
+  readonly code: number = 0x32;
+  readonly name: string = 'FieldTypeMismatch';
+  constructor() {
+    super('Payload fields have different types');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, FieldTypeMismatchError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x32, () => new FieldTypeMismatchError());
+createErrorFromNameLookup.set('FieldTypeMismatch', () => new FieldTypeMismatchError());
+
+/**
+ * FieldCompareCheckFailed: 'Failed to compare payload fields'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class FieldCompareCheckFailedError extends Error {
+  readonly code: number = 0x33;
+  readonly name: string = 'FieldCompareCheckFailed';
+  constructor() {
+    super('Failed to compare payload fields');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, FieldCompareCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x33, () => new FieldCompareCheckFailedError());
+createErrorFromNameLookup.set('FieldCompareCheckFailed', () => new FieldCompareCheckFailedError());
+
+/**
+ * AmountRangeCheckFailed: 'Amount is out of range'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class AmountRangeCheckFailedError extends Error {
+  readonly code: number = 0x34;
+  readonly name: string = 'AmountRangeCheckFailed';
+  constructor() {
+    super('Amount is out of range');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, AmountRangeCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x34, () => new AmountRangeCheckFailedError());
+createErrorFromNameLookup.set('AmountRangeCheckFailed', () => new AmountRangeCheckFailedError());
+
+/**
+ * AmountRatioCheckFailed: 'Failed to check amount ratio'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class AmountRatioCheckFailedError extends Error {
+  readonly code: number = 0x35;
+  readonly name: string = 'AmountRatioCheckFailed';
+  constructor() {
+    super('Failed to check amount ratio');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, AmountRatioCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x35, () => new AmountRatioCheckFailedError());
+createErrorFromNameLookup.set('AmountRatioCheckFailed', () => new AmountRatioCheckFailedError());
+
+/**
+ * PayloadKeyTooLong: 'Payload key is too long'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class PayloadKeyTooLongError extends Error {
+  readonly code: number = 0x36;
+  readonly name: string = 'PayloadKeyTooLong';
+  constructor() {
+    super('Payload key is too long');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, PayloadKeyTooLongError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x36, () => new PayloadKeyTooLongError());
+createErrorFromNameLookup.set('PayloadKeyTooLong', () => new PayloadKeyTooLongError());
+
+/**
+ * PayloadTooLarge: 'Payload exceeds the maximum size'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class PayloadTooLargeError extends Error {
+  readonly code: number = 0x37;
+  readonly name: string = 'PayloadTooLarge';
+  constructor() {
+    super('Payload exceeds the maximum size');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, PayloadTooLargeError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x37, () => new PayloadTooLargeError());
+createErrorFromNameLookup.set('PayloadTooLarge', () => new PayloadTooLargeError());
+
+/**
+ * PubkeyTreeExclusionCheckFailed: 'Failed to prove that the Pubkey is excluded from the tree'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class PubkeyTreeExclusionCheckFailedError extends Error {
+  readonly code: number = 0x38;
+  readonly name: string = 'PubkeyTreeExclusionCheckFailed';
+  constructor() {
+    super('Failed to prove that the Pubkey is excluded from the tree');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, PubkeyTreeExclusionCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x38, () => new PubkeyTreeExclusionCheckFailedError());
+createErrorFromNameLookup.set(
+  'PubkeyTreeExclusionCheckFailed',
+  () => new PubkeyTreeExclusionCheckFailedError(),
+);
+
+/**
+ * RegistryAuthorityIsNotSigner: 'Registry authority is not signer'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class RegistryAuthorityIsNotSignerError extends Error {
+  readonly code: number = 0x39;
+  readonly name: string = 'RegistryAuthorityIsNotSigner';
+  constructor() {
+    super('Registry authority is not signer');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, RegistryAuthorityIsNotSignerError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x39, () => new RegistryAuthorityIsNotSignerError());
+createErrorFromNameLookup.set(
+  'RegistryAuthorityIsNotSigner',
+  () => new RegistryAuthorityIsNotSignerError(),
+);
+
+/**
+ * RegistryAuthorityMismatch: 'Registry authority does not match the authority stored in the account'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class RegistryAuthorityMismatchError extends Error {
+  readonly code: number = 0x3a;
+  readonly name: string = 'RegistryAuthorityMismatch';
+  constructor() {
+    super('Registry authority does not match the authority stored in the account');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, RegistryAuthorityMismatchError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x3a, () => new RegistryAuthorityMismatchError());
+createErrorFromNameLookup.set(
+  'RegistryAuthorityMismatch',
+  () => new RegistryAuthorityMismatchError(),
+);
+
+/**
+ * PubkeyListAuthorityIsNotSigner: 'Pubkey List authority is not signer'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class PubkeyListAuthorityIsNotSignerError extends Error {
+  readonly code: number = 0x3b;
+  readonly name: string = 'PubkeyListAuthorityIsNotSigner';
+  constructor() {
+    super('Pubkey List authority is not signer');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, PubkeyListAuthorityIsNotSignerError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x3b, () => new PubkeyListAuthorityIsNotSignerError());
+createErrorFromNameLookup.set(
+  'PubkeyListAuthorityIsNotSigner',
+  () => new PubkeyListAuthorityIsNotSignerError(),
+);
+
+/**
+ * PubkeyListAuthorityMismatch: 'Pubkey List authority does not match the authority stored in the account'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class PubkeyListAuthorityMismatchError extends Error {
+  readonly code: number = 0x3c;
+  readonly name: string = 'PubkeyListAuthorityMismatch';
+  constructor() {
+    super('Pubkey List authority does not match the authority stored in the account');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, PubkeyListAuthorityMismatchError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x3c, () => new PubkeyListAuthorityMismatchError());
+createErrorFromNameLookup.set(
+  'PubkeyListAuthorityMismatch',
+  () => new PubkeyListAuthorityMismatchError(),
+);
+
+/**
+ * PubkeyInListAccountCheckFailed: 'Pubkey not found in the Pubkey List account'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class PubkeyInListAccountCheckFailedError extends Error {
+  readonly code: number = 0x3d;
+  readonly name: string = 'PubkeyInListAccountCheckFailed';
+  constructor() {
+    super('Pubkey not found in the Pubkey List account');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, PubkeyInListAccountCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x3d, () => new PubkeyInListAccountCheckFailedError());
+createErrorFromNameLookup.set(
+  'PubkeyInListAccountCheckFailed',
+  () => new PubkeyInListAccountCheckFailedError(),
+);
+
+/**
+ * OwnerInListAccountCheckFailed: 'Owner not found in the Pubkey List account'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class OwnerInListAccountCheckFailedError extends Error {
+  readonly code: number = 0x3e;
+  readonly name: string = 'OwnerInListAccountCheckFailed';
+  constructor() {
+    super('Owner not found in the Pubkey List account');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, OwnerInListAccountCheckFailedError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x3e, () => new OwnerInListAccountCheckFailedError());
+createErrorFromNameLookup.set(
+  'OwnerInListAccountCheckFailed',
+  () => new OwnerInListAccountCheckFailedError(),
+);
+
+/**
+ * UnsortedPubkeyList: 'Pubkey list is not sorted or contains duplicates'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class UnsortedPubkeyListError extends Error {
+  readonly code: number = 0x3f;
+  readonly name: string = 'UnsortedPubkeyList';
+  constructor() {
+    super('Pubkey list is not sorted or contains duplicates');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, UnsortedPubkeyListError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x3f, () => new UnsortedPubkeyListError());
+createErrorFromNameLookup.set('UnsortedPubkeyList', () => new UnsortedPubkeyListError());
+
+/**
+ * RuleSetHashMismatch: 'RuleSet revision hash does not match the expected hash'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class RuleSetHashMismatchError extends Error {
+  readonly code: number = 0x40;
+  readonly name: string = 'RuleSetHashMismatch';
+  constructor() {
+    super('RuleSet revision hash does not match the expected hash');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, RuleSetHashMismatchError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x40, () => new RuleSetHashMismatchError());
+createErrorFromNameLookup.set('RuleSetHashMismatch', () => new RuleSetHashMismatchError());
+
+/**
+ * InvalidBufferWrite: 'Write to the buffer is not aligned to its blocks or exceeds its length'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class InvalidBufferWriteError extends Error {
+  readonly code: number = 0x41;
+  readonly name: string = 'InvalidBufferWrite';
+  constructor() {
+    super('Write to the buffer is not aligned to its blocks or exceeds its length');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, InvalidBufferWriteError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x41, () => new InvalidBufferWriteError());
+createErrorFromNameLookup.set('InvalidBufferWrite', () => new InvalidBufferWriteError());
+
+/**
+ * BufferTooSmall: 'Buffer has not grown to the end of the chunk yet'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class BufferTooSmallError extends Error {
+  readonly code: number = 0x42;
+  readonly name: string = 'BufferTooSmall';
+  constructor() {
+    super('Buffer has not grown to the end of the chunk yet');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, BufferTooSmallError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x42, () => new BufferTooSmallError());
+createErrorFromNameLookup.set('BufferTooSmall', () => new BufferTooSmallError());
+
+/**
+ * BufferIncomplete: 'Buffer is missing chunks of the RuleSet'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class BufferIncompleteError extends Error {
+  readonly code: number = 0x43;
+  readonly name: string = 'BufferIncomplete';
+  constructor() {
+    super('Buffer is missing chunks of the RuleSet');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, BufferIncompleteError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x43, () => new BufferIncompleteError());
+createErrorFromNameLookup.set('BufferIncomplete', () => new BufferIncompleteError());
+
+/**
+ * BufferHashMismatch: 'RuleSet in the buffer does not match its hash'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class BufferHashMismatchError extends Error {
+  readonly code: number = 0x44;
+  readonly name: string = 'BufferHashMismatch';
+  constructor() {
+    super('RuleSet in the buffer does not match its hash');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, BufferHashMismatchError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x44, () => new BufferHashMismatchError());
+createErrorFromNameLookup.set('BufferHashMismatch', () => new BufferHashMismatchError());
+
+/**
+ * TransferHookAuthorityMismatch: 'Authority is not the transfer hook authority of the mint'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class TransferHookAuthorityMismatchError extends Error {
+  readonly code: number = 0x45;
+  readonly name: string = 'TransferHookAuthorityMismatch';
+  constructor() {
+    super('Authority is not the transfer hook authority of the mint');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, TransferHookAuthorityMismatchError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x45, () => new TransferHookAuthorityMismatchError());
+createErrorFromNameLookup.set(
+  'TransferHookAuthorityMismatch',
+  () => new TransferHookAuthorityMismatchError(),
+);
+
+/**
+ * TransferHookAccountMismatch: 'Accounts do not match the extra account metas of the mint'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class TransferHookAccountMismatchError extends Error {
+  readonly code: number = 0x46;
+  readonly name: string = 'TransferHookAccountMismatch';
+  constructor() {
+    super('Accounts do not match the extra account metas of the mint');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, TransferHookAccountMismatchError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x46, () => new TransferHookAccountMismatchError());
+createErrorFromNameLookup.set(
+  'TransferHookAccountMismatch',
+  () => new TransferHookAccountMismatchError(),
+);
+
+/**
+ * InvalidTokenAccount: 'Token account is invalid for the mint'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class InvalidTokenAccountError extends Error {
+  readonly code: number = 0x47;
+  readonly name: string = 'InvalidTokenAccount';
+  constructor() {
+    super('Token account is invalid for the mint');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, InvalidTokenAccountError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x47, () => new InvalidTokenAccountError());
+createErrorFromNameLookup.set('InvalidTokenAccount', () => new InvalidTokenAccountError());
+
+/**
+ * InvalidThreshold: 'Threshold is larger than the number of rules'
+ *
+ * @category Errors
+ * @category generated
+ */
+export class InvalidThresholdError extends Error {
+  readonly code: number = 0x48;
+  readonly name: string = 'InvalidThreshold';
+  constructor() {
+    super('Threshold is larger than the number of rules');
+    if (typeof Error.captureStackTrace === 'function') {
+      Error.captureStackTrace(this, InvalidThresholdError);
+    }
+  }
+}
+
+createErrorFromCodeLookup.set(0x48, () => new InvalidThresholdError());
+createErrorFromNameLookup.set('InvalidThreshold', () => new InvalidThresholdError());
+
 /**
  * Attempts to resolve a custom program error from the provided error code.
  * @category Errors
@@ -1,4 +1,8 @@
+export * from './AddToPubkeyList';
 export * from './CreateOrUpdate';
+export * from './CreateOrUpdateRootRegistry';
+export * from './CreateOrUpdateTransferHook';
 export * from './PuffRuleSet';
+export * from './RemoveFromPubkeyList';
 export * from './Validate';
 export * from './WriteToBuffer';
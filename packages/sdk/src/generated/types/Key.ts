@@ -14,6 +14,9 @@ export enum Key {
   Uninitialized,
   RuleSet,
   Frequency,
+  RootRegistry,
+  PubkeyList,
+  Buffer,
 }
 
 /**
@@ -10,6 +10,7 @@ import * as beet from '@miraplex/beet';
 import * as beetMiraland from '@miraplex/beet-miraland';
 import { SeedsVec, seedsVecBeet } from './SeedsVec';
 import { ProofInfo, proofInfoBeet } from './ProofInfo';
+import { ExclusionProofInfo, exclusionProofInfoBeet } from './ExclusionProofInfo';
 /**
  * This type is used to derive the {@link PayloadType} type as well as the de/serializer.
  * However don't refer to it in your code but use the {@link PayloadType} type instead.
@@ -24,6 +25,13 @@ export type PayloadTypeRecord = {
   Seeds: { fields: [SeedsVec] };
   MerkleProof: { fields: [ProofInfo] };
   Number: { fields: [beet.bignum] };
+  Bytes: { fields: [Uint8Array] };
+  Str: { fields: [string] };
+  Bool: { fields: [boolean] };
+  I64: { fields: [beet.bignum] };
+  U128: { fields: [beet.bignum] };
+  PubkeyList: { fields: [web3.PublicKey[]] };
+  ExclusionProof: { fields: [ExclusionProofInfo] };
 };
 
 /**
@@ -48,6 +56,22 @@ export const isPayloadTypeMerkleProof = (
 ): x is PayloadType & { __kind: 'MerkleProof' } => x.__kind === 'MerkleProof';
 export const isPayloadTypeNumber = (x: PayloadType): x is PayloadType & { __kind: 'Number' } =>
   x.__kind === 'Number';
+export const isPayloadTypeBytes = (x: PayloadType): x is PayloadType & { __kind: 'Bytes' } =>
+  x.__kind === 'Bytes';
+export const isPayloadTypeStr = (x: PayloadType): x is PayloadType & { __kind: 'Str' } =>
+  x.__kind === 'Str';
+export const isPayloadTypeBool = (x: PayloadType): x is PayloadType & { __kind: 'Bool' } =>
+  x.__kind === 'Bool';
+export const isPayloadTypeI64 = (x: PayloadType): x is PayloadType & { __kind: 'I64' } =>
+  x.__kind === 'I64';
+export const isPayloadTypeU128 = (x: PayloadType): x is PayloadType & { __kind: 'U128' } =>
+  x.__kind === 'U128';
+export const isPayloadTypePubkeyList = (
+  x: PayloadType,
+): x is PayloadType & { __kind: 'PubkeyList' } => x.__kind === 'PubkeyList';
+export const isPayloadTypeExclusionProof = (
+  x: PayloadType,
+): x is PayloadType & { __kind: 'ExclusionProof' } => x.__kind === 'ExclusionProof';
 
 /**
  * @category userTypes
@@ -82,4 +106,53 @@ export const payloadTypeBeet = beet.dataEnum<PayloadTypeRecord>([
       'PayloadTypeRecord["Number"]',
     ),
   ],
+  [
+    'Bytes',
+    new beet.FixableBeetArgsStruct<PayloadTypeRecord['Bytes']>(
+      [['fields', beet.tuple([beet.bytes])]],
+      'PayloadTypeRecord["Bytes"]',
+    ),
+  ],
+  [
+    'Str',
+    new beet.FixableBeetArgsStruct<PayloadTypeRecord['Str']>(
+      [['fields', beet.tuple([beet.utf8String])]],
+      'PayloadTypeRecord["Str"]',
+    ),
+  ],
+  [
+    'Bool',
+    new beet.BeetArgsStruct<PayloadTypeRecord['Bool']>(
+      [['fields', beet.fixedSizeTuple([beet.bool])]],
+      'PayloadTypeRecord["Bool"]',
+    ),
+  ],
+  [
+    'I64',
+    new beet.BeetArgsStruct<PayloadTypeRecord['I64']>(
+      [['fields', beet.fixedSizeTuple([beet.i64])]],
+      'PayloadTypeRecord["I64"]',
+    ),
+  ],
+  [
+    'U128',
+    new beet.BeetArgsStruct<PayloadTypeRecord['U128']>(
+      [['fields', beet.fixedSizeTuple([beet.u128])]],
+      'PayloadTypeRecord["U128"]',
+    ),
+  ],
+  [
+    'PubkeyList',
+    new beet.FixableBeetArgsStruct<PayloadTypeRecord['PubkeyList']>(
+      [['fields', beet.tuple([beet.array(beetMiraland.publicKey)])]],
+      'PayloadTypeRecord["PubkeyList"]',
+    ),
+  ],
+  [
+    'ExclusionProof',
+    new beet.FixableBeetArgsStruct<PayloadTypeRecord['ExclusionProof']>(
+      [['fields', beet.tuple([exclusionProofInfoBeet])]],
+      'PayloadTypeRecord["ExclusionProof"]',
+    ),
+  ],
 ]) as beet.FixableBeet<PayloadType, PayloadType>;
@@ -17,6 +17,12 @@ import * as beet from '@miraplex/beet';
  */
 export type WriteToBufferArgsRecord = {
   V1: { serializedRuleSet: Uint8Array; overwrite: boolean };
+  V2: {
+    offset: number;
+    data: Uint8Array;
+    totalLen: number;
+    hash: beet.COption<number[] /* size: 32 */>;
+  };
 };
 
 /**
@@ -35,6 +41,9 @@ export type WriteToBufferArgs = beet.DataEnumKeyAsKind<WriteToBufferArgsRecord>;
 export const isWriteToBufferArgsV1 = (
   x: WriteToBufferArgs,
 ): x is WriteToBufferArgs & { __kind: 'V1' } => x.__kind === 'V1';
+export const isWriteToBufferArgsV2 = (
+  x: WriteToBufferArgs,
+): x is WriteToBufferArgs & { __kind: 'V2' } => x.__kind === 'V2';
 
 /**
  * @category userTypes
@@ -51,4 +60,16 @@ export const writeToBufferArgsBeet = beet.dataEnum<WriteToBufferArgsRecord>([
       'WriteToBufferArgsRecord["V1"]',
     ),
   ],
+  [
+    'V2',
+    new beet.FixableBeetArgsStruct<WriteToBufferArgsRecord['V2']>(
+      [
+        ['offset', beet.u32],
+        ['data', beet.bytes],
+        ['totalLen', beet.u32],
+        ['hash', beet.coption(beet.uniformFixedSizeArray(beet.u8, 32))],
+      ],
+      'WriteToBufferArgsRecord["V2"]',
+    ),
+  ],
 ]) as beet.FixableBeet<WriteToBufferArgs, WriteToBufferArgs>;
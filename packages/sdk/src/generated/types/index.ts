@@ -1,11 +1,16 @@
 export * from './CreateOrUpdateArgs';
+export * from './CreateOrUpdateRootRegistryArgs';
+export * from './CreateOrUpdateTransferHookArgs';
+export * from './ExclusionProofInfo';
 export * from './Key';
+export * from './ModifyPubkeyListArgs';
 export * from './Payload';
 export * from './PayloadType';
 export * from './ProofInfo';
 export * from './PuffRuleSetArgs';
 export * from './RuleSetHeader';
 export * from './RuleSetRevisionMapV1';
+export * from './RuleSetRevisionMapV2';
 export * from './SeedsVec';
 export * from './ValidateArgs';
 export * from './WriteToBufferArgs';
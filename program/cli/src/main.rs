@@ -0,0 +1,382 @@
+//! Command line interface to create, inspect and simulate Token Authorization `RuleSet`s.
+//!
+//! `RuleSet`s are compiled from the JSON layout used by the TypeScript CLI or from the policy
+//! language, uploaded through the buffer PDA when they do not fit in a transaction, and decoded
+//! back from their PDA.  Transactions can be printed unsigned for offline signing, and the rule
+//! of an operation can be evaluated locally on accounts fetched from the cluster.
+mod revisions;
+mod simulate;
+mod source;
+mod submit;
+
+use std::{error::Error, fs, path::PathBuf, process::ExitCode};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{Args, Parser, Subcommand};
+use mpl_token_auth_rules::{
+    payload::Payload,
+    pda::find_rule_set_address,
+    solana_program::{hash::Hash, pubkey::Pubkey},
+    state::{RuleSetV1, RuleSetV2},
+    upload::UploadPlanner,
+    utils::{deserialize_rule_set, get_rule_set_revision_location_from_data},
+};
+use simulate::{aligned, bytes, describe, evaluate, parse_field, AccountStore};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    signature::{read_keypair_file, Signer},
+};
+use source::{Format, SourceArgs, Version};
+use submit::Submitter;
+
+/// Result of the commands.
+pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser, Debug)]
+#[command(name = "mpl-token-auth-rules", version, about)]
+/// Create, inspect and simulate Token Authorization RuleSets.
+struct Cli {
+    /// URL of the cluster RPC endpoint.
+    #[arg(long, short, global = true, default_value = "http://localhost:8899")]
+    url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compile a RuleSet and write the serialized bytes passed to `CreateOrUpdate`.
+    Compile {
+        #[command(flatten)]
+        source: SourceArgs,
+
+        /// Owner of the RuleSet, replacing the owner in the source file.
+        #[arg(long)]
+        owner: Option<Pubkey>,
+
+        /// File the serialized RuleSet is written to.  It is printed in base64 otherwise.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Create a RuleSet owned by the payer, or add a revision to it.
+    Create {
+        #[command(flatten)]
+        source: SourceArgs,
+
+        #[command(flatten)]
+        signer: SignerArgs,
+
+        /// Compute unit limit of the `CreateOrUpdate` transaction.
+        #[arg(long)]
+        compute_units: Option<u32>,
+
+        /// Keep the chunks an interrupted upload already wrote to the buffer PDA.
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Print a revision of a RuleSet in the policy language.
+    Print {
+        /// Address of the RuleSet PDA.
+        address: Pubkey,
+
+        /// Revision to print.  The latest revision is printed by default.
+        #[arg(long)]
+        revision: Option<usize>,
+
+        /// Print the JSON of the equivalent `RuleSetV1` instead.
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the revisions of a RuleSet.
+    Revisions {
+        /// Address of the RuleSet PDA.
+        address: Pubkey,
+    },
+    /// Evaluate the rule of an operation locally, as `Validate` would.
+    Validate(ValidateArgs),
+}
+
+/// Arguments selecting how transactions are signed.
+#[derive(Args, Debug)]
+struct SignerArgs {
+    /// Keypair file of the payer, which signs the transactions.
+    #[arg(long, short, required_unless_present = "payer")]
+    keypair: Option<PathBuf>,
+
+    /// Print the transactions unsigned, in base64, instead of sending them.
+    #[arg(long)]
+    unsigned: bool,
+
+    /// Payer of the unsigned transactions, when the keypair is not available.
+    #[arg(long, requires = "unsigned")]
+    payer: Option<Pubkey>,
+
+    /// Recent blockhash of the unsigned transactions.  It is fetched from the cluster otherwise.
+    #[arg(long, requires = "unsigned")]
+    blockhash: Option<Hash>,
+}
+
+/// Arguments of the `validate` command.
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    /// Address of the RuleSet PDA to evaluate.
+    #[arg(long, required_unless_present = "file", conflicts_with = "file")]
+    rule_set: Option<Pubkey>,
+
+    /// RuleSet source file to evaluate instead of a RuleSet PDA.
+    #[arg(long)]
+    file: Option<PathBuf>,
+
+    /// Format of the RuleSet source file.
+    #[arg(long, value_enum, default_value_t = Format::Auto)]
+    format: Format,
+
+    /// Version the RuleSet source file is compiled to.
+    #[arg(long = "rule-set-version", value_enum, default_value_t = Version::V2)]
+    rule_set_version: Version,
+
+    /// Revision of the RuleSet PDA to evaluate.  The latest revision is evaluated by default.
+    #[arg(long, conflicts_with = "file")]
+    revision: Option<usize>,
+
+    /// Operation to evaluate.
+    #[arg(long)]
+    operation: String,
+
+    /// Payload entry, as `NAME=TYPE:VALUE`.  The types are pubkey, number, bool, i64, u128,
+    /// str, bytes, pubkeys, seeds and proof.
+    #[arg(long = "field", value_name = "NAME=TYPE:VALUE")]
+    fields: Vec<String>,
+
+    /// JSON file of a `Payload`, for entries that cannot be given with `--field`.
+    #[arg(long)]
+    payload: Option<PathBuf>,
+
+    /// Account passed to the rules, fetched from the cluster.
+    #[arg(long = "account", value_name = "PUBKEY")]
+    accounts: Vec<Pubkey>,
+
+    /// Account passed to the rules as a signer, fetched from the cluster.
+    #[arg(long = "signer", value_name = "PUBKEY")]
+    signers: Vec<Pubkey>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+
+    match run(cli.command, &rpc) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command, rpc: &RpcClient) -> Result<()> {
+    match command {
+        Command::Compile {
+            source,
+            owner,
+            output,
+        } => {
+            let compiled = source.compile(owner)?;
+
+            eprintln!(
+                "Compiled RuleSet `{}` owned by {}: {} bytes",
+                compiled.name,
+                compiled.owner,
+                compiled.serialized.len()
+            );
+
+            match output {
+                Some(output) => fs::write(output, &compiled.serialized)?,
+                None => println!("{}", STANDARD.encode(&compiled.serialized)),
+            }
+        }
+        Command::Create {
+            source,
+            signer,
+            compute_units,
+            resume,
+        } => {
+            let submitter = signer.submitter(rpc)?;
+            let payer = submitter.payer();
+
+            // `CreateOrUpdate` requires the payer to own the `RuleSet`.
+            let compiled = source.compile(Some(payer))?;
+            let (rule_set_pda, _) = find_rule_set_address(payer, compiled.name.clone());
+
+            let existing_data = account_data(rpc, &rule_set_pda)?;
+
+            let planner = UploadPlanner::new(
+                payer,
+                &compiled.name,
+                compiled.serialized.clone(),
+                &existing_data,
+            )?
+            .with_instructions(
+                compute_units
+                    .map(|units| vec![ComputeBudgetInstruction::set_compute_unit_limit(units)])
+                    .unwrap_or_default(),
+            );
+
+            let steps = if resume {
+                planner.resume(&account_data(rpc, &planner.buffer_pda())?)?
+            } else {
+                planner.plan()?
+            };
+
+            let transactions = steps
+                .into_iter()
+                .map(|step| step.instructions)
+                .collect::<Vec<_>>();
+
+            eprintln!(
+                "RuleSet `{}` at {}: {} bytes in {} transaction(s)",
+                compiled.name,
+                rule_set_pda,
+                compiled.serialized.len(),
+                transactions.len()
+            );
+
+            submitter.submit(&transactions)?;
+        }
+        Command::Print {
+            address,
+            revision,
+            json,
+        } => revisions::print(&rpc.get_account_data(&address)?, revision, json)?,
+        Command::Revisions { address } => revisions::list(&rpc.get_account_data(&address)?)?,
+        Command::Validate(args) => args.run(rpc)?,
+    }
+
+    Ok(())
+}
+
+/// Returns the data of an account, empty if it does not exist.
+fn account_data(rpc: &RpcClient, address: &Pubkey) -> Result<Vec<u8>> {
+    Ok(rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .map(|account| account.data)
+        .unwrap_or_default())
+}
+
+impl SignerArgs {
+    fn submitter<'a>(&self, rpc: &'a RpcClient) -> Result<Submitter<'a>> {
+        let keypair = self
+            .keypair
+            .as_ref()
+            .map(|path| {
+                read_keypair_file(path)
+                    .map_err(|error| format!("cannot read {}: {}", path.display(), error))
+            })
+            .transpose()?;
+
+        if self.unsigned {
+            let payer = match (&self.payer, &keypair) {
+                (Some(payer), _) => *payer,
+                (None, Some(keypair)) => keypair.pubkey(),
+                (None, None) => return Err("the payer or its keypair is required".into()),
+            };
+
+            let blockhash = match self.blockhash {
+                Some(blockhash) => blockhash,
+                None => rpc.get_latest_blockhash()?,
+            };
+
+            Ok(Submitter::Unsigned { payer, blockhash })
+        } else {
+            let payer = keypair.ok_or("the keypair is required to send transactions")?;
+
+            Ok(Submitter::Send { rpc, payer })
+        }
+    }
+}
+
+impl ValidateArgs {
+    fn run(&self, rpc: &RpcClient) -> Result<()> {
+        let mut payload = match &self.payload {
+            Some(path) => serde_json::from_slice::<Payload>(&fs::read(path)?)?,
+            None => Payload::new(),
+        };
+
+        for field in &self.fields {
+            let (name, value) = parse_field(field)?;
+            payload.insert(name, value);
+        }
+
+        // Fetch the accounts passed to the rules.
+        let keys = self
+            .accounts
+            .iter()
+            .map(|key| (*key, false))
+            .chain(self.signers.iter().map(|key| (*key, true)))
+            .collect::<Vec<_>>();
+
+        let fetched = if keys.is_empty() {
+            vec![]
+        } else {
+            rpc.get_multiple_accounts(&keys.iter().map(|(key, _)| *key).collect::<Vec<_>>())?
+        };
+
+        let mut store = AccountStore::new(
+            keys.into_iter()
+                .zip(fetched)
+                .map(|((key, is_signer), account)| (key, account, is_signer)),
+        );
+        let accounts = store.infos();
+
+        let result = match &self.rule_set {
+            Some(address) => {
+                let account = rpc.get_account(address)?;
+
+                if account.owner != mpl_token_auth_rules::ID {
+                    return Err(format!("{} is not owned by the RuleSet program", address).into());
+                }
+
+                let buffer = aligned(&account.data);
+                let data = bytes(&buffer, account.data.len());
+                let (start, end) = get_rule_set_revision_location_from_data(data, self.revision)?;
+                let rule_set = deserialize_rule_set(data, start, end, 0)?;
+
+                evaluate(rule_set.as_ref(), &self.operation, &payload, &accounts)
+            }
+            None => {
+                let source = SourceArgs {
+                    file: self.file.clone().ok_or("the RuleSet file is required")?,
+                    format: self.format,
+                    rule_set_version: self.rule_set_version,
+                    name: None,
+                };
+                let compiled = source.compile(None)?;
+
+                match self.rule_set_version {
+                    Version::V1 => {
+                        let rule_set = rmp_serde::from_slice::<RuleSetV1>(&compiled.serialized)?;
+                        evaluate(&rule_set, &self.operation, &payload, &accounts)
+                    }
+                    Version::V2 => {
+                        let buffer = aligned(&compiled.serialized);
+                        let rule_set =
+                            RuleSetV2::from_bytes(bytes(&buffer, compiled.serialized.len()))?;
+                        evaluate(&rule_set, &self.operation, &payload, &accounts)
+                    }
+                }
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                println!("Validation passed");
+                Ok(())
+            }
+            Err(error) => Err(format!("validation failed: {}", describe(&error)).into()),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Decoding and printing the revisions stored in a `RuleSet` PDA.
+use mpl_token_auth_rules::{
+    diff::decode_revision, types::LibVersion, utils::get_revision_map_from_data,
+};
+
+use crate::Result;
+
+/// Prints a revision of the `RuleSet` in the policy language, or as the JSON of the equivalent
+/// `RuleSetV1`, which the `compile` command reads back.  If `revision` is `None`, the latest
+/// revision is printed.
+pub fn print(data: &[u8], revision: Option<usize>, json: bool) -> Result<()> {
+    let policy = decode_revision(data, revision)?;
+
+    if json {
+        let rule_set = policy.to_v1()?;
+        println!("{}", serde_json::to_string_pretty(&rule_set)?);
+    } else {
+        print!("{}", policy);
+    }
+
+    Ok(())
+}
+
+/// Prints the location, lib version and hash of every revision of the `RuleSet`.
+pub fn list(data: &[u8]) -> Result<()> {
+    let (revision_map, _) = get_revision_map_from_data(data)?;
+
+    println!(
+        "{:>8}  {:>10}  {:>7}  hash",
+        "revision", "offset", "version"
+    );
+
+    for (revision, location) in revision_map.rule_set_revisions.iter().enumerate() {
+        let version = match data
+            .get(*location)
+            .map(|version| LibVersion::try_from(*version))
+        {
+            Some(Ok(LibVersion::V1)) => "V1",
+            Some(Ok(LibVersion::V2)) => "V2",
+            _ => "?",
+        };
+
+        // Revisions written before hashes were recorded have a hash of zeros.
+        let hash = match revision_map.rule_set_hashes.get(revision) {
+            Some(hash) if *hash != [0; 32] => hash
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>(),
+            _ => "-".to_string(),
+        };
+
+        println!(
+            "{:>8}  {:>10}  {:>7}  {}",
+            revision, location, version, hash
+        );
+    }
+
+    Ok(())
+}
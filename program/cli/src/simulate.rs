@@ -0,0 +1,203 @@
+//! Local `Validate` simulations with the off-chain evaluator.
+//!
+//! The rule of the operation is evaluated by the same code as the `Validate` instruction, on
+//! accounts fetched from the cluster.  `RuleSet` state is never updated.
+use std::{collections::HashMap, str::FromStr};
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    payload::{Payload, PayloadType, ProofInfo, SeedsVec},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    types::RuleSet,
+    utils::validate_operation,
+};
+use num_traits::FromPrimitive;
+use solana_sdk::account::Account;
+
+use crate::Result;
+
+/// Storage of the accounts passed to the rules.
+pub struct AccountStore {
+    accounts: Vec<StoredAccount>,
+}
+
+struct StoredAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u64>,
+    len: usize,
+    is_signer: bool,
+    executable: bool,
+}
+
+impl AccountStore {
+    /// Stores the accounts, given with whether they sign.  Accounts that do not exist are
+    /// stored as empty system accounts.
+    pub fn new(accounts: impl IntoIterator<Item = (Pubkey, Option<Account>, bool)>) -> Self {
+        Self {
+            accounts: accounts
+                .into_iter()
+                .map(|(key, account, is_signer)| {
+                    let account = account.unwrap_or_default();
+
+                    StoredAccount {
+                        key,
+                        owner: account.owner,
+                        lamports: account.lamports,
+                        data: aligned(&account.data),
+                        len: account.data.len(),
+                        is_signer,
+                        executable: account.executable,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the `AccountInfo`s borrowing the storage.
+    pub fn infos(&mut self) -> Vec<AccountInfo<'_>> {
+        self.accounts
+            .iter_mut()
+            .map(|account| {
+                AccountInfo::new(
+                    &account.key,
+                    account.is_signer,
+                    false,
+                    &mut account.lamports,
+                    &mut bytemuck::cast_slice_mut::<u64, u8>(&mut account.data)[..account.len],
+                    &account.owner,
+                    account.executable,
+                    0,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Copies `data` into an 8-byte aligned buffer, as account data is on chain.
+pub fn aligned(data: &[u8]) -> Vec<u64> {
+    let mut buffer = vec![0u64; data.len().div_ceil(8)];
+    bytemuck::cast_slice_mut::<u64, u8>(&mut buffer)[..data.len()].copy_from_slice(data);
+    buffer
+}
+
+/// Returns the first `length` bytes of an aligned buffer.
+pub fn bytes(buffer: &[u64], length: usize) -> &[u8] {
+    &bytemuck::cast_slice::<u64, u8>(buffer)[..length]
+}
+
+/// Evaluates the rule of the operation, the way `Validate` does.
+pub fn evaluate<'a>(
+    rule_set: &dyn RuleSet<'a>,
+    operation: &str,
+    payload: &Payload,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = accounts
+        .iter()
+        .map(|account| (*account.key, account))
+        .collect::<HashMap<Pubkey, &AccountInfo>>();
+
+    validate_operation(
+        rule_set,
+        operation.to_string(),
+        &accounts,
+        payload,
+        false,
+        &None,
+        &None,
+    )
+}
+
+/// Describes an error returned by the evaluation, naming the `RuleSetError`.
+pub fn describe(error: &ProgramError) -> String {
+    match error {
+        ProgramError::Custom(code) => match RuleSetError::from_u32(*code) {
+            Some(rule_set_error) => format!("{:?} ({}): {}", rule_set_error, code, rule_set_error),
+            None => error.to_string(),
+        },
+        _ => error.to_string(),
+    }
+}
+
+/// Parses a payload entry given as `NAME=TYPE:VALUE`.  Lists are separated by commas and bytes
+/// are hex encoded.
+pub fn parse_field(field: &str) -> Result<(String, PayloadType)> {
+    let (name, value) = field
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=TYPE:VALUE, found `{}`", field))?;
+    let (kind, value) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected TYPE:VALUE for `{}`", name))?;
+
+    let list = |value: &str| -> Vec<String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(String::from)
+            .collect()
+    };
+
+    let value = match kind {
+        "pubkey" => PayloadType::Pubkey(Pubkey::from_str(value)?),
+        "number" => PayloadType::Number(value.parse()?),
+        "bool" => PayloadType::Bool(value.parse()?),
+        "i64" => PayloadType::I64(value.parse()?),
+        "u128" => PayloadType::U128(value.parse()?),
+        "str" => PayloadType::Str(value.to_string()),
+        "bytes" => PayloadType::Bytes(hex(value)?),
+        "pubkeys" => PayloadType::PubkeyList(
+            list(value)
+                .iter()
+                .map(|item| Pubkey::from_str(item))
+                .collect::<std::result::Result<_, _>>()?,
+        ),
+        "seeds" => PayloadType::Seeds(SeedsVec::new(
+            list(value)
+                .iter()
+                .map(|item| hex(item))
+                .collect::<Result<_>>()?,
+        )),
+        "proof" => PayloadType::MerkleProof(ProofInfo::new(
+            list(value)
+                .iter()
+                .map(|item| {
+                    <[u8; 32]>::try_from(hex(item)?)
+                        .map_err(|_| format!("`{}` is not a 32-byte hash", item).into())
+                })
+                .collect::<Result<_>>()?,
+        )),
+        _ => {
+            return Err(format!(
+                "unknown payload type `{}`, expected one of pubkey, number, bool, i64, u128, \
+                 str, bytes, pubkeys, seeds or proof",
+                kind
+            )
+            .into())
+        }
+    };
+
+    Ok((name.to_string(), value))
+}
+
+/// Decodes a hex string, with an optional `0x` prefix.
+fn hex(value: &str) -> Result<Vec<u8>> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+
+    if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return Err(format!("`{}` is not hex encoded", value).into());
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| {
+            u8::from_str_radix(&digits[index..index + 2], 16)
+                .map_err(|_| format!("`{}` is not hex encoded", value).into())
+        })
+        .collect()
+}
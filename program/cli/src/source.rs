@@ -0,0 +1,128 @@
+//! Loading `RuleSet`s from JSON or policy language files.
+use std::{fs, path::PathBuf};
+
+use clap::{Args, ValueEnum};
+use mpl_token_auth_rules::{
+    dsl::{Expr, Policy, Span},
+    solana_program::pubkey::Pubkey,
+    state::RuleSetV1,
+    types::RuleSet,
+};
+
+use crate::Result;
+
+/// Format of a `RuleSet` source file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Detected from the file extension: `.json` files are JSON, other files are policies.
+    Auto,
+    /// A `RuleSetV1` in the JSON layout of its MessagePack encoding, as in `cli/examples`.
+    Json,
+    /// A policy in the policy language.
+    Dsl,
+}
+
+/// Version of the serialized `RuleSet`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Version {
+    /// A MessagePack `RuleSetV1`.
+    V1,
+    /// A `RuleSetV2`.
+    V2,
+}
+
+/// Arguments selecting and compiling a `RuleSet` source file.
+#[derive(Args, Debug)]
+pub struct SourceArgs {
+    /// RuleSet source file, in JSON or in the policy language.
+    pub file: PathBuf,
+
+    /// Format of the source file.
+    #[arg(long, value_enum, default_value_t = Format::Auto)]
+    pub format: Format,
+
+    /// Version of the serialized RuleSet.
+    #[arg(long = "rule-set-version", value_enum, default_value_t = Version::V2)]
+    pub rule_set_version: Version,
+
+    /// Name of the RuleSet, replacing the name in the source file.
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+/// A compiled `RuleSet`.
+#[derive(Debug)]
+pub struct Compiled {
+    /// Name of the `RuleSet`.
+    pub name: String,
+    /// Owner of the `RuleSet`.
+    pub owner: Pubkey,
+    /// Serialized `RuleSet`, as passed to `CreateOrUpdate`.
+    pub serialized: Vec<u8>,
+}
+
+impl SourceArgs {
+    /// Compiles the source file.  The `owner` replaces the owner in the source file.
+    pub fn compile(&self, owner: Option<Pubkey>) -> Result<Compiled> {
+        let source = fs::read_to_string(&self.file)
+            .map_err(|error| format!("cannot read {}: {}", self.file.display(), error))?;
+
+        let format = match self.format {
+            Format::Auto if self.file.extension().is_some_and(|ext| ext == "json") => Format::Json,
+            Format::Auto => Format::Dsl,
+            format => format,
+        };
+
+        match format {
+            Format::Json => self.compile_json(&source, owner),
+            _ => self.compile_dsl(&source, owner),
+        }
+    }
+
+    fn compile_json(&self, source: &str, owner: Option<Pubkey>) -> Result<Compiled> {
+        let parsed = serde_json::from_str::<RuleSetV1>(source)
+            .map_err(|error| format!("invalid RuleSet JSON: {}", error))?;
+
+        let mut rule_set = RuleSetV1::new(
+            self.name.clone().unwrap_or_else(|| parsed.name()),
+            owner.unwrap_or(*parsed.owner()),
+        );
+        rule_set.operations = parsed.operations;
+        rule_set.definitions = parsed.definitions;
+        rule_set.assert_valid_references()?;
+
+        let serialized = match self.rule_set_version {
+            Version::V1 => rmp_serde::to_vec(&rule_set)?,
+            Version::V2 => rule_set.to_v2()?,
+        };
+
+        Ok(Compiled {
+            name: rule_set.name(),
+            owner: *rule_set.owner(),
+            serialized,
+        })
+    }
+
+    fn compile_dsl(&self, source: &str, owner: Option<Pubkey>) -> Result<Compiled> {
+        let mut policy = Policy::parse(source).map_err(|error| error.render(source))?;
+
+        if let Some(name) = &self.name {
+            policy.name = Some((name.clone(), Span::default()));
+        }
+        if let Some(owner) = owner {
+            policy.owner = Some(Expr::word(owner));
+        }
+
+        let rule_set = policy.to_v1().map_err(|error| error.render(source))?;
+        let serialized = match self.rule_set_version {
+            Version::V1 => rmp_serde::to_vec(&rule_set)?,
+            Version::V2 => policy.to_v2().map_err(|error| error.render(source))?,
+        };
+
+        Ok(Compiled {
+            name: rule_set.name(),
+            owner: *rule_set.owner(),
+            serialized,
+        })
+    }
+}
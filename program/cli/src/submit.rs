@@ -0,0 +1,81 @@
+//! Sending transactions, or printing them unsigned for offline signing.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use mpl_token_auth_rules::solana_program::{hash::Hash, instruction::Instruction, pubkey::Pubkey};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    message::Message,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::Result;
+
+/// How the transactions of a command are submitted.
+pub enum Submitter<'a> {
+    /// Sign the transactions with the payer and send them one after the other.
+    Send {
+        /// Client of the cluster.
+        rpc: &'a RpcClient,
+        /// Payer and signer of the transactions.
+        payer: Keypair,
+    },
+    /// Print the transactions unsigned, as base64 encoded wire transactions.
+    Unsigned {
+        /// Payer of the transactions.
+        payer: Pubkey,
+        /// Recent blockhash of the transactions.
+        blockhash: Hash,
+    },
+}
+
+impl Submitter<'_> {
+    /// Returns the payer of the transactions.
+    pub fn payer(&self) -> Pubkey {
+        match self {
+            Self::Send { payer, .. } => payer.pubkey(),
+            Self::Unsigned { payer, .. } => *payer,
+        }
+    }
+
+    /// Submits the transactions, given as lists of instructions, in order.  Sending stops at the
+    /// first transaction that fails.
+    pub fn submit(&self, transactions: &[Vec<Instruction>]) -> Result<()> {
+        for (index, instructions) in transactions.iter().enumerate() {
+            match self {
+                Self::Send { rpc, payer } => {
+                    let transaction = Transaction::new_signed_with_payer(
+                        instructions,
+                        Some(&payer.pubkey()),
+                        &[payer],
+                        rpc.get_latest_blockhash()?,
+                    );
+
+                    let signature =
+                        rpc.send_and_confirm_transaction(&transaction)
+                            .map_err(|error| {
+                                format!(
+                                    "transaction {} of {} failed: {}",
+                                    index + 1,
+                                    transactions.len(),
+                                    error
+                                )
+                            })?;
+
+                    eprintln!("Transaction {} of {}:", index + 1, transactions.len());
+                    println!("{}", signature);
+                }
+                Self::Unsigned { payer, blockhash } => {
+                    let transaction = Transaction::new_unsigned(Message::new_with_blockhash(
+                        instructions,
+                        Some(payer),
+                        blockhash,
+                    ));
+
+                    println!("{}", STANDARD.encode(bincode::serialize(&transaction)?));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
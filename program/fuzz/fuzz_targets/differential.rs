@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use miraplex_token_auth_rules_fuzz::DifferentialInput;
+
+fuzz_target!(|input: DifferentialInput| miraplex_token_auth_rules_fuzz::differential(&input));
@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use miraplex_token_auth_rules_fuzz::ValidateInput;
+
+fuzz_target!(|input: ValidateInput| miraplex_token_auth_rules_fuzz::validate(&input));
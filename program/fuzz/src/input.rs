@@ -0,0 +1,566 @@
+//! Structured inputs generated from the fuzzer bytes.
+use arbitrary::{Arbitrary, Result, Unstructured};
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    payload::{Payload, PayloadType, ProofInfo, SeedsVec},
+    solana_program::{account_info::AccountInfo, pubkey::Pubkey, system_program},
+    state::{
+        AdditionalSigner, All, Amount, AmountRange, Any, BoolMatch, BytesMatch, CompareOp,
+        FieldCompare, IfThenElse, IsWallet, MatchOp, MatchOperator, Not, Operator, Pass,
+        ProgramOwned, ProgramOwnedList, PubkeyListContains, PubkeyListMatch, PubkeyMatch, Rule,
+        Threshold,
+    },
+};
+use std::collections::HashMap;
+
+/// Maximum depth of the generated rule trees.
+const MAX_RULE_DEPTH: u8 = 4;
+
+/// Maximum number of rules contained under a composite rule.
+const MAX_RULES: usize = 3;
+
+/// Payload field names used by the generated rules and payloads.
+const FIELDS: [&str; 5] = ["Amount", "Destination", "Source", "Authority", "Flag"];
+
+/// Operation names used to look up rules.
+pub const OPERATIONS: [&str; 5] = ["transfer", "transfer:owner", "delegate", "unknown", "*"];
+
+/// Returns a pubkey from a small pool, so that rules, payload values and accounts are likely to
+/// refer to the same keys.
+pub fn key(index: u8) -> Pubkey {
+    match index % 8 {
+        0 => system_program::ID,
+        1 => mpl_token_auth_rules::ID,
+        n => Pubkey::new_from_array([n; 32]),
+    }
+}
+
+/// Returns a payload field name from the pool.
+pub fn field(index: u8) -> String {
+    FIELDS[index as usize % FIELDS.len()].to_string()
+}
+
+/// Returns an operation name from the pool.
+pub fn operation(index: u8) -> String {
+    OPERATIONS[index as usize % OPERATIONS.len()].to_string()
+}
+
+/// An account passed to the rules.
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzAccount {
+    key: u8,
+    owner: u8,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+    executable: bool,
+}
+
+/// Owned storage for the accounts passed to the rules.
+#[derive(Debug, Clone)]
+pub struct AccountSet {
+    accounts: Vec<StoredAccount>,
+}
+
+/// Owned data of an account, with the pubkeys resolved from the pool.
+#[derive(Debug, Clone)]
+struct StoredAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+    executable: bool,
+}
+
+impl AccountSet {
+    /// Creates the storage for the generated accounts.
+    pub fn new(accounts: &[FuzzAccount]) -> Self {
+        Self {
+            accounts: accounts
+                .iter()
+                .map(|account| StoredAccount {
+                    key: key(account.key),
+                    owner: key(account.owner),
+                    lamports: account.lamports,
+                    data: account.data.clone(),
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                    executable: account.executable,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the `AccountInfo`s borrowing the storage.
+    pub fn infos(&mut self) -> Vec<AccountInfo<'_>> {
+        self.accounts
+            .iter_mut()
+            .map(|account| {
+                AccountInfo::new(
+                    &account.key,
+                    account.is_signer,
+                    account.is_writable,
+                    &mut account.lamports,
+                    &mut account.data,
+                    &account.owner,
+                    account.executable,
+                    0,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Maps the accounts by key, the same way `Validate` maps its remaining accounts.
+pub fn account_map<'a, 'b>(infos: &'a [AccountInfo<'b>]) -> HashMap<Pubkey, &'a AccountInfo<'b>> {
+    infos.iter().map(|info| (*info.key, info)).collect()
+}
+
+/// A payload value.
+#[derive(Arbitrary, Debug, Clone)]
+pub enum FuzzValue {
+    Pubkey(u8),
+    Number(u64),
+    Bool(bool),
+    PubkeyList(Vec<u8>),
+    Bytes(Vec<u8>),
+    Str(String),
+    Seeds(Vec<Vec<u8>>),
+    MerkleProof(Vec<[u8; 32]>),
+    I64(i64),
+    U128(u128),
+}
+
+impl FuzzValue {
+    fn to_payload_type(&self) -> PayloadType {
+        match self {
+            FuzzValue::Pubkey(index) => PayloadType::Pubkey(key(*index)),
+            FuzzValue::Number(value) => PayloadType::Number(*value),
+            FuzzValue::Bool(value) => PayloadType::Bool(*value),
+            FuzzValue::PubkeyList(indices) => {
+                PayloadType::PubkeyList(indices.iter().map(|index| key(*index)).collect())
+            }
+            FuzzValue::Bytes(value) => PayloadType::Bytes(value.clone()),
+            FuzzValue::Str(value) => PayloadType::Str(value.clone()),
+            FuzzValue::Seeds(seeds) => PayloadType::Seeds(SeedsVec::new(seeds.clone())),
+            FuzzValue::MerkleProof(proof) => {
+                PayloadType::MerkleProof(ProofInfo::new(proof.clone()))
+            }
+            FuzzValue::I64(value) => PayloadType::I64(*value),
+            FuzzValue::U128(value) => PayloadType::U128(*value),
+        }
+    }
+}
+
+/// A payload with fields from the pool of field names.
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzPayload {
+    entries: Vec<(u8, FuzzValue)>,
+}
+
+impl FuzzPayload {
+    /// Creates the `Payload`.
+    pub fn to_payload(&self) -> Payload {
+        let mut payload = Payload::new();
+
+        for (name, value) in &self.entries {
+            payload.insert(field(*name), value.to_payload_type());
+        }
+
+        payload
+    }
+}
+
+/// A comparison operator available in both `RuleSet` versions.
+#[derive(Arbitrary, Debug, Clone, Copy)]
+pub enum FuzzOperator {
+    Lt,
+    LtEq,
+    Eq,
+    GtEq,
+    Gt,
+}
+
+impl FuzzOperator {
+    fn to_v1(self) -> CompareOp {
+        match self {
+            FuzzOperator::Lt => CompareOp::Lt,
+            FuzzOperator::LtEq => CompareOp::LtEq,
+            FuzzOperator::Eq => CompareOp::Eq,
+            FuzzOperator::GtEq => CompareOp::GtEq,
+            FuzzOperator::Gt => CompareOp::Gt,
+        }
+    }
+
+    fn to_v2(self) -> Operator {
+        match self {
+            FuzzOperator::Lt => Operator::Lt,
+            FuzzOperator::LtEq => Operator::LtEq,
+            FuzzOperator::Eq => Operator::Eq,
+            FuzzOperator::GtEq => Operator::GtEq,
+            FuzzOperator::Gt => Operator::Gt,
+        }
+    }
+}
+
+/// Borrows serialized rules as slices.
+fn slices(rules: &[Vec<u8>]) -> Vec<&[u8]> {
+    rules.iter().map(Vec::as_slice).collect()
+}
+
+/// A rule that can be expressed by both `RuleSet` versions.
+#[derive(Debug, Clone)]
+pub enum FuzzRule {
+    Pass,
+    AdditionalSigner {
+        account: u8,
+    },
+    Amount {
+        amount: u64,
+        operator: FuzzOperator,
+        field: u8,
+    },
+    AmountRange {
+        min: u64,
+        max: u64,
+        field: u8,
+    },
+    BoolMatch {
+        value: bool,
+        field: u8,
+    },
+    BytesMatch {
+        value: Vec<u8>,
+        prefix: bool,
+        field: u8,
+    },
+    FieldCompare {
+        left: u8,
+        operator: FuzzOperator,
+        right: u8,
+    },
+    IsWallet {
+        field: u8,
+    },
+    ProgramOwned {
+        program: u8,
+        field: u8,
+    },
+    ProgramOwnedList {
+        programs: Vec<u8>,
+        field: u8,
+    },
+    PubkeyListContains {
+        pubkey: u8,
+        field: u8,
+    },
+    PubkeyListMatch {
+        pubkeys: Vec<u8>,
+        field: u8,
+    },
+    PubkeyMatch {
+        pubkey: u8,
+        field: u8,
+    },
+    All {
+        rules: Vec<FuzzRule>,
+    },
+    Any {
+        rules: Vec<FuzzRule>,
+    },
+    Not {
+        rule: Box<FuzzRule>,
+    },
+    Threshold {
+        k: u64,
+        rules: Vec<FuzzRule>,
+    },
+    IfThenElse {
+        rules: Box<[FuzzRule; 3]>,
+    },
+}
+
+impl<'a> Arbitrary<'a> for FuzzRule {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_with_depth(u, 0)
+    }
+}
+
+impl FuzzRule {
+    fn arbitrary_with_depth(u: &mut Unstructured<'_>, depth: u8) -> Result<Self> {
+        // composite rules are only generated below the maximum depth
+        let variants = if depth < MAX_RULE_DEPTH { 18 } else { 13 };
+
+        let rules = |u: &mut Unstructured<'_>| -> Result<Vec<FuzzRule>> {
+            let size = u.int_in_range(1..=MAX_RULES)?;
+            (0..size)
+                .map(|_| Self::arbitrary_with_depth(u, depth + 1))
+                .collect()
+        };
+
+        Ok(match u.choose_index(variants)? {
+            0 => FuzzRule::Pass,
+            1 => FuzzRule::AdditionalSigner {
+                account: u.arbitrary()?,
+            },
+            2 => FuzzRule::Amount {
+                amount: u.arbitrary()?,
+                operator: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            3 => FuzzRule::AmountRange {
+                min: u.arbitrary()?,
+                max: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            4 => FuzzRule::BoolMatch {
+                value: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            5 => FuzzRule::BytesMatch {
+                value: u.arbitrary()?,
+                prefix: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            6 => FuzzRule::FieldCompare {
+                left: u.arbitrary()?,
+                operator: u.arbitrary()?,
+                right: u.arbitrary()?,
+            },
+            7 => FuzzRule::IsWallet {
+                field: u.arbitrary()?,
+            },
+            8 => FuzzRule::ProgramOwned {
+                program: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            9 => FuzzRule::ProgramOwnedList {
+                programs: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            10 => FuzzRule::PubkeyListContains {
+                pubkey: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            11 => FuzzRule::PubkeyListMatch {
+                pubkeys: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            12 => FuzzRule::PubkeyMatch {
+                pubkey: u.arbitrary()?,
+                field: u.arbitrary()?,
+            },
+            13 => FuzzRule::All { rules: rules(u)? },
+            14 => FuzzRule::Any { rules: rules(u)? },
+            15 => FuzzRule::Not {
+                rule: Box::new(Self::arbitrary_with_depth(u, depth + 1)?),
+            },
+            16 => {
+                let rules = rules(u)?;
+                FuzzRule::Threshold {
+                    k: u.int_in_range(0..=rules.len() as u64 + 1)?,
+                    rules,
+                }
+            }
+            _ => FuzzRule::IfThenElse {
+                rules: Box::new([
+                    Self::arbitrary_with_depth(u, depth + 1)?,
+                    Self::arbitrary_with_depth(u, depth + 1)?,
+                    Self::arbitrary_with_depth(u, depth + 1)?,
+                ]),
+            },
+        })
+    }
+
+    /// Returns the equivalent `RuleSetV1` rule.
+    pub fn to_v1(&self) -> Rule {
+        let keys = |indices: &[u8]| indices.iter().map(|index| key(*index)).collect();
+        let rules = |rules: &[FuzzRule]| rules.iter().map(FuzzRule::to_v1).collect();
+
+        match self {
+            FuzzRule::Pass => Rule::Pass,
+            FuzzRule::AdditionalSigner { account } => Rule::AdditionalSigner {
+                account: key(*account),
+            },
+            FuzzRule::Amount {
+                amount,
+                operator,
+                field: name,
+            } => Rule::Amount {
+                amount: *amount,
+                operator: operator.to_v1(),
+                field: field(*name),
+            },
+            FuzzRule::AmountRange {
+                min,
+                max,
+                field: name,
+            } => Rule::AmountRange {
+                min: *min,
+                max: *max,
+                field: field(*name),
+            },
+            FuzzRule::BoolMatch { value, field: name } => Rule::BoolMatch {
+                value: *value,
+                field: field(*name),
+            },
+            FuzzRule::BytesMatch {
+                value,
+                prefix,
+                field: name,
+            } => Rule::BytesMatch {
+                value: value.clone(),
+                operator: if *prefix {
+                    MatchOp::Prefix
+                } else {
+                    MatchOp::Eq
+                },
+                field: field(*name),
+            },
+            FuzzRule::FieldCompare {
+                left,
+                operator,
+                right,
+            } => Rule::FieldCompare {
+                left_field: field(*left),
+                operator: operator.to_v1(),
+                right_field: field(*right),
+            },
+            FuzzRule::IsWallet { field: name } => Rule::IsWallet {
+                field: field(*name),
+            },
+            FuzzRule::ProgramOwned {
+                program,
+                field: name,
+            } => Rule::ProgramOwned {
+                program: key(*program),
+                field: field(*name),
+            },
+            FuzzRule::ProgramOwnedList {
+                programs,
+                field: name,
+            } => Rule::ProgramOwnedList {
+                programs: keys(programs),
+                field: field(*name),
+            },
+            FuzzRule::PubkeyListContains {
+                pubkey,
+                field: name,
+            } => Rule::PubkeyListContains {
+                pubkey: key(*pubkey),
+                field: field(*name),
+            },
+            FuzzRule::PubkeyListMatch {
+                pubkeys,
+                field: name,
+            } => Rule::PubkeyListMatch {
+                pubkeys: keys(pubkeys),
+                field: field(*name),
+            },
+            FuzzRule::PubkeyMatch {
+                pubkey,
+                field: name,
+            } => Rule::PubkeyMatch {
+                pubkey: key(*pubkey),
+                field: field(*name),
+            },
+            FuzzRule::All { rules: children } => Rule::All {
+                rules: rules(children),
+            },
+            FuzzRule::Any { rules: children } => Rule::Any {
+                rules: rules(children),
+            },
+            FuzzRule::Not { rule } => Rule::Not {
+                rule: Box::new(rule.to_v1()),
+            },
+            FuzzRule::Threshold { k, rules: children } => Rule::Threshold {
+                k: *k,
+                rules: rules(children),
+            },
+            FuzzRule::IfThenElse { rules } => Rule::IfThenElse {
+                condition: Box::new(rules[0].to_v1()),
+                then: Box::new(rules[1].to_v1()),
+                otherwise: Box::new(rules[2].to_v1()),
+            },
+        }
+    }
+
+    /// Returns the equivalent serialized `RuleSetV2` rule.
+    pub fn to_v2(&self) -> std::result::Result<Vec<u8>, RuleSetError> {
+        let keys = |indices: &[u8]| indices.iter().map(|index| key(*index)).collect::<Vec<_>>();
+        let rules = |rules: &[FuzzRule]| {
+            rules
+                .iter()
+                .map(FuzzRule::to_v2)
+                .collect::<std::result::Result<Vec<_>, _>>()
+        };
+
+        match self {
+            FuzzRule::Pass => Pass::serialize(),
+            FuzzRule::AdditionalSigner { account } => AdditionalSigner::serialize(key(*account)),
+            FuzzRule::Amount {
+                amount,
+                operator,
+                field: name,
+            } => Amount::serialize(field(*name), operator.to_v2(), *amount),
+            FuzzRule::AmountRange {
+                min,
+                max,
+                field: name,
+            } => AmountRange::serialize(field(*name), *min, *max),
+            FuzzRule::BoolMatch { value, field: name } => {
+                BoolMatch::serialize(field(*name), *value)
+            }
+            FuzzRule::BytesMatch {
+                value,
+                prefix,
+                field: name,
+            } => {
+                let operator = if *prefix {
+                    MatchOperator::Prefix
+                } else {
+                    MatchOperator::Eq
+                };
+                BytesMatch::serialize(field(*name), operator, value.clone())
+            }
+            FuzzRule::FieldCompare {
+                left,
+                operator,
+                right,
+            } => FieldCompare::serialize(field(*left), operator.to_v2(), field(*right)),
+            FuzzRule::IsWallet { field: name } => IsWallet::serialize(field(*name)),
+            FuzzRule::ProgramOwned {
+                program,
+                field: name,
+            } => ProgramOwned::serialize(field(*name), key(*program)),
+            FuzzRule::ProgramOwnedList {
+                programs,
+                field: name,
+            } => ProgramOwnedList::serialize(field(*name), &keys(programs)),
+            FuzzRule::PubkeyListContains {
+                pubkey,
+                field: name,
+            } => PubkeyListContains::serialize(field(*name), key(*pubkey)),
+            FuzzRule::PubkeyListMatch {
+                pubkeys,
+                field: name,
+            } => PubkeyListMatch::serialize(field(*name), &keys(pubkeys)),
+            FuzzRule::PubkeyMatch {
+                pubkey,
+                field: name,
+            } => PubkeyMatch::serialize(field(*name), key(*pubkey)),
+            FuzzRule::All { rules: children } => All::serialize(&slices(&rules(children)?)),
+            FuzzRule::Any { rules: children } => Any::serialize(&slices(&rules(children)?)),
+            FuzzRule::Not { rule } => Not::serialize(&rule.to_v2()?),
+            FuzzRule::Threshold { k, rules: children } => {
+                Threshold::serialize(*k, &slices(&rules(children)?))
+            }
+            FuzzRule::IfThenElse { rules } => {
+                IfThenElse::serialize(&rules[0].to_v2()?, &rules[1].to_v2()?, &rules[2].to_v2()?)
+            }
+        }
+    }
+}
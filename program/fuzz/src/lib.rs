@@ -0,0 +1,295 @@
+//! Host-side harness for the decoders and validators of the program.
+//!
+//! Each function takes attacker-controlled input and runs it through the same code paths used
+//! by the program instructions.  Errors are expected; panics, out-of-bounds reads and
+//! disagreements between equivalent `RuleSetV1` and `RuleSetV2` rules are not.  The fuzz
+//! targets call these functions, and `tests/harness.rs` runs them on generated inputs without
+//! a fuzzing engine.
+pub mod input;
+
+use arbitrary::Arbitrary;
+use borsh::BorshDeserialize;
+use input::{account_map, operation, AccountSet, FuzzAccount, FuzzPayload, FuzzRule};
+use mpl_token_auth_rules::{
+    instruction::RuleSetInstruction,
+    payload::Payload,
+    solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
+    state::{Constraint, RuleResult, RuleSetV1, RuleSetV2, RuleV2},
+    types::RuleSet,
+    utils::{deserialize_rule_set, get_existing_revision_map, get_rule_set_revision_location},
+};
+
+/// Copies `data` into an 8-byte aligned buffer, as account data is, so that misalignment is not
+/// the only reason for rejecting an input.
+pub fn aligned(data: &[u8]) -> Vec<u64> {
+    let mut buffer = vec![0u64; data.len().div_ceil(8)];
+    bytemuck::cast_slice_mut::<u64, u8>(&mut buffer)[..data.len()].copy_from_slice(data);
+    buffer
+}
+
+/// Returns the first `length` bytes of an aligned buffer.
+fn bytes(buffer: &[u64], length: usize) -> &[u8] {
+    &bytemuck::cast_slice::<u64, u8>(buffer)[..length]
+}
+
+/// Looks up and evaluates the rule of every operation in the pool.
+fn evaluate<'a>(
+    rule_set: &dyn RuleSet<'a>,
+    payload: &Payload,
+    accounts: &[AccountInfo],
+) -> Vec<Result<(), ProgramError>> {
+    let accounts = account_map(accounts);
+
+    (0..input::OPERATIONS.len() as u8)
+        .map(|index| {
+            let rule = rule_set.get_rule_with_definitions(operation(index))?;
+            rule.validate(&accounts, payload, false, &None, &None)
+        })
+        .collect()
+}
+
+/// Decodes a MessagePack `RuleSetV1` and checks that it round-trips.
+pub fn rule_set_v1(data: &[u8]) {
+    let Ok(rule_set) = rmp_serde::from_slice::<RuleSetV1>(data) else {
+        return;
+    };
+
+    let _ = rule_set.assert_valid_references();
+
+    let serialized = rmp_serde::to_vec(&rule_set).expect("a decoded RuleSetV1 serializes");
+    let decoded =
+        rmp_serde::from_slice::<RuleSetV1>(&serialized).expect("a serialized RuleSetV1 decodes");
+
+    assert_eq!(rule_set, decoded);
+
+    evaluate(&rule_set, &Payload::new(), &[]);
+}
+
+/// Decodes a `RuleSetV2` and parses every rule.
+pub fn rule_set_v2(data: &[u8]) {
+    let buffer = aligned(data);
+
+    let Ok(rule_set) = RuleSetV2::from_bytes(bytes(&buffer, data.len())) else {
+        return;
+    };
+
+    let _ = rule_set.assert_well_formed();
+    let _ = rule_set.assert_valid_references();
+
+    for operation in rule_set.operations {
+        let _ = rule_set.get(operation.to_string());
+    }
+
+    evaluate(&rule_set, &Payload::new(), &[]);
+}
+
+/// Decodes a single `RuleV2` and the rules nested under it.
+pub fn rule_v2(data: &[u8]) {
+    let buffer = aligned(data);
+
+    let Ok(rule) = RuleV2::from_bytes(bytes(&buffer, data.len())) else {
+        return;
+    };
+
+    let _ = rule.assert_well_formed();
+    let _ = rule.references();
+}
+
+/// Decodes the revision map of `RuleSet` PDA data and every revision it points to.
+pub fn revision_map(data: &[u8]) {
+    let key = Pubkey::new_unique();
+    let owner = mpl_token_auth_rules::ID;
+    let mut lamports = 0;
+    let mut data = data.to_vec();
+    let account = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+
+    let Ok((revision_map, _)) = get_existing_revision_map(&account) else {
+        return;
+    };
+
+    let revisions = (0..revision_map.rule_set_revisions.len())
+        .map(Some)
+        .chain([None, Some(usize::MAX)]);
+
+    for revision in revisions {
+        let Ok((start, end)) = get_rule_set_revision_location(&account, revision) else {
+            continue;
+        };
+
+        let data = account.data.borrow();
+
+        if let Ok(rule_set) = deserialize_rule_set(&data, start, end, 0) {
+            evaluate(rule_set.as_ref(), &Payload::new(), &[]);
+        };
+    }
+}
+
+/// Decodes instruction data and checks that re-encoding it is stable.
+pub fn instruction(data: &[u8]) {
+    let Ok(instruction) = RuleSetInstruction::try_from_slice(data) else {
+        return;
+    };
+
+    let serialized = borsh::to_vec(&instruction).expect("a decoded instruction serializes");
+    let decoded =
+        RuleSetInstruction::try_from_slice(&serialized).expect("a serialized instruction decodes");
+
+    assert_eq!(
+        serialized,
+        borsh::to_vec(&decoded).expect("a decoded instruction serializes")
+    );
+}
+
+/// Decodes a `Payload` and checks that it round-trips.
+pub fn payload(data: &[u8]) {
+    let Ok(payload) = Payload::try_from_slice(data) else {
+        return;
+    };
+
+    let _ = payload.assert_within_limits();
+
+    let serialized = borsh::to_vec(&payload).expect("a decoded payload serializes");
+    let decoded = Payload::try_from_slice(&serialized).expect("a serialized payload decodes");
+
+    assert_eq!(payload, decoded);
+}
+
+/// Input for validating a corrupted `RuleSet` against arbitrary accounts and payload.
+#[derive(Arbitrary, Debug)]
+pub struct ValidateInput {
+    /// Rule compiled to both versions before the corruptions are applied.
+    pub rule: FuzzRule,
+    /// Bytes overwritten in the serialized `RuleSet`s, as positions and values.
+    pub corruptions: Vec<(u16, u8)>,
+    /// Payload passed to the rules.
+    pub payload: FuzzPayload,
+    /// Accounts passed to the rules.
+    pub accounts: Vec<FuzzAccount>,
+}
+
+/// Overwrites bytes of `data`, wrapping the positions around its length.
+fn corrupt(mut data: Vec<u8>, corruptions: &[(u16, u8)]) -> Vec<u8> {
+    if !data.is_empty() {
+        for (position, value) in corruptions {
+            let position = *position as usize % data.len();
+            data[position] = *value;
+        }
+    }
+
+    data
+}
+
+/// Decodes a corrupted `RuleSet` of each version and validates every operation in the pool.
+pub fn validate(input: &ValidateInput) {
+    let owner = Pubkey::new_from_array([7; 32]);
+    let name = "Validate";
+    let operations = ["transfer".to_string(), "*".to_string()];
+    let payload = input.payload.to_payload();
+    let mut accounts = AccountSet::new(&input.accounts);
+    let infos = accounts.infos();
+
+    let mut v1 = RuleSetV1::new(name.to_string(), owner);
+
+    for operation in &operations {
+        v1.add(operation.clone(), input.rule.to_v1())
+            .expect("distinct operations are added");
+    }
+
+    let serialized = rmp_serde::to_vec(&v1).expect("a RuleSetV1 serializes");
+    let serialized = corrupt(serialized, &input.corruptions);
+
+    if let Ok(rule_set) = rmp_serde::from_slice::<RuleSetV1>(&serialized) {
+        evaluate(&rule_set, &payload, &infos);
+    }
+
+    let rule = input.rule.to_v2().expect("a generated rule serializes");
+    let serialized = RuleSetV2::serialize(owner, name, &operations, &[&rule, &rule])
+        .expect("a RuleSetV2 serializes");
+    let serialized = corrupt(serialized, &input.corruptions);
+    let buffer = aligned(&serialized);
+
+    if let Ok(rule_set) = RuleSetV2::from_bytes(bytes(&buffer, serialized.len())) {
+        evaluate(&rule_set, &payload, &infos);
+    };
+}
+
+/// Input for comparing equivalent `RuleSetV1` and `RuleSetV2` rules.
+#[derive(Arbitrary, Debug)]
+pub struct DifferentialInput {
+    /// Rule compiled to both versions.
+    pub rule: FuzzRule,
+    /// Payload passed to the rules.
+    pub payload: FuzzPayload,
+    /// Accounts passed to the rules.
+    pub accounts: Vec<FuzzAccount>,
+}
+
+/// Outcome of a rule evaluation, compared across `RuleSet` versions.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Success,
+    Failure(ProgramError),
+    Error(ProgramError),
+}
+
+impl From<RuleResult> for Outcome {
+    fn from(result: RuleResult) -> Self {
+        match result {
+            RuleResult::Success(_) => Outcome::Success,
+            RuleResult::Failure(error) => Outcome::Failure(error),
+            RuleResult::Error(error) => Outcome::Error(error),
+        }
+    }
+}
+
+/// Compiles a rule to both `RuleSet` versions and checks that they agree on the result.
+pub fn differential(input: &DifferentialInput) {
+    let operation = "transfer".to_string();
+    let owner = Pubkey::new_from_array([7; 32]);
+    let name = "Differential";
+
+    let mut v1 = RuleSetV1::new(name.to_string(), owner);
+    v1.add(operation.clone(), input.rule.to_v1())
+        .expect("a single operation is added");
+
+    // the V1 rule set is evaluated after a round-trip through its serialized form
+    let serialized = rmp_serde::to_vec(&v1).expect("a RuleSetV1 serializes");
+    let v1 = rmp_serde::from_slice::<RuleSetV1>(&serialized).expect("a RuleSetV1 decodes");
+
+    let rule = input.rule.to_v2().expect("a generated rule serializes");
+    let serialized = RuleSetV2::serialize(owner, name, std::slice::from_ref(&operation), &[&rule])
+        .expect("a RuleSetV2 serializes");
+    let buffer = aligned(&serialized);
+    let v2 = RuleSetV2::from_bytes(bytes(&buffer, serialized.len())).expect("a RuleSetV2 decodes");
+    v2.assert_well_formed().expect("a RuleSetV2 is well formed");
+
+    let payload = input.payload.to_payload();
+    let mut accounts = AccountSet::new(&input.accounts);
+    let infos = accounts.infos();
+    let accounts = account_map(&infos);
+
+    let outcome = |rule_set: &dyn RuleSet| -> Outcome {
+        match rule_set.get_rule_with_definitions(operation.clone()) {
+            Ok(rule) => rule
+                .low_level_validate(&accounts, &payload, false, &None, &None)
+                .into(),
+            Err(error) => Outcome::Error(error),
+        }
+    };
+
+    assert_eq!(
+        outcome(&v1),
+        outcome(&v2),
+        "V1 and V2 disagree on {:?}",
+        input.rule
+    );
+}
@@ -0,0 +1,228 @@
+//! Runs the harness on generated inputs, without a fuzzing engine.
+use arbitrary::{Arbitrary, Unstructured};
+use borsh::BorshSerialize;
+use miraplex_token_auth_rules_fuzz::{
+    differential, input::FuzzRule, instruction, payload, revision_map, rule_set_v1, rule_set_v2,
+    rule_v2, validate, DifferentialInput, ValidateInput,
+};
+use mpl_token_auth_rules::{
+    instruction::{CreateOrUpdateArgs, RuleSetInstruction, ValidateArgs},
+    payload::{Payload, PayloadType},
+    solana_program::pubkey::Pubkey,
+    state::{
+        RuleSetHeader, RuleSetRevisionMapV1, RuleSetV1, RuleSetV2, RULE_SET_REV_MAP_VERSION,
+        RULE_SET_SERIALIZED_HEADER_LEN,
+    },
+};
+
+/// Number of generated inputs per target.
+const ITERATIONS: usize = 2_000;
+
+/// Deterministic xorshift generator for the input bytes.
+struct Generator(u64);
+
+impl Generator {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns random bytes of a random length up to `max_length`.
+    fn bytes(&mut self, max_length: usize) -> Vec<u8> {
+        let length = self.next() as usize % (max_length + 1);
+        (0..length).map(|_| self.next() as u8).collect()
+    }
+
+    /// Returns a copy of `seed` with a few bytes overwritten, truncated or extended.
+    fn mutate(&mut self, seed: &[u8]) -> Vec<u8> {
+        let mut data = seed.to_vec();
+
+        for _ in 0..=self.next() % 4 {
+            if data.is_empty() {
+                break;
+            }
+
+            let position = self.next() as usize % data.len();
+
+            match self.next() % 4 {
+                0 => data[position] = self.next() as u8,
+                1 => data[position] ^= 1 << (self.next() % 8),
+                2 => data.truncate(position),
+                _ => {
+                    let end = (position + 4).min(data.len());
+                    let value = [0, 1, 0xff, 0x7f][self.next() as usize % 4];
+                    data[position..end].fill(value);
+                }
+            }
+        }
+
+        data
+    }
+}
+
+/// Runs `target` on random bytes and on mutations of the seeds.
+fn run(target: fn(&[u8]), seeds: &[Vec<u8>]) {
+    let mut generator = Generator(0x2545_f491_4f6c_dd1d);
+
+    for seed in seeds {
+        target(seed);
+    }
+
+    for iteration in 0..ITERATIONS {
+        let data = if seeds.is_empty() || iteration % 4 == 0 {
+            generator.bytes(512)
+        } else {
+            generator.mutate(&seeds[iteration % seeds.len()])
+        };
+
+        target(&data);
+    }
+}
+
+/// Runs `target` on structured inputs generated from random bytes.
+fn run_structured<T: for<'a> Arbitrary<'a>>(target: fn(&T)) {
+    let mut generator = Generator(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..ITERATIONS {
+        let data = generator.bytes(1024);
+
+        if let Ok(input) = T::arbitrary_take_rest(Unstructured::new(&data)) {
+            target(&input);
+        }
+    }
+}
+
+/// Rules compiled to both `RuleSet` versions, used as seeds.
+fn rules() -> Vec<FuzzRule> {
+    let mut generator = Generator(0xd1b5_4a32_d192_ed03);
+
+    (0..8)
+        .filter_map(|_| FuzzRule::arbitrary(&mut Unstructured::new(&generator.bytes(256))).ok())
+        .collect()
+}
+
+fn v1_rule_sets() -> Vec<Vec<u8>> {
+    rules()
+        .iter()
+        .map(|rule| {
+            let mut rule_set = RuleSetV1::new("Seed".to_string(), Pubkey::default());
+            rule_set.add("transfer".to_string(), rule.to_v1()).unwrap();
+            rmp_serde::to_vec(&rule_set).unwrap()
+        })
+        .collect()
+}
+
+fn v2_rule_sets() -> Vec<Vec<u8>> {
+    rules()
+        .iter()
+        .map(|rule| {
+            let rule = rule.to_v2().unwrap();
+            RuleSetV2::serialize(
+                Pubkey::default(),
+                "Seed",
+                &["transfer".to_string()],
+                &[&rule],
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn fuzz_rule_set_v1() {
+    run(rule_set_v1, &v1_rule_sets());
+}
+
+#[test]
+fn fuzz_rule_set_v2() {
+    run(rule_set_v2, &v2_rule_sets());
+}
+
+#[test]
+fn fuzz_rule_v2() {
+    let seeds = rules()
+        .iter()
+        .map(|rule| rule.to_v2().unwrap())
+        .collect::<Vec<_>>();
+
+    run(rule_v2, &seeds);
+}
+
+#[test]
+fn fuzz_revision_map() {
+    // PDA data with the layout written by `CreateOrUpdate`, V2 revisions are 8-byte aligned
+    let seeds = v1_rule_sets()
+        .into_iter()
+        .zip(v2_rule_sets())
+        .map(|(v1, v2)| {
+            let start = RULE_SET_SERIALIZED_HEADER_LEN.next_multiple_of(8);
+            let revisions = vec![start, start + v2.len()];
+            let location = revisions[1] + 1 + v1.len();
+
+            let mut data = borsh::to_vec(&RuleSetHeader::new(location)).unwrap();
+            data.resize(start, 0);
+            data.extend(v2);
+            data.push(1);
+            data.extend(v1);
+            data.push(RULE_SET_REV_MAP_VERSION);
+            RuleSetRevisionMapV1 {
+                rule_set_revisions: revisions,
+            }
+            .serialize(&mut data)
+            .unwrap();
+
+            data
+        })
+        .collect::<Vec<_>>();
+
+    run(revision_map, &seeds);
+}
+
+#[test]
+fn fuzz_instruction() {
+    let seeds = [
+        RuleSetInstruction::CreateOrUpdate(CreateOrUpdateArgs::V1 {
+            serialized_rule_set: vec![1, 2, 3],
+        }),
+        RuleSetInstruction::Validate(ValidateArgs::V1 {
+            operation: "transfer".to_string(),
+            payload: Payload::from([("Amount".to_string(), PayloadType::Number(1))]),
+            update_rule_state: false,
+            rule_set_revision: Some(0),
+        }),
+    ]
+    .iter()
+    .map(|instruction| borsh::to_vec(instruction).unwrap())
+    .collect::<Vec<_>>();
+
+    run(instruction, &seeds);
+}
+
+#[test]
+fn fuzz_payload() {
+    let seeds = [Payload::from([
+        ("Amount".to_string(), PayloadType::Number(1)),
+        (
+            "Destination".to_string(),
+            PayloadType::Pubkey(Pubkey::default()),
+        ),
+        ("Flag".to_string(), PayloadType::Bool(true)),
+    ])]
+    .iter()
+    .map(|payload| borsh::to_vec(payload).unwrap())
+    .collect::<Vec<_>>();
+
+    run(payload, &seeds);
+}
+
+#[test]
+fn fuzz_validate() {
+    run_structured::<ValidateInput>(validate);
+}
+
+#[test]
+fn fuzz_differential() {
+    run_structured::<DifferentialInput>(differential);
+}
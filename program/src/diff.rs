@@ -0,0 +1,549 @@
+//! Differences between two revisions of a `RuleSet`.
+//!
+//! Revisions are decoded from the data of a `RuleSet` PDA through its revision map and
+//! decompiled to a `Policy`, so that a V1 revision can be compared with a V2 revision.  The
+//! rules of operations and definitions with the same name are compared as trees: the rules of
+//! `all`, `any` and `threshold` are aligned so that inserting a rule does not change the rules
+//! after it, and the lists of `pubkey_list_match` and `program_owned_list` rules are compared
+//! entry by entry.  Any other difference replaces the smallest subtree containing it.
+//!
+//! A `RevisionDiff` is serializable for machine-readable output and prints as text:
+//!
+//! ```text
+//! ~ owner AuthxYNhPnnrGBo1wdzeUdukrsFpHvR42wghx8ZPNEo4 -> 11111111111111111111111111111111
+//! ~ definition allowed > any[0] list
+//!     + M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K
+//! + operation Delegate:Sale => pass
+//! ~ operation Transfer:Owner > all[0]
+//!     - pass
+//!     + is_wallet(Destination)
+//! ```
+use crate::{
+    dsl::{write_name, Expr, ExprKind, Policy, Statement},
+    error::RuleSetError,
+    state::{RuleSetV1, RuleSetV2},
+    types::LibVersion,
+    utils::get_rule_set_revision_location_from_data,
+};
+use serde::Serialize;
+use solana_program::program_error::ProgramError;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// Rules whose last argument is a list of pubkeys compared entry by entry.
+const LIST_RULES: [&str; 4] = [
+    "pubkey_list_match",
+    "pubkey_list_match_sorted",
+    "program_owned_list",
+    "program_owned_set",
+];
+
+/// Decode a revision of the `RuleSet` stored in the data of a `RuleSet` PDA.  If `revision` is
+/// `None`, the latest revision is decoded.
+pub fn decode_revision(data: &[u8], revision: Option<usize>) -> Result<Policy, ProgramError> {
+    let (start, end) = get_rule_set_revision_location_from_data(data, revision)?;
+    let rule_set_data = data.get(start..end).ok_or(RuleSetError::DataTypeMismatch)?;
+
+    match rule_set_data.first() {
+        Some(lib_version) => match LibVersion::try_from(*lib_version)? {
+            LibVersion::V1 => {
+                let rule_set = rmp_serde::from_slice::<RuleSetV1>(&rule_set_data[1..])
+                    .map_err(|_| RuleSetError::MessagePackDeserializationError)?;
+
+                Ok(Policy::from_v1(&rule_set))
+            }
+            LibVersion::V2 => {
+                // A `RuleSetV2` is read in place, which requires the alignment the PDA data has
+                // on chain.
+                let mut aligned = vec![0u64; rule_set_data.len().div_ceil(8)];
+                let bytes =
+                    &mut bytemuck::cast_slice_mut::<u64, u8>(&mut aligned)[..rule_set_data.len()];
+                bytes.copy_from_slice(rule_set_data);
+
+                let rule_set = RuleSetV2::from_bytes(bytes)?;
+
+                Ok(Policy::from_v2(&rule_set)?)
+            }
+        },
+        None => Err(RuleSetError::DataTypeMismatch.into()),
+    }
+}
+
+/// Decode two revisions of the `RuleSet` stored in the data of a `RuleSet` PDA and compare
+/// them.
+pub fn diff_revisions(
+    data: &[u8],
+    old_revision: usize,
+    new_revision: usize,
+) -> Result<RevisionDiff, ProgramError> {
+    let old = decode_revision(data, Some(old_revision))?;
+    let new = decode_revision(data, Some(new_revision))?;
+
+    Ok(RevisionDiff::new(&old, &new))
+}
+
+/// Whether a rule belongs to an operation or to a rule definition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Scope {
+    /// The rule of an operation.
+    Operation,
+    /// A rule definition referenced with `rule_ref`.
+    Definition,
+}
+
+/// A step from a rule to one of its arguments.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Step {
+    /// Name of the rule, such as `all`.
+    pub rule: String,
+    /// Index of the argument in the call, so the first rule of a `threshold` is at index 1.
+    pub index: usize,
+}
+
+/// The location of a rule in a `RuleSet`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RulePath {
+    /// Whether the path starts at an operation or a definition.
+    pub scope: Scope,
+    /// Name of the operation or the definition.
+    pub name: String,
+    /// Steps from the rule of the operation or the definition.  A path without steps is the
+    /// whole operation or definition.
+    pub steps: Vec<Step>,
+}
+
+impl RulePath {
+    fn step(&self, rule: &str, index: usize) -> Self {
+        let mut path = self.clone();
+        path.steps.push(Step {
+            rule: rule.to_string(),
+            index,
+        });
+        path
+    }
+}
+
+/// A change between two revisions.  Rules and pubkeys are printed in the policy language.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "camelCase")]
+pub enum Change {
+    /// The `RuleSet` was renamed.
+    Renamed {
+        /// Name in the old revision.
+        old: String,
+        /// Name in the new revision.
+        new: String,
+    },
+    /// The owner of the `RuleSet` changed.
+    OwnerChanged {
+        /// Owner in the old revision.
+        old: String,
+        /// Owner in the new revision.
+        new: String,
+    },
+    /// An operation, a definition or a rule of a group was added.  The path is in the new
+    /// revision.
+    Added {
+        /// Location of the added rule.
+        path: RulePath,
+        /// The added rule.
+        rule: String,
+    },
+    /// An operation, a definition or a rule of a group was removed.  The path is in the old
+    /// revision.
+    Removed {
+        /// Location of the removed rule.
+        path: RulePath,
+        /// The removed rule.
+        rule: String,
+    },
+    /// A rule was replaced.  The path is in the new revision.
+    Changed {
+        /// Location of the rule.
+        path: RulePath,
+        /// The rule in the old revision.
+        old: String,
+        /// The rule in the new revision.
+        new: String,
+    },
+    /// Pubkeys were added to or removed from the list of a `pubkey_list_match` or a
+    /// `program_owned_list` rule.  The path is in the new revision.
+    ListChanged {
+        /// Location of the rule.
+        path: RulePath,
+        /// Pubkeys only in the new revision.
+        added: Vec<String>,
+        /// Pubkeys only in the old revision.
+        removed: Vec<String>,
+    },
+}
+
+/// The changes between two revisions, in the order of the names of the changed definitions
+/// and operations.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct RevisionDiff {
+    /// The changes, with the definitions before the operations.
+    pub changes: Vec<Change>,
+}
+
+impl RevisionDiff {
+    /// Compare two decompiled revisions.
+    pub fn new(old: &Policy, new: &Policy) -> Self {
+        let mut changes = Vec::new();
+
+        let name = |policy: &Policy| policy.name.as_ref().map(|(name, _)| name.clone());
+        let owner = |policy: &Policy| policy.owner.as_ref().map(Expr::to_string);
+
+        if let (Some(old), Some(new)) = (name(old), name(new)) {
+            if old != new {
+                changes.push(Change::Renamed { old, new });
+            }
+        }
+
+        if let (Some(old), Some(new)) = (owner(old), owner(new)) {
+            if old != new {
+                changes.push(Change::OwnerChanged { old, new });
+            }
+        }
+
+        diff_statements(
+            Scope::Definition,
+            &old.definitions,
+            &new.definitions,
+            &mut changes,
+        );
+        diff_statements(
+            Scope::Operation,
+            &old.operations,
+            &new.operations,
+            &mut changes,
+        );
+
+        Self { changes }
+    }
+
+    /// Returns whether the revisions are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the names of the operations only in the new revision.
+    pub fn added_operations(&self) -> Vec<&str> {
+        self.whole_operations(|change| match change {
+            Change::Added { path, .. } => Some(path),
+            _ => None,
+        })
+    }
+
+    /// Returns the names of the operations only in the old revision.
+    pub fn removed_operations(&self) -> Vec<&str> {
+        self.whole_operations(|change| match change {
+            Change::Removed { path, .. } => Some(path),
+            _ => None,
+        })
+    }
+
+    fn whole_operations(&self, path: impl Fn(&Change) -> Option<&RulePath>) -> Vec<&str> {
+        self.changes
+            .iter()
+            .filter_map(path)
+            .filter(|path| path.scope == Scope::Operation && path.steps.is_empty())
+            .map(|path| path.name.as_str())
+            .collect()
+    }
+}
+
+/// Compares the operations or the definitions of two revisions by name.
+fn diff_statements(scope: Scope, old: &[Statement], new: &[Statement], changes: &mut Vec<Change>) {
+    let mut statements = BTreeMap::<&str, (Option<&Expr>, Option<&Expr>)>::new();
+
+    for statement in old {
+        statements.entry(&statement.name).or_default().0 = Some(&statement.value);
+    }
+
+    for statement in new {
+        statements.entry(&statement.name).or_default().1 = Some(&statement.value);
+    }
+
+    for (name, rules) in statements {
+        let path = RulePath {
+            scope,
+            name: name.to_string(),
+            steps: Vec::new(),
+        };
+
+        match rules {
+            (Some(old), Some(new)) => diff_rule(&path, old, new, changes),
+            (Some(old), None) => changes.push(Change::Removed {
+                path,
+                rule: old.to_string(),
+            }),
+            (None, Some(new)) => changes.push(Change::Added {
+                path,
+                rule: new.to_string(),
+            }),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Returns whether two expressions are the same regardless of their location in the source.
+fn same(a: &Expr, b: &Expr) -> bool {
+    match (&a.kind, &b.kind) {
+        (
+            ExprKind::Call { name, args },
+            ExprKind::Call {
+                name: other_name,
+                args: other_args,
+            },
+        ) => name == other_name && same_all(args, other_args),
+        (ExprKind::List(list), ExprKind::List(other)) => same_all(list, other),
+        (kind, other) => kind == other,
+    }
+}
+
+fn same_all(a: &[Expr], b: &[Expr]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b))
+}
+
+/// Returns the name and the arguments of a call.
+fn call(expr: &Expr) -> Option<(&str, &[Expr])> {
+    match &expr.kind {
+        ExprKind::Call { name, args } => Some((name, args)),
+        _ => None,
+    }
+}
+
+/// Compares two rules at the same location.
+fn diff_rule(path: &RulePath, old: &Expr, new: &Expr, changes: &mut Vec<Change>) {
+    if same(old, new) {
+        return;
+    }
+
+    if let (Some((name, old_args)), Some((new_name, new_args))) = (call(old), call(new)) {
+        if name == new_name && diff_args(path, name, old_args, new_args, changes) {
+            return;
+        }
+    }
+
+    changes.push(Change::Changed {
+        path: path.clone(),
+        old: old.to_string(),
+        new: new.to_string(),
+    });
+}
+
+/// Compares the arguments of two calls of the same rule, returning `false` if the calls can
+/// only be compared as a whole.
+fn diff_args(
+    path: &RulePath,
+    name: &str,
+    old: &[Expr],
+    new: &[Expr],
+    changes: &mut Vec<Change>,
+) -> bool {
+    match name {
+        "all" | "any" => diff_group(path, name, 0, old, new, changes),
+        // The rules of a threshold are compared if the count is the same.
+        "threshold" if !old.is_empty() && !new.is_empty() && same(&old[0], &new[0]) => {
+            diff_group(path, name, 1, &old[1..], &new[1..], changes);
+        }
+        "not" | "if_then_else" if old.len() == new.len() => {
+            for (index, (old, new)) in old.iter().zip(new).enumerate() {
+                diff_rule(&path.step(name, index), old, new, changes);
+            }
+        }
+        name if LIST_RULES.contains(&name) => {
+            let (
+                Some((ExprKind::List(old_list), old_rest)),
+                Some((ExprKind::List(new_list), new_rest)),
+            ) = (
+                old.split_last().map(|(last, rest)| (&last.kind, rest)),
+                new.split_last().map(|(last, rest)| (&last.kind, rest)),
+            )
+            else {
+                return false;
+            };
+
+            let added = difference(new_list, old_list);
+            let removed = difference(old_list, new_list);
+
+            // A list that is only reordered is replaced as a whole.
+            if !same_all(old_rest, new_rest) || (added.is_empty() && removed.is_empty()) {
+                return false;
+            }
+
+            changes.push(Change::ListChanged {
+                path: path.clone(),
+                added,
+                removed,
+            });
+        }
+        _ => return false,
+    }
+
+    true
+}
+
+/// Returns the entries of `list` that are not in `other`.
+fn difference(list: &[Expr], other: &[Expr]) -> Vec<String> {
+    list.iter()
+        .filter(|entry| !other.iter().any(|other| same(entry, other)))
+        .map(Expr::to_string)
+        .collect()
+}
+
+/// Returns the name of a rule, which is written as a call or as a word.
+fn kind(rule: &Expr) -> Option<&str> {
+    match &rule.kind {
+        ExprKind::Call { name, .. } | ExprKind::Word(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// Compares the rules of two groups, starting at argument `offset`.  The rules common to both
+/// groups are aligned, and each rule between them is compared with a rule of the same kind
+/// from the other group or is added or removed.
+fn diff_group(
+    path: &RulePath,
+    name: &str,
+    offset: usize,
+    old: &[Expr],
+    new: &[Expr],
+    changes: &mut Vec<Change>,
+) {
+    // Longest common subsequence of the rules, computed from the end of both groups.
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if same(&old[i], &new[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut removed = Vec::<usize>::new();
+    let mut added = Vec::<usize>::new();
+
+    loop {
+        let common = i < old.len() && j < new.len() && same(&old[i], &new[j]);
+
+        if common || (i == old.len() && j == new.len()) {
+            // Rules between two common rules are compared with the next rule of the same kind.
+            for old_index in removed.drain(..) {
+                let same_kind = added
+                    .iter()
+                    .position(|&new_index| kind(&old[old_index]) == kind(&new[new_index]));
+
+                match same_kind {
+                    Some(position) => {
+                        let new_index = added.remove(position);
+                        diff_rule(
+                            &path.step(name, offset + new_index),
+                            &old[old_index],
+                            &new[new_index],
+                            changes,
+                        );
+                    }
+                    None => changes.push(Change::Removed {
+                        path: path.step(name, offset + old_index),
+                        rule: old[old_index].to_string(),
+                    }),
+                }
+            }
+
+            for new_index in added.drain(..) {
+                changes.push(Change::Added {
+                    path: path.step(name, offset + new_index),
+                    rule: new[new_index].to_string(),
+                });
+            }
+
+            if !common {
+                break;
+            }
+
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(i);
+            i += 1;
+        } else {
+            added.push(j);
+            j += 1;
+        }
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Scope::Operation => formatter.write_str("operation"),
+            Scope::Definition => formatter.write_str("definition"),
+        }
+    }
+}
+
+impl Display for RulePath {
+    /// Prints the path as the operation or the definition followed by the steps, such as
+    /// `operation Transfer:Owner > all[1] > any[0]`.
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        write!(formatter, "{} ", self.scope)?;
+        write_name(formatter, &self.name)?;
+
+        for step in &self.steps {
+            write!(formatter, " > {}[{}]", step.rule, step.index)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Change::Renamed { old, new } => write!(formatter, "~ name {:?} -> {:?}", old, new),
+            Change::OwnerChanged { old, new } => write!(formatter, "~ owner {} -> {}", old, new),
+            Change::Added { path, rule } => write!(formatter, "+ {} => {}", path, rule),
+            Change::Removed { path, rule } => write!(formatter, "- {} => {}", path, rule),
+            Change::Changed { path, old, new } => {
+                write!(formatter, "~ {}\n    - {}\n    + {}", path, old, new)
+            }
+            Change::ListChanged {
+                path,
+                added,
+                removed,
+            } => {
+                write!(formatter, "~ {} list", path)?;
+
+                for pubkey in removed {
+                    write!(formatter, "\n    - {}", pubkey)?;
+                }
+
+                for pubkey in added {
+                    write!(formatter, "\n    + {}", pubkey)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for RevisionDiff {
+    /// Prints one change after the other, as in the module documentation.
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        for change in &self.changes {
+            writeln!(formatter, "{}", change)?;
+        }
+
+        Ok(())
+    }
+}
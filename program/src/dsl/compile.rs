@@ -0,0 +1,708 @@
+//! Compiles a policy to a `RuleSetV1` or a `RuleSetV2`.
+use super::{DslError, Expr, ExprKind, Policy, Span, Statement};
+use crate::{
+    error::RuleSetError,
+    state::{
+        All, Any, CompareOp, IfThenElse, MatchOp, Not, PubkeyListMatch, Rule, RuleSetV1, RuleSetV2,
+        Threshold,
+    },
+    types::MAX_NAME_LENGTH,
+};
+use solana_program::pubkey::Pubkey;
+use std::{collections::HashMap, str::FromStr};
+
+/// Resolves the constants and the arguments of the rules of a policy.
+struct Compiler<'p> {
+    constants: HashMap<&'p str, &'p Expr>,
+}
+
+/// Checks that no two items share a name.
+fn assert_unique(statements: &[Statement], kind: &str) -> Result<(), DslError> {
+    let mut names = HashMap::new();
+
+    for statement in statements {
+        if names
+            .insert(statement.name.as_str(), statement.span)
+            .is_some()
+        {
+            return Err(DslError::new(
+                format!("the {} `{}` is declared twice", kind, statement.name),
+                statement.span,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn assert_name_length(name: &str, span: Span) -> Result<(), DslError> {
+    if name.len() > MAX_NAME_LENGTH {
+        Err(DslError::new(
+            format!("names are limited to {} bytes", MAX_NAME_LENGTH),
+            span,
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the arguments of a call, checking their number.
+fn arguments<'e, const N: usize>(
+    name: &str,
+    args: &'e [Expr],
+    span: Span,
+) -> Result<&'e [Expr; N], DslError> {
+    args.try_into().map_err(|_| {
+        DslError::new(
+            format!(
+                "`{}` expects {} argument{}, found {}",
+                name,
+                N,
+                if N == 1 { "" } else { "s" },
+                args.len()
+            ),
+            span,
+        )
+    })
+}
+
+/// Returns the rules of a group, which must not be empty.
+fn group<'e>(name: &str, args: &'e [Expr], span: Span) -> Result<&'e [Expr], DslError> {
+    if args.is_empty() {
+        Err(DslError::new(
+            format!("`{}` expects at least one rule", name),
+            span,
+        ))
+    } else {
+        Ok(args)
+    }
+}
+
+fn slices(rules: &[Vec<u8>]) -> Vec<&[u8]> {
+    rules.iter().map(Vec::as_slice).collect()
+}
+
+fn expected(what: &str, expr: &Expr) -> DslError {
+    DslError::new(format!("expected {}", what), expr.span)
+}
+
+impl<'p> Compiler<'p> {
+    fn new(policy: &'p Policy) -> Result<Self, DslError> {
+        assert_unique(&policy.constants, "constant")?;
+        assert_unique(&policy.definitions, "definition")?;
+        assert_unique(&policy.operations, "operation")?;
+
+        let constants = policy
+            .constants
+            .iter()
+            .map(|constant| (constant.name.as_str(), &constant.value))
+            .collect();
+
+        Ok(Self { constants })
+    }
+
+    /// Follows constants until an expression that is not a constant.
+    fn resolve<'e>(&'e self, mut expr: &'e Expr) -> Result<&'e Expr, DslError> {
+        // Each constant can be followed at most once without a cycle.
+        for _ in 0..=self.constants.len() {
+            match &expr.kind {
+                ExprKind::Word(word) => match self.constants.get(word.as_str()) {
+                    Some(value) => expr = value,
+                    None => return Ok(expr),
+                },
+                _ => return Ok(expr),
+            }
+        }
+
+        Err(DslError::new("constants reference each other", expr.span))
+    }
+
+    fn field(&self, expr: &Expr) -> Result<String, DslError> {
+        let field = match &expr.kind {
+            ExprKind::Word(word) if !word.starts_with(|c: char| c.is_ascii_digit()) => word.clone(),
+            ExprKind::Fields(fields) => fields.join("|"),
+            ExprKind::Str(value) => value.clone(),
+            _ => return Err(expected("a payload field", expr)),
+        };
+
+        assert_name_length(&field, expr.span)?;
+
+        Ok(field)
+    }
+
+    fn name(&self, expr: &Expr) -> Result<String, DslError> {
+        let name = match &expr.kind {
+            ExprKind::Word(word) => word.clone(),
+            ExprKind::Str(value) => value.clone(),
+            _ => return Err(expected("a name", expr)),
+        };
+
+        assert_name_length(&name, expr.span)?;
+
+        Ok(name)
+    }
+
+    fn string(&self, expr: &Expr) -> Result<String, DslError> {
+        match &expr.kind {
+            ExprKind::Str(value) => Ok(value.clone()),
+            _ => Err(expected("a string", expr)),
+        }
+    }
+
+    fn pubkey(&self, expr: &Expr) -> Result<Pubkey, DslError> {
+        match &self.resolve(expr)?.kind {
+            ExprKind::Word(word) => Pubkey::from_str(word)
+                .map_err(|_| DslError::new(format!("`{}` is not a pubkey", word), expr.span)),
+            _ => Err(expected("a pubkey", expr)),
+        }
+    }
+
+    fn pubkeys(&self, expr: &Expr) -> Result<Vec<Pubkey>, DslError> {
+        match &self.resolve(expr)?.kind {
+            ExprKind::List(items) => items.iter().map(|item| self.pubkey(item)).collect(),
+            _ => Err(expected("a list of pubkeys", expr)),
+        }
+    }
+
+    fn number<T: FromStr>(&self, expr: &Expr, what: &str) -> Result<T, DslError> {
+        match &expr.kind {
+            ExprKind::Word(word) => word.parse().map_err(|_| expected(what, expr)),
+            _ => Err(expected(what, expr)),
+        }
+    }
+
+    fn bool(&self, expr: &Expr) -> Result<bool, DslError> {
+        match &expr.kind {
+            ExprKind::Word(word) if word == "true" => Ok(true),
+            ExprKind::Word(word) if word == "false" => Ok(false),
+            _ => Err(expected("`true` or `false`", expr)),
+        }
+    }
+
+    fn bytes(&self, expr: &Expr) -> Result<Vec<u8>, DslError> {
+        let invalid = || expected("hex bytes starting with `0x`", expr);
+
+        let digits = match &expr.kind {
+            ExprKind::Word(word) => word.strip_prefix("0x").ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        };
+
+        if digits.len() % 2 != 0 || !digits.is_ascii() {
+            return Err(invalid());
+        }
+
+        (0..digits.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&digits[index..index + 2], 16).map_err(|_| invalid()))
+            .collect()
+    }
+
+    fn root(&self, expr: &Expr) -> Result<[u8; 32], DslError> {
+        self.bytes(expr)?
+            .try_into()
+            .map_err(|_| expected("a 32 byte root", expr))
+    }
+
+    fn compare_op(&self, expr: &Expr) -> Result<CompareOp, DslError> {
+        match &expr.kind {
+            ExprKind::Operator(operator) => match operator.as_str() {
+                "<" => Ok(CompareOp::Lt),
+                "<=" => Ok(CompareOp::LtEq),
+                "==" => Ok(CompareOp::Eq),
+                ">=" => Ok(CompareOp::GtEq),
+                ">" => Ok(CompareOp::Gt),
+                _ => Err(expected("a comparison operator", expr)),
+            },
+            _ => Err(expected("a comparison operator", expr)),
+        }
+    }
+
+    fn match_op(&self, expr: &Expr) -> Result<MatchOp, DslError> {
+        match &expr.kind {
+            ExprKind::Operator(operator) if operator == "==" => Ok(MatchOp::Eq),
+            ExprKind::Word(word) if word == "prefix" => Ok(MatchOp::Prefix),
+            _ => Err(expected("`==` or `prefix`", expr)),
+        }
+    }
+
+    /// Compiles a rule to its `Rule`.
+    fn rule(&self, expr: &Expr) -> Result<Rule, DslError> {
+        let (name, args) = match &expr.kind {
+            ExprKind::Call { name, args } => (name.as_str(), args.as_slice()),
+            ExprKind::Word(name) => (name.as_str(), &[] as &[Expr]),
+            _ => return Err(expected("a rule", expr)),
+        };
+        let span = expr.span;
+
+        let rules = |args: &[Expr]| {
+            args.iter()
+                .map(|rule| self.rule(rule))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let rule = match name {
+            "pass" => {
+                arguments::<0>(name, args, span)?;
+                Rule::Pass
+            }
+            "namespace" => {
+                arguments::<0>(name, args, span)?;
+                Rule::Namespace
+            }
+            "all" => Rule::All {
+                rules: rules(group(name, args, span)?)?,
+            },
+            "any" => Rule::Any {
+                rules: rules(group(name, args, span)?)?,
+            },
+            "not" => {
+                let [rule] = arguments(name, args, span)?;
+                Rule::Not {
+                    rule: Box::new(self.rule(rule)?),
+                }
+            }
+            "threshold" => {
+                let (k, rest) = args.split_first().ok_or_else(|| {
+                    DslError::new("`threshold` expects a count and at least one rule", span)
+                })?;
+
+                Rule::Threshold {
+                    k: self.number(k, "a count")?,
+                    rules: rules(group(name, rest, span)?)?,
+                }
+            }
+            "if_then_else" => {
+                let [condition, then, otherwise] = arguments(name, args, span)?;
+                Rule::IfThenElse {
+                    condition: Box::new(self.rule(condition)?),
+                    then: Box::new(self.rule(then)?),
+                    otherwise: Box::new(self.rule(otherwise)?),
+                }
+            }
+            "rule_ref" => {
+                let [definition] = arguments(name, args, span)?;
+                Rule::RuleRef {
+                    name: self.name(definition)?,
+                }
+            }
+            "external_rule_set" => {
+                let (rule_set, operation, revision) = match args {
+                    [rule_set, operation] => (rule_set, operation, None),
+                    [rule_set, operation, revision] => (
+                        rule_set,
+                        operation,
+                        Some(self.number(revision, "a revision")?),
+                    ),
+                    _ => {
+                        return Err(DslError::new(
+                            "`external_rule_set` expects a rule set, an operation and an \
+                             optional revision",
+                            span,
+                        ))
+                    }
+                };
+
+                Rule::ExternalRuleSet {
+                    rule_set: self.pubkey(rule_set)?,
+                    operation: self.name(operation)?,
+                    revision,
+                }
+            }
+            "additional_signer" => {
+                let [account] = arguments(name, args, span)?;
+                Rule::AdditionalSigner {
+                    account: self.pubkey(account)?,
+                }
+            }
+            "frequency" => {
+                let [authority] = arguments(name, args, span)?;
+                Rule::Frequency {
+                    authority: self.pubkey(authority)?,
+                }
+            }
+            "pubkey_match" => {
+                let [field, pubkey] = arguments(name, args, span)?;
+                Rule::PubkeyMatch {
+                    pubkey: self.pubkey(pubkey)?,
+                    field: self.field(field)?,
+                }
+            }
+            "pubkey_list_match" => {
+                let [field, pubkeys] = arguments(name, args, span)?;
+                Rule::PubkeyListMatch {
+                    pubkeys: self.pubkeys(pubkeys)?,
+                    field: self.field(field)?,
+                }
+            }
+            "pubkey_list_match_sorted" => {
+                let [field, pubkeys] = arguments(name, args, span)?;
+                let mut pubkeys = self.pubkeys(pubkeys)?;
+                pubkeys.sort();
+                pubkeys.dedup();
+
+                Rule::PubkeyListMatch {
+                    pubkeys,
+                    field: self.field(field)?,
+                }
+            }
+            "pubkey_list_contains" => {
+                let [field, pubkey] = arguments(name, args, span)?;
+                Rule::PubkeyListContains {
+                    pubkey: self.pubkey(pubkey)?,
+                    field: self.field(field)?,
+                }
+            }
+            "program_owned" => {
+                let [field, program] = arguments(name, args, span)?;
+                Rule::ProgramOwned {
+                    program: self.pubkey(program)?,
+                    field: self.field(field)?,
+                }
+            }
+            "program_owned_list" => {
+                let [field, programs] = arguments(name, args, span)?;
+                Rule::ProgramOwnedList {
+                    programs: self.pubkeys(programs)?,
+                    field: self.field(field)?,
+                }
+            }
+            "program_owned_set" => {
+                let [field, programs] = arguments(name, args, span)?;
+                Rule::ProgramOwnedSet {
+                    programs: self.pubkeys(programs)?.into_iter().collect(),
+                    field: self.field(field)?,
+                }
+            }
+            "pubkey_tree_match" | "program_owned_tree" | "pubkey_tree_exclusion" => {
+                let [pubkey_field, proof_field, root] = arguments(name, args, span)?;
+                let root = self.root(root)?;
+                let pubkey_field = self.field(pubkey_field)?;
+                let proof_field = self.field(proof_field)?;
+
+                match name {
+                    "pubkey_tree_match" => Rule::PubkeyTreeMatch {
+                        root,
+                        pubkey_field,
+                        proof_field,
+                    },
+                    "program_owned_tree" => Rule::ProgramOwnedTree {
+                        root,
+                        pubkey_field,
+                        proof_field,
+                    },
+                    _ => Rule::PubkeyTreeExclusion {
+                        root,
+                        pubkey_field,
+                        proof_field,
+                    },
+                }
+            }
+            "pubkey_tree_match_registry" | "program_owned_tree_registry" => {
+                let [pubkey_field, proof_field, registry] = arguments(name, args, span)?;
+                let registry = self.pubkey(registry)?;
+                let pubkey_field = self.field(pubkey_field)?;
+                let proof_field = self.field(proof_field)?;
+
+                if name == "pubkey_tree_match_registry" {
+                    Rule::PubkeyTreeMatchRegistry {
+                        registry,
+                        pubkey_field,
+                        proof_field,
+                    }
+                } else {
+                    Rule::ProgramOwnedTreeRegistry {
+                        registry,
+                        pubkey_field,
+                        proof_field,
+                    }
+                }
+            }
+            "pubkey_in_list_account" => {
+                let [field, list] = arguments(name, args, span)?;
+                Rule::PubkeyInListAccount {
+                    list: self.pubkey(list)?,
+                    field: self.field(field)?,
+                }
+            }
+            "owner_in_list_account" => {
+                let [field, list] = arguments(name, args, span)?;
+                Rule::OwnerInListAccount {
+                    list: self.pubkey(list)?,
+                    field: self.field(field)?,
+                }
+            }
+            "pda_match" => {
+                let (pda_field, seeds_field, program) = match args {
+                    [pda_field, seeds_field] => (pda_field, seeds_field, None),
+                    [pda_field, seeds_field, program] => {
+                        (pda_field, seeds_field, Some(self.pubkey(program)?))
+                    }
+                    _ => {
+                        return Err(DslError::new(
+                            "`pda_match` expects a pda field, a seeds field and an optional \
+                             program",
+                            span,
+                        ))
+                    }
+                };
+
+                Rule::PDAMatch {
+                    program,
+                    pda_field: self.field(pda_field)?,
+                    seeds_field: self.field(seeds_field)?,
+                }
+            }
+            "is_wallet" => {
+                let [field] = arguments(name, args, span)?;
+                Rule::IsWallet {
+                    field: self.field(field)?,
+                }
+            }
+            "amount" => {
+                let [field, operator, amount] = arguments(name, args, span)?;
+                Rule::Amount {
+                    amount: self.number(amount, "an unsigned 64-bit integer")?,
+                    operator: self.compare_op(operator)?,
+                    field: self.field(field)?,
+                }
+            }
+            "amount_i64" => {
+                let [field, operator, amount] = arguments(name, args, span)?;
+                Rule::AmountI64 {
+                    amount: self.number(amount, "a signed 64-bit integer")?,
+                    operator: self.compare_op(operator)?,
+                    field: self.field(field)?,
+                }
+            }
+            "amount_u128" => {
+                let [field, operator, amount] = arguments(name, args, span)?;
+                Rule::AmountU128 {
+                    amount: self.number(amount, "an unsigned 128-bit integer")?,
+                    operator: self.compare_op(operator)?,
+                    field: self.field(field)?,
+                }
+            }
+            "amount_range" => {
+                let [field, min, max] = arguments(name, args, span)?;
+                Rule::AmountRange {
+                    min: self.number(min, "an unsigned 64-bit integer")?,
+                    max: self.number(max, "an unsigned 64-bit integer")?,
+                    field: self.field(field)?,
+                }
+            }
+            "amount_ratio" => {
+                let [numerator_field, denominator_field, num, denom] = arguments(name, args, span)?;
+                Rule::AmountRatio {
+                    num: self.number(num, "an unsigned 64-bit integer")?,
+                    denom: self.number(denom, "an unsigned 64-bit integer")?,
+                    numerator_field: self.field(numerator_field)?,
+                    denominator_field: self.field(denominator_field)?,
+                }
+            }
+            "field_compare" => {
+                let [left_field, operator, right_field] = arguments(name, args, span)?;
+                Rule::FieldCompare {
+                    left_field: self.field(left_field)?,
+                    operator: self.compare_op(operator)?,
+                    right_field: self.field(right_field)?,
+                }
+            }
+            "bytes_match" => {
+                let [field, operator, value] = arguments(name, args, span)?;
+                Rule::BytesMatch {
+                    value: self.bytes(value)?,
+                    operator: self.match_op(operator)?,
+                    field: self.field(field)?,
+                }
+            }
+            "str_match" => {
+                let [field, operator, value] = arguments(name, args, span)?;
+                Rule::StrMatch {
+                    value: self.string(value)?,
+                    operator: self.match_op(operator)?,
+                    field: self.field(field)?,
+                }
+            }
+            "bool_match" => {
+                let [field, value] = arguments(name, args, span)?;
+                Rule::BoolMatch {
+                    value: self.bool(value)?,
+                    field: self.field(field)?,
+                }
+            }
+            _ => {
+                return Err(DslError::new(
+                    format!("unknown rule `{}`", name),
+                    match &expr.kind {
+                        ExprKind::Call { .. } => Span::new(span.start, span.start + name.len()),
+                        _ => span,
+                    },
+                ))
+            }
+        };
+
+        Ok(rule)
+    }
+
+    /// Compiles a rule to a serialized `RuleV2`.  Rules are compiled with `Rule::to_v2`,
+    /// except for `pubkey_list_match_sorted` and the groups that may contain it.
+    fn rule_v2(&self, expr: &Expr) -> Result<Vec<u8>, DslError> {
+        let to_error = |error: RuleSetError| DslError::new(error.to_string(), expr.span);
+
+        let (name, args) = match &expr.kind {
+            ExprKind::Call { name, args } => (name.as_str(), args.as_slice()),
+            _ => return self.rule(expr)?.to_v2().map_err(to_error),
+        };
+        let span = expr.span;
+
+        let rules = |args: &[Expr]| {
+            args.iter()
+                .map(|rule| self.rule_v2(rule))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        match name {
+            "all" => All::serialize(&slices(&rules(group(name, args, span)?)?)),
+            "any" => Any::serialize(&slices(&rules(group(name, args, span)?)?)),
+            "not" => {
+                let [rule] = arguments(name, args, span)?;
+                Not::serialize(&self.rule_v2(rule)?)
+            }
+            "threshold" => {
+                let (k, rest) = args.split_first().ok_or_else(|| {
+                    DslError::new("`threshold` expects a count and at least one rule", span)
+                })?;
+
+                Threshold::serialize(
+                    self.number(k, "a count")?,
+                    &slices(&rules(group(name, rest, span)?)?),
+                )
+            }
+            "if_then_else" => {
+                let [condition, then, otherwise] = arguments(name, args, span)?;
+                IfThenElse::serialize(
+                    &self.rule_v2(condition)?,
+                    &self.rule_v2(then)?,
+                    &self.rule_v2(otherwise)?,
+                )
+            }
+            "pubkey_list_match_sorted" => {
+                let [field, pubkeys] = arguments(name, args, span)?;
+                PubkeyListMatch::serialize_sorted(self.field(field)?, &self.pubkeys(pubkeys)?)
+            }
+            _ => self.rule(expr)?.to_v2(),
+        }
+        .map_err(to_error)
+    }
+
+    /// Checks that every `rule_ref` names a definition.
+    fn assert_defined(&self, expr: &Expr, definitions: &[Statement]) -> Result<(), DslError> {
+        if let ExprKind::Call { name, args } = &expr.kind {
+            if name == "rule_ref" {
+                if let [definition] = args.as_slice() {
+                    let definition_name = self.name(definition)?;
+
+                    if !definitions.iter().any(|d| d.name == definition_name) {
+                        return Err(DslError::new(
+                            format!("the definition `{}` is not declared", definition_name),
+                            definition.span,
+                        ));
+                    }
+                }
+            }
+
+            for arg in args {
+                self.assert_defined(arg, definitions)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Policy {
+    fn header(&self) -> Result<(String, Pubkey, Compiler<'_>), DslError> {
+        let compiler = Compiler::new(self)?;
+
+        let (name, span) = self
+            .name
+            .as_ref()
+            .ok_or_else(|| DslError::new("the rule set name is not declared", Span::default()))?;
+        assert_name_length(name, *span)?;
+
+        let owner = self
+            .owner
+            .as_ref()
+            .ok_or_else(|| DslError::new("the owner is not declared", Span::default()))?;
+        let owner = compiler.pubkey(owner)?;
+
+        for statement in self.operations.iter().chain(self.definitions.iter()) {
+            assert_name_length(&statement.name, statement.span)?;
+            compiler.assert_defined(&statement.value, &self.definitions)?;
+        }
+
+        Ok((name.clone(), owner, compiler))
+    }
+
+    /// Checks that the definitions do not reference each other in a cycle.
+    fn assert_acyclic(&self, rule_set: &RuleSetV1) -> Result<(), DslError> {
+        rule_set.assert_valid_references().map_err(|_| {
+            DslError::new(
+                "rule definitions reference each other in a cycle",
+                self.definitions
+                    .first()
+                    .map_or(Span::default(), |definition| definition.span),
+            )
+        })
+    }
+
+    /// Compile the policy to a `RuleSetV1`.
+    pub fn to_v1(&self) -> Result<RuleSetV1, DslError> {
+        let (name, owner, compiler) = self.header()?;
+        let mut rule_set = RuleSetV1::new(name, owner);
+
+        for operation in &self.operations {
+            rule_set
+                .operations
+                .insert(operation.name.clone(), compiler.rule(&operation.value)?);
+        }
+
+        for definition in &self.definitions {
+            rule_set
+                .definitions
+                .insert(definition.name.clone(), compiler.rule(&definition.value)?);
+        }
+
+        self.assert_acyclic(&rule_set)?;
+
+        Ok(rule_set)
+    }
+
+    /// Compile the policy to a serialized `RuleSetV2`.
+    pub fn to_v2(&self) -> Result<Vec<u8>, DslError> {
+        // The V1 rule set checks every rule and the references between definitions.
+        self.to_v1()?;
+        let (name, owner, compiler) = self.header()?;
+
+        let compile = |statements: &[Statement]| {
+            statements
+                .iter()
+                .map(|statement| Ok((statement.name.clone(), compiler.rule_v2(&statement.value)?)))
+                .collect::<Result<Vec<_>, DslError>>()
+        };
+
+        let (operations, rules): (Vec<_>, Vec<_>) = compile(&self.operations)?.into_iter().unzip();
+        let (definition_names, definitions): (Vec<_>, Vec<_>) =
+            compile(&self.definitions)?.into_iter().unzip();
+
+        RuleSetV2::serialize_with_definitions(
+            owner,
+            &name,
+            &operations,
+            &slices(&rules),
+            &definition_names,
+            &slices(&definitions),
+        )
+        .map_err(|error| DslError::new(error.to_string(), Span::default()))
+    }
+}
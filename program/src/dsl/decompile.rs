@@ -0,0 +1,629 @@
+//! Decompiles a `RuleSetV1` or a `RuleSetV2` to a policy.
+use super::{Expr, ExprKind, Policy, Span, Statement};
+use crate::{
+    error::RuleSetError,
+    state::{
+        constraint::*, CompareOp, ConstraintType, MatchOp, MatchOperator, Operator, Rule, RuleList,
+        RuleSetV1, RuleSetV2, RuleV2, Str32,
+    },
+    types::RuleSet,
+};
+use solana_program::pubkey::Pubkey;
+
+/// Returns whether `value` can be written as a word rather than a string.
+fn is_word(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn field(field: &str) -> Expr {
+    let fields = field.split('|').collect::<Vec<_>>();
+
+    if fields.len() > 1 && fields.iter().all(|field| is_word(field)) {
+        Expr::new(ExprKind::Fields(
+            fields.into_iter().map(str::to_string).collect(),
+        ))
+    } else {
+        name(field)
+    }
+}
+
+fn name(name: &str) -> Expr {
+    if is_word(name) {
+        Expr::word(name)
+    } else {
+        Expr::new(ExprKind::Str(name.to_string()))
+    }
+}
+
+fn pubkeys<'a>(pubkeys: impl IntoIterator<Item = &'a Pubkey>) -> Expr {
+    Expr::new(ExprKind::List(
+        pubkeys.into_iter().map(Expr::word).collect(),
+    ))
+}
+
+fn bytes(bytes: &[u8]) -> Expr {
+    let digits = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    Expr::word(format!("0x{}", digits))
+}
+
+fn compare_op(operator: &CompareOp) -> Expr {
+    let operator = match operator {
+        CompareOp::Lt => "<",
+        CompareOp::LtEq => "<=",
+        CompareOp::Eq => "==",
+        CompareOp::GtEq => ">=",
+        CompareOp::Gt => ">",
+    };
+    Expr::new(ExprKind::Operator(operator.to_string()))
+}
+
+fn match_op(operator: &MatchOp) -> Expr {
+    match operator {
+        MatchOp::Eq => Expr::new(ExprKind::Operator("==".to_string())),
+        MatchOp::Prefix => Expr::word("prefix"),
+    }
+}
+
+/// Decompiles a `Rule` tree.
+pub(crate) fn rule_v1(rule: &Rule) -> Expr {
+    let rules = |rules: &[Rule]| rules.iter().map(rule_v1).collect::<Vec<_>>();
+
+    match rule {
+        Rule::All { rules: children } => Expr::call("all", rules(children)),
+        Rule::Any { rules: children } => Expr::call("any", rules(children)),
+        Rule::Not { rule } => Expr::call("not", vec![rule_v1(rule)]),
+        Rule::AdditionalSigner { account } => {
+            Expr::call("additional_signer", vec![Expr::word(account)])
+        }
+        Rule::PubkeyMatch { pubkey, field: f } => {
+            Expr::call("pubkey_match", vec![field(f), Expr::word(pubkey)])
+        }
+        Rule::PubkeyListMatch {
+            pubkeys: list,
+            field: f,
+        } => Expr::call("pubkey_list_match", vec![field(f), pubkeys(list)]),
+        Rule::PubkeyTreeMatch {
+            root,
+            pubkey_field,
+            proof_field,
+        } => Expr::call(
+            "pubkey_tree_match",
+            vec![field(pubkey_field), field(proof_field), bytes(root)],
+        ),
+        Rule::PDAMatch {
+            program,
+            pda_field,
+            seeds_field,
+        } => {
+            let mut args = vec![field(pda_field), field(seeds_field)];
+            args.extend(program.as_ref().map(Expr::word));
+            Expr::call("pda_match", args)
+        }
+        Rule::ProgramOwned { program, field: f } => {
+            Expr::call("program_owned", vec![field(f), Expr::word(program)])
+        }
+        Rule::ProgramOwnedList { programs, field: f } => {
+            Expr::call("program_owned_list", vec![field(f), pubkeys(programs)])
+        }
+        Rule::ProgramOwnedTree {
+            root,
+            pubkey_field,
+            proof_field,
+        } => Expr::call(
+            "program_owned_tree",
+            vec![field(pubkey_field), field(proof_field), bytes(root)],
+        ),
+        Rule::Amount {
+            amount,
+            operator,
+            field: f,
+        } => Expr::call(
+            "amount",
+            vec![field(f), compare_op(operator), Expr::word(amount)],
+        ),
+        Rule::Frequency { authority } => Expr::call("frequency", vec![Expr::word(authority)]),
+        Rule::IsWallet { field: f } => Expr::call("is_wallet", vec![field(f)]),
+        Rule::Pass => Expr::word("pass"),
+        Rule::ProgramOwnedSet { programs, field: f } => {
+            let mut programs = programs.iter().collect::<Vec<_>>();
+            programs.sort();
+            Expr::call("program_owned_set", vec![field(f), pubkeys(programs)])
+        }
+        Rule::Namespace => Expr::word("namespace"),
+        Rule::Threshold { k, rules: children } => {
+            let mut args = vec![Expr::word(k)];
+            args.extend(rules(children));
+            Expr::call("threshold", args)
+        }
+        Rule::IfThenElse {
+            condition,
+            then,
+            otherwise,
+        } => Expr::call(
+            "if_then_else",
+            vec![rule_v1(condition), rule_v1(then), rule_v1(otherwise)],
+        ),
+        Rule::RuleRef { name: definition } => Expr::call("rule_ref", vec![name(definition)]),
+        Rule::ExternalRuleSet {
+            rule_set,
+            operation,
+            revision,
+        } => {
+            let mut args = vec![Expr::word(rule_set), name(operation)];
+            args.extend(revision.map(Expr::word));
+            Expr::call("external_rule_set", args)
+        }
+        Rule::BytesMatch {
+            value,
+            operator,
+            field: f,
+        } => Expr::call(
+            "bytes_match",
+            vec![field(f), match_op(operator), bytes(value)],
+        ),
+        Rule::StrMatch {
+            value,
+            operator,
+            field: f,
+        } => Expr::call(
+            "str_match",
+            vec![
+                field(f),
+                match_op(operator),
+                Expr::new(ExprKind::Str(value.clone())),
+            ],
+        ),
+        Rule::BoolMatch { value, field: f } => {
+            Expr::call("bool_match", vec![field(f), Expr::word(value)])
+        }
+        Rule::AmountI64 {
+            amount,
+            operator,
+            field: f,
+        } => Expr::call(
+            "amount_i64",
+            vec![field(f), compare_op(operator), Expr::word(amount)],
+        ),
+        Rule::AmountU128 {
+            amount,
+            operator,
+            field: f,
+        } => Expr::call(
+            "amount_u128",
+            vec![field(f), compare_op(operator), Expr::word(amount)],
+        ),
+        Rule::PubkeyListContains { pubkey, field: f } => {
+            Expr::call("pubkey_list_contains", vec![field(f), Expr::word(pubkey)])
+        }
+        Rule::FieldCompare {
+            left_field,
+            operator,
+            right_field,
+        } => Expr::call(
+            "field_compare",
+            vec![field(left_field), compare_op(operator), field(right_field)],
+        ),
+        Rule::AmountRange { min, max, field: f } => Expr::call(
+            "amount_range",
+            vec![field(f), Expr::word(min), Expr::word(max)],
+        ),
+        Rule::AmountRatio {
+            num,
+            denom,
+            numerator_field,
+            denominator_field,
+        } => Expr::call(
+            "amount_ratio",
+            vec![
+                field(numerator_field),
+                field(denominator_field),
+                Expr::word(num),
+                Expr::word(denom),
+            ],
+        ),
+        Rule::PubkeyTreeExclusion {
+            root,
+            pubkey_field,
+            proof_field,
+        } => Expr::call(
+            "pubkey_tree_exclusion",
+            vec![field(pubkey_field), field(proof_field), bytes(root)],
+        ),
+        Rule::PubkeyTreeMatchRegistry {
+            registry,
+            pubkey_field,
+            proof_field,
+        } => Expr::call(
+            "pubkey_tree_match_registry",
+            vec![
+                field(pubkey_field),
+                field(proof_field),
+                Expr::word(registry),
+            ],
+        ),
+        Rule::ProgramOwnedTreeRegistry {
+            registry,
+            pubkey_field,
+            proof_field,
+        } => Expr::call(
+            "program_owned_tree_registry",
+            vec![
+                field(pubkey_field),
+                field(proof_field),
+                Expr::word(registry),
+            ],
+        ),
+        Rule::PubkeyInListAccount { list, field: f } => {
+            Expr::call("pubkey_in_list_account", vec![field(f), Expr::word(list)])
+        }
+        Rule::OwnerInListAccount { list, field: f } => {
+            Expr::call("owner_in_list_account", vec![field(f), Expr::word(list)])
+        }
+    }
+}
+
+fn str32(value: &Str32) -> Expr {
+    field(&value.to_string())
+}
+
+fn operator_v2(operator: &u64) -> Result<Expr, RuleSetError> {
+    Ok(compare_op(&CompareOp::from(&Operator::try_from(
+        *operator,
+    )?)))
+}
+
+fn match_operator_v2(operator: &u64) -> Result<Expr, RuleSetError> {
+    Ok(match_op(&MatchOp::from(&MatchOperator::try_from(
+        *operator,
+    )?)))
+}
+
+fn rule_list_v2(rules: RuleList) -> Result<Vec<Expr>, RuleSetError> {
+    rules.iter().map(|rule| rule_v2(&rule?)).collect()
+}
+
+/// Decompiles a `RuleV2` and the rules nested under it.
+pub(crate) fn rule_v2(rule: &RuleV2) -> Result<Expr, RuleSetError> {
+    let data = rule.data;
+
+    let expr = match rule.header.constraint_type()? {
+        ConstraintType::Uninitialized => return Err(RuleSetError::InvalidConstraintType),
+        ConstraintType::AdditionalSigner => {
+            let constraint = AdditionalSigner::from_bytes(data)?;
+            Expr::call("additional_signer", vec![Expr::word(constraint.account)])
+        }
+        ConstraintType::All => Expr::call("all", rule_list_v2(All::from_bytes(data)?.rules)?),
+        ConstraintType::Any => Expr::call("any", rule_list_v2(Any::from_bytes(data)?.rules)?),
+        ConstraintType::Not => Expr::call("not", rule_list_v2(Not::from_bytes(data)?.rule)?),
+        ConstraintType::Threshold => {
+            let constraint = Threshold::from_bytes(data)?;
+            let mut args = vec![Expr::word(constraint.k)];
+            args.extend(rule_list_v2(constraint.rules)?);
+            Expr::call("threshold", args)
+        }
+        ConstraintType::IfThenElse => Expr::call(
+            "if_then_else",
+            rule_list_v2(IfThenElse::from_bytes(data)?.rules)?,
+        ),
+        ConstraintType::Amount => {
+            let constraint = Amount::from_bytes(data)?;
+            Expr::call(
+                "amount",
+                vec![
+                    str32(constraint.field),
+                    operator_v2(constraint.operator)?,
+                    Expr::word(constraint.amount),
+                ],
+            )
+        }
+        ConstraintType::AmountI64 => {
+            let constraint = AmountI64::from_bytes(data)?;
+            Expr::call(
+                "amount_i64",
+                vec![
+                    str32(constraint.field),
+                    operator_v2(constraint.operator)?,
+                    Expr::word(constraint.amount),
+                ],
+            )
+        }
+        ConstraintType::AmountU128 => {
+            let constraint = AmountU128::from_bytes(data)?;
+            Expr::call(
+                "amount_u128",
+                vec![
+                    str32(constraint.field),
+                    operator_v2(constraint.operator)?,
+                    Expr::word(u128::from_le_bytes(*constraint.amount)),
+                ],
+            )
+        }
+        ConstraintType::AmountRange => {
+            let constraint = AmountRange::from_bytes(data)?;
+            Expr::call(
+                "amount_range",
+                vec![
+                    str32(constraint.field),
+                    Expr::word(constraint.min),
+                    Expr::word(constraint.max),
+                ],
+            )
+        }
+        ConstraintType::AmountRatio => {
+            let constraint = AmountRatio::from_bytes(data)?;
+            Expr::call(
+                "amount_ratio",
+                vec![
+                    str32(constraint.numerator_field),
+                    str32(constraint.denominator_field),
+                    Expr::word(constraint.num),
+                    Expr::word(constraint.denom),
+                ],
+            )
+        }
+        ConstraintType::Frequency => {
+            let constraint = Frequency::from_bytes(data)?;
+            Expr::call("frequency", vec![Expr::word(constraint.authority)])
+        }
+        ConstraintType::IsWallet => {
+            let constraint = IsWallet::from_bytes(data)?;
+            Expr::call("is_wallet", vec![str32(constraint.field)])
+        }
+        ConstraintType::Namespace => Expr::word("namespace"),
+        ConstraintType::Pass => Expr::word("pass"),
+        ConstraintType::PDAMatch => {
+            let constraint = PDAMatch::from_bytes(data)?;
+            let mut args = vec![str32(constraint.pda_field), str32(constraint.seeds_field)];
+
+            // The default pubkey stands for the owner of the account.
+            if *constraint.program != Pubkey::default() {
+                args.push(Expr::word(constraint.program));
+            }
+
+            Expr::call("pda_match", args)
+        }
+        ConstraintType::ProgramOwned => {
+            let constraint = ProgramOwned::from_bytes(data)?;
+            Expr::call(
+                "program_owned",
+                vec![str32(constraint.field), Expr::word(constraint.program)],
+            )
+        }
+        ConstraintType::ProgramOwnedList => {
+            let constraint = ProgramOwnedList::from_bytes(data)?;
+            Expr::call(
+                "program_owned_list",
+                vec![str32(constraint.field), pubkeys(constraint.programs)],
+            )
+        }
+        ConstraintType::ProgramOwnedListSorted => {
+            let constraint = ProgramOwnedList::from_sorted_bytes(data)?;
+            Expr::call(
+                "program_owned_set",
+                vec![str32(constraint.field), pubkeys(constraint.programs)],
+            )
+        }
+        ConstraintType::PubkeyListMatch => {
+            let constraint = PubkeyListMatch::from_bytes(data)?;
+            Expr::call(
+                "pubkey_list_match",
+                vec![str32(constraint.field), pubkeys(constraint.pubkeys)],
+            )
+        }
+        ConstraintType::PubkeyListMatchSorted => {
+            let constraint = PubkeyListMatch::from_sorted_bytes(data)?;
+            Expr::call(
+                "pubkey_list_match_sorted",
+                vec![str32(constraint.field), pubkeys(constraint.pubkeys)],
+            )
+        }
+        ConstraintType::PubkeyMatch => {
+            let constraint = PubkeyMatch::from_bytes(data)?;
+            Expr::call(
+                "pubkey_match",
+                vec![str32(constraint.field), Expr::word(constraint.pubkey)],
+            )
+        }
+        ConstraintType::PubkeyListContains => {
+            let constraint = PubkeyListContains::from_bytes(data)?;
+            Expr::call(
+                "pubkey_list_contains",
+                vec![str32(constraint.field), Expr::word(constraint.pubkey)],
+            )
+        }
+        ConstraintType::PubkeyTreeMatch => {
+            let constraint = PubkeyTreeMatch::from_bytes(data)?;
+            Expr::call(
+                "pubkey_tree_match",
+                vec![
+                    str32(constraint.pubkey_field),
+                    str32(constraint.proof_field),
+                    bytes(constraint.root),
+                ],
+            )
+        }
+        ConstraintType::ProgramOwnedTree => {
+            let constraint = ProgramOwnedTree::from_bytes(data)?;
+            Expr::call(
+                "program_owned_tree",
+                vec![
+                    str32(constraint.pubkey_field),
+                    str32(constraint.proof_field),
+                    bytes(constraint.root),
+                ],
+            )
+        }
+        ConstraintType::PubkeyTreeExclusion => {
+            let constraint = PubkeyTreeExclusion::from_bytes(data)?;
+            Expr::call(
+                "pubkey_tree_exclusion",
+                vec![
+                    str32(constraint.pubkey_field),
+                    str32(constraint.proof_field),
+                    bytes(constraint.root),
+                ],
+            )
+        }
+        ConstraintType::PubkeyTreeMatchRegistry => {
+            let constraint = PubkeyTreeMatchRegistry::from_bytes(data)?;
+            Expr::call(
+                "pubkey_tree_match_registry",
+                vec![
+                    str32(constraint.pubkey_field),
+                    str32(constraint.proof_field),
+                    Expr::word(constraint.registry),
+                ],
+            )
+        }
+        ConstraintType::ProgramOwnedTreeRegistry => {
+            let constraint = ProgramOwnedTreeRegistry::from_bytes(data)?;
+            Expr::call(
+                "program_owned_tree_registry",
+                vec![
+                    str32(constraint.pubkey_field),
+                    str32(constraint.proof_field),
+                    Expr::word(constraint.registry),
+                ],
+            )
+        }
+        ConstraintType::PubkeyInListAccount => {
+            let constraint = PubkeyInListAccount::from_bytes(data)?;
+            Expr::call(
+                "pubkey_in_list_account",
+                vec![str32(constraint.field), Expr::word(constraint.list)],
+            )
+        }
+        ConstraintType::OwnerInListAccount => {
+            let constraint = OwnerInListAccount::from_bytes(data)?;
+            Expr::call(
+                "owner_in_list_account",
+                vec![str32(constraint.field), Expr::word(constraint.list)],
+            )
+        }
+        ConstraintType::RuleRef => {
+            let constraint = RuleRef::from_bytes(data)?;
+            Expr::call("rule_ref", vec![name(&constraint.name.to_string())])
+        }
+        ConstraintType::ExternalRuleSet => {
+            let constraint = ExternalRuleSet::from_bytes(data)?;
+            let mut args = vec![
+                Expr::word(constraint.rule_set),
+                name(&constraint.operation.to_string()),
+            ];
+
+            if *constraint.revision != LATEST_REVISION {
+                args.push(Expr::word(constraint.revision));
+            }
+
+            Expr::call("external_rule_set", args)
+        }
+        ConstraintType::BytesMatch => {
+            let constraint = BytesMatch::from_bytes(data)?;
+            Expr::call(
+                "bytes_match",
+                vec![
+                    str32(constraint.field),
+                    match_operator_v2(constraint.operator)?,
+                    bytes(constraint.value),
+                ],
+            )
+        }
+        ConstraintType::StrMatch => {
+            let constraint = StrMatch::from_bytes(data)?;
+            let value = String::from_utf8(constraint.value.to_vec())
+                .map_err(|_| RuleSetError::RuleSetReadFailed)?;
+
+            Expr::call(
+                "str_match",
+                vec![
+                    str32(constraint.field),
+                    match_operator_v2(constraint.operator)?,
+                    Expr::new(ExprKind::Str(value)),
+                ],
+            )
+        }
+        ConstraintType::BoolMatch => {
+            let constraint = BoolMatch::from_bytes(data)?;
+            Expr::call(
+                "bool_match",
+                vec![str32(constraint.field), Expr::word(*constraint.value != 0)],
+            )
+        }
+        ConstraintType::FieldCompare => {
+            let constraint = FieldCompare::from_bytes(data)?;
+            Expr::call(
+                "field_compare",
+                vec![
+                    str32(constraint.left_field),
+                    operator_v2(constraint.operator)?,
+                    str32(constraint.right_field),
+                ],
+            )
+        }
+    };
+
+    Ok(expr)
+}
+
+impl Policy {
+    /// Decompile a `RuleSetV1`.  Operations and definitions are sorted by name.
+    pub fn from_v1(rule_set: &RuleSetV1) -> Self {
+        let statements = |rules: &std::collections::HashMap<String, Rule>| {
+            let mut statements = rules
+                .iter()
+                .map(|(name, rule)| Statement {
+                    name: name.clone(),
+                    span: Span::default(),
+                    value: rule_v1(rule),
+                })
+                .collect::<Vec<_>>();
+            statements.sort_by(|a, b| a.name.cmp(&b.name));
+            statements
+        };
+
+        Self {
+            name: Some((rule_set.name(), Span::default())),
+            owner: Some(Expr::word(rule_set.owner())),
+            constants: Vec::new(),
+            definitions: statements(&rule_set.definitions),
+            operations: statements(&rule_set.operations),
+        }
+    }
+
+    /// Decompile a `RuleSetV2`.  Operations and definitions are in the order they are stored,
+    /// which is sorted by name unless the rule set predates the index.
+    pub fn from_v2(rule_set: &RuleSetV2) -> Result<Self, RuleSetError> {
+        let statement = |name: &Str32, rule: &RuleV2| -> Result<Statement, RuleSetError> {
+            Ok(Statement {
+                name: name.to_string(),
+                span: Span::default(),
+                value: rule_v2(rule)?,
+            })
+        };
+
+        let operations = rule_set
+            .operations
+            .iter()
+            .enumerate()
+            .map(|(index, name)| statement(name, rule_set.rule(index)?))
+            .collect::<Result<Vec<_>, _>>()?;
+        let definitions = rule_set
+            .definition_names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| statement(name, rule_set.definition(index)?))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name: Some((rule_set.rule_set_name.to_string(), Span::default())),
+            owner: Some(Expr::word(rule_set.owner)),
+            constants: Vec::new(),
+            definitions,
+            operations,
+        })
+    }
+}
@@ -0,0 +1,144 @@
+//! Splits the source of a policy into tokens.
+use super::{DslError, Span};
+
+/// A token of the policy language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Token {
+    /// A sequence of letters, digits and underscores, optionally starting with a `-`.  Words
+    /// are identifiers, numbers, pubkeys and hex bytes depending on where they are used.
+    Word(String),
+    /// A string literal, with the escape sequences resolved.
+    Str(String),
+    /// A comparison operator: `<`, `<=`, `==`, `>=` or `>`.
+    Operator(String),
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Colon,
+    Pipe,
+    Equals,
+    Arrow,
+    Eof,
+}
+
+impl Token {
+    /// Returns a description of the token for error messages.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("`{}`", word),
+            Token::Str(_) => "a string".to_string(),
+            Token::Operator(operator) => format!("`{}`", operator),
+            Token::LeftParen => "`(`".to_string(),
+            Token::RightParen => "`)`".to_string(),
+            Token::LeftBracket => "`[`".to_string(),
+            Token::RightBracket => "`]`".to_string(),
+            Token::Comma => "`,`".to_string(),
+            Token::Colon => "`:`".to_string(),
+            Token::Pipe => "`|`".to_string(),
+            Token::Equals => "`=`".to_string(),
+            Token::Arrow => "`=>`".to_string(),
+            Token::Eof => "the end of the input".to_string(),
+        }
+    }
+}
+
+fn is_word(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_'
+}
+
+/// Splits `source` into tokens and their spans.  The last token is always `Token::Eof`.
+pub(crate) fn tokenize(source: &str) -> Result<Vec<(Token, Span)>, DslError> {
+    let mut tokens = Vec::new();
+    let mut characters = source.char_indices().peekable();
+
+    while let Some((start, character)) = characters.next() {
+        let next = characters.peek().map(|(_, next)| *next);
+
+        let token = match (character, next) {
+            (c, _) if c.is_whitespace() => continue,
+            ('/', Some('/')) => {
+                // Comments run until the end of the line.
+                while characters.next_if(|(_, c)| *c != '\n').is_some() {}
+                continue;
+            }
+            ('(', _) => Token::LeftParen,
+            (')', _) => Token::RightParen,
+            ('[', _) => Token::LeftBracket,
+            (']', _) => Token::RightBracket,
+            (',', _) => Token::Comma,
+            (':', _) => Token::Colon,
+            ('|', _) => Token::Pipe,
+            ('=', Some('>')) => {
+                characters.next();
+                Token::Arrow
+            }
+            ('=', Some('=')) | ('<', Some('=')) | ('>', Some('=')) => {
+                characters.next();
+                Token::Operator(format!("{}=", character))
+            }
+            ('=', _) => Token::Equals,
+            ('<', _) | ('>', _) => Token::Operator(character.to_string()),
+            ('"', _) => {
+                let mut value = String::new();
+
+                loop {
+                    match characters.next() {
+                        Some((_, '"')) => break,
+                        Some((position, '\\')) => {
+                            let escaped = match characters.next() {
+                                Some((_, '"')) => '"',
+                                Some((_, '\\')) => '\\',
+                                Some((_, 'n')) => '\n',
+                                Some((_, 't')) => '\t',
+                                Some((_, 'r')) => '\r',
+                                Some((_, '0')) => '\0',
+                                other => {
+                                    let end = other
+                                        .map_or(source.len(), |(index, c)| index + c.len_utf8());
+                                    return Err(DslError::new(
+                                        "unknown escape sequence",
+                                        Span::new(position, end),
+                                    ));
+                                }
+                            };
+                            value.push(escaped);
+                        }
+                        Some((_, c)) => value.push(c),
+                        None => {
+                            return Err(DslError::new(
+                                "unterminated string",
+                                Span::new(start, source.len()),
+                            ))
+                        }
+                    }
+                }
+
+                Token::Str(value)
+            }
+            (c, next) if is_word(c) || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let mut end = start + c.len_utf8();
+
+                while let Some((index, c)) = characters.next_if(|(_, c)| is_word(*c)) {
+                    end = index + c.len_utf8();
+                }
+
+                Token::Word(source[start..end].to_string())
+            }
+            (c, _) => {
+                return Err(DslError::new(
+                    format!("unexpected character `{}`", c),
+                    Span::new(start, start + c.len_utf8()),
+                ))
+            }
+        };
+
+        let end = characters.peek().map_or(source.len(), |(index, _)| *index);
+        tokens.push((token, Span::new(start, end)));
+    }
+
+    tokens.push((Token::Eof, Span::new(source.len(), source.len())));
+
+    Ok(tokens)
+}
@@ -0,0 +1,230 @@
+//! A text language for authoring `RuleSet`s.
+//!
+//! A policy names the `RuleSet`, its owner and the rule of each operation.  Rules are written
+//! as calls named after the `Rule` variants in snake case, with the payload fields first:
+//!
+//! ```text
+//! rule_set "Marketplace Rules"
+//! owner AuthxYNhPnnrGBo1wdzeUdukrsFpHvR42wghx8ZPNEo4
+//!
+//! // Constants hold pubkeys or lists of pubkeys.
+//! const TOKEN = TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA
+//! const MARKETS = [TOKEN, M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K]
+//!
+//! // Definitions can be shared by several operations with `rule_ref`.
+//! define allowed => any(program_owned_list(Destination, MARKETS), is_wallet(Destination))
+//!
+//! transfer:owner => all(rule_ref(allowed), amount(Amount, <=, 1))
+//! "Delegate:Sale" => pass
+//! ```
+//!
+//! Besides calls, arguments are words (payload fields, pubkeys, constants, numbers, `true` and
+//! `false`, and hex bytes such as `0x01ff`), string literals, comparison operators (`<`, `<=`,
+//! `==`, `>=` and `>`), lists in brackets and alternatives of payload fields such as
+//! `Destination | Source`.  `//` starts a comment that runs until the end of the line.
+//!
+//! A `Policy` is compiled to a `RuleSetV1` or to the bytes of a `RuleSetV2`, and both versions
+//! can be decompiled back to a `Policy`, which prints in the same language.  `ProgramOwnedSet`
+//! is written `program_owned_set` and is compiled to a sorted `ProgramOwnedList` in V2, and
+//! `pubkey_list_match_sorted` is compiled to a sorted `PubkeyListMatch` in V2 and to a
+//! `PubkeyListMatch` in V1.
+mod compile;
+mod decompile;
+mod lexer;
+mod parser;
+mod printer;
+
+pub(crate) use printer::write_name;
+
+use crate::state::RuleSetV1;
+use std::fmt::Display;
+
+/// A range of bytes in the source of a policy.  Expressions decompiled from a `RuleSet` have
+/// an empty span at the start of the source.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    /// Offset of the first byte.
+    pub start: usize,
+    /// Offset after the last byte.
+    pub end: usize,
+}
+
+impl Span {
+    /// Create a span from the offsets of its first byte and of the byte after its last byte.
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Create the smallest span containing both spans.
+    pub fn to(self, other: Span) -> Self {
+        Self::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+/// An error in the source of a policy, with the span of the offending text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DslError {
+    /// Description of the error.
+    pub message: String,
+    /// Location of the error in the source.
+    pub span: Span,
+}
+
+impl DslError {
+    /// Create an error located at `span`.
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Returns the line and column, both starting at 1, of the start of the error in `source`.
+    pub fn line_column(&self, source: &str) -> (usize, usize) {
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line = source[..start].matches('\n').count() + 1;
+        let column = source[line_start..start].chars().count() + 1;
+
+        (line, column)
+    }
+
+    /// Format the error with the line of `source` it occurs in and the span underlined.
+    pub fn render(&self, source: &str) -> String {
+        let (line, column) = self.line_column(source);
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |index| start + index);
+        let text = &source[line_start..line_end];
+
+        // The underline stops at the end of the line for spans over several lines.
+        let end = self.span.end.clamp(start, line_end);
+        let width = source[start..end].chars().count().max(1);
+        let margin = " ".repeat(line.to_string().len());
+
+        format!(
+            "error: {}\n{}--> {}:{}\n{} |\n{} | {}\n{} | {}{}",
+            self.message,
+            margin,
+            line,
+            column,
+            margin,
+            line,
+            text,
+            margin,
+            " ".repeat(column - 1),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl Display for DslError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for DslError {}
+
+/// The kind of an expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExprKind {
+    /// A call, used for rules.
+    Call {
+        /// Name of the called rule.
+        name: String,
+        /// Arguments of the call.
+        args: Vec<Expr>,
+    },
+    /// A word: a payload field, a pubkey, a constant, a number, a boolean or hex bytes.
+    Word(String),
+    /// Alternative payload fields separated by `|`.
+    Fields(Vec<String>),
+    /// A string literal.
+    Str(String),
+    /// A comparison operator.
+    Operator(String),
+    /// A list in brackets.
+    List(Vec<Expr>),
+}
+
+/// An expression of the policy language and its location in the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expr {
+    /// The kind of the expression.
+    pub kind: ExprKind,
+    /// Location of the expression in the source.
+    pub span: Span,
+}
+
+impl Expr {
+    /// Create an expression without a location.
+    pub fn new(kind: ExprKind) -> Self {
+        Self {
+            kind,
+            span: Span::default(),
+        }
+    }
+
+    /// Create a call expression without a location.
+    pub fn call(name: &str, args: Vec<Expr>) -> Self {
+        Self::new(ExprKind::Call {
+            name: name.to_string(),
+            args,
+        })
+    }
+
+    /// Create a word expression without a location.
+    pub fn word(word: impl Display) -> Self {
+        Self::new(ExprKind::Word(word.to_string()))
+    }
+}
+
+/// A named item of a policy: an operation, a definition or a constant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    /// Name of the item.
+    pub name: String,
+    /// Location of the name in the source.
+    pub span: Span,
+    /// Value of the item, which is a rule for operations and definitions.
+    pub value: Expr,
+}
+
+/// A parsed policy.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Policy {
+    /// Name of the `RuleSet` and its location.
+    pub name: Option<(String, Span)>,
+    /// Owner of the `RuleSet`.
+    pub owner: Option<Expr>,
+    /// Constants, in the order they are declared.
+    pub constants: Vec<Statement>,
+    /// Rule definitions, in the order they are declared.
+    pub definitions: Vec<Statement>,
+    /// Rules of the operations, in the order they are declared.
+    pub operations: Vec<Statement>,
+}
+
+impl Policy {
+    /// Parse a policy from its source.
+    pub fn parse(source: &str) -> Result<Self, DslError> {
+        parser::parse(source)
+    }
+}
+
+/// Parse and compile a policy to a `RuleSetV1`.
+pub fn compile_v1(source: &str) -> Result<RuleSetV1, DslError> {
+    Policy::parse(source)?.to_v1()
+}
+
+/// Parse and compile a policy to a serialized `RuleSetV2`.
+pub fn compile_v2(source: &str) -> Result<Vec<u8>, DslError> {
+    Policy::parse(source)?.to_v2()
+}
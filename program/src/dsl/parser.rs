@@ -0,0 +1,254 @@
+//! Parses the tokens of a policy.
+use super::{
+    lexer::{tokenize, Token},
+    DslError, Expr, ExprKind, Policy, Span, Statement,
+};
+
+/// Recursive descent parser over the tokens of a policy.
+struct Parser {
+    tokens: Vec<(Token, Span)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn peek_at(&self, offset: usize) -> &Token {
+        let index = (self.position + offset).min(self.tokens.len() - 1);
+        &self.tokens[index].0
+    }
+
+    fn span(&self) -> Span {
+        self.tokens[self.position].1
+    }
+
+    /// Returns the current token and moves to the next one.  The last token is `Token::Eof`,
+    /// which is never consumed.
+    fn next(&mut self) -> (Token, Span) {
+        let token = self.tokens[self.position].clone();
+
+        if self.position < self.tokens.len() - 1 {
+            self.position += 1;
+        }
+
+        token
+    }
+
+    fn unexpected(&self, expected: &str) -> DslError {
+        DslError::new(
+            format!("expected {}, found {}", expected, self.peek().describe()),
+            self.span(),
+        )
+    }
+
+    fn expect(&mut self, token: Token, expected: &str) -> Result<Span, DslError> {
+        if *self.peek() == token {
+            Ok(self.next().1)
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    fn word(&mut self, expected: &str) -> Result<(String, Span), DslError> {
+        match self.peek() {
+            Token::Word(_) => match self.next() {
+                (Token::Word(word), span) => Ok((word, span)),
+                _ => unreachable!("the token is a word"),
+            },
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    /// Parses a name of an operation or a definition: a string, or words separated by `:`.
+    fn name(&mut self) -> Result<(String, Span), DslError> {
+        if let Token::Str(_) = self.peek() {
+            return match self.next() {
+                (Token::Str(name), span) => Ok((name, span)),
+                _ => unreachable!("the token is a string"),
+            };
+        }
+
+        let (mut name, mut span) = self.word("an operation name")?;
+
+        while *self.peek() == Token::Colon {
+            self.next();
+            let (word, end) = self.word("a name after `:`")?;
+            name.push(':');
+            name.push_str(&word);
+            span = span.to(end);
+        }
+
+        Ok((name, span))
+    }
+
+    /// Parses a comma separated list of expressions until the closing token, which is consumed.
+    fn arguments(&mut self, close: Token, expected: &str) -> Result<(Vec<Expr>, Span), DslError> {
+        let mut args = Vec::new();
+
+        loop {
+            if *self.peek() == close {
+                return Ok((args, self.next().1));
+            }
+
+            args.push(self.expression()?);
+
+            match self.peek() {
+                Token::Comma => {
+                    self.next();
+                }
+                token if *token == close => (),
+                _ => return Err(self.unexpected(expected)),
+            }
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, DslError> {
+        let (token, span) = self.next();
+
+        let expr = match token {
+            Token::Word(name) if *self.peek() == Token::LeftParen => {
+                self.next();
+                let (args, end) = self.arguments(Token::RightParen, "`,` or `)`")?;
+
+                Expr {
+                    kind: ExprKind::Call { name, args },
+                    span: span.to(end),
+                }
+            }
+            Token::Word(word) if *self.peek() == Token::Pipe => {
+                let mut fields = vec![word];
+                let mut span = span;
+
+                while *self.peek() == Token::Pipe {
+                    self.next();
+                    let (field, end) = self.word("a field after `|`")?;
+                    fields.push(field);
+                    span = span.to(end);
+                }
+
+                Expr {
+                    kind: ExprKind::Fields(fields),
+                    span,
+                }
+            }
+            Token::Word(word) => Expr {
+                kind: ExprKind::Word(word),
+                span,
+            },
+            Token::Str(value) => Expr {
+                kind: ExprKind::Str(value),
+                span,
+            },
+            Token::Operator(operator) => Expr {
+                kind: ExprKind::Operator(operator),
+                span,
+            },
+            Token::LeftBracket => {
+                let (items, end) = self.arguments(Token::RightBracket, "`,` or `]`")?;
+
+                Expr {
+                    kind: ExprKind::List(items),
+                    span: span.to(end),
+                }
+            }
+            token => {
+                return Err(DslError::new(
+                    format!("expected an expression, found {}", token.describe()),
+                    span,
+                ))
+            }
+        };
+
+        Ok(expr)
+    }
+
+    /// Parses a name followed by `=>` and a rule.
+    fn rule_statement(&mut self) -> Result<Statement, DslError> {
+        let (name, span) = self.name()?;
+        self.expect(Token::Arrow, "`=>`")?;
+        let value = self.expression()?;
+
+        Ok(Statement { name, span, value })
+    }
+
+    fn policy(&mut self) -> Result<Policy, DslError> {
+        let mut policy = Policy::default();
+
+        while *self.peek() != Token::Eof {
+            // Keywords followed by `=>` or `:` are operation names.
+            let keyword = match (self.peek(), self.peek_at(1)) {
+                (Token::Word(word), next) if next != &Token::Arrow && next != &Token::Colon => {
+                    word.clone()
+                }
+                _ => String::new(),
+            };
+
+            match keyword.as_str() {
+                "rule_set" => {
+                    let keyword = self.next().1;
+
+                    match self.next() {
+                        (Token::Str(name), span) => {
+                            if policy.name.is_some() {
+                                return Err(DslError::new(
+                                    "the rule set name is declared twice",
+                                    keyword.to(span),
+                                ));
+                            }
+                            policy.name = Some((name, span));
+                        }
+                        (token, span) => {
+                            return Err(DslError::new(
+                                format!("expected a string, found {}", token.describe()),
+                                span,
+                            ))
+                        }
+                    }
+                }
+                "owner" => {
+                    let keyword = self.next().1;
+                    let owner = self.expression()?;
+
+                    if policy.owner.is_some() {
+                        return Err(DslError::new(
+                            "the owner is declared twice",
+                            keyword.to(owner.span),
+                        ));
+                    }
+                    policy.owner = Some(owner);
+                }
+                "const" => {
+                    self.next();
+                    let (name, span) = self.word("a constant name")?;
+                    self.expect(Token::Equals, "`=`")?;
+                    let value = self.expression()?;
+
+                    policy.constants.push(Statement { name, span, value });
+                }
+                "define" => {
+                    self.next();
+                    let definition = self.rule_statement()?;
+                    policy.definitions.push(definition);
+                }
+                _ => {
+                    let operation = self.rule_statement()?;
+                    policy.operations.push(operation);
+                }
+            }
+        }
+
+        Ok(policy)
+    }
+}
+
+/// Parses the source of a policy.
+pub(crate) fn parse(source: &str) -> Result<Policy, DslError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+    };
+
+    parser.policy()
+}
@@ -0,0 +1,166 @@
+//! Prints policies in the policy language.
+use super::{Expr, ExprKind, Policy, Statement};
+use std::fmt::{Display, Formatter, Result, Write};
+
+/// Maximum width of a printed line before a call or a list is broken over several lines.
+const WIDTH: usize = 100;
+
+/// Indentation of the arguments of a call or the items of a list broken over several lines.
+const INDENT: usize = 4;
+
+/// Writes a string literal, escaping the characters the lexer resolves.
+fn write_str(output: &mut impl Write, value: &str) -> Result {
+    output.write_char('"')?;
+
+    for character in value.chars() {
+        match character {
+            '"' => output.write_str("\\\"")?,
+            '\\' => output.write_str("\\\\")?,
+            '\n' => output.write_str("\\n")?,
+            '\t' => output.write_str("\\t")?,
+            '\r' => output.write_str("\\r")?,
+            '\0' => output.write_str("\\0")?,
+            character => output.write_char(character)?,
+        }
+    }
+
+    output.write_char('"')
+}
+
+/// Writes the name of an operation or a definition, quoting it unless it is made of words
+/// separated by `:`.
+pub(crate) fn write_name(output: &mut impl Write, name: &str) -> Result {
+    let is_word = |word: &str| {
+        !word.is_empty() && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    let keyword = matches!(name, "rule_set" | "owner" | "const" | "define");
+
+    if !keyword && name.split(':').all(is_word) {
+        output.write_str(name)
+    } else {
+        write_str(output, name)
+    }
+}
+
+impl Display for Expr {
+    /// Prints the expression on a single line.
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result {
+        let items = |formatter: &mut Formatter<'_>, items: &[Expr]| {
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    formatter.write_str(", ")?;
+                }
+                write!(formatter, "{}", item)?;
+            }
+            Ok(())
+        };
+
+        match &self.kind {
+            ExprKind::Call { name, args } => {
+                write!(formatter, "{}(", name)?;
+                items(formatter, args)?;
+                formatter.write_str(")")
+            }
+            ExprKind::Word(word) => formatter.write_str(word),
+            ExprKind::Fields(fields) => formatter.write_str(&fields.join(" | ")),
+            ExprKind::Str(value) => write_str(formatter, value),
+            ExprKind::Operator(operator) => formatter.write_str(operator),
+            ExprKind::List(list) => {
+                formatter.write_str("[")?;
+                items(formatter, list)?;
+                formatter.write_str("]")
+            }
+        }
+    }
+}
+
+impl Expr {
+    /// Prints the expression starting at column `indent`, breaking calls and lists that do
+    /// not fit in `WIDTH` columns with one argument per line.
+    pub fn pretty(&self, indent: usize) -> String {
+        self.pretty_at(indent, indent)
+    }
+
+    /// Prints the expression starting at `column` in a line indented by `indent` columns.
+    fn pretty_at(&self, indent: usize, column: usize) -> String {
+        let flat = self.to_string();
+
+        let (open, items, close) = match &self.kind {
+            ExprKind::Call { name, args } if !args.is_empty() => (format!("{}(", name), args, ")"),
+            ExprKind::List(list) if !list.is_empty() => ("[".to_string(), list, "]"),
+            _ => return flat,
+        };
+
+        if column + flat.len() <= WIDTH {
+            return flat;
+        }
+
+        let mut output = open;
+
+        for item in items {
+            output.push('\n');
+            output.push_str(&" ".repeat(indent + INDENT));
+            output.push_str(&item.pretty(indent + INDENT));
+            output.push(',');
+        }
+
+        output.push('\n');
+        output.push_str(&" ".repeat(indent));
+        output.push_str(close);
+
+        output
+    }
+}
+
+impl Display for Policy {
+    /// Prints the policy in a form that parses back to an equivalent policy.
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result {
+        let mut sections = Vec::new();
+
+        let mut header = String::new();
+
+        if let Some((name, _)) = &self.name {
+            header.push_str("rule_set ");
+            write_str(&mut header, name)?;
+            header.push('\n');
+        }
+
+        if let Some(owner) = &self.owner {
+            writeln!(header, "owner {}", owner)?;
+        }
+
+        sections.push(header);
+
+        let mut constants = String::new();
+
+        for Statement { name, value, .. } in &self.constants {
+            let prefix = format!("const {} = ", name);
+            writeln!(constants, "{}{}", prefix, value.pretty_at(0, prefix.len()))?;
+        }
+
+        sections.push(constants);
+
+        let rules = |keyword: &str, statements: &[Statement]| {
+            let mut output = String::new();
+
+            for Statement { name, value, .. } in statements {
+                let mut prefix = keyword.to_string();
+                write_name(&mut prefix, name)?;
+                prefix.push_str(" => ");
+                writeln!(output, "{}{}", prefix, value.pretty_at(0, prefix.len()))?;
+            }
+
+            Ok(output)
+        };
+
+        sections.push(rules("define ", &self.definitions)?);
+        sections.push(rules("", &self.operations)?);
+
+        let sections = sections
+            .into_iter()
+            .filter(|section| !section.is_empty())
+            .collect::<Vec<_>>();
+
+        formatter.write_str(&sections.join("\n"))
+    }
+}
@@ -173,6 +173,134 @@ pub enum RuleSetError {
     /// 40 - Could not determine alignemnt
     #[error("Could not determine alignemnt")]
     AlignmentError,
+
+    /// 41 - Rule definition not found
+    #[error("Rule definition not found")]
+    RuleDefinitionNotFound,
+
+    /// 42 - Rule definitions reference each other in a cycle
+    #[error("Rule definitions reference each other in a cycle")]
+    RuleDefinitionCycle,
+
+    /// 43 - Duplicated rule definition name
+    #[error("Duplicated rule definition name")]
+    DuplicatedDefinitionName,
+
+    /// 44 - Too many nested RuleSets
+    #[error("Too many nested RuleSets")]
+    RuleSetDepthExceeded,
+
+    /// 45 - Failed to match bytes
+    #[error("Failed to match bytes")]
+    BytesMatchCheckFailed,
+
+    /// 46 - Failed to match string
+    #[error("Failed to match string")]
+    StrMatchCheckFailed,
+
+    /// 47 - Failed to match boolean flag
+    #[error("Failed to match boolean flag")]
+    BoolMatchCheckFailed,
+
+    /// 48 - Pubkey not found in list
+    #[error("Pubkey not found in list")]
+    PubkeyListContainsCheckFailed,
+
+    /// 49 - Invalid match operator
+    #[error("Invalid match operator")]
+    InvalidMatchOp,
+
+    /// 50 - Payload fields have different types
+    #[error("Payload fields have different types")]
+    FieldTypeMismatch,
+
+    /// 51 - Failed to compare payload fields
+    #[error("Failed to compare payload fields")]
+    FieldCompareCheckFailed,
+
+    /// 52 - Amount is out of range
+    #[error("Amount is out of range")]
+    AmountRangeCheckFailed,
+
+    /// 53 - Failed to check amount ratio
+    #[error("Failed to check amount ratio")]
+    AmountRatioCheckFailed,
+
+    /// 54 - Payload key is too long
+    #[error("Payload key is too long")]
+    PayloadKeyTooLong,
+
+    /// 55 - Payload exceeds the maximum size
+    #[error("Payload exceeds the maximum size")]
+    PayloadTooLarge,
+
+    /// 56 - Failed to prove that the Pubkey is excluded from the tree
+    #[error("Failed to prove that the Pubkey is excluded from the tree")]
+    PubkeyTreeExclusionCheckFailed,
+
+    /// 57 - Registry authority is not signer
+    #[error("Registry authority is not signer")]
+    RegistryAuthorityIsNotSigner,
+
+    /// 58 - Registry authority does not match the authority stored in the account
+    #[error("Registry authority does not match the authority stored in the account")]
+    RegistryAuthorityMismatch,
+
+    /// 59 - Pubkey List authority is not signer
+    #[error("Pubkey List authority is not signer")]
+    PubkeyListAuthorityIsNotSigner,
+
+    /// 60 - Pubkey List authority does not match the authority stored in the account
+    #[error("Pubkey List authority does not match the authority stored in the account")]
+    PubkeyListAuthorityMismatch,
+
+    /// 61 - Pubkey not found in the Pubkey List account
+    #[error("Pubkey not found in the Pubkey List account")]
+    PubkeyInListAccountCheckFailed,
+
+    /// 62 - Owner not found in the Pubkey List account
+    #[error("Owner not found in the Pubkey List account")]
+    OwnerInListAccountCheckFailed,
+
+    /// 63 - Pubkey list is not sorted or contains duplicates
+    #[error("Pubkey list is not sorted or contains duplicates")]
+    UnsortedPubkeyList,
+
+    /// 64 - RuleSet revision hash does not match the expected hash
+    #[error("RuleSet revision hash does not match the expected hash")]
+    RuleSetHashMismatch,
+
+    /// 65 - Write to the buffer is not aligned to its blocks or exceeds its length
+    #[error("Write to the buffer is not aligned to its blocks or exceeds its length")]
+    InvalidBufferWrite,
+
+    /// 66 - Buffer has not grown to the end of the chunk yet
+    #[error("Buffer has not grown to the end of the chunk yet")]
+    BufferTooSmall,
+
+    /// 67 - Buffer is missing chunks of the RuleSet
+    #[error("Buffer is missing chunks of the RuleSet")]
+    BufferIncomplete,
+
+    /// 68 - RuleSet in the buffer does not match its hash
+    #[error("RuleSet in the buffer does not match its hash")]
+    BufferHashMismatch,
+
+    /// 69 - Authority is not the transfer hook authority of the mint
+    #[error("Authority is not the transfer hook authority of the mint")]
+    TransferHookAuthorityMismatch,
+
+    /// 70 - Accounts do not match the extra account metas of the mint
+    #[error("Accounts do not match the extra account metas of the mint")]
+    TransferHookAccountMismatch,
+
+    /// 71 - Token account is invalid for the mint
+    #[error("Token account is invalid for the mint")]
+    InvalidTokenAccount,
+
+    /// 72 - Threshold is larger than the number of rules
+    #[error("Threshold is larger than the number of rules")]
+    InvalidThreshold,
 }
 
 impl PrintProgramError for RuleSetError {
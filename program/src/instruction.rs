@@ -5,6 +5,7 @@ use shank::ShankInstruction;
 use solana_program::{
     account_info::AccountInfo,
     instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
 };
 
 #[repr(C)]
@@ -34,6 +35,20 @@ pub enum ValidateArgs {
         /// Optional revision of the `RuleSet` to use.  If `None`, the latest revision is used.
         rule_set_revision: Option<usize>,
     },
+    /// V2 implementation of the `validate` instruction arguments.
+    V2 {
+        /// `Operation` to validate.
+        operation: String,
+        /// `Payload` data used for rule validation.
+        payload: Payload,
+        /// Update any relevant state stored in Rule, such as the Frequency `last_update` time value.
+        update_rule_state: bool,
+        /// Optional revision of the `RuleSet` to use.  If `None`, the latest revision is used.
+        rule_set_revision: Option<usize>,
+        /// Optional keccak hash of the serialized `RuleSet` the revision must match, as recorded
+        /// in the revision map when the revision was written.
+        expected_hash: Option<[u8; 32]>,
+    },
 }
 
 #[repr(C)]
@@ -47,6 +62,21 @@ pub enum WriteToBufferArgs {
         /// Whether the or not the any old data should be overwritten.
         overwrite: bool,
     },
+    /// V2 implementation of the `write_to_buffer` instruction arguments.  The buffer is
+    /// preallocated to `total_len` and chunks are written at explicit offsets, in any order.
+    V2 {
+        /// Offset of the chunk in the pre-serialized `RuleSet`.  Must be a multiple of
+        /// `BUFFER_BLOCK_LEN`.
+        offset: u32,
+        /// Chunk of the pre-serialized `RuleSet`.  Must end at a multiple of `BUFFER_BLOCK_LEN`
+        /// or at `total_len`.
+        data: Vec<u8>,
+        /// Length of the pre-serialized `RuleSet`.
+        total_len: u32,
+        /// Optional keccak hash of the pre-serialized `RuleSet`, checked by `CreateOrUpdate`.
+        /// A write with another `total_len` or hash than the buffer starts a new upload.
+        hash: Option<[u8; 32]>,
+    },
 }
 
 #[repr(C)]
@@ -60,6 +90,50 @@ pub enum PuffRuleSetArgs {
     },
 }
 
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone)]
+/// Args for `create_or_update_root_registry` instruction.
+pub enum CreateOrUpdateRootRegistryArgs {
+    /// V1 implementation of the `create_or_update_root_registry` instruction arguments.
+    V1 {
+        /// Root Registry name.
+        registry_name: String,
+        /// The Merkle root to store in the registry.
+        root: [u8; 32],
+    },
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone)]
+/// Args for `add_to_pubkey_list` and `remove_from_pubkey_list` instructions.
+pub enum ModifyPubkeyListArgs {
+    /// V1 implementation of the `add_to_pubkey_list` and `remove_from_pubkey_list` instruction
+    /// arguments.
+    V1 {
+        /// Pubkey List name.
+        list_name: String,
+        /// The `Pubkey`s to add or remove.
+        pubkeys: Vec<Pubkey>,
+    },
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone)]
+/// Args for `create_or_update_transfer_hook` instruction.
+pub enum CreateOrUpdateTransferHookArgs {
+    /// V1 implementation of the `create_or_update_transfer_hook` instruction arguments.
+    V1 {
+        /// Accounts passed to the `RuleSet` on every transfer, after the `RuleSet` PDA, such as
+        /// the Pubkey List accounts used by its rules.
+        additional_rule_accounts: Vec<Pubkey>,
+    },
+}
+
+/// Discriminator of the `Execute` instruction of the transfer hook interface, which is the first
+/// 8 bytes of the SHA-256 hash of `spl-transfer-hook-interface:execute`.  It is followed by the
+/// transferred amount as a little-endian `u64`.
+pub const EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+
 #[derive(Debug, Clone, ShankInstruction, AccountContext, BorshSerialize, BorshDeserialize)]
 #[rustfmt::skip]
 /// Instructions available in this program.
@@ -101,6 +175,40 @@ pub enum RuleSetInstruction {
     #[account(1, writable, name="rule_set_pda", desc = "The PDA account where the RuleSet is stored")]
     #[account(2, name = "system_program", desc = "System program")]
     PuffRuleSet(PuffRuleSetArgs),
+
+    /// This instruction creates a Root Registry PDA account or updates the Merkle root stored in
+    /// it.  Only the registry authority can update the root.
+    #[account(0, signer, writable, name="payer", desc="Payer for the Root Registry account")]
+    #[account(1, signer, name="authority", desc="Authority of the Root Registry")]
+    #[account(2, writable, name="root_registry_pda", desc = "The PDA account where the Merkle root is stored")]
+    #[account(3, name = "system_program", desc = "System program")]
+    CreateOrUpdateRootRegistry(CreateOrUpdateRootRegistryArgs),
+
+    /// This instruction adds `Pubkey`s to a Pubkey List PDA account, creating the account if
+    /// needed.  The list is kept sorted so it can be searched without deserialization.
+    #[account(0, signer, writable, name="payer", desc="Payer for the Pubkey List account")]
+    #[account(1, signer, name="authority", desc="Authority of the Pubkey List")]
+    #[account(2, writable, name="pubkey_list_pda", desc = "The PDA account where the Pubkey List is stored")]
+    #[account(3, name = "system_program", desc = "System program")]
+    AddToPubkeyList(ModifyPubkeyListArgs),
+
+    /// This instruction removes `Pubkey`s from a Pubkey List PDA account.
+    #[account(0, signer, writable, name="payer", desc="Payer for the Pubkey List account")]
+    #[account(1, signer, name="authority", desc="Authority of the Pubkey List")]
+    #[account(2, writable, name="pubkey_list_pda", desc = "The PDA account where the Pubkey List is stored")]
+    #[account(3, name = "system_program", desc = "System program")]
+    RemoveFromPubkeyList(ModifyPubkeyListArgs),
+
+    /// This instruction creates or updates the extra account metas PDA of a Token-2022 mint whose
+    /// transfer hook is this program, so that its transfers are validated by the `RuleSet`.  Only
+    /// the authority of the `TransferHook` extension of the mint can configure the hook.
+    #[account(0, signer, writable, name="payer", desc="Payer for the extra account metas account")]
+    #[account(1, signer, name="authority", desc="Transfer hook authority of the mint")]
+    #[account(2, name="mint", desc="Mint of the token")]
+    #[account(3, writable, name="extra_account_metas_pda", desc = "The PDA account where the extra account metas of the mint are stored")]
+    #[account(4, name="rule_set_pda", desc = "The PDA account where the RuleSet is stored")]
+    #[account(5, name = "system_program", desc = "System program")]
+    CreateOrUpdateTransferHook(CreateOrUpdateTransferHookArgs),
 }
 
 /// Builds a `CreateOrUpdate` instruction.
@@ -204,6 +312,117 @@ impl InstructionBuilder for builders::PuffRuleSet {
     }
 }
 
+/// Builds a `CreateOrUpdateRootRegistry` instruction.
+impl InstructionBuilder for builders::CreateOrUpdateRootRegistry {
+    fn instruction(&self) -> solana_program::instruction::Instruction {
+        let accounts = vec![
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new(self.root_registry_pda, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ];
+
+        Instruction {
+            program_id: crate::ID,
+            accounts,
+            data: borsh::to_vec(&RuleSetInstruction::CreateOrUpdateRootRegistry(
+                self.args.clone(),
+            ))
+            .unwrap(),
+        }
+    }
+}
+
+/// Builds a `AddToPubkeyList` instruction.
+impl InstructionBuilder for builders::AddToPubkeyList {
+    fn instruction(&self) -> solana_program::instruction::Instruction {
+        let accounts = vec![
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new(self.pubkey_list_pda, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ];
+
+        Instruction {
+            program_id: crate::ID,
+            accounts,
+            data: borsh::to_vec(&RuleSetInstruction::AddToPubkeyList(self.args.clone())).unwrap(),
+        }
+    }
+}
+
+/// Builds a `RemoveFromPubkeyList` instruction.
+impl InstructionBuilder for builders::RemoveFromPubkeyList {
+    fn instruction(&self) -> solana_program::instruction::Instruction {
+        let accounts = vec![
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new(self.pubkey_list_pda, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ];
+
+        Instruction {
+            program_id: crate::ID,
+            accounts,
+            data: borsh::to_vec(&RuleSetInstruction::RemoveFromPubkeyList(self.args.clone()))
+                .unwrap(),
+        }
+    }
+}
+
+/// Builds a `CreateOrUpdateTransferHook` instruction.
+impl InstructionBuilder for builders::CreateOrUpdateTransferHook {
+    fn instruction(&self) -> solana_program::instruction::Instruction {
+        let accounts = vec![
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new_readonly(self.mint, false),
+            AccountMeta::new(self.extra_account_metas_pda, false),
+            AccountMeta::new_readonly(self.rule_set_pda, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ];
+
+        Instruction {
+            program_id: crate::ID,
+            accounts,
+            data: borsh::to_vec(&RuleSetInstruction::CreateOrUpdateTransferHook(
+                self.args.clone(),
+            ))
+            .unwrap(),
+        }
+    }
+}
+
+/// Builds the `Execute` instruction of the transfer hook interface, as Token-2022 does when it
+/// calls the hook during a transfer.  The `extra_accounts` are the accounts listed in the extra
+/// account metas PDA of the mint, starting with the `RuleSet` PDA.
+pub fn transfer_hook_execute(
+    source: Pubkey,
+    mint: Pubkey,
+    destination: Pubkey,
+    authority: Pubkey,
+    extra_accounts: Vec<AccountMeta>,
+    amount: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(source, false),
+        AccountMeta::new_readonly(mint, false),
+        AccountMeta::new_readonly(destination, false),
+        AccountMeta::new_readonly(authority, false),
+        AccountMeta::new_readonly(crate::pda::find_extra_account_metas_address(mint).0, false),
+    ];
+    accounts.extend(extra_accounts);
+
+    let mut data = EXECUTE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data,
+    }
+}
+
 /// Account context holding the accounts used by various instructions.
 pub struct Context<'a, T> {
     /// The struct holding the named accounts used by an instruction.
@@ -1,8 +1,17 @@
+#[cfg(feature = "client")]
+#[deny(missing_docs)]
+pub mod diff;
+#[cfg(feature = "client")]
+#[deny(missing_docs)]
+pub mod dsl;
 #[deny(missing_docs)]
 pub mod entrypoint;
 #[deny(missing_docs)]
 pub mod error;
 pub mod instruction;
+#[cfg(feature = "client")]
+#[deny(missing_docs)]
+pub mod merkle;
 #[deny(missing_docs)]
 pub mod payload;
 #[deny(missing_docs)]
@@ -12,6 +21,9 @@ pub mod processor;
 #[deny(missing_docs)]
 pub mod state;
 pub mod types;
+#[cfg(feature = "client")]
+#[deny(missing_docs)]
+pub mod upload;
 #[deny(missing_docs)]
 pub mod utils;
 
@@ -0,0 +1,393 @@
+//! Off-chain builders for the Merkle trees used by the tree rules.
+//!
+//! The `MerkleTree` builds the roots and proofs used by the `PubkeyTreeMatch` and
+//! `ProgramOwnedTree` rules.  The leaves of the tree are the keys themselves, and each pair of
+//! nodes is sorted and hashed with a constant value of 0x01, so every proof can be verified
+//! with `utils::compute_merkle_root`.
+//!
+//! The `ExclusionTree` commits to a sorted set of keys, so that a key can be proven to not be a
+//! member of the set.  Each leaf of the tree commits to two adjacent keys of the set, and the
+//! first and last leaves have an open-ended bound below the smallest key and above the largest
+//! key, so that any other key falls between two adjacent bounds.
+use crate::{
+    payload::{ExclusionProofInfo, ProofInfo},
+    utils::hash_exclusion_leaf,
+};
+use solana_program::{keccak, pubkey::Pubkey};
+use std::{collections::BTreeMap, iter};
+
+/// Hash a pair of nodes the same way as `utils::compute_merkle_root_from_node`: the nodes are
+/// sorted and hashed with a constant value of 0x01.
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    if left <= right {
+        keccak::hashv(&[&[0x01], left, right]).0
+    } else {
+        keccak::hashv(&[&[0x01], right, left]).0
+    }
+}
+
+/// Build every layer of a tree from its leaves, the last layer containing only the root.  A node
+/// without a sibling is moved up to the next layer unchanged.
+fn build_layers(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut layers = vec![leaves];
+
+    while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+        let next = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                [node] => *node,
+                _ => unreachable!("chunks always have one or two nodes"),
+            })
+            .collect();
+        layers.push(next);
+    }
+
+    layers
+}
+
+/// Get the proof of the leaf at `index` from the layers of a tree.
+fn proof_from_layers(layers: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+
+    for layer in &layers[..layers.len() - 1] {
+        if let Some(sibling) = layer.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+
+    proof
+}
+
+/// A Merkle tree of keys, used by the `PubkeyTreeMatch` and `ProgramOwnedTree` rules.
+pub struct MerkleTree {
+    /// The leaves of the tree, in the order they were provided.
+    leaves: Vec<Pubkey>,
+    /// The layers of the tree, from the leaves to the root.
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Create a new `MerkleTree` from a list of leaves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaves` is empty.
+    pub fn new(leaves: &[Pubkey]) -> Self {
+        assert!(!leaves.is_empty(), "a Merkle tree needs at least one leaf");
+
+        Self {
+            leaves: leaves.to_vec(),
+            layers: build_layers(leaves.iter().map(|leaf| leaf.to_bytes()).collect()),
+        }
+    }
+
+    /// The root of the tree.
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    /// The leaves of the tree.
+    pub fn leaves(&self) -> &[Pubkey] {
+        &self.leaves
+    }
+
+    /// Get the proof of the leaf at `index`, or `None` if the index is out of bounds.
+    pub fn proof(&self, index: usize) -> Option<ProofInfo> {
+        if index < self.leaves.len() {
+            Some(ProofInfo::new(proof_from_layers(&self.layers, index)))
+        } else {
+            None
+        }
+    }
+
+    /// Get the proof of the first occurrence of `leaf`, or `None` if it is not a leaf of the tree.
+    pub fn proof_for(&self, leaf: &Pubkey) -> Option<ProofInfo> {
+        self.proof(self.leaves.iter().position(|key| key == leaf)?)
+    }
+
+    /// Get a single proof for the leaves at `indices`, which omits the nodes that can be computed
+    /// from the leaves themselves.  Returns `None` if an index is out of bounds.
+    pub fn multi_proof(&self, indices: &[usize]) -> Option<MultiProof> {
+        if indices.iter().any(|index| *index >= self.leaves.len()) {
+            return None;
+        }
+
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut proof = Vec::new();
+        let mut known = indices.clone();
+
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let mut parents = Vec::with_capacity(known.len());
+            for index in &known {
+                let sibling = index ^ 1;
+                if sibling < layer.len() && known.binary_search(&sibling).is_err() {
+                    proof.push(layer[sibling]);
+                }
+                if parents.last() != Some(&(index / 2)) {
+                    parents.push(index / 2);
+                }
+            }
+            known = parents;
+        }
+
+        Some(MultiProof {
+            leaf_count: self.leaves.len(),
+            indices,
+            proof,
+        })
+    }
+}
+
+/// A proof for several leaves of a `MerkleTree`.  Unlike a `ProofInfo`, the proof depends on the
+/// position of the leaves, so the indices of the leaves and the number of leaves of the tree
+/// are part of the proof.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MultiProof {
+    /// The number of leaves of the tree.
+    pub leaf_count: usize,
+    /// The sorted indices of the proven leaves.
+    pub indices: Vec<usize>,
+    /// The nodes of the tree needed to compute the root, in the order they are consumed.
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl MultiProof {
+    /// Compute the root of the tree given the proven leaves, in the order of `indices`.  Returns
+    /// `None` if the number of leaves or proof nodes does not match the proof.
+    pub fn compute_root(&self, leaves: &[Pubkey]) -> Option<[u8; 32]> {
+        if leaves.len() != self.indices.len() || self.indices.is_empty() {
+            return None;
+        }
+
+        let mut known = self
+            .indices
+            .iter()
+            .copied()
+            .zip(leaves.iter().map(|leaf| leaf.to_bytes()))
+            .collect::<BTreeMap<_, _>>();
+        let mut proof = self.proof.iter();
+        let mut width = self.leaf_count;
+
+        while width > 1 {
+            let mut parents = BTreeMap::new();
+            for (index, node) in &known {
+                if parents.contains_key(&(index / 2)) {
+                    continue;
+                }
+
+                let sibling = index ^ 1;
+                let parent = if sibling >= width {
+                    *node
+                } else if let Some(sibling_node) = known.get(&sibling) {
+                    hash_pair(node, sibling_node)
+                } else {
+                    hash_pair(node, proof.next()?)
+                };
+                parents.insert(index / 2, parent);
+            }
+            known = parents;
+            width = width.div_ceil(2);
+        }
+
+        // Every node of the proof must be used.
+        if proof.next().is_some() {
+            return None;
+        }
+
+        known.get(&0).copied()
+    }
+}
+
+/// A Merkle tree committing to a sorted set of keys, used by the `PubkeyTreeExclusion` rule.
+pub struct ExclusionTree {
+    /// The sorted keys of the set.
+    keys: Vec<Pubkey>,
+    /// The layers of the tree, from the leaves to the root.
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl ExclusionTree {
+    /// Create a new `ExclusionTree` from a set of keys.  The keys do not need to be sorted, and
+    /// duplicated keys are ignored.
+    pub fn new(keys: &[Pubkey]) -> Self {
+        let mut keys = keys.to_vec();
+        keys.sort();
+        keys.dedup();
+
+        // The keys are bracketed by the open-ended bounds.
+        let bounds = iter::once(None)
+            .chain(keys.iter().map(Some))
+            .chain(iter::once(None))
+            .collect::<Vec<_>>();
+
+        let leaves = bounds
+            .windows(2)
+            .map(|pair| hash_exclusion_leaf(pair[0], pair[1]))
+            .collect();
+
+        Self {
+            keys,
+            layers: build_layers(leaves),
+        }
+    }
+
+    /// The root of the tree.
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    /// Returns whether the key is a member of the tree.
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.keys.binary_search(key).is_ok()
+    }
+
+    /// Get the proof that a key is not a member of the tree, or `None` if the key is a member.
+    pub fn exclusion_proof(&self, key: &Pubkey) -> Option<ExclusionProofInfo> {
+        // A key that is not found is inserted between two bounds, the leaf at `index` being the
+        // one that commits to the bounds before and after `key`.
+        let index = self.keys.binary_search(key).err()?;
+
+        Some(ExclusionProofInfo::new(
+            index.checked_sub(1).map(|low| self.keys[low]),
+            self.keys.get(index).copied(),
+            proof_from_layers(&self.layers, index),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{compute_merkle_root, is_excluded};
+    use solana_program::system_program;
+
+    #[test]
+    fn test_proofs() {
+        for size in 1..=33 {
+            let leaves = (0..size).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+            let tree = MerkleTree::new(&leaves);
+
+            // Every proof is accepted by the on-chain verifier.
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(compute_merkle_root(leaf, &proof), tree.root());
+                assert_eq!(tree.proof_for(leaf), Some(proof.clone()));
+
+                // A proof does not prove a different leaf.
+                let other = Pubkey::new_unique();
+                assert_ne!(compute_merkle_root(&other, &proof), tree.root());
+            }
+
+            assert!(tree.proof(size).is_none());
+        }
+    }
+
+    #[test]
+    fn test_multi_proofs() {
+        for size in 1..=17 {
+            let leaves = (0..size).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+            let tree = MerkleTree::new(&leaves);
+
+            // Check every subset of leaves of small trees, and a sample of the larger ones.
+            let subsets = (1u32..(1 << size.min(8))).map(|mask| {
+                (0..size)
+                    .filter(|index| mask & (1 << (index % 8)) != 0)
+                    .collect::<Vec<_>>()
+            });
+
+            for indices in subsets {
+                let proof = tree.multi_proof(&indices).unwrap();
+                let proven = indices
+                    .iter()
+                    .map(|index| leaves[*index])
+                    .collect::<Vec<_>>();
+                assert_eq!(proof.compute_root(&proven), Some(tree.root()));
+
+                // The multi-proof is never larger than the separate proofs.
+                let separate = indices
+                    .iter()
+                    .map(|index| tree.proof(*index).unwrap().proof.len())
+                    .sum::<usize>();
+                assert!(proof.proof.len() <= separate);
+
+                // A different leaf does not lead to the root.
+                let mut tampered = proven.clone();
+                tampered[0] = Pubkey::new_unique();
+                assert_ne!(proof.compute_root(&tampered), Some(tree.root()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_exclusion_proofs() {
+        for size in [0, 1, 2, 3, 7, 8, 33] {
+            let keys = (0..size).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+            let tree = ExclusionTree::new(&keys);
+
+            // Members cannot be excluded.
+            for key in &keys {
+                assert!(tree.contains(key));
+                assert!(tree.exclusion_proof(key).is_none());
+            }
+
+            // Any other key has a valid exclusion proof.
+            for _ in 0..8 {
+                let key = Pubkey::new_unique();
+                let proof = tree.exclusion_proof(&key).unwrap();
+                assert!(is_excluded(&key, &proof, &tree.root()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_exclusion_of_extreme_keys() {
+        // The System Program ID is the all-zero key.
+        let extremes = [system_program::ID, Pubkey::new_from_array([0xff; 32])];
+
+        for size in [0, 1, 5] {
+            let keys = (0..size).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+            // The extreme keys can be excluded.
+            let tree = ExclusionTree::new(&keys);
+            for key in &extremes {
+                let proof = tree.exclusion_proof(key).unwrap();
+                assert!(is_excluded(key, &proof, &tree.root()));
+            }
+
+            // Unless they are members of the tree.
+            let members = keys.iter().chain(&extremes).copied().collect::<Vec<_>>();
+            let tree = ExclusionTree::new(&members);
+            for key in &extremes {
+                assert!(tree.contains(key));
+                assert!(tree.exclusion_proof(key).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_exclusion_proofs() {
+        let keys = (0..5).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let tree = ExclusionTree::new(&keys);
+        let key = Pubkey::new_unique();
+        let proof = tree.exclusion_proof(&key).unwrap();
+
+        // The proof of a key does not prove that a member is excluded.
+        for member in &keys {
+            assert!(!is_excluded(member, &proof, &tree.root()));
+        }
+
+        // The bracketing keys must be adjacent keys of the tree.
+        let mut skipped = proof.clone();
+        skipped.low = None;
+        skipped.high = None;
+        assert!(!is_excluded(&key, &skipped, &tree.root()));
+
+        // The proof must lead to the root.
+        assert!(!is_excluded(&key, &proof, &ExclusionTree::new(&[]).root()));
+    }
+}
@@ -1,9 +1,16 @@
 //! The definition and associated functions of the `Payload` type that is passed from the program client to the auth rules program for validation.
-use crate::error::RuleSetError;
+//!
+//! The `Payload` entries are kept sorted by key.  The encoding is the same as the previous
+//! `HashMap` based encoding (a `u32` length followed by the key-value pairs sorted by key), and
+//! entries in any order are still accepted when decoding.
+use crate::{
+    error::RuleSetError,
+    types::{MAX_NAME_LENGTH, MAX_PAYLOAD_SIZE},
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use solana_program::{entrypoint::ProgramResult, pubkey::Pubkey};
-use std::collections::HashMap;
+use std::collections::{btree_map::Entry, BTreeMap};
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
@@ -35,6 +42,28 @@ impl ProofInfo {
     }
 }
 
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+/// A non-membership proof type used by the `PubkeyTreeExclusion` rule.  The `low` and `high`
+/// keys are adjacent keys of the sorted set committed by the tree, and the proof is the Merkle
+/// proof of the leaf committing to them.  A missing `low` or `high` key is the open-ended
+/// bound below the smallest key or above the largest key of the set.
+pub struct ExclusionProofInfo {
+    /// The largest key of the set that is smaller than the excluded key, if any.
+    pub low: Option<Pubkey>,
+    /// The smallest key of the set that is larger than the excluded key, if any.
+    pub high: Option<Pubkey>,
+    /// The merkle proof.
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl ExclusionProofInfo {
+    /// Create a new `ExclusionProofInfo`.
+    pub fn new(low: Option<Pubkey>, high: Option<Pubkey>, proof: Vec<[u8; 32]>) -> Self {
+        Self { low, high, proof }
+    }
+}
+
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 /// Variants representing the different types represented in a payload.
@@ -47,22 +76,36 @@ pub enum PayloadType {
     MerkleProof(ProofInfo),
     /// A plain `u64` used for `Amount`.
     Number(u64),
+    /// Arbitrary bytes.
+    Bytes(Vec<u8>),
+    /// A UTF-8 string.
+    Str(String),
+    /// A boolean flag.
+    Bool(bool),
+    /// A signed `i64`.
+    I64(i64),
+    /// A plain `u128`.
+    U128(u128),
+    /// A list of `Pubkey`s.
+    PubkeyList(Vec<Pubkey>),
+    /// A merkle non-membership proof.
+    ExclusionProof(ExclusionProofInfo),
 }
 
 #[repr(C)]
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default,
 )]
-/// A wrapper type for the payload hashmap.
+/// A wrapper type for the payload map.
 pub struct Payload {
-    map: HashMap<String, PayloadType>,
+    map: BTreeMap<String, PayloadType>,
 }
 
 impl Payload {
     /// Create a new empty `Payload`.
     pub fn new() -> Self {
         Self {
-            map: HashMap::new(),
+            map: BTreeMap::new(),
         }
     }
 
@@ -70,27 +113,45 @@ impl Payload {
     /// `(PayloadKey, PayloadType)` tuples.
     pub fn from<const N: usize>(arr: [(String, PayloadType); N]) -> Self {
         Self {
-            map: HashMap::from(arr),
+            map: BTreeMap::from(arr),
         }
     }
 
     /// Inserts a key-value pair into the `Payload`.  If the `Payload` did not have this key
     ///  present, then `None` is returned.  If the `Payload` did have this key present, the value
     /// is updated, and the old value is returned.  The key is not updated, though; this matters
-    /// for types that can be `==` without being identical.  See `std::collections::HashMap`
+    /// for types that can be `==` without being identical.  See `std::collections::BTreeMap`
     /// documentation for more info.
     pub fn insert(&mut self, key: String, value: PayloadType) -> Option<PayloadType> {
         self.map.insert(key, value)
     }
 
     /// Tries to insert a key-value pair into a `Payload`.  If this key is already in the `Payload`
-    /// nothing is updated and an error is returned.
+    /// or the key is longer than `MAX_NAME_LENGTH`, nothing is updated and an error is returned.
     pub fn try_insert(&mut self, key: String, value: PayloadType) -> ProgramResult {
-        if self.map.get(&key).is_none() {
-            self.map.insert(key, value);
-            Ok(())
-        } else {
-            Err(RuleSetError::ValueOccupied.into())
+        if key.len() > MAX_NAME_LENGTH {
+            return Err(RuleSetError::PayloadKeyTooLong.into());
+        }
+
+        match self.map.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                Ok(())
+            }
+            Entry::Occupied(_) => Err(RuleSetError::ValueOccupied.into()),
+        }
+    }
+
+    /// Checks that every key is at most `MAX_NAME_LENGTH` bytes long and that the serialized
+    /// `Payload` is at most `MAX_PAYLOAD_SIZE` bytes.
+    pub fn assert_within_limits(&self) -> ProgramResult {
+        if self.map.keys().any(|key| key.len() > MAX_NAME_LENGTH) {
+            return Err(RuleSetError::PayloadKeyTooLong.into());
+        }
+
+        match borsh::object_length(self) {
+            Ok(size) if size <= MAX_PAYLOAD_SIZE => Ok(()),
+            _ => Err(RuleSetError::PayloadTooLarge.into()),
         }
     }
 
@@ -141,6 +202,20 @@ impl Payload {
         }
     }
 
+    /// Get a reference to the `ExclusionProofInfo` associated with a key, if and only if the
+    /// `Payload` value is the `PayloadType::ExclusionProof` variant.  Returns `None` if the key
+    /// is not present in the `Payload` or the value is a different `PayloadType` variant.
+    pub fn get_exclusion_proof(&self, key: &String) -> Option<&ExclusionProofInfo> {
+        if let Some(val) = self.map.get(key) {
+            match val {
+                PayloadType::ExclusionProof(exclusion_proof) => Some(exclusion_proof),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
     /// Get the `u64` associated with a key, if and only if the `Payload` value is the
     /// `PayloadType::Number` variant.  Returns `None` if the key is not present in the `Payload`
     /// or the value is a different `PayloadType` variant.
@@ -154,4 +229,214 @@ impl Payload {
             None
         }
     }
+
+    /// Get a reference to the bytes associated with a key, if and only if the `Payload` value is
+    /// the `PayloadType::Bytes` variant.  Returns `None` if the key is not present in the
+    /// `Payload` or the value is a different `PayloadType` variant.
+    pub fn get_bytes(&self, key: &String) -> Option<&[u8]> {
+        if let Some(val) = self.map.get(key) {
+            match val {
+                PayloadType::Bytes(bytes) => Some(bytes),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Get a reference to the string associated with a key, if and only if the `Payload` value
+    /// is the `PayloadType::Str` variant.  Returns `None` if the key is not present in the
+    /// `Payload` or the value is a different `PayloadType` variant.
+    pub fn get_str(&self, key: &String) -> Option<&str> {
+        if let Some(val) = self.map.get(key) {
+            match val {
+                PayloadType::Str(string) => Some(string),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Get the `bool` associated with a key, if and only if the `Payload` value is the
+    /// `PayloadType::Bool` variant.  Returns `None` if the key is not present in the `Payload`
+    /// or the value is a different `PayloadType` variant.
+    pub fn get_bool(&self, key: &String) -> Option<bool> {
+        if let Some(val) = self.map.get(key) {
+            match val {
+                PayloadType::Bool(flag) => Some(*flag),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Get the `i64` associated with a key, if and only if the `Payload` value is the
+    /// `PayloadType::I64` variant.  Returns `None` if the key is not present in the `Payload`
+    /// or the value is a different `PayloadType` variant.
+    pub fn get_i64(&self, key: &String) -> Option<i64> {
+        if let Some(val) = self.map.get(key) {
+            match val {
+                PayloadType::I64(number) => Some(*number),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Get the `u128` associated with a key, if and only if the `Payload` value is the
+    /// `PayloadType::U128` variant.  Returns `None` if the key is not present in the `Payload`
+    /// or the value is a different `PayloadType` variant.
+    pub fn get_u128(&self, key: &String) -> Option<u128> {
+        if let Some(val) = self.map.get(key) {
+            match val {
+                PayloadType::U128(number) => Some(*number),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Get the amount associated with a key as a `u128`, if and only if the `Payload` value is
+    /// the `PayloadType::Number` or `PayloadType::U128` variant.  Returns `None` if the key is
+    /// not present in the `Payload` or the value is a different `PayloadType` variant.
+    pub fn get_amount_u128(&self, key: &String) -> Option<u128> {
+        if let Some(val) = self.map.get(key) {
+            match val {
+                PayloadType::Number(number) => Some(*number as u128),
+                PayloadType::U128(number) => Some(*number),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Get a reference to the list of `Pubkey`s associated with a key, if and only if the
+    /// `Payload` value is the `PayloadType::PubkeyList` variant.  Returns `None` if the key is
+    /// not present in the `Payload` or the value is a different `PayloadType` variant.
+    pub fn get_pubkey_list(&self, key: &String) -> Option<&[Pubkey]> {
+        if let Some(val) = self.map.get(key) {
+            match val {
+                PayloadType::PubkeyList(pubkeys) => Some(pubkeys),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borsh_variant_indices() {
+        // Existing variants must keep their borsh discriminants.
+        let pubkey = Pubkey::new_unique();
+        assert_eq!(borsh::to_vec(&PayloadType::Pubkey(pubkey)).unwrap()[0], 0);
+        assert_eq!(
+            borsh::to_vec(&PayloadType::Seeds(SeedsVec::new(vec![]))).unwrap()[0],
+            1
+        );
+        assert_eq!(
+            borsh::to_vec(&PayloadType::MerkleProof(ProofInfo::new(vec![]))).unwrap()[0],
+            2
+        );
+        assert_eq!(
+            borsh::to_vec(&PayloadType::Number(1)).unwrap(),
+            [3, 1, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        // New variants are appended.
+        assert_eq!(borsh::to_vec(&PayloadType::Bytes(vec![])).unwrap()[0], 4);
+        assert_eq!(
+            borsh::to_vec(&PayloadType::PubkeyList(vec![])).unwrap()[0],
+            9
+        );
+    }
+
+    #[test]
+    fn test_canonical_encoding() {
+        let first = ("a".to_string(), PayloadType::Number(1));
+        let second = ("b".to_string(), PayloadType::Bool(true));
+
+        // The insertion order does not change the encoding.
+        let payload = Payload::from([first.clone(), second.clone()]);
+        let reversed = Payload::from([second.clone(), first.clone()]);
+        let bytes = borsh::to_vec(&payload).unwrap();
+        assert_eq!(bytes, borsh::to_vec(&reversed).unwrap());
+
+        // Payloads with unsorted entries are still decoded.
+        let legacy = borsh::to_vec(&(2u32, second, first)).unwrap();
+        assert_ne!(legacy, bytes);
+        assert_eq!(Payload::try_from_slice(&legacy).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut payload = Payload::new();
+        assert!(payload
+            .try_insert("k".repeat(MAX_NAME_LENGTH + 1), PayloadType::Number(1))
+            .is_err());
+
+        // Keys inserted without checks are caught by the limits check.
+        payload.insert("k".repeat(MAX_NAME_LENGTH + 1), PayloadType::Number(1));
+        assert_eq!(
+            payload.assert_within_limits(),
+            Err(RuleSetError::PayloadKeyTooLong.into())
+        );
+
+        let payload = Payload::from([(
+            "k".repeat(MAX_NAME_LENGTH),
+            PayloadType::Bytes(vec![0; MAX_PAYLOAD_SIZE]),
+        )]);
+        assert_eq!(
+            payload.assert_within_limits(),
+            Err(RuleSetError::PayloadTooLarge.into())
+        );
+
+        let payload = Payload::from([("k".repeat(MAX_NAME_LENGTH), PayloadType::Bytes(vec![]))]);
+        assert!(payload.assert_within_limits().is_ok());
+    }
+
+    #[test]
+    fn test_getters() {
+        let payload = Payload::from([
+            ("bytes".to_string(), PayloadType::Bytes(vec![1, 2, 3])),
+            ("str".to_string(), PayloadType::Str("abc".to_string())),
+            ("bool".to_string(), PayloadType::Bool(true)),
+            ("i64".to_string(), PayloadType::I64(-5)),
+            ("u128".to_string(), PayloadType::U128(u128::MAX)),
+            (
+                "list".to_string(),
+                PayloadType::PubkeyList(vec![Pubkey::default()]),
+            ),
+        ]);
+
+        assert_eq!(
+            payload.get_bytes(&"bytes".to_string()),
+            Some(&[1u8, 2, 3][..])
+        );
+        assert_eq!(payload.get_str(&"str".to_string()), Some("abc"));
+        assert_eq!(payload.get_bool(&"bool".to_string()), Some(true));
+        assert_eq!(payload.get_i64(&"i64".to_string()), Some(-5));
+        assert_eq!(payload.get_u128(&"u128".to_string()), Some(u128::MAX));
+        assert_eq!(
+            payload.get_pubkey_list(&"list".to_string()),
+            Some(&[Pubkey::default()][..])
+        );
+
+        // Wrong variant type.
+        assert_eq!(payload.get_bool(&"i64".to_string()), None);
+        assert_eq!(payload.get_amount(&"u128".to_string()), None);
+        assert_eq!(
+            payload.get_amount_u128(&"u128".to_string()),
+            Some(u128::MAX)
+        );
+    }
 }
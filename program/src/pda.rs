@@ -7,6 +7,15 @@ pub const PREFIX: &str = "rule_set";
 /// The string prefix for Rule Set State PDA seeds.
 pub const STATE_PDA: &str = "rule_set_state";
 
+/// The string prefix for Root Registry PDA seeds.
+pub const ROOT_REGISTRY_PREFIX: &str = "root_registry";
+
+/// The string prefix for Pubkey List PDA seeds.
+pub const PUBKEY_LIST_PREFIX: &str = "pubkey_list";
+
+/// The string prefix for extra account metas PDA seeds, defined by the transfer hook interface.
+pub const EXTRA_ACCOUNT_METAS_PREFIX: &str = "extra-account-metas";
+
 /// Find the PDA for a Rule Set account.
 pub fn find_rule_set_address(creator: Pubkey, rule_set_name: String) -> (Pubkey, u8) {
     Pubkey::find_program_address(
@@ -40,3 +49,36 @@ pub fn find_rule_set_state_address(
 pub fn find_buffer_address(creator: Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[PREFIX.as_bytes(), creator.as_ref()], &crate::ID)
 }
+
+/// Find the PDA for a Root Registry account.
+pub fn find_root_registry_address(authority: Pubkey, registry_name: String) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ROOT_REGISTRY_PREFIX.as_bytes(),
+            authority.as_ref(),
+            registry_name.as_bytes(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Find the PDA for a Pubkey List account.
+pub fn find_pubkey_list_address(authority: Pubkey, list_name: String) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PUBKEY_LIST_PREFIX.as_bytes(),
+            authority.as_ref(),
+            list_name.as_bytes(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Find the PDA for the extra account metas account of a mint whose transfer hook is this
+/// program.
+pub fn find_extra_account_metas_address(mint: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[EXTRA_ACCOUNT_METAS_PREFIX.as_bytes(), mint.as_ref()],
+        &crate::ID,
+    )
+}
@@ -1,6 +1,6 @@
 use borsh::BorshSerialize;
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    account_info::AccountInfo, entrypoint::ProgramResult, keccak, program_error::ProgramError,
     program_memory::sol_memcpy, pubkey::Pubkey,
 };
 
@@ -9,8 +9,8 @@ use crate::{
     instruction::{Context, CreateOrUpdate, CreateOrUpdateArgs},
     pda::PREFIX,
     state::{
-        RuleSetHeader, RuleSetRevisionMapV1, RuleSetV1, RuleSetV2, RULE_SET_REV_MAP_VERSION,
-        RULE_SET_SERIALIZED_HEADER_LEN, U64_BYTES,
+        BufferAccount, RuleSetHeader, RuleSetRevisionMapV2, RuleSetV1, RuleSetV2,
+        RULE_SET_REV_MAP_VERSION, RULE_SET_SERIALIZED_HEADER_LEN, U64_BYTES,
     },
     types::{LibVersion, RuleSet, MAX_NAME_LENGTH},
     utils::{
@@ -44,12 +44,25 @@ fn create_or_update_v1(
         return Err(RuleSetError::PayerIsNotSigner.into());
     }
 
-    // Deserialize the `RuleSet`.
-    let (rule_set_version, rule_set_name, owner) = match ctx.accounts.buffer_pda_info {
-        Some(account_info) => get_rule_set_info(&(*account_info.data).borrow())?,
-        None => get_rule_set_info(&serialized_rule_set)?,
+    // The user-pre-serialized `RuleSet` is either in a buffer account or provided as an
+    // argument.  A buffer written in chunks must have all of them.
+    let buffer_data = ctx
+        .accounts
+        .buffer_pda_info
+        .map(|account_info| account_info.try_borrow_data())
+        .transpose()?;
+
+    let serialized_rule_set: &[u8] = match &buffer_data {
+        Some(data) if BufferAccount::is_chunked(data) => {
+            BufferAccount::from_bytes(data)?.complete_rule_set()?
+        }
+        Some(data) => data,
+        None => &serialized_rule_set,
     };
 
+    // Deserialize the `RuleSet`.
+    let (rule_set_version, rule_set_name, owner) = get_rule_set_info(serialized_rule_set)?;
+
     // Check that the name is not too long.
     if rule_set_name.len() > MAX_NAME_LENGTH {
         return Err(RuleSetError::NameTooLong.into());
@@ -79,10 +92,10 @@ fn create_or_update_v1(
     ];
 
     // Get new or existing revision map.
-    let revision_map = if ctx.accounts.rule_set_pda_info.data_is_empty()
+    let mut revision_map = if ctx.accounts.rule_set_pda_info.data_is_empty()
         || is_zeroed(&ctx.accounts.rule_set_pda_info.data.borrow())
     {
-        let mut revision_map = RuleSetRevisionMapV1::default();
+        let mut revision_map = RuleSetRevisionMapV2::default();
 
         // Initially set the latest revision location to a the value right after the header.
         revision_map
@@ -118,18 +131,17 @@ fn create_or_update_v1(
         revision_map
     };
 
-    // Borsh serialize (or re-serialize) the revision map.
+    // Reserve the hash of the new revision, which is computed when the revision is written.
+    revision_map.rule_set_hashes.push([0; 32]);
+
+    // Borsh serialize (or re-serialize) the revision map to get its length.
     let mut serialized_rev_map = Vec::new();
     revision_map
         .serialize(&mut serialized_rev_map)
         .map_err(|_| RuleSetError::BorshSerializationError)?;
 
-    // Get new user-pre-serialized `RuleSet` data length based on whether it's in a buffer account
-    // or provided as an argument.
-    let new_rule_set_data_len = match ctx.accounts.buffer_pda_info {
-        Some(account_info) => account_info.data_len(),
-        None => serialized_rule_set.len(),
-    };
+    // Get new user-pre-serialized `RuleSet` data length.
+    let new_rule_set_data_len = serialized_rule_set.len();
 
     // Determine size needed for PDA: next revision location (which is:
     // (RULE_SET_SERIALIZED_HEADER_LEN || existing latest revision map location))
@@ -175,39 +187,35 @@ fn create_or_update_v1(
 
     // Write all the data to the PDA.  The user-pre-serialized `RuleSet` is either in a buffer
     // account or provided as an argument.
-    match ctx.accounts.buffer_pda_info {
-        Some(account_info) => write_data_to_pda(
-            ctx.accounts.rule_set_pda_info,
-            *revision_map
-                .rule_set_revisions
-                .last()
-                .ok_or(RuleSetError::RuleSetRevisionNotAvailable)?,
-            &serialized_rev_map,
-            &account_info.data.borrow(),
-            matches!(rule_set_version, LibVersion::V1),
-        ),
-        None => write_data_to_pda(
-            ctx.accounts.rule_set_pda_info,
-            *revision_map
-                .rule_set_revisions
-                .last()
-                .ok_or(RuleSetError::RuleSetRevisionNotAvailable)?,
-            &serialized_rev_map,
-            &serialized_rule_set,
-            matches!(rule_set_version, LibVersion::V1),
-        ),
-    }
+    let starting_location = *revision_map
+        .rule_set_revisions
+        .last()
+        .ok_or(RuleSetError::RuleSetRevisionNotAvailable)?;
+
+    write_data_to_pda(
+        ctx.accounts.rule_set_pda_info,
+        starting_location,
+        &mut revision_map,
+        serialized_rule_set,
+        matches!(rule_set_version, LibVersion::V1),
+    )
 }
 
 /// Returns the lib version, name, and owner of a rule set.
 fn get_rule_set_info(data: &[u8]) -> Result<(LibVersion, String, Pubkey), ProgramError> {
     if let Ok(rule_set) = rmp_serde::from_slice::<RuleSetV1>(data) {
+        rule_set.assert_well_formed()?;
+        rule_set.assert_valid_references()?;
+
         Ok((
             LibVersion::try_from(rule_set.lib_version())?,
             rule_set.name(),
             *rule_set.owner(),
         ))
     } else if let Ok(rule_set) = RuleSetV2::from_bytes(data) {
+        rule_set.assert_well_formed()?;
+        rule_set.assert_valid_references()?;
+
         Ok((
             LibVersion::try_from(rule_set.lib_version())?,
             rule_set.name(),
@@ -219,11 +227,12 @@ fn get_rule_set_info(data: &[u8]) -> Result<(LibVersion, String, Pubkey), Progra
 }
 
 /// Write the `RuleSet` lib version, a serialized `RuleSet`, the revision map version,
-/// a revision map, and a header to the `RuleSet` PDA.
+/// a revision map, and a header to the `RuleSet` PDA.  The keccak hash of the serialized
+/// `RuleSet` is recorded as the hash of the latest revision in the revision map.
 fn write_data_to_pda(
     rule_set_pda_info: &AccountInfo,
     starting_location: usize,
-    serialized_rev_map: &[u8],
+    revision_map: &mut RuleSetRevisionMapV2,
     serialized_rule_set: &[u8],
     write_lib_version: bool,
 ) -> ProgramResult {
@@ -284,6 +293,18 @@ fn write_data_to_pda(
         .serialize(&mut serialized_header)
         .map_err(|_| RuleSetError::BorshSerializationError)?;
 
+    // Record the hash of the new revision and Borsh serialize the revision map.
+    let hash = revision_map
+        .rule_set_hashes
+        .last_mut()
+        .ok_or(RuleSetError::RuleSetRevisionNotAvailable)?;
+    *hash = keccak::hash(serialized_rule_set).to_bytes();
+
+    let mut serialized_rev_map = Vec::new();
+    revision_map
+        .serialize(&mut serialized_rev_map)
+        .map_err(|_| RuleSetError::BorshSerializationError)?;
+
     // Copy the serialized revision map to PDA account.
     let start = end;
     let end = start
@@ -292,7 +313,7 @@ fn write_data_to_pda(
     if end <= data.len() {
         sol_memcpy(
             &mut data[start..end],
-            serialized_rev_map,
+            &serialized_rev_map,
             serialized_rev_map.len(),
         );
     } else {
@@ -0,0 +1,85 @@
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::{
+    error::RuleSetError,
+    instruction::{Context, CreateOrUpdateRootRegistry, CreateOrUpdateRootRegistryArgs},
+    pda::ROOT_REGISTRY_PREFIX,
+    state::{RootRegistryAccount, SolanaAccount, ROOT_REGISTRY_ACCOUNT_LEN},
+    types::MAX_NAME_LENGTH,
+    utils::{assert_derivation, create_or_allocate_account_raw},
+};
+
+// Function to match on `CreateOrUpdateRootRegistryArgs` version and call correct implementation.
+pub(crate) fn create_or_update_root_registry<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CreateOrUpdateRootRegistryArgs,
+) -> ProgramResult {
+    let context = CreateOrUpdateRootRegistry::to_context(accounts)?;
+
+    match args {
+        CreateOrUpdateRootRegistryArgs::V1 {
+            registry_name,
+            root,
+        } => create_or_update_root_registry_v1(program_id, context, registry_name, root),
+    }
+}
+
+/// V1 implementation of the `create_or_update_root_registry` instruction.
+fn create_or_update_root_registry_v1(
+    program_id: &Pubkey,
+    ctx: Context<CreateOrUpdateRootRegistry>,
+    registry_name: String,
+    root: [u8; 32],
+) -> ProgramResult {
+    if !ctx.accounts.payer_info.is_signer {
+        return Err(RuleSetError::PayerIsNotSigner.into());
+    }
+
+    if !ctx.accounts.authority_info.is_signer {
+        return Err(RuleSetError::RegistryAuthorityIsNotSigner.into());
+    }
+
+    // Check that the name is not too long.
+    if registry_name.len() > MAX_NAME_LENGTH {
+        return Err(RuleSetError::NameTooLong.into());
+    }
+
+    // Check Root Registry account info derivation.
+    let bump = assert_derivation(
+        program_id,
+        ctx.accounts.root_registry_pda_info.key,
+        &[
+            ROOT_REGISTRY_PREFIX.as_bytes(),
+            ctx.accounts.authority_info.key.as_ref(),
+            registry_name.as_bytes(),
+        ],
+    )?;
+
+    if ctx.accounts.root_registry_pda_info.data_is_empty() {
+        let registry_seeds = &[
+            ROOT_REGISTRY_PREFIX.as_ref(),
+            ctx.accounts.authority_info.key.as_ref(),
+            registry_name.as_ref(),
+            &[bump],
+        ];
+
+        create_or_allocate_account_raw(
+            *program_id,
+            ctx.accounts.root_registry_pda_info,
+            ctx.accounts.system_program_info,
+            ctx.accounts.payer_info,
+            ROOT_REGISTRY_ACCOUNT_LEN,
+            registry_seeds,
+        )?;
+    } else {
+        // Only the stored authority can update an existing registry.
+        let registry = RootRegistryAccount::from_account_info(ctx.accounts.root_registry_pda_info)?;
+        if registry.authority != *ctx.accounts.authority_info.key {
+            return Err(RuleSetError::RegistryAuthorityMismatch.into());
+        }
+    }
+
+    RootRegistryAccount::new(*ctx.accounts.authority_info.key, root)
+        .to_account_data(ctx.accounts.root_registry_pda_info)
+}
@@ -3,7 +3,10 @@
 //! See state module for description of PDA memory layout.
 
 mod create_or_update;
+mod create_or_update_root_registry;
+mod modify_pubkey_list;
 mod puff_rule_set;
+mod transfer_hook;
 mod validate;
 mod write_to_buffer;
 
@@ -14,9 +17,14 @@ use solana_program::{
 };
 
 use crate::{
-    instruction::RuleSetInstruction,
+    instruction::{RuleSetInstruction, EXECUTE_DISCRIMINATOR},
     processor::{
-        create_or_update::create_or_update, puff_rule_set::puff_rule_set, validate::validate,
+        create_or_update::create_or_update,
+        create_or_update_root_registry::create_or_update_root_registry,
+        modify_pubkey_list::{add_to_pubkey_list, remove_from_pubkey_list},
+        puff_rule_set::puff_rule_set,
+        transfer_hook::{create_or_update_transfer_hook, execute},
+        validate::validate,
         write_to_buffer::write_to_buffer,
     },
     utils::cmp_pubkeys,
@@ -31,6 +39,17 @@ impl Processor {
         accounts: &'a [AccountInfo<'a>],
         instruction_data: &[u8],
     ) -> ProgramResult {
+        // Token-2022 calls the transfer hook with the `Execute` instruction of the transfer hook
+        // interface, which is not Borsh encoded.
+        if let Some(amount) = instruction_data.strip_prefix(&EXECUTE_DISCRIMINATOR) {
+            let amount = amount
+                .try_into()
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+            msg!("Instruction: Execute");
+            return execute(program_id, accounts, u64::from_le_bytes(amount));
+        }
+
         let instruction = RuleSetInstruction::try_from_slice(instruction_data)?;
         match instruction {
             RuleSetInstruction::CreateOrUpdate(args) => {
@@ -49,6 +68,22 @@ impl Processor {
                 msg!("Instruction: PuffRuleSet");
                 puff_rule_set(program_id, accounts, args)
             }
+            RuleSetInstruction::CreateOrUpdateRootRegistry(args) => {
+                msg!("Instruction: CreateOrUpdateRootRegistry");
+                create_or_update_root_registry(program_id, accounts, args)
+            }
+            RuleSetInstruction::AddToPubkeyList(args) => {
+                msg!("Instruction: AddToPubkeyList");
+                add_to_pubkey_list(program_id, accounts, args)
+            }
+            RuleSetInstruction::RemoveFromPubkeyList(args) => {
+                msg!("Instruction: RemoveFromPubkeyList");
+                remove_from_pubkey_list(program_id, accounts, args)
+            }
+            RuleSetInstruction::CreateOrUpdateTransferHook(args) => {
+                msg!("Instruction: CreateOrUpdateTransferHook");
+                create_or_update_transfer_hook(program_id, accounts, args)
+            }
         }
     }
 }
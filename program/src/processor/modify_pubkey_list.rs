@@ -0,0 +1,160 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_memory::sol_memcpy,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::RuleSetError,
+    instruction::{AddToPubkeyList, ModifyPubkeyListArgs, RemoveFromPubkeyList},
+    pda::PUBKEY_LIST_PREFIX,
+    state::PubkeyListAccount,
+    types::MAX_NAME_LENGTH,
+    utils::{
+        assert_derivation, assert_owned_by, create_or_allocate_account_raw,
+        resize_or_reallocate_account_raw,
+    },
+};
+
+// Function to match on `ModifyPubkeyListArgs` version and call correct implementation.
+pub(crate) fn add_to_pubkey_list<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ModifyPubkeyListArgs,
+) -> ProgramResult {
+    let ctx = AddToPubkeyList::to_context(accounts)?;
+
+    match args {
+        ModifyPubkeyListArgs::V1 { list_name, pubkeys } => modify_pubkey_list_v1(
+            program_id,
+            ctx.accounts.payer_info,
+            ctx.accounts.authority_info,
+            ctx.accounts.pubkey_list_pda_info,
+            ctx.accounts.system_program_info,
+            list_name,
+            |list| list.extend(pubkeys),
+        ),
+    }
+}
+
+// Function to match on `ModifyPubkeyListArgs` version and call correct implementation.
+pub(crate) fn remove_from_pubkey_list<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: ModifyPubkeyListArgs,
+) -> ProgramResult {
+    let ctx = RemoveFromPubkeyList::to_context(accounts)?;
+
+    match args {
+        ModifyPubkeyListArgs::V1 {
+            list_name,
+            mut pubkeys,
+        } => {
+            // Can't remove from a list that was never created.
+            if ctx.accounts.pubkey_list_pda_info.data_is_empty() {
+                return Err(RuleSetError::DataIsEmpty.into());
+            }
+
+            pubkeys.sort();
+            modify_pubkey_list_v1(
+                program_id,
+                ctx.accounts.payer_info,
+                ctx.accounts.authority_info,
+                ctx.accounts.pubkey_list_pda_info,
+                ctx.accounts.system_program_info,
+                list_name,
+                |list| list.retain(|pubkey| pubkeys.binary_search(pubkey).is_err()),
+            )
+        }
+    }
+}
+
+/// V1 implementation shared by the `add_to_pubkey_list` and `remove_from_pubkey_list`
+/// instructions.  The current list is read from the PDA, modified by `update`, and written back
+/// sorted and deduplicated.
+fn modify_pubkey_list_v1<'a, F: FnOnce(&mut Vec<Pubkey>)>(
+    program_id: &Pubkey,
+    payer_info: &'a AccountInfo<'a>,
+    authority_info: &'a AccountInfo<'a>,
+    pubkey_list_pda_info: &'a AccountInfo<'a>,
+    system_program_info: &'a AccountInfo<'a>,
+    list_name: String,
+    update: F,
+) -> ProgramResult {
+    if !payer_info.is_signer {
+        return Err(RuleSetError::PayerIsNotSigner.into());
+    }
+
+    if !authority_info.is_signer {
+        return Err(RuleSetError::PubkeyListAuthorityIsNotSigner.into());
+    }
+
+    // Check that the name is not too long.
+    if list_name.len() > MAX_NAME_LENGTH {
+        return Err(RuleSetError::NameTooLong.into());
+    }
+
+    // Check Pubkey List account info derivation.
+    let bump = assert_derivation(
+        program_id,
+        pubkey_list_pda_info.key,
+        &[
+            PUBKEY_LIST_PREFIX.as_bytes(),
+            authority_info.key.as_ref(),
+            list_name.as_bytes(),
+        ],
+    )?;
+
+    // Get the current list.
+    let mut pubkeys = if pubkey_list_pda_info.data_is_empty() {
+        Vec::new()
+    } else {
+        assert_owned_by(pubkey_list_pda_info, program_id)?;
+
+        let data = pubkey_list_pda_info.try_borrow_data()?;
+        let list = PubkeyListAccount::from_bytes(&data)?;
+
+        // Only the stored authority can modify an existing list.
+        if list.authority != authority_info.key {
+            return Err(RuleSetError::PubkeyListAuthorityMismatch.into());
+        }
+
+        list.pubkeys.to_vec()
+    };
+
+    update(&mut pubkeys);
+    let serialized_list = PubkeyListAccount::serialize(authority_info.key, &pubkeys);
+
+    // Create or allocate, resize or reallocate the Pubkey List PDA.
+    if pubkey_list_pda_info.data_is_empty() {
+        let list_seeds = &[
+            PUBKEY_LIST_PREFIX.as_ref(),
+            authority_info.key.as_ref(),
+            list_name.as_ref(),
+            &[bump],
+        ];
+
+        create_or_allocate_account_raw(
+            *program_id,
+            pubkey_list_pda_info,
+            system_program_info,
+            payer_info,
+            serialized_list.len(),
+            list_seeds,
+        )?;
+    } else {
+        resize_or_reallocate_account_raw(
+            pubkey_list_pda_info,
+            payer_info,
+            system_program_info,
+            serialized_list.len(),
+        )?;
+    }
+
+    sol_memcpy(
+        &mut pubkey_list_pda_info.try_borrow_mut_data()?,
+        &serialized_list,
+        serialized_list.len(),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_memory::sol_memcpy,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use crate::{
+    error::RuleSetError,
+    instruction::{CreateOrUpdateTransferHook, CreateOrUpdateTransferHookArgs},
+    payload::{Payload, PayloadType},
+    pda::EXTRA_ACCOUNT_METAS_PREFIX,
+    state::ExtraAccountMetasAccount,
+    utils::{
+        assert_derivation, assert_owned_by, cmp_pubkeys, create_or_allocate_account_raw,
+        deserialize_rule_set, get_rule_set_revision_location, resize_or_reallocate_account_raw,
+        validate_operation,
+    },
+};
+
+/// The operation validated on every transfer of a mint using this program as its transfer hook.
+const TRANSFER_OPERATION: &str = "Transfer";
+
+/// The length of a token account without extensions.
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// The offset of the account type of a Token-2022 account with extensions.  Mints with
+/// extensions are padded to the length of a token account.
+const ACCOUNT_TYPE_OFFSET: usize = TOKEN_ACCOUNT_LEN;
+
+/// The account type of a Token-2022 mint with extensions.
+const MINT_ACCOUNT_TYPE: u8 = 1;
+
+/// The length of the type and of the length of a Token-2022 extension.
+const EXTENSION_HEADER_LEN: usize = 4;
+
+/// The type of the uninitialized Token-2022 extension, after which no extension is written.
+const UNINITIALIZED_EXTENSION_TYPE: u16 = 0;
+
+/// The type of the `TransferHook` Token-2022 extension.
+const TRANSFER_HOOK_EXTENSION_TYPE: u16 = 14;
+
+// Function to match on `CreateOrUpdateTransferHookArgs` version and call correct implementation.
+pub(crate) fn create_or_update_transfer_hook<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    args: CreateOrUpdateTransferHookArgs,
+) -> ProgramResult {
+    let ctx = CreateOrUpdateTransferHook::to_context(accounts)?;
+
+    match args {
+        CreateOrUpdateTransferHookArgs::V1 {
+            additional_rule_accounts,
+        } => create_or_update_transfer_hook_v1(
+            program_id,
+            ctx.accounts.payer_info,
+            ctx.accounts.authority_info,
+            ctx.accounts.mint_info,
+            ctx.accounts.extra_account_metas_pda_info,
+            ctx.accounts.rule_set_pda_info,
+            ctx.accounts.system_program_info,
+            additional_rule_accounts,
+        ),
+    }
+}
+
+/// V1 implementation of the `create_or_update_transfer_hook` instruction.  The extra account
+/// metas list the `RuleSet` PDA followed by the additional accounts its `Rule`s read.
+#[allow(clippy::too_many_arguments)]
+fn create_or_update_transfer_hook_v1<'a>(
+    program_id: &Pubkey,
+    payer_info: &'a AccountInfo<'a>,
+    authority_info: &'a AccountInfo<'a>,
+    mint_info: &'a AccountInfo<'a>,
+    extra_account_metas_pda_info: &'a AccountInfo<'a>,
+    rule_set_pda_info: &'a AccountInfo<'a>,
+    system_program_info: &'a AccountInfo<'a>,
+    additional_rule_accounts: Vec<Pubkey>,
+) -> ProgramResult {
+    if !payer_info.is_signer {
+        return Err(RuleSetError::PayerIsNotSigner.into());
+    }
+
+    // The mint must be a Token-2022 mint, and only the authority of its `TransferHook`
+    // extension can choose its `RuleSet`.
+    assert_owned_by(mint_info, &spl_token_2022::id())?;
+    if !authority_info.is_signer || transfer_hook_authority(mint_info)? != Some(*authority_info.key)
+    {
+        return Err(RuleSetError::TransferHookAuthorityMismatch.into());
+    }
+
+    // `RuleSet` must be owned by this program and must not be empty.
+    assert_owned_by(rule_set_pda_info, program_id)?;
+    if rule_set_pda_info.data_is_empty() {
+        return Err(RuleSetError::DataIsEmpty.into());
+    }
+
+    // Check extra account metas account info derivation.
+    let bump = assert_derivation(
+        program_id,
+        extra_account_metas_pda_info.key,
+        &[
+            EXTRA_ACCOUNT_METAS_PREFIX.as_bytes(),
+            mint_info.key.as_ref(),
+        ],
+    )?;
+
+    let mut pubkeys = vec![*rule_set_pda_info.key];
+    pubkeys.extend(additional_rule_accounts);
+    let serialized_metas = ExtraAccountMetasAccount::serialize(&pubkeys);
+
+    // Create or allocate, resize or reallocate the extra account metas PDA.  Token-2022 reads
+    // the metas to the end of the account, so it must have their exact length.
+    if extra_account_metas_pda_info.data_is_empty() {
+        let metas_seeds = &[
+            EXTRA_ACCOUNT_METAS_PREFIX.as_bytes(),
+            mint_info.key.as_ref(),
+            &[bump],
+        ];
+
+        create_or_allocate_account_raw(
+            *program_id,
+            extra_account_metas_pda_info,
+            system_program_info,
+            payer_info,
+            serialized_metas.len(),
+            metas_seeds,
+        )?;
+    } else {
+        assert_owned_by(extra_account_metas_pda_info, program_id)?;
+
+        resize_or_reallocate_account_raw(
+            extra_account_metas_pda_info,
+            payer_info,
+            system_program_info,
+            serialized_metas.len(),
+        )?;
+    }
+
+    sol_memcpy(
+        &mut extra_account_metas_pda_info.try_borrow_mut_data()?,
+        &serialized_metas,
+        serialized_metas.len(),
+    );
+
+    Ok(())
+}
+
+/// Implementation of the `Execute` instruction of the transfer hook interface, called by
+/// Token-2022 on every transfer of a mint using this program as its transfer hook.  The transfer
+/// is validated as a `Transfer` operation by the latest revision of the `RuleSet` listed in the
+/// extra account metas of the mint.
+pub(crate) fn execute<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    if accounts.len() < 5 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // The accounts of the transfer hook interface, followed by the extra accounts.
+    let (source_info, mint_info, destination_info, authority_info) =
+        (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+    let extra_account_metas_pda_info = &accounts[4];
+    let remaining_accounts = &accounts[5..];
+
+    // The mint must be a Token-2022 mint.
+    assert_owned_by(mint_info, &spl_token_2022::id())?;
+
+    // Check extra account metas account info derivation.
+    let _bump = assert_derivation(
+        program_id,
+        extra_account_metas_pda_info.key,
+        &[
+            EXTRA_ACCOUNT_METAS_PREFIX.as_bytes(),
+            mint_info.key.as_ref(),
+        ],
+    )?;
+    assert_owned_by(extra_account_metas_pda_info, program_id)?;
+
+    // The extra accounts must be the ones configured for the mint, in order.
+    let metas_data = extra_account_metas_pda_info.try_borrow_data()?;
+    let metas = ExtraAccountMetasAccount::from_bytes(&metas_data)?;
+
+    if metas.pubkeys.is_empty()
+        || remaining_accounts.len() < metas.pubkeys.len()
+        || !metas
+            .pubkeys
+            .iter()
+            .zip(remaining_accounts)
+            .all(|(pubkey, account)| cmp_pubkeys(pubkey, account.key))
+    {
+        return Err(RuleSetError::TransferHookAccountMismatch.into());
+    }
+
+    // `RuleSet` must be owned by this program and must not be empty.
+    let rule_set_pda_info = &remaining_accounts[0];
+    assert_owned_by(rule_set_pda_info, program_id)?;
+    if rule_set_pda_info.data_is_empty() {
+        return Err(RuleSetError::DataIsEmpty.into());
+    }
+
+    // Build the `Payload` from the wallets owning the token accounts.
+    let source = token_account_owner(source_info, mint_info)?;
+    let destination = token_account_owner(destination_info, mint_info)?;
+
+    let payload = Payload::from([
+        ("Amount".to_string(), PayloadType::Number(amount)),
+        ("Source".to_string(), PayloadType::Pubkey(source)),
+        ("Destination".to_string(), PayloadType::Pubkey(destination)),
+        (
+            "Authority".to_string(),
+            PayloadType::Pubkey(*authority_info.key),
+        ),
+    ]);
+
+    // Transfers are always validated by the latest revision.
+    let (start, end) = get_rule_set_revision_location(rule_set_pda_info, None)?;
+
+    let data = rule_set_pda_info
+        .data
+        .try_borrow()
+        .map_err(|_| ProgramError::AccountBorrowFailed)?;
+
+    // Deserialize the `RuleSet` revision.
+    let rule_set = deserialize_rule_set(&data, start, end, 0)?;
+
+    // Every account of the transfer can be read by the `Rule`s.
+    let accounts_map = accounts
+        .iter()
+        .map(|account| (*account.key, account))
+        .collect::<HashMap<Pubkey, &AccountInfo>>();
+
+    // Validate the `Rule`.
+    validate_operation(
+        rule_set.as_ref(),
+        TRANSFER_OPERATION.to_string(),
+        &accounts_map,
+        &payload,
+        false,
+        &None,
+        &None,
+    )
+}
+
+/// Returns the authority of the `TransferHook` extension of a Token-2022 mint, if the mint has
+/// the extension and the authority is set.
+fn transfer_hook_authority(mint_info: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+    let data = mint_info.try_borrow_data()?;
+
+    // A mint without extensions ends before the account type.
+    if data.len() <= ACCOUNT_TYPE_OFFSET || data[ACCOUNT_TYPE_OFFSET] != MINT_ACCOUNT_TYPE {
+        return Ok(None);
+    }
+
+    // Each extension is its type and the length of its value, followed by the value.
+    let mut extensions = &data[ACCOUNT_TYPE_OFFSET + 1..];
+
+    while extensions.len() >= EXTENSION_HEADER_LEN {
+        let extension_type = u16::from_le_bytes([extensions[0], extensions[1]]);
+        let len = usize::from(u16::from_le_bytes([extensions[2], extensions[3]]));
+
+        let value = extensions
+            .get(EXTENSION_HEADER_LEN..EXTENSION_HEADER_LEN + len)
+            .ok_or(RuleSetError::DataTypeMismatch)?;
+
+        match extension_type {
+            TRANSFER_HOOK_EXTENSION_TYPE => {
+                // The authority is an `OptionalNonZeroPubkey`, which is `None` when zeroed.
+                let authority = value
+                    .get(..PUBKEY_BYTES)
+                    .ok_or(RuleSetError::DataTypeMismatch)?;
+
+                return Ok(Some(Pubkey::try_from(authority).unwrap())
+                    .filter(|authority| *authority != Pubkey::default()));
+            }
+            UNINITIALIZED_EXTENSION_TYPE => break,
+            _ => extensions = &extensions[EXTENSION_HEADER_LEN + len..],
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the wallet owning a Token-2022 token account of the mint.
+fn token_account_owner(
+    token_account_info: &AccountInfo,
+    mint_info: &AccountInfo,
+) -> Result<Pubkey, ProgramError> {
+    let data = token_account_info.try_borrow_data()?;
+
+    if !cmp_pubkeys(token_account_info.owner, &spl_token_2022::id())
+        || data.len() < TOKEN_ACCOUNT_LEN
+        || !cmp_pubkeys(
+            &Pubkey::try_from(&data[..PUBKEY_BYTES]).unwrap(),
+            mint_info.key,
+        )
+    {
+        msg!("Token account {} is invalid", token_account_info.key);
+        return Err(RuleSetError::InvalidTokenAccount.into());
+    }
+
+    Ok(Pubkey::try_from(&data[PUBKEY_BYTES..2 * PUBKEY_BYTES]).unwrap())
+}
@@ -8,10 +8,12 @@ use solana_program::{
 use crate::{
     error::RuleSetError,
     instruction::{Context, Validate, ValidateArgs},
+    payload::Payload,
     pda::STATE_PDA,
-    state::{RuleSetV1, RuleSetV2},
-    types::{LibVersion, RuleSet},
-    utils::{assert_derivation, get_existing_revision_map},
+    utils::{
+        assert_derivation, deserialize_rule_set, get_existing_revision_map,
+        get_rule_set_revision_location, validate_operation,
+    },
 };
 
 // Function to match on `ValidateArgs` version and call correct implementation.
@@ -23,20 +25,49 @@ pub(crate) fn validate<'a>(
     let context = Validate::to_context(accounts)?;
 
     match args {
-        ValidateArgs::V1 { .. } => validate_v1(program_id, context, args),
+        ValidateArgs::V1 {
+            operation,
+            payload,
+            update_rule_state,
+            rule_set_revision,
+        } => validate_revision(
+            program_id,
+            context,
+            operation,
+            payload,
+            update_rule_state,
+            rule_set_revision,
+            None,
+        ),
+        ValidateArgs::V2 {
+            operation,
+            payload,
+            update_rule_state,
+            rule_set_revision,
+            expected_hash,
+        } => validate_revision(
+            program_id,
+            context,
+            operation,
+            payload,
+            update_rule_state,
+            rule_set_revision,
+            expected_hash,
+        ),
     }
 }
 
-/// V1 implementation of the `validate` instruction.
-fn validate_v1(program_id: &Pubkey, ctx: Context<Validate>, args: ValidateArgs) -> ProgramResult {
-    // Get the V1 arguments for the instruction.
-    let ValidateArgs::V1 {
-        operation,
-        payload,
-        update_rule_state,
-        rule_set_revision,
-    } = args;
-
+/// Implementation of the `validate` instruction for all versions of `ValidateArgs`.  Only V2
+/// carries the `expected_hash` of the `RuleSet` revision, which is `None` for V1.
+fn validate_revision(
+    program_id: &Pubkey,
+    ctx: Context<Validate>,
+    operation: String,
+    payload: Payload,
+    update_rule_state: bool,
+    rule_set_revision: Option<usize>,
+    expected_hash: Option<[u8; 32]>,
+) -> ProgramResult {
     // If state is being updated for any `Rule`s, the payer must be present and must be a signer so
     // that the `RuleSet` state PDA can be created or reallocated.
     if update_rule_state {
@@ -59,36 +90,20 @@ fn validate_v1(program_id: &Pubkey, ctx: Context<Validate>, args: ValidateArgs)
         return Err(RuleSetError::DataIsEmpty.into());
     }
 
-    // Get existing revision map and its serialized length.
-    let (revision_map, rev_map_location) =
-        get_existing_revision_map(ctx.accounts.rule_set_pda_info)?;
+    // The revision must have the hash the caller expects.  Revisions written before hashes were
+    // recorded have a hash of zeros, which never matches.
+    if let Some(expected_hash) = expected_hash {
+        let (revision_map, _) = get_existing_revision_map(ctx.accounts.rule_set_pda_info)?;
+        let hash = revision_map.hash(rule_set_revision)?;
 
-    // Use the user-provided revision number to look up the `RuleSet` revision location in the PDA.
-    let (start, end) = match rule_set_revision {
-        Some(revision) => {
-            let start = revision_map
-                .rule_set_revisions
-                .get(revision)
-                .ok_or(RuleSetError::RuleSetRevisionNotAvailable)?;
-
-            let end_index = revision
-                .checked_add(1)
-                .ok_or(RuleSetError::NumericalOverflow)?;
-
-            let end = revision_map
-                .rule_set_revisions
-                .get(end_index)
-                .unwrap_or(&rev_map_location);
-            (*start, *end)
+        if *hash == [0; 32] || *hash != expected_hash {
+            return Err(RuleSetError::RuleSetHashMismatch.into());
         }
-        None => {
-            let start = revision_map
-                .rule_set_revisions
-                .last()
-                .ok_or(RuleSetError::RuleSetRevisionNotAvailable)?;
-            (*start, rev_map_location)
-        }
-    };
+    }
+
+    // Use the user-provided revision number to look up the `RuleSet` revision location in the PDA.
+    let (start, end) =
+        get_rule_set_revision_location(ctx.accounts.rule_set_pda_info, rule_set_revision)?;
 
     // Mutably borrow the existing `RuleSet` PDA data.
     let data = ctx
@@ -98,37 +113,8 @@ fn validate_v1(program_id: &Pubkey, ctx: Context<Validate>, args: ValidateArgs)
         .try_borrow()
         .map_err(|_| ProgramError::AccountBorrowFailed)?;
 
-    // Check `RuleSet` lib version.
-    let lib_version = match data.get(start) {
-        Some(lib_version) => LibVersion::try_from(*lib_version)?,
-        None => return Err(RuleSetError::DataTypeMismatch.into()),
-    };
-
-    let rule_set: Box<dyn RuleSet> = match lib_version {
-        LibVersion::V1 => {
-            // Increment starting location by size of lib version.
-            let start = start
-                .checked_add(1)
-                .ok_or(RuleSetError::NumericalOverflow)?;
-
-            // Deserialize `RuleSet`.
-            if end < ctx.accounts.rule_set_pda_info.data_len() {
-                Box::new(
-                    rmp_serde::from_slice::<RuleSetV1>(&data[start..end])
-                        .map_err(|_| RuleSetError::MessagePackDeserializationError)?,
-                )
-            } else {
-                return Err(RuleSetError::DataTypeMismatch.into());
-            }
-        }
-        LibVersion::V2 => {
-            if end < ctx.accounts.rule_set_pda_info.data_len() {
-                Box::new(RuleSetV2::from_bytes(&data[start..end])?)
-            } else {
-                return Err(RuleSetError::DataTypeMismatch.into());
-            }
-        }
-    };
+    // Deserialize the `RuleSet` revision.
+    let rule_set = deserialize_rule_set(&data, start, end, 0)?;
 
     // If `RuleSet` state is to be updated, check account info derivation.
     if update_rule_state {
@@ -158,7 +144,9 @@ fn validate_v1(program_id: &Pubkey, ctx: Context<Validate>, args: ValidateArgs)
         .collect::<HashMap<Pubkey, &AccountInfo>>();
 
     // Validate the `Rule`.
-    rule_set.get_rule(operation)?.validate(
+    validate_operation(
+        rule_set.as_ref(),
+        operation,
         &accounts_map,
         &payload,
         update_rule_state,
@@ -1,5 +1,8 @@
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_memory::sol_memcpy,
+    account_info::AccountInfo,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    msg,
+    program_memory::{sol_memcpy, sol_memset},
     pubkey::Pubkey,
 };
 
@@ -7,6 +10,7 @@ use crate::{
     error::RuleSetError,
     instruction::{Context, WriteToBuffer, WriteToBufferArgs},
     pda::PREFIX,
+    state::{BufferAccount, BUFFER_HEADER_LEN},
     utils::{assert_derivation, create_or_allocate_account_raw, resize_or_reallocate_account_raw},
 };
 
@@ -19,7 +23,16 @@ pub(crate) fn write_to_buffer<'a>(
     let context = WriteToBuffer::to_context(accounts)?;
 
     match args {
-        WriteToBufferArgs::V1 { .. } => write_to_buffer_v1(program_id, context, args),
+        WriteToBufferArgs::V1 {
+            serialized_rule_set,
+            overwrite,
+        } => write_to_buffer_v1(program_id, context, serialized_rule_set, overwrite),
+        WriteToBufferArgs::V2 {
+            offset,
+            data,
+            total_len,
+            hash,
+        } => write_to_buffer_v2(program_id, context, offset, data, total_len, hash),
     }
 }
 
@@ -27,13 +40,9 @@ pub(crate) fn write_to_buffer<'a>(
 fn write_to_buffer_v1(
     program_id: &Pubkey,
     ctx: Context<WriteToBuffer>,
-    args: WriteToBufferArgs,
+    serialized_rule_set: Vec<u8>,
+    overwrite: bool,
 ) -> ProgramResult {
-    let WriteToBufferArgs::V1 {
-        serialized_rule_set,
-        overwrite,
-    } = args;
-
     if !ctx.accounts.payer_info.is_signer {
         return Err(RuleSetError::PayerIsNotSigner.into());
     }
@@ -102,3 +111,84 @@ fn write_to_buffer_v1(
 
     Ok(())
 }
+
+/// V2 implementation of the `write_to_buffer` instruction.  The chunk is written at `offset` of
+/// a buffer preallocated for `total_len` bytes, and its blocks are marked in the chunk bitmap.
+fn write_to_buffer_v2(
+    program_id: &Pubkey,
+    ctx: Context<WriteToBuffer>,
+    offset: u32,
+    data: Vec<u8>,
+    total_len: u32,
+    hash: Option<[u8; 32]>,
+) -> ProgramResult {
+    if !ctx.accounts.payer_info.is_signer {
+        return Err(RuleSetError::PayerIsNotSigner.into());
+    }
+
+    BufferAccount::assert_valid_write(offset as usize, data.len(), total_len as usize)?;
+
+    // Check buffer account info derivation.
+    let bump = assert_derivation(
+        program_id,
+        ctx.accounts.buffer_pda_info.key,
+        &[PREFIX.as_bytes(), ctx.accounts.payer_info.key.as_ref()],
+    )?;
+
+    let buffer_seeds = &[
+        PREFIX.as_ref(),
+        ctx.accounts.payer_info.key.as_ref(),
+        &[bump],
+    ];
+
+    // A buffer written for another `RuleSet`, or by `WriteToBuffer` V1, starts a new upload.
+    let header = BufferAccount::serialize_header(total_len, &hash.unwrap_or_default());
+    let is_same_upload = ctx
+        .accounts
+        .buffer_pda_info
+        .try_borrow_data()?
+        .starts_with(&header[..BUFFER_HEADER_LEN]);
+
+    // Grow the buffer towards its full length, by at most `MAX_PERMITTED_DATA_INCREASE` bytes
+    // if it already exists.
+    let account_len = BufferAccount::account_len(total_len as usize);
+    let current_len = ctx.accounts.buffer_pda_info.data_len();
+
+    if ctx.accounts.buffer_pda_info.data_is_empty() {
+        create_or_allocate_account_raw(
+            *program_id,
+            ctx.accounts.buffer_pda_info,
+            ctx.accounts.system_program_info,
+            ctx.accounts.payer_info,
+            account_len,
+            buffer_seeds,
+        )?;
+    } else if current_len != account_len && (current_len < account_len || !is_same_upload) {
+        let new_len = account_len.min(current_len.saturating_add(MAX_PERMITTED_DATA_INCREASE));
+
+        if new_len < header.len() {
+            return Err(RuleSetError::BufferTooSmall.into());
+        }
+
+        resize_or_reallocate_account_raw(
+            ctx.accounts.buffer_pda_info,
+            ctx.accounts.payer_info,
+            ctx.accounts.system_program_info,
+            new_len,
+        )?;
+    }
+
+    let mut buffer = ctx.accounts.buffer_pda_info.try_borrow_mut_data()?;
+
+    // Clear the chunk bitmap of a new upload.
+    if !is_same_upload {
+        let len = buffer.len();
+        sol_memset(&mut buffer, 0, len);
+        sol_memcpy(&mut buffer, &header, header.len());
+    }
+
+    msg!("Writing {:?} bytes at offset {:?}", data.len(), offset);
+    BufferAccount::write(&mut buffer, offset as usize, &data)?;
+
+    Ok(())
+}
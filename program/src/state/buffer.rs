@@ -0,0 +1,231 @@
+use solana_program::{keccak, msg};
+
+use super::Key;
+use crate::error::RuleSetError;
+
+/// The size of the fixed section at the beginning of a chunked buffer account.
+pub const BUFFER_HEADER_LEN: usize = 1 + 4 + 32;
+
+/// The number of bytes of the `RuleSet` tracked by each bit of the chunk bitmap.  A write must
+/// start at a multiple of it, and end at a multiple of it or at the end of the `RuleSet`.
+pub const BUFFER_BLOCK_LEN: usize = 32;
+
+/// A zero-copy view of a buffer account written at explicit offsets by `WriteToBuffer` V2.
+///
+/// Chunked buffer account data layout
+/// ```text
+/// | Key    | Total length | Hash     | Chunk bitmap               | RuleSet              |
+/// |--------|--------------|----------|----------------------------|----------------------|
+/// | 1 byte | 4 bytes      | 32 bytes | 1 bit per BUFFER_BLOCK_LEN | Total length bytes   |
+/// ```
+///
+/// The bitmap records which blocks of the `RuleSet` have been written, so chunks can be written
+/// in any order and `CreateOrUpdate` refuses a buffer with missing chunks.  An existing buffer
+/// grows by at most `MAX_PERMITTED_DATA_INCREASE` bytes per instruction, so the `RuleSet`
+/// section can be shorter than the total length until enough chunks have been written.
+pub struct BufferAccount<'a> {
+    /// Length of the `RuleSet` being written.
+    pub total_len: usize,
+    /// Keccak hash of the `RuleSet`, or zeros if the writer did not provide one.
+    pub hash: [u8; 32],
+    /// One bit per block of the `RuleSet`, set once the block is written.
+    pub bitmap: &'a [u8],
+    /// The `RuleSet` section allocated so far.
+    pub rule_set: &'a [u8],
+}
+
+impl<'a> BufferAccount<'a> {
+    /// Deserialize a chunked buffer account from its data without copying.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        if !Self::is_chunked(bytes) || bytes.len() < BUFFER_HEADER_LEN {
+            return Err(RuleSetError::DataTypeMismatch);
+        }
+
+        let mut total_len = [0; 4];
+        total_len.copy_from_slice(&bytes[1..5]);
+        let total_len = u32::from_le_bytes(total_len) as usize;
+
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&bytes[5..BUFFER_HEADER_LEN]);
+
+        let rule_set_start = BUFFER_HEADER_LEN + Self::bitmap_len(total_len);
+        if bytes.len() < rule_set_start {
+            return Err(RuleSetError::DataTypeMismatch);
+        }
+
+        Ok(Self {
+            total_len,
+            hash,
+            bitmap: &bytes[BUFFER_HEADER_LEN..rule_set_start],
+            rule_set: &bytes[rule_set_start..],
+        })
+    }
+
+    /// Returns whether the data is a chunked buffer, as opposed to a `RuleSet` written by
+    /// `WriteToBuffer` V1.  Serialized `RuleSet`s never start with the `Buffer` key.
+    pub fn is_chunked(bytes: &[u8]) -> bool {
+        bytes.first() == Some(&(Key::Buffer as u8))
+    }
+
+    /// Returns the length of the chunk bitmap of a `RuleSet` of `total_len` bytes.
+    pub fn bitmap_len(total_len: usize) -> usize {
+        total_len.div_ceil(BUFFER_BLOCK_LEN).div_ceil(8)
+    }
+
+    /// Returns the length of the account holding a `RuleSet` of `total_len` bytes.
+    pub fn account_len(total_len: usize) -> usize {
+        BUFFER_HEADER_LEN + Self::bitmap_len(total_len) + total_len
+    }
+
+    /// Serialize the header and an empty bitmap of a chunked buffer.
+    pub fn serialize_header(total_len: u32, hash: &[u8; 32]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(BUFFER_HEADER_LEN);
+        data.push(Key::Buffer as u8);
+        data.extend_from_slice(&total_len.to_le_bytes());
+        data.extend_from_slice(hash);
+        data.resize(BUFFER_HEADER_LEN + Self::bitmap_len(total_len as usize), 0);
+
+        data
+    }
+
+    /// Returns whether the block of the `RuleSet` starting at `block * BUFFER_BLOCK_LEN` has
+    /// been written.
+    pub fn is_written(&self, block: usize) -> bool {
+        self.bitmap
+            .get(block / 8)
+            .is_some_and(|byte| byte & (1 << (block % 8)) != 0)
+    }
+
+    /// Returns the `RuleSet` once every chunk has been written and it matches the hash, if one
+    /// was provided.
+    pub fn complete_rule_set(&self) -> Result<&'a [u8], RuleSetError> {
+        let blocks = self.total_len.div_ceil(BUFFER_BLOCK_LEN);
+
+        if self.rule_set.len() != self.total_len || !(0..blocks).all(|block| self.is_written(block))
+        {
+            msg!("The buffer is missing chunks of the RuleSet");
+            return Err(RuleSetError::BufferIncomplete);
+        }
+
+        if self.hash != [0; 32] && keccak::hash(self.rule_set).to_bytes() != self.hash {
+            return Err(RuleSetError::BufferHashMismatch);
+        }
+
+        Ok(self.rule_set)
+    }
+
+    /// Checks that a chunk of `len` bytes at `offset` is aligned to blocks and within a `RuleSet`
+    /// of `total_len` bytes.
+    pub fn assert_valid_write(
+        offset: usize,
+        len: usize,
+        total_len: usize,
+    ) -> Result<(), RuleSetError> {
+        let end = offset
+            .checked_add(len)
+            .ok_or(RuleSetError::NumericalOverflow)?;
+
+        if len == 0
+            || end > total_len
+            || offset.next_multiple_of(BUFFER_BLOCK_LEN) != offset
+            || (end.next_multiple_of(BUFFER_BLOCK_LEN) != end && end != total_len)
+        {
+            msg!(
+                "Invalid write of {} bytes at offset {} of {}",
+                len,
+                offset,
+                total_len
+            );
+            return Err(RuleSetError::InvalidBufferWrite);
+        }
+
+        Ok(())
+    }
+
+    /// Copy a chunk of the `RuleSet` at `offset` of the data of a chunked buffer, and mark its
+    /// blocks as written.  The write must have been checked with `assert_valid_write`.
+    pub fn write(bytes: &mut [u8], offset: usize, chunk: &[u8]) -> Result<(), RuleSetError> {
+        let total_len = BufferAccount::from_bytes(bytes)?.total_len;
+        let rule_set_start = BUFFER_HEADER_LEN + Self::bitmap_len(total_len);
+
+        let start = rule_set_start + offset;
+        let end = start + chunk.len();
+        if end > bytes.len() {
+            msg!("The buffer has not grown to the end of the chunk yet");
+            return Err(RuleSetError::BufferTooSmall);
+        }
+
+        bytes[start..end].copy_from_slice(chunk);
+
+        let first_block = offset / BUFFER_BLOCK_LEN;
+        let last_block = (offset + chunk.len()).div_ceil(BUFFER_BLOCK_LEN);
+        let bitmap = &mut bytes[BUFFER_HEADER_LEN..rule_set_start];
+        (first_block..last_block).for_each(|block| bitmap[block / 8] |= 1 << (block % 8));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(total_len: usize, hash: [u8; 32]) -> Vec<u8> {
+        let mut data = BufferAccount::serialize_header(total_len as u32, &hash);
+        data.resize(BufferAccount::account_len(total_len), 0);
+        data
+    }
+
+    #[test]
+    fn test_chunks_written_out_of_order() {
+        let rule_set = (0..100).collect::<Vec<u8>>();
+        let mut data = buffer(rule_set.len(), keccak::hash(&rule_set).to_bytes());
+        assert_eq!(data.len(), BUFFER_HEADER_LEN + 1 + 100);
+
+        // The last chunk is written first, and the chunk in the middle is missing.
+        BufferAccount::write(&mut data, 64, &rule_set[64..]).unwrap();
+        BufferAccount::write(&mut data, 0, &rule_set[..32]).unwrap();
+
+        let buffer = BufferAccount::from_bytes(&data).unwrap();
+        assert_eq!(buffer.total_len, 100);
+        assert!(buffer.is_written(0) && !buffer.is_written(1));
+        assert!(buffer.is_written(2) && buffer.is_written(3));
+        assert_eq!(
+            buffer.complete_rule_set().unwrap_err(),
+            RuleSetError::BufferIncomplete
+        );
+
+        BufferAccount::write(&mut data, 32, &rule_set[32..64]).unwrap();
+        let buffer = BufferAccount::from_bytes(&data).unwrap();
+        assert_eq!(buffer.complete_rule_set().unwrap(), rule_set.as_slice());
+
+        // A chunk written again with other data no longer matches the hash.
+        BufferAccount::write(&mut data, 32, &[0; 32]).unwrap();
+        let buffer = BufferAccount::from_bytes(&data).unwrap();
+        assert_eq!(
+            buffer.complete_rule_set().unwrap_err(),
+            RuleSetError::BufferHashMismatch
+        );
+    }
+
+    #[test]
+    fn test_invalid_writes() {
+        assert!(BufferAccount::assert_valid_write(0, 32, 100).is_ok());
+        assert!(BufferAccount::assert_valid_write(96, 4, 100).is_ok());
+        for (offset, len) in [(0, 0), (16, 32), (0, 40), (96, 8), (usize::MAX, 1)] {
+            assert!(BufferAccount::assert_valid_write(offset, len, 100).is_err());
+        }
+
+        // A buffer that has not grown to the end of the `RuleSet` yet.
+        let mut data = buffer(100, [0; 32]);
+        data.truncate(data.len() - 10);
+        assert_eq!(
+            BufferAccount::write(&mut data, 64, &[1; 36]).unwrap_err(),
+            RuleSetError::BufferTooSmall
+        );
+        assert_eq!(
+            BufferAccount::from_bytes(&[Key::RuleSet as u8; 64]).err(),
+            Some(RuleSetError::DataTypeMismatch)
+        );
+    }
+}
@@ -17,6 +17,12 @@
 //! This is not included in the data struct itself to give flexibility to update `RuleSet`s and
 //! the revision map data structs and even change serialization format.
 //!
+//! New revisions are written with a `RuleSetRevisionMapV2`, which also records the keccak hash
+//! of each revision so that the exact `RuleSet` content can be pinned.  It is stored with the
+//! version of a `RuleSetRevisionMapV1`, followed by the hashes, so programs reading only
+//! `RuleSetRevisionMapV1`s can still update the PDA.  Revisions written without hashes have a
+//! hash of zeros.
+//!
 //! RuleSet PDA data layout
 //! ```text
 //! | Header  | RuleSet version | RuleSet Revision 0 | RuleSet version | RuleSet Revision 1 | RuleSet version | RuleSet Revision 2 | ... | RuleSetRevisionMap version | RuleSetRevisionMap |
@@ -30,14 +36,22 @@ use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
 };
 
+mod buffer;
 mod frequency;
+mod pubkey_list;
+mod root_registry;
 mod rule_set;
 mod rules;
+mod transfer_hook;
 mod v2;
 
+pub use buffer::*;
 pub use frequency::*;
+pub use pubkey_list::*;
+pub use root_registry::*;
 pub use rule_set::*;
 pub use rules::*;
+pub use transfer_hook::*;
 pub use v2::*;
 
 use crate::{error::RuleSetError, utils::assert_owned_by};
@@ -56,6 +70,12 @@ pub enum Key {
     RuleSet,
     /// An account containing frequency state.
     Frequency,
+    /// An account containing a Merkle root that can be updated by its authority.
+    RootRegistry,
+    /// An account containing a sorted list of `Pubkey`s.
+    PubkeyList,
+    /// A buffer account containing a `RuleSet` written in chunks at explicit offsets.
+    Buffer,
 }
 
 /// A trait implementing generic functions required by all accounts on Solana.
@@ -0,0 +1,100 @@
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+use super::{try_cast_slice, try_from_bytes, Key};
+use crate::error::RuleSetError;
+
+/// The size of the fixed section at the beginning of a Pubkey List account.
+pub const PUBKEY_LIST_HEADER_LEN: usize = 1 + PUBKEY_BYTES;
+
+/// A zero-copy view of an account containing a sorted list of `Pubkey`s.
+///
+/// Pubkey List account data layout
+/// ```text
+/// | Key    | Authority | Pubkey 0 | Pubkey 1 | ... |
+/// |--------|-----------|----------|----------|-----|
+/// | 1 byte | 32 bytes  | 32 bytes | 32 bytes | ... |
+/// ```
+///
+/// The `Pubkey`s are kept sorted and without duplicates, so membership can be checked with a
+/// binary search directly on the account data.
+pub struct PubkeyListAccount<'a> {
+    /// The authority allowed to add and remove `Pubkey`s.
+    pub authority: &'a Pubkey,
+    /// The sorted list of `Pubkey`s.
+    pub pubkeys: &'a [Pubkey],
+}
+
+impl<'a> PubkeyListAccount<'a> {
+    /// Deserialize a Pubkey List account from its data without copying.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        if bytes.len() < PUBKEY_LIST_HEADER_LEN || bytes[0] != Key::PubkeyList as u8 {
+            return Err(RuleSetError::DataTypeMismatch);
+        }
+
+        let authority = try_from_bytes::<Pubkey>(1, PUBKEY_BYTES, bytes)?;
+        let pubkeys = try_cast_slice(&bytes[PUBKEY_LIST_HEADER_LEN..])?;
+
+        Ok(Self { authority, pubkeys })
+    }
+
+    /// Serialize a Pubkey List account.  The `Pubkey`s are sorted and deduplicated.
+    pub fn serialize(authority: &Pubkey, pubkeys: &[Pubkey]) -> Vec<u8> {
+        let mut pubkeys = pubkeys.to_vec();
+        pubkeys.sort();
+        pubkeys.dedup();
+
+        let mut data = Vec::with_capacity(PUBKEY_LIST_HEADER_LEN + pubkeys.len() * PUBKEY_BYTES);
+        data.push(Key::PubkeyList as u8);
+        data.extend_from_slice(authority.as_ref());
+        pubkeys
+            .iter()
+            .for_each(|pubkey| data.extend_from_slice(pubkey.as_ref()));
+
+        data
+    }
+
+    /// Returns whether the list contains the `Pubkey`.
+    pub fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.pubkeys.binary_search(pubkey).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pubkey_list_account() {
+        let authority = Pubkey::new_unique();
+        let pubkeys = (0..10).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        // Serialize in reverse order with a duplicate.
+        let mut unsorted = pubkeys.iter().rev().cloned().collect::<Vec<_>>();
+        unsorted.push(pubkeys[3]);
+        let data = PubkeyListAccount::serialize(&authority, &unsorted);
+        assert_eq!(
+            data.len(),
+            PUBKEY_LIST_HEADER_LEN + pubkeys.len() * PUBKEY_BYTES
+        );
+
+        let list = PubkeyListAccount::from_bytes(&data).unwrap();
+        assert_eq!(*list.authority, authority);
+        assert!(list.pubkeys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(pubkeys.iter().all(|pubkey| list.contains(pubkey)));
+        assert!(!list.contains(&Pubkey::new_unique()));
+
+        // Wrong account type.
+        let mut wrong_key = data.clone();
+        wrong_key[0] = Key::Frequency as u8;
+        assert!(matches!(
+            PubkeyListAccount::from_bytes(&wrong_key),
+            Err(RuleSetError::DataTypeMismatch)
+        ));
+
+        // Truncated entry.
+        assert!(matches!(
+            PubkeyListAccount::from_bytes(&data[..data.len() - 1]),
+            Err(RuleSetError::RuleSetReadFailed)
+        ));
+    }
+}
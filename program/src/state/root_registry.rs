@@ -0,0 +1,37 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::pubkey::Pubkey;
+
+use super::{Key, SolanaAccount};
+
+/// The serialized size of a `RootRegistryAccount`.
+pub const ROOT_REGISTRY_ACCOUNT_LEN: usize = 1 + 32 + 32;
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone, ShankAccount)]
+/// An account containing a Merkle root that rules can read at validation time, so the root
+/// can be rotated without creating a new `RuleSet` revision.
+pub struct RootRegistryAccount {
+    /// The `Key` for this account which identifies it as a Root Registry account.
+    pub key: Key,
+    /// The authority allowed to update the root.
+    pub authority: Pubkey,
+    /// The current Merkle root.
+    pub root: [u8; 32],
+}
+
+impl RootRegistryAccount {
+    /// Create a new `RootRegistryAccount`.
+    pub fn new(authority: Pubkey, root: [u8; 32]) -> Self {
+        Self {
+            key: Key::RootRegistry,
+            authority,
+            root,
+        }
+    }
+}
+
+impl SolanaAccount for RootRegistryAccount {
+    fn key() -> Key {
+        Key::RootRegistry
+    }
+}
@@ -1,17 +1,19 @@
 /// See state module for description of PDA memory layout.
 use crate::{
     error::RuleSetError,
-    state::{Key, Rule},
-    types::{Assertable, LibVersion, RuleSet},
+    state::{Key, Rule, RuleSetV2, ScopedRule},
+    types::{Assertable, LibVersion, RuleSet, DEFAULT_OPERATION, MAX_NAME_LENGTH},
+    utils::assert_valid_references,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "serde-with-feature")]
 use serde_with::{As, DisplayFromStr};
 use solana_program::{entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey};
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 
-/// Version of the `RuleSetRevisionMapV1` struct.
+/// Version of the `RuleSetRevisionMapV1` struct.  A `RuleSetRevisionMapV2` is stored with the
+/// same version.
 pub const RULE_SET_REV_MAP_VERSION: u8 = 1;
 
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone)]
@@ -47,6 +49,42 @@ pub struct RuleSetRevisionMapV1 {
     pub rule_set_revisions: Vec<usize>,
 }
 
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone, Default)]
+/// Revision map that also records the keccak hash of each `RuleSet` revision.  The hash is
+/// computed over the serialized `RuleSet` as passed to `CreateOrUpdate`, without the lib version
+/// byte written before a `RuleSetV1`, so it can be computed off-chain.  The map is stored with
+/// the version of a `RuleSetRevisionMapV1` and the hashes are serialized after the locations, so
+/// readers of a `RuleSetRevisionMapV1` ignore them.
+/// See top-level module for description of PDA memory layout.
+pub struct RuleSetRevisionMapV2 {
+    /// `Vec` used to map a `RuleSet` revision number to its location in the PDA.
+    pub rule_set_revisions: Vec<usize>,
+    /// `Vec` used to map a `RuleSet` revision number to its keccak hash.  Revisions written
+    /// before the revision map was upgraded have a hash of zeros.
+    pub rule_set_hashes: Vec<[u8; 32]>,
+}
+
+impl RuleSetRevisionMapV2 {
+    /// Get the hash of a `RuleSet` revision.  If `revision` is `None`, the hash of the latest
+    /// revision is returned.
+    pub fn hash(&self, revision: Option<usize>) -> Result<&[u8; 32], RuleSetError> {
+        match revision {
+            Some(revision) => self.rule_set_hashes.get(revision),
+            None => self.rule_set_hashes.last(),
+        }
+        .ok_or(RuleSetError::RuleSetRevisionNotAvailable)
+    }
+}
+
+impl From<RuleSetRevisionMapV1> for RuleSetRevisionMapV2 {
+    fn from(revision_map: RuleSetRevisionMapV1) -> Self {
+        Self {
+            rule_set_hashes: vec![[0; 32]; revision_map.rule_set_revisions.len()],
+            rule_set_revisions: revision_map.rule_set_revisions,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 /// The struct containing all Rule Set data, most importantly the map of operations to `Rules`.
@@ -63,6 +101,12 @@ pub struct RuleSetV1 {
     rule_set_name: String,
     /// A map to determine the `Rule` that belongs to a given `Operation`.
     pub operations: HashMap<String, Rule>,
+    /// A map of named `Rule` definitions that can be shared by operations through `RuleRef`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub definitions: HashMap<String, Rule>,
+    /// Number of `ExternalRuleSet` rules followed to load the RuleSet.  This is not stored.
+    #[serde(skip)]
+    depth: u8,
 }
 
 impl RuleSetV1 {
@@ -73,6 +117,8 @@ impl RuleSetV1 {
             rule_set_name,
             owner,
             operations: HashMap::new(),
+            definitions: HashMap::new(),
+            depth: 0,
         }
     }
 
@@ -91,6 +137,153 @@ impl RuleSetV1 {
     pub fn get(&self, operation: String) -> Option<&Rule> {
         self.operations.get(&operation)
     }
+
+    /// Retrieve the `Rule` tree for a given `Operation`, resolving any `RuleRef` to its
+    /// definition.
+    fn get_resolved(&self, operation: &str) -> Result<Option<&Rule>, ProgramError> {
+        let mut rule = self.operations.get(operation);
+
+        // Resolve the rule definition when the operation rule is a reference.
+        while let Some(Rule::RuleRef { name }) = rule {
+            rule = Some(
+                self.get_definition(name)
+                    .ok_or(RuleSetError::RuleDefinitionNotFound)?,
+            );
+        }
+
+        Ok(rule)
+    }
+
+    /// Add a named rule definition into a `RuleSet`.  If a definition with the same name is
+    /// already in the `RuleSet` nothing is updated and an error is returned.
+    pub fn add_definition(&mut self, name: String, rule: Rule) -> ProgramResult {
+        match self.definitions.entry(name) {
+            Entry::Vacant(entry) => {
+                entry.insert(rule);
+                Ok(())
+            }
+            Entry::Occupied(_) => Err(RuleSetError::DuplicatedDefinitionName.into()),
+        }
+    }
+
+    /// Retrieve the `Rule` tree for a given definition name.
+    pub fn get_definition(&self, name: &str) -> Option<&Rule> {
+        self.definitions.get(name)
+    }
+
+    /// Returns the number of `ExternalRuleSet` rules followed to load the `RuleSet`.
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Set the number of `ExternalRuleSet` rules followed to load the `RuleSet`.
+    pub(crate) fn set_depth(&mut self, depth: u8) {
+        self.depth = depth;
+    }
+
+    /// Check that every operation rule and definition is well formed.
+    pub fn assert_well_formed(&self) -> ProgramResult {
+        self.operations
+            .values()
+            .chain(self.definitions.values())
+            .try_for_each(|rule| rule.assert_well_formed())
+            .map_err(|error| error.into())
+    }
+
+    /// Check that every `RuleRef` in the `RuleSet` resolves to a definition and that the
+    /// definitions do not reference each other in a cycle.
+    pub fn assert_valid_references(&self) -> ProgramResult {
+        let to_owned = |rule: &Rule| {
+            rule.references()
+                .into_iter()
+                .map(str::to_owned)
+                .collect::<Vec<String>>()
+        };
+
+        let roots = self
+            .operations
+            .values()
+            .flat_map(to_owned)
+            .collect::<Vec<String>>();
+        let definitions = self
+            .definitions
+            .iter()
+            .map(|(name, rule)| (name.clone(), to_owned(rule)))
+            .collect();
+
+        assert_valid_references(&roots, &definitions).map_err(|error| error.into())
+    }
+
+    /// Serialize the equivalent `RuleSetV2`, converting every rule with `Rule::to_v2`.
+    pub fn to_v2(&self) -> Result<Vec<u8>, RuleSetError> {
+        if self.rule_set_name.len() > MAX_NAME_LENGTH {
+            return Err(RuleSetError::NameTooLong);
+        }
+
+        let (operations, rules): (Vec<_>, Vec<_>) =
+            Self::to_v2_rules(&self.operations)?.into_iter().unzip();
+        let (definition_names, definitions): (Vec<_>, Vec<_>) =
+            Self::to_v2_rules(&self.definitions)?.into_iter().unzip();
+
+        RuleSetV2::serialize_with_definitions(
+            self.owner,
+            &self.rule_set_name,
+            &operations,
+            &rules.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+            &definition_names,
+            &definitions.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+        )
+    }
+
+    /// Serialize each rule of a map, checking the length of its name.
+    fn to_v2_rules(rules: &HashMap<String, Rule>) -> Result<Vec<(String, Vec<u8>)>, RuleSetError> {
+        rules
+            .iter()
+            .map(|(name, rule)| {
+                if name.len() > MAX_NAME_LENGTH {
+                    Err(RuleSetError::NameTooLong)
+                } else {
+                    Ok((name.clone(), rule.to_v2()?))
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the rule for an operation by searching through fallbacks.
+    fn find_rule(&self, operation: &str) -> Result<&Rule, ProgramError> {
+        let rule = match self.get_resolved(operation)? {
+            Some(Rule::Namespace) => {
+                // Check for ':' namespace separators. If they exist try the longest operation
+                // namespace that has a rule. E.g. 'transfer:owner:escrow' will check for a
+                // fallback for 'transfer:owner' and then for 'transfer'.
+                let mut fallback = None;
+
+                for (index, _) in operation.rmatch_indices(':') {
+                    match self.get_resolved(&operation[..index])? {
+                        Some(Rule::Namespace) | None => continue,
+                        Some(rule) => {
+                            fallback = Some(rule);
+                            break;
+                        }
+                    }
+                }
+
+                fallback
+            }
+            rule => rule,
+        };
+
+        // If no rule was found, use the default operation rule if there is one.
+        let rule = match rule {
+            Some(rule) => rule,
+            None => match self.get_resolved(DEFAULT_OPERATION)? {
+                Some(Rule::Namespace) | None => return Err(RuleSetError::OperationNotFound.into()),
+                Some(rule) => rule,
+            },
+        };
+
+        Ok(rule)
+    }
 }
 
 impl<'a> RuleSet<'a> for RuleSetV1 {
@@ -107,24 +300,21 @@ impl<'a> RuleSet<'a> for RuleSetV1 {
         self.lib_version
     }
 
-    /// This function returns the rule for an operation by recursively searching through fallbacks
+    /// This function returns the rule for an operation by searching through fallbacks.
     fn get_rule(&self, operation: String) -> Result<&dyn Assertable<'a>, ProgramError> {
-        let rule = self.get(operation.to_string());
+        Ok(self.find_rule(&operation)?)
+    }
 
-        match rule {
-            Some(Rule::Namespace) => {
-                // Check for a ':' namespace separator. If it exists try to operation namespace to see if
-                // a fallback exists. E.g. 'transfer:owner' will check for a fallback for 'transfer'.
-                // If it doesn't exist then fail.
-                let split = operation.split(':').collect::<Vec<&str>>();
-                if split.len() > 1 {
-                    self.get_rule(split[0].to_owned())
-                } else {
-                    Err(RuleSetError::OperationNotFound.into())
-                }
-            }
-            Some(r) => Ok(r),
-            None => Err(RuleSetError::OperationNotFound.into()),
-        }
+    fn get_rule_with_definitions<'s>(
+        &'s self,
+        operation: String,
+    ) -> Result<Box<dyn Assertable<'a> + 's>, ProgramError>
+    where
+        'a: 's,
+    {
+        Ok(Box::new(ScopedRule {
+            rule: self.find_rule(&operation)?,
+            rule_set: self,
+        }))
     }
 }
@@ -1,10 +1,14 @@
 use crate::{
     error::RuleSetError,
-    payload::Payload,
-    types::Assertable,
+    payload::{Payload, PayloadType},
+    state::{v2, RuleSetV1},
+    types::{Assertable, MAX_NAME_LENGTH},
     // TODO: Uncomment this after on-curve sycall available.
     // utils::is_on_curve,
-    utils::{assert_derivation, compute_merkle_root, is_zeroed},
+    utils::{
+        assert_derivation, compute_merkle_root, get_registry_root, is_excluded, is_zeroed,
+        pubkey_list_contains, validate_external_rule_set,
+    },
 };
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "serde-with-feature")]
@@ -30,6 +34,25 @@ pub enum CompareOp {
     Gt,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+/// Operators that can be used to match against a `BytesMatch` or `StrMatch` rule.
+pub enum MatchOp {
+    /// Equal To
+    Eq,
+    /// Starts With
+    Prefix,
+}
+
+impl MatchOp {
+    /// Returns whether `value` matches `expected` using the operator.
+    pub fn apply(&self, value: &[u8], expected: &[u8]) -> bool {
+        match self {
+            MatchOp::Eq => value == expected,
+            MatchOp::Prefix => value.starts_with(expected),
+        }
+    }
+}
+
 /// Enum representation of Rule failure conditions
 pub enum RuleResult {
     /// The rule succeeded.
@@ -210,6 +233,269 @@ pub enum Rule {
     },
     /// A rule that tells the operation finder to use the default namespace rule.
     Namespace,
+    /// Group threshold (k-of-n), where at least `k` of the rules contained must pass.
+    Threshold {
+        /// The minimum number of Rules that must pass.
+        k: u64,
+        /// The vector of Rules contained under Threshold.
+        rules: Vec<Rule>,
+    },
+    /// Conditional, where the `then` rule must pass if the `condition` rule passes, and the
+    /// `otherwise` rule must pass if the `condition` rule fails.  The condition is evaluated only
+    /// once, and if it returns an error that error is returned without evaluating either branch.
+    IfThenElse {
+        /// The Rule used as the condition.
+        condition: Box<Rule>,
+        /// The Rule evaluated when the condition passes.
+        then: Box<Rule>,
+        /// The Rule evaluated when the condition fails.
+        otherwise: Box<Rule>,
+    },
+    /// A reference to a named rule definition stored in the `RuleSet`, which allows the same
+    /// rule tree to be shared by several operations.
+    RuleRef {
+        /// The name of the rule definition.
+        name: String,
+    },
+    /// The rule of an operation in another `RuleSet` must pass.  When the `Validate` instruction
+    /// is called, the `RuleSet` PDA must be provided to `Validate` via the
+    /// `additional_rule_accounts` argument, and the same `Payload` is used to validate the
+    /// operation.  The number of nested `RuleSet`s is limited by `MAX_RULE_SET_DEPTH`.
+    ExternalRuleSet {
+        /// The address of the `RuleSet` PDA.
+        #[cfg_attr(feature = "serde-with-feature", serde(with = "As::<DisplayFromStr>"))]
+        rule_set: Pubkey,
+        /// The operation to validate in the `RuleSet`.
+        operation: String,
+        /// Optional revision of the `RuleSet` to use.  If `None`, the latest revision is used.
+        revision: Option<usize>,
+    },
+    /// Comparison against bytes.  When the `Validate` instruction is called, this rule requires
+    /// a `PayloadType` value of `PayloadType::Bytes`.  The `field` value in the rule is used to
+    /// locate the bytes in the payload to match against the value stored in the rule, using the
+    /// match operator stored in the rule.
+    BytesMatch {
+        /// The bytes to be matched against.
+        value: Vec<u8>,
+        /// The operator to be used in the match.
+        operator: MatchOp,
+        /// The field the bytes are stored in.
+        field: String,
+    },
+    /// Comparison against a string.  When the `Validate` instruction is called, this rule
+    /// requires a `PayloadType` value of `PayloadType::Str`.  The `field` value in the rule is
+    /// used to locate the string in the payload to match against the value stored in the rule,
+    /// using the match operator stored in the rule.
+    StrMatch {
+        /// The string to be matched against.
+        value: String,
+        /// The operator to be used in the match.
+        operator: MatchOp,
+        /// The field the string is stored in.
+        field: String,
+    },
+    /// Comparison against a boolean flag.  When the `Validate` instruction is called, this rule
+    /// requires a `PayloadType` value of `PayloadType::Bool`.  The `field` value in the rule is
+    /// used to locate the flag in the payload that must be equal to the value stored in the rule.
+    BoolMatch {
+        /// The expected value.
+        value: bool,
+        /// The field the flag is stored in.
+        field: String,
+    },
+    /// Comparison against a signed amount.  When the `Validate` instruction is called, this rule
+    /// requires a `PayloadType` value of `PayloadType::I64`.  The `field` value in the rule is
+    /// used to locate the number in the payload to compare to the amount stored in the rule,
+    /// using the comparison operator stored in the rule.
+    AmountI64 {
+        /// The amount to be compared against.
+        amount: i64,
+        /// The operator to be used in the comparison.
+        operator: CompareOp,
+        /// The field the amount is stored in.
+        field: String,
+    },
+    /// Comparison against a 128-bit amount.  When the `Validate` instruction is called, this
+    /// rule requires a `PayloadType` value of `PayloadType::U128`.  The `field` value in the rule
+    /// is used to locate the number in the payload to compare to the amount stored in the rule,
+    /// using the comparison operator stored in the rule.
+    AmountU128 {
+        /// The amount to be compared against.
+        amount: u128,
+        /// The operator to be used in the comparison.
+        operator: CompareOp,
+        /// The field the amount is stored in.
+        field: String,
+    },
+    /// The `Pubkey` stored in the rule must be contained in a list of `Pubkey`s.  When the
+    /// `Validate` instruction is called, this rule requires a `PayloadType` value of
+    /// `PayloadType::PubkeyList`.  The `field` value in the rule is used to locate the list in
+    /// the payload.
+    PubkeyListContains {
+        /// The public key that must be in the list.
+        #[cfg_attr(feature = "serde-with-feature", serde(with = "As::<DisplayFromStr>"))]
+        pubkey: Pubkey,
+        /// The field the list is stored in.
+        field: String,
+    },
+    /// Comparison between two values in the payload.  When the `Validate` instruction is called,
+    /// this rule requires both fields to be present in the payload with the same `PayloadType`,
+    /// either `PayloadType::Number` or `PayloadType::Pubkey`.  The value of the `left_field` is
+    /// compared to the value of the `right_field` using the comparison operator stored in the
+    /// rule.  `Pubkey`s can only be compared using `CompareOp::Eq`.
+    FieldCompare {
+        /// The field of the left-hand side value.
+        left_field: String,
+        /// The operator to be used in the comparison.
+        operator: CompareOp,
+        /// The field of the right-hand side value.
+        right_field: String,
+    },
+    /// The amount must be within an inclusive range.  When the `Validate` instruction is called,
+    /// this rule requires a `PayloadType` value of `PayloadType::Number`.  The `field` value in
+    /// the rule is used to locate the numerical amount in the payload.
+    AmountRange {
+        /// The minimum amount (inclusive).
+        min: u64,
+        /// The maximum amount (inclusive).
+        max: u64,
+        /// The field the amount is stored in.
+        field: String,
+    },
+    /// The ratio between two amounts must be at least `num / denom`, which is checked as
+    /// `numerator * denom >= denominator * num` using 128-bit math.  When the `Validate`
+    /// instruction is called, this rule requires `PayloadType` values of either
+    /// `PayloadType::Number` or `PayloadType::U128`.  A multiplication overflow is an error
+    /// rather than a failure.
+    AmountRatio {
+        /// The numerator of the minimum ratio.
+        num: u64,
+        /// The denominator of the minimum ratio.
+        denom: u64,
+        /// The field the numerator amount is stored in.
+        numerator_field: String,
+        /// The field the denominator amount is stored in.
+        denominator_field: String,
+    },
+    /// The `Pubkey` must not be a member of the exclusion tree used to produce the root in the
+    /// rule.  When the `Validate` instruction is called, this rule requires `PayloadType` values
+    /// of `PayloadType::Pubkey` and `PayloadType::ExclusionProof`.  The `field` values in the Rule
+    /// are used to locate them in the `Payload`.  The proof must contain two adjacent keys of the
+    /// tree that bracket the `Pubkey`, and lead to the root stored in the rule.
+    PubkeyTreeExclusion {
+        /// The root of the exclusion tree.
+        root: [u8; 32],
+        /// The field in the `Payload` to be compared
+        /// when looking for the `Pubkey`.
+        pubkey_field: String,
+        /// The field in the `Payload` to be compared
+        /// when looking for the exclusion proof.
+        proof_field: String,
+    },
+    /// Same as `PubkeyTreeMatch`, except the Merkle root is read from a `RootRegistryAccount`
+    /// instead of being stored in the rule, so it can be rotated without a new `RuleSet`
+    /// revision.  The registry account must be provided to `Validate` via the
+    /// `additional_rule_accounts` argument.
+    PubkeyTreeMatchRegistry {
+        /// The registry account holding the root of the Merkle tree.
+        #[cfg_attr(feature = "serde-with-feature", serde(with = "As::<DisplayFromStr>"))]
+        registry: Pubkey,
+        /// The field in the `Payload` to be compared
+        /// when looking for the `Pubkey`.
+        pubkey_field: String,
+        /// The field in the `Payload` to be compared
+        /// when looking for the Merkle proof.
+        proof_field: String,
+    },
+    /// Same as `ProgramOwnedTree`, except the Merkle root is read from a `RootRegistryAccount`
+    /// instead of being stored in the rule.  Both the registry account and the `Pubkey` account
+    /// must be provided to `Validate` via the `additional_rule_accounts` argument.
+    ProgramOwnedTreeRegistry {
+        /// The registry account holding the root of the Merkle tree.
+        #[cfg_attr(feature = "serde-with-feature", serde(with = "As::<DisplayFromStr>"))]
+        registry: Pubkey,
+        /// The field in the `Payload` to be compared
+        /// when looking for the `Pubkey`.
+        pubkey_field: String,
+        /// The field in the `Payload` to be compared
+        /// when looking for the Merkle proof.
+        proof_field: String,
+    },
+    /// The `Pubkey` must be in the list stored in a `PubkeyListAccount`.  When the `Validate`
+    /// instruction is called, this rule requires a `PayloadType` value of `PayloadType::Pubkey`.
+    /// The `field` value in the rule is used to locate the `Pubkey` in the payload.  The list
+    /// account must be provided to `Validate` via the `additional_rule_accounts` argument.
+    PubkeyInListAccount {
+        /// The account holding the list of `Pubkey`s.
+        #[cfg_attr(feature = "serde-with-feature", serde(with = "As::<DisplayFromStr>"))]
+        list: Pubkey,
+        /// The field in the `Payload` to be compared.
+        field: String,
+    },
+    /// The `Pubkey` must be owned by a program in the list stored in a `PubkeyListAccount`.
+    /// When the `Validate` instruction is called, this rule requires a `PayloadType` value of
+    /// `PayloadType::Pubkey`.  The `field` value in the rule is used to locate the `Pubkey` in
+    /// the payload.  Both the list account and the `Pubkey` account must be provided to
+    /// `Validate` via the `additional_rule_accounts` argument.
+    OwnerInListAccount {
+        /// The account holding the list of programs.
+        #[cfg_attr(feature = "serde-with-feature", serde(with = "As::<DisplayFromStr>"))]
+        list: Pubkey,
+        /// The field in the `Payload` to be compared.
+        field: String,
+    },
+}
+
+/// A `Rule` bound to the `RuleSetV1` that contains it, so that any `RuleRef` in the rule tree
+/// can be resolved against the `RuleSet` definitions.
+pub struct ScopedRule<'r> {
+    /// The top level rule.
+    pub rule: &'r Rule,
+    /// The `RuleSet` that owns the rule.
+    pub rule_set: &'r RuleSetV1,
+}
+
+impl<'a, 'r> Assertable<'a> for ScopedRule<'r> {
+    fn validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> ProgramResult {
+        let result = self.low_level_validate(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+        );
+
+        match result {
+            Success(_) => Ok(()),
+            Failure(err) => Err(err),
+            Error(err) => Err(err),
+        }
+    }
+
+    fn low_level_validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> RuleResult {
+        self.rule.low_level_validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            self.rule_set,
+        )
+    }
 }
 
 impl<'a> Assertable<'a> for Rule {
@@ -236,29 +522,74 @@ impl<'a> Assertable<'a> for Rule {
             Error(err) => Err(err),
         }
     }
+
+    fn low_level_validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> RuleResult {
+        Rule::low_level_validate(
+            self,
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+        )
+    }
 }
 
 impl Rule {
     /// Lower level validation function which iterates through a rule tree and applies boolean logic to rule results.
+    ///
+    /// Any `RuleRef` in the rule tree fails with `RuleDefinitionNotFound`, use
+    /// `low_level_validate_with_definitions` to resolve them against a `RuleSet`.
     pub fn low_level_validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> RuleResult {
+        self.low_level_validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            &RuleSetV1::new(String::new(), Pubkey::default()),
+        )
+    }
+
+    /// Lower level validation function which iterates through a rule tree and applies boolean logic to rule results.
+    ///
+    /// The `rule_set` is the `RuleSet` that owns the rule and is used to resolve any `RuleRef`
+    /// to its definition.
+    pub fn low_level_validate_with_definitions(
         &self,
         accounts: &HashMap<Pubkey, &AccountInfo>,
         payload: &Payload,
         _update_rule_state: bool,
         _rule_set_state_pda: &Option<&AccountInfo>,
         rule_authority: &Option<&AccountInfo>,
+        rule_set: &RuleSetV1,
     ) -> RuleResult {
         match self {
             Rule::All { rules } => {
                 msg!("Validating All");
                 let mut last: Option<ProgramError> = None;
                 for rule in rules {
-                    let result = rule.low_level_validate(
+                    let result = rule.low_level_validate_with_definitions(
                         accounts,
                         payload,
                         _update_rule_state,
                         _rule_set_state_pda,
                         rule_authority,
+                        rule_set,
                     );
                     // Return failure on the first failing rule.
                     match result {
@@ -275,12 +606,13 @@ impl Rule {
                 let mut last_failure: Option<ProgramError> = None;
                 let mut last_error: Option<ProgramError> = None;
                 for rule in rules {
-                    let result = rule.low_level_validate(
+                    let result = rule.low_level_validate_with_definitions(
                         accounts,
                         payload,
                         _update_rule_state,
                         _rule_set_state_pda,
                         rule_authority,
+                        rule_set,
                     );
                     match result {
                         Success(_) => return result,
@@ -302,12 +634,13 @@ impl Rule {
             }
             Rule::Not { rule } => {
                 msg!("Validating Not");
-                let result = rule.low_level_validate(
+                let result = rule.low_level_validate_with_definitions(
                     accounts,
                     payload,
                     _update_rule_state,
                     _rule_set_state_pda,
                     rule_authority,
+                    rule_set,
                 );
 
                 // Negate the result.
@@ -359,12 +692,13 @@ impl Rule {
                             .collect(),
                     };
 
-                    return new_rule.low_level_validate(
+                    return new_rule.low_level_validate_with_definitions(
                         accounts,
                         payload,
                         _update_rule_state,
                         _rule_set_state_pda,
                         rule_authority,
+                        rule_set,
                     );
                 } else {
                     let key = match payload.get_pubkey(&field.to_owned()) {
@@ -500,12 +834,13 @@ impl Rule {
                             .collect(),
                     };
 
-                    return new_rule.low_level_validate(
+                    return new_rule.low_level_validate_with_definitions(
                         accounts,
                         payload,
                         _update_rule_state,
                         _rule_set_state_pda,
                         rule_authority,
+                        rule_set,
                     );
                 } else {
                     let key = match payload.get_pubkey(&field.to_string()) {
@@ -600,19 +935,7 @@ impl Rule {
             } => {
                 msg!("Validating Amount");
                 if let Some(payload_amount) = &payload.get_amount(field) {
-                    let operator_fn = match operator {
-                        CompareOp::Lt => PartialOrd::lt,
-                        CompareOp::LtEq => PartialOrd::le,
-                        CompareOp::Eq => PartialEq::eq,
-                        CompareOp::Gt => PartialOrd::gt,
-                        CompareOp::GtEq => PartialOrd::ge,
-                    };
-
-                    if operator_fn(payload_amount, rule_amount) {
-                        Success(self.to_error())
-                    } else {
-                        Failure(self.to_error())
-                    }
+                    self.compare(operator, payload_amount, rule_amount)
                 } else {
                     Error(RuleSetError::MissingPayloadValue.into())
                 }
@@ -678,12 +1001,13 @@ impl Rule {
                             .collect(),
                     };
 
-                    return new_rule.low_level_validate(
+                    return new_rule.low_level_validate_with_definitions(
                         accounts,
                         payload,
                         _update_rule_state,
                         _rule_set_state_pda,
                         rule_authority,
+                        rule_set,
                     );
                 } else {
                     let key = match payload.get_pubkey(&field.to_string()) {
@@ -722,6 +1046,620 @@ impl Rule {
                 msg!("Validating Namespace");
                 Failure(self.to_error())
             }
+            Rule::Threshold { k, rules } => {
+                msg!("Validating Threshold");
+
+                // A threshold of zero is always met.
+                if *k == 0 {
+                    return Success(self.to_error());
+                }
+
+                let mut passed: u64 = 0;
+                let mut last_failure: Option<ProgramError> = None;
+                let mut last_error: Option<ProgramError> = None;
+                for rule in rules {
+                    let result = rule.low_level_validate_with_definitions(
+                        accounts,
+                        payload,
+                        _update_rule_state,
+                        _rule_set_state_pda,
+                        rule_authority,
+                        rule_set,
+                    );
+                    match result {
+                        Success(err) => {
+                            // Return pass as soon as `k` rules passed.
+                            passed += 1;
+                            if passed >= *k {
+                                return Success(err);
+                            }
+                        }
+                        Failure(err) => last_failure = Some(err),
+                        Error(err) => last_error = Some(err),
+                    }
+                }
+
+                // Return the last failure if and only if fewer than `k` rules passed and there
+                // was at least one failure, otherwise return the last error.
+                if let Some(err) = last_failure {
+                    Failure(err)
+                } else if let Some(err) = last_error {
+                    Error(err)
+                } else {
+                    Error(RuleSetError::UnexpectedRuleSetFailure.into())
+                }
+            }
+            Rule::IfThenElse {
+                condition,
+                then,
+                otherwise,
+            } => {
+                msg!("Validating IfThenElse");
+                let result = condition.low_level_validate_with_definitions(
+                    accounts,
+                    payload,
+                    _update_rule_state,
+                    _rule_set_state_pda,
+                    rule_authority,
+                    rule_set,
+                );
+
+                // Select the branch based on the condition, an error is not a failure so
+                // neither branch is evaluated.
+                let branch = match result {
+                    Success(_) => then,
+                    Failure(_) => otherwise,
+                    Error(err) => return Error(err),
+                };
+
+                branch.low_level_validate_with_definitions(
+                    accounts,
+                    payload,
+                    _update_rule_state,
+                    _rule_set_state_pda,
+                    rule_authority,
+                    rule_set,
+                )
+            }
+            Rule::RuleRef { name } => {
+                msg!("Validating RuleRef");
+
+                match rule_set.get_definition(name) {
+                    Some(rule) => rule.low_level_validate_with_definitions(
+                        accounts,
+                        payload,
+                        _update_rule_state,
+                        _rule_set_state_pda,
+                        rule_authority,
+                        rule_set,
+                    ),
+                    None => Error(RuleSetError::RuleDefinitionNotFound.into()),
+                }
+            }
+            Rule::ExternalRuleSet {
+                rule_set: external,
+                operation,
+                revision,
+            } => {
+                msg!("Validating ExternalRuleSet");
+
+                validate_external_rule_set(
+                    external,
+                    operation.to_string(),
+                    *revision,
+                    rule_set.depth(),
+                    accounts,
+                    payload,
+                    rule_authority,
+                )
+            }
+            Rule::BytesMatch {
+                value,
+                operator,
+                field,
+            } => {
+                msg!("Validating BytesMatch");
+                match payload.get_bytes(field) {
+                    Some(bytes) if operator.apply(bytes, value) => Success(self.to_error()),
+                    Some(_) => Failure(self.to_error()),
+                    None => Error(RuleSetError::MissingPayloadValue.into()),
+                }
+            }
+            Rule::StrMatch {
+                value,
+                operator,
+                field,
+            } => {
+                msg!("Validating StrMatch");
+                match payload.get_str(field) {
+                    Some(string) if operator.apply(string.as_bytes(), value.as_bytes()) => {
+                        Success(self.to_error())
+                    }
+                    Some(_) => Failure(self.to_error()),
+                    None => Error(RuleSetError::MissingPayloadValue.into()),
+                }
+            }
+            Rule::BoolMatch { value, field } => {
+                msg!("Validating BoolMatch");
+                match payload.get_bool(field) {
+                    Some(flag) if flag == *value => Success(self.to_error()),
+                    Some(_) => Failure(self.to_error()),
+                    None => Error(RuleSetError::MissingPayloadValue.into()),
+                }
+            }
+            Rule::AmountI64 {
+                amount: rule_amount,
+                operator,
+                field,
+            } => {
+                msg!("Validating AmountI64");
+                if let Some(payload_amount) = &payload.get_i64(field) {
+                    self.compare(operator, payload_amount, rule_amount)
+                } else {
+                    Error(RuleSetError::MissingPayloadValue.into())
+                }
+            }
+            Rule::AmountU128 {
+                amount: rule_amount,
+                operator,
+                field,
+            } => {
+                msg!("Validating AmountU128");
+                if let Some(payload_amount) = &payload.get_u128(field) {
+                    self.compare(operator, payload_amount, rule_amount)
+                } else {
+                    Error(RuleSetError::MissingPayloadValue.into())
+                }
+            }
+            Rule::PubkeyListContains { pubkey, field } => {
+                msg!("Validating PubkeyListContains");
+                match payload.get_pubkey_list(field) {
+                    Some(pubkeys) if pubkeys.contains(pubkey) => Success(self.to_error()),
+                    Some(_) => Failure(self.to_error()),
+                    None => Error(RuleSetError::MissingPayloadValue.into()),
+                }
+            }
+            Rule::FieldCompare {
+                left_field,
+                operator,
+                right_field,
+            } => {
+                msg!("Validating FieldCompare");
+
+                let (left, right) = match (payload.get(left_field), payload.get(right_field)) {
+                    (Some(left), Some(right)) => (left, right),
+                    _ => return Error(RuleSetError::MissingPayloadValue.into()),
+                };
+
+                match (left, right) {
+                    (PayloadType::Number(left), PayloadType::Number(right)) => {
+                        self.compare(operator, left, right)
+                    }
+                    (PayloadType::Pubkey(left), PayloadType::Pubkey(right)) => {
+                        if *operator != CompareOp::Eq {
+                            Error(RuleSetError::InvalidCompareOp.into())
+                        } else if left == right {
+                            Success(self.to_error())
+                        } else {
+                            Failure(self.to_error())
+                        }
+                    }
+                    _ => Error(RuleSetError::FieldTypeMismatch.into()),
+                }
+            }
+            Rule::AmountRange { min, max, field } => {
+                msg!("Validating AmountRange");
+                match payload.get_amount(field) {
+                    Some(amount) if (*min..=*max).contains(&amount) => Success(self.to_error()),
+                    Some(_) => Failure(self.to_error()),
+                    None => Error(RuleSetError::MissingPayloadValue.into()),
+                }
+            }
+            Rule::AmountRatio {
+                num,
+                denom,
+                numerator_field,
+                denominator_field,
+            } => {
+                msg!("Validating AmountRatio");
+
+                let (numerator, denominator) = match (
+                    payload.get_amount_u128(numerator_field),
+                    payload.get_amount_u128(denominator_field),
+                ) {
+                    (Some(numerator), Some(denominator)) => (numerator, denominator),
+                    _ => return Error(RuleSetError::MissingPayloadValue.into()),
+                };
+
+                let (lhs, rhs) = match (
+                    numerator.checked_mul(*denom as u128),
+                    denominator.checked_mul(*num as u128),
+                ) {
+                    (Some(lhs), Some(rhs)) => (lhs, rhs),
+                    _ => return Error(RuleSetError::NumericalOverflow.into()),
+                };
+
+                if lhs >= rhs {
+                    Success(self.to_error())
+                } else {
+                    Failure(self.to_error())
+                }
+            }
+            Rule::PubkeyTreeExclusion {
+                root,
+                pubkey_field,
+                proof_field,
+            } => {
+                msg!("Validating PubkeyTreeExclusion");
+
+                // Get the `Pubkey` we are checking from the payload.
+                let key = match payload.get_pubkey(pubkey_field) {
+                    Some(pubkey) => pubkey,
+                    _ => return Error(RuleSetError::MissingPayloadValue.into()),
+                };
+
+                // Get the exclusion proof from the payload.
+                let exclusion_proof = match payload.get_exclusion_proof(proof_field) {
+                    Some(exclusion_proof) => exclusion_proof,
+                    _ => return Error(RuleSetError::MissingPayloadValue.into()),
+                };
+
+                if is_excluded(key, exclusion_proof, root) {
+                    Success(self.to_error())
+                } else {
+                    Failure(self.to_error())
+                }
+            }
+            Rule::PubkeyTreeMatchRegistry {
+                registry,
+                pubkey_field,
+                proof_field,
+            } => {
+                msg!("Validating PubkeyTreeMatchRegistry");
+
+                // Get the current root from the registry account.
+                let root = match get_registry_root(registry, accounts) {
+                    Ok(root) => root,
+                    Err(error) => return Error(error),
+                };
+
+                Rule::PubkeyTreeMatch {
+                    root,
+                    pubkey_field: pubkey_field.clone(),
+                    proof_field: proof_field.clone(),
+                }
+                .low_level_validate_with_definitions(
+                    accounts,
+                    payload,
+                    _update_rule_state,
+                    _rule_set_state_pda,
+                    rule_authority,
+                    rule_set,
+                )
+            }
+            Rule::ProgramOwnedTreeRegistry {
+                registry,
+                pubkey_field,
+                proof_field,
+            } => {
+                msg!("Validating ProgramOwnedTreeRegistry");
+
+                // Get the current root from the registry account.
+                let root = match get_registry_root(registry, accounts) {
+                    Ok(root) => root,
+                    Err(error) => return Error(error),
+                };
+
+                Rule::ProgramOwnedTree {
+                    root,
+                    pubkey_field: pubkey_field.clone(),
+                    proof_field: proof_field.clone(),
+                }
+                .low_level_validate_with_definitions(
+                    accounts,
+                    payload,
+                    _update_rule_state,
+                    _rule_set_state_pda,
+                    rule_authority,
+                    rule_set,
+                )
+            }
+            Rule::PubkeyInListAccount { list, field } => {
+                msg!("Validating PubkeyInListAccount");
+
+                let key = match payload.get_pubkey(field) {
+                    Some(pubkey) => pubkey,
+                    _ => return Error(RuleSetError::MissingPayloadValue.into()),
+                };
+
+                match pubkey_list_contains(list, key, accounts) {
+                    Ok(true) => Success(self.to_error()),
+                    Ok(false) => Failure(self.to_error()),
+                    Err(error) => Error(error),
+                }
+            }
+            Rule::OwnerInListAccount { list, field } => {
+                msg!("Validating OwnerInListAccount");
+
+                let key = match payload.get_pubkey(field) {
+                    Some(pubkey) => pubkey,
+                    _ => return Error(RuleSetError::MissingPayloadValue.into()),
+                };
+
+                let account = match accounts.get(key) {
+                    Some(account) => account,
+                    _ => return Error(RuleSetError::MissingAccount.into()),
+                };
+
+                let data = match account.data.try_borrow() {
+                    Ok(data) => data,
+                    Err(_) => return Error(ProgramError::AccountBorrowFailed),
+                };
+
+                // Account must have nonzero data to count as program-owned.
+                if is_zeroed(&data) {
+                    // Print helpful errors.
+                    if data.len() == 0 {
+                        msg!("Account data is empty");
+                    } else {
+                        msg!("Account data is zeroed");
+                    }
+
+                    return Error(RuleSetError::DataIsEmpty.into());
+                }
+
+                match pubkey_list_contains(list, account.owner, accounts) {
+                    Ok(true) => Success(self.to_error()),
+                    Ok(false) => Failure(self.to_error()),
+                    Err(error) => Error(error),
+                }
+            }
+        }
+    }
+
+    /// Compares a payload value against the value stored in the rule using the comparison
+    /// operator.
+    fn compare<T: PartialOrd>(
+        &self,
+        operator: &CompareOp,
+        payload_value: &T,
+        rule_value: &T,
+    ) -> RuleResult {
+        let operator_fn = match operator {
+            CompareOp::Lt => PartialOrd::lt,
+            CompareOp::LtEq => PartialOrd::le,
+            CompareOp::Eq => PartialEq::eq,
+            CompareOp::Gt => PartialOrd::gt,
+            CompareOp::GtEq => PartialOrd::ge,
+        };
+
+        if operator_fn(payload_value, rule_value) {
+            Success(self.to_error())
+        } else {
+            Failure(self.to_error())
+        }
+    }
+
+    /// Checks that every `Threshold` in the rule tree can be met, i.e. that `k` is not larger
+    /// than the number of rules it contains.
+    pub fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        match self {
+            Rule::Threshold { k, rules } if *k > rules.len() as u64 => {
+                msg!(
+                    "Threshold {} is larger than the number of rules {}",
+                    k,
+                    rules.len()
+                );
+                Err(RuleSetError::InvalidThreshold)
+            }
+            Rule::All { rules } | Rule::Any { rules } | Rule::Threshold { rules, .. } => {
+                rules.iter().try_for_each(|rule| rule.assert_well_formed())
+            }
+            Rule::Not { rule } => rule.assert_well_formed(),
+            Rule::IfThenElse {
+                condition,
+                then,
+                otherwise,
+            } => {
+                condition.assert_well_formed()?;
+                then.assert_well_formed()?;
+                otherwise.assert_well_formed()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the names of the rule definitions referenced by this rule tree.  References
+    /// made by the definitions themselves are not followed.
+    pub fn references(&self) -> Vec<&str> {
+        match self {
+            Rule::All { rules } | Rule::Any { rules } | Rule::Threshold { rules, .. } => {
+                rules.iter().flat_map(|rule| rule.references()).collect()
+            }
+            Rule::Not { rule } => rule.references(),
+            Rule::IfThenElse {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let mut names = condition.references();
+                names.extend(then.references());
+                names.extend(otherwise.references());
+                names
+            }
+            Rule::RuleRef { name } => vec![name.as_str()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Serialize the equivalent `RuleV2` of the rule tree.  A `ProgramOwnedSet` is serialized
+    /// as a sorted `ProgramOwnedList`, which fails with `ProgramOwnedListCheckFailed`, and
+    /// fields and names are limited to `MAX_NAME_LENGTH` bytes.
+    ///
+    /// A `PubkeyListMatch` on several fields separated by `|` is serialized as a V2
+    /// `PubkeyListMatch`, which checks that one of the fields is in the list.  V1 validates such
+    /// a rule as a `ProgramOwnedList` on each field instead, which is kept for the `RuleSet`s
+    /// already deployed.
+    pub fn to_v2(&self) -> Result<Vec<u8>, RuleSetError> {
+        let name = |name: &String| {
+            if name.len() > MAX_NAME_LENGTH {
+                Err(RuleSetError::NameTooLong)
+            } else {
+                Ok(name.clone())
+            }
+        };
+        let rules = |rules: &[Rule]| {
+            rules
+                .iter()
+                .map(|rule| rule.to_v2())
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        match self {
+            Rule::All { rules: children } => {
+                let children = rules(children)?;
+                v2::All::serialize(&children.iter().map(Vec::as_slice).collect::<Vec<_>>())
+            }
+            Rule::Any { rules: children } => {
+                let children = rules(children)?;
+                v2::Any::serialize(&children.iter().map(Vec::as_slice).collect::<Vec<_>>())
+            }
+            Rule::Not { rule } => v2::Not::serialize(&rule.to_v2()?),
+            Rule::AdditionalSigner { account } => v2::AdditionalSigner::serialize(*account),
+            Rule::PubkeyMatch { pubkey, field } => {
+                v2::PubkeyMatch::serialize(name(field)?, *pubkey)
+            }
+            Rule::PubkeyListMatch { pubkeys, field } => {
+                v2::PubkeyListMatch::serialize(name(field)?, pubkeys)
+            }
+            Rule::PubkeyTreeMatch {
+                root,
+                pubkey_field,
+                proof_field,
+            } => v2::PubkeyTreeMatch::serialize(name(pubkey_field)?, name(proof_field)?, root),
+            Rule::PDAMatch {
+                program,
+                pda_field,
+                seeds_field,
+            } => v2::PDAMatch::serialize(name(pda_field)?, *program, name(seeds_field)?),
+            Rule::ProgramOwned { program, field } => {
+                v2::ProgramOwned::serialize(name(field)?, *program)
+            }
+            Rule::ProgramOwnedList { programs, field } => {
+                v2::ProgramOwnedList::serialize(name(field)?, programs)
+            }
+            Rule::ProgramOwnedTree {
+                root,
+                pubkey_field,
+                proof_field,
+            } => v2::ProgramOwnedTree::serialize(name(pubkey_field)?, name(proof_field)?, root),
+            Rule::Amount {
+                amount,
+                operator,
+                field,
+            } => v2::Amount::serialize(name(field)?, operator.into(), *amount),
+            Rule::Frequency { authority } => v2::Frequency::serialize(*authority),
+            Rule::IsWallet { field } => v2::IsWallet::serialize(name(field)?),
+            Rule::Pass => v2::Pass::serialize(),
+            Rule::ProgramOwnedSet { programs, field } => v2::ProgramOwnedList::serialize_sorted(
+                name(field)?,
+                &programs.iter().copied().collect::<Vec<_>>(),
+            ),
+            Rule::Namespace => v2::Namespace::serialize(),
+            Rule::Threshold { k, rules: children } => {
+                let children = rules(children)?;
+                v2::Threshold::serialize(
+                    *k,
+                    &children.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                )
+            }
+            Rule::IfThenElse {
+                condition,
+                then,
+                otherwise,
+            } => {
+                v2::IfThenElse::serialize(&condition.to_v2()?, &then.to_v2()?, &otherwise.to_v2()?)
+            }
+            Rule::RuleRef { name: definition } => v2::RuleRef::serialize(name(definition)?),
+            Rule::ExternalRuleSet {
+                rule_set,
+                operation,
+                revision,
+            } => v2::ExternalRuleSet::serialize(*rule_set, name(operation)?, *revision),
+            Rule::BytesMatch {
+                value,
+                operator,
+                field,
+            } => v2::BytesMatch::serialize(name(field)?, operator.into(), value.clone()),
+            Rule::StrMatch {
+                value,
+                operator,
+                field,
+            } => v2::StrMatch::serialize(name(field)?, operator.into(), value.clone()),
+            Rule::BoolMatch { value, field } => v2::BoolMatch::serialize(name(field)?, *value),
+            Rule::AmountI64 {
+                amount,
+                operator,
+                field,
+            } => v2::AmountI64::serialize(name(field)?, operator.into(), *amount),
+            Rule::AmountU128 {
+                amount,
+                operator,
+                field,
+            } => v2::AmountU128::serialize(name(field)?, operator.into(), *amount),
+            Rule::PubkeyListContains { pubkey, field } => {
+                v2::PubkeyListContains::serialize(name(field)?, *pubkey)
+            }
+            Rule::FieldCompare {
+                left_field,
+                operator,
+                right_field,
+            } => {
+                v2::FieldCompare::serialize(name(left_field)?, operator.into(), name(right_field)?)
+            }
+            Rule::AmountRange { min, max, field } => {
+                v2::AmountRange::serialize(name(field)?, *min, *max)
+            }
+            Rule::AmountRatio {
+                num,
+                denom,
+                numerator_field,
+                denominator_field,
+            } => v2::AmountRatio::serialize(
+                name(numerator_field)?,
+                name(denominator_field)?,
+                *num,
+                *denom,
+            ),
+            Rule::PubkeyTreeExclusion {
+                root,
+                pubkey_field,
+                proof_field,
+            } => v2::PubkeyTreeExclusion::serialize(name(pubkey_field)?, name(proof_field)?, root),
+            Rule::PubkeyTreeMatchRegistry {
+                registry,
+                pubkey_field,
+                proof_field,
+            } => v2::PubkeyTreeMatchRegistry::serialize(
+                name(pubkey_field)?,
+                name(proof_field)?,
+                registry,
+            ),
+            Rule::ProgramOwnedTreeRegistry {
+                registry,
+                pubkey_field,
+                proof_field,
+            } => v2::ProgramOwnedTreeRegistry::serialize(
+                name(pubkey_field)?,
+                name(proof_field)?,
+                registry,
+            ),
+            Rule::PubkeyInListAccount { list, field } => {
+                v2::PubkeyInListAccount::serialize(name(field)?, *list)
+            }
+            Rule::OwnerInListAccount { list, field } => {
+                v2::OwnerInListAccount::serialize(name(field)?, *list)
+            }
         }
     }
 
@@ -732,19 +1670,39 @@ impl Rule {
             | Rule::Any { .. }
             | Rule::Not { .. }
             | Rule::Pass
-            | Rule::Namespace => RuleSetError::UnexpectedRuleSetFailure.into(),
+            | Rule::Namespace
+            | Rule::Threshold { .. }
+            | Rule::IfThenElse { .. }
+            | Rule::RuleRef { .. }
+            | Rule::ExternalRuleSet { .. } => RuleSetError::UnexpectedRuleSetFailure.into(),
             Rule::AdditionalSigner { .. } => RuleSetError::AdditionalSignerCheckFailed.into(),
             Rule::PubkeyMatch { .. } => RuleSetError::PubkeyMatchCheckFailed.into(),
             Rule::PubkeyListMatch { .. } => RuleSetError::PubkeyListMatchCheckFailed.into(),
-            Rule::PubkeyTreeMatch { .. } => RuleSetError::PubkeyTreeMatchCheckFailed.into(),
+            Rule::PubkeyTreeMatch { .. } | Rule::PubkeyTreeMatchRegistry { .. } => {
+                RuleSetError::PubkeyTreeMatchCheckFailed.into()
+            }
             Rule::PDAMatch { .. } => RuleSetError::PDAMatchCheckFailed.into(),
             Rule::ProgramOwned { .. } => RuleSetError::ProgramOwnedCheckFailed.into(),
             Rule::ProgramOwnedList { .. } => RuleSetError::ProgramOwnedListCheckFailed.into(),
-            Rule::ProgramOwnedTree { .. } => RuleSetError::ProgramOwnedTreeCheckFailed.into(),
-            Rule::Amount { .. } => RuleSetError::AmountCheckFailed.into(),
+            Rule::ProgramOwnedTree { .. } | Rule::ProgramOwnedTreeRegistry { .. } => {
+                RuleSetError::ProgramOwnedTreeCheckFailed.into()
+            }
+            Rule::Amount { .. } | Rule::AmountI64 { .. } | Rule::AmountU128 { .. } => {
+                RuleSetError::AmountCheckFailed.into()
+            }
             Rule::Frequency { .. } => RuleSetError::FrequencyCheckFailed.into(),
             Rule::IsWallet { .. } => RuleSetError::IsWalletCheckFailed.into(),
             Rule::ProgramOwnedSet { .. } => RuleSetError::ProgramOwnedSetCheckFailed.into(),
+            Rule::BytesMatch { .. } => RuleSetError::BytesMatchCheckFailed.into(),
+            Rule::StrMatch { .. } => RuleSetError::StrMatchCheckFailed.into(),
+            Rule::BoolMatch { .. } => RuleSetError::BoolMatchCheckFailed.into(),
+            Rule::PubkeyListContains { .. } => RuleSetError::PubkeyListContainsCheckFailed.into(),
+            Rule::FieldCompare { .. } => RuleSetError::FieldCompareCheckFailed.into(),
+            Rule::AmountRange { .. } => RuleSetError::AmountRangeCheckFailed.into(),
+            Rule::AmountRatio { .. } => RuleSetError::AmountRatioCheckFailed.into(),
+            Rule::PubkeyTreeExclusion { .. } => RuleSetError::PubkeyTreeExclusionCheckFailed.into(),
+            Rule::PubkeyInListAccount { .. } => RuleSetError::PubkeyInListAccountCheckFailed.into(),
+            Rule::OwnerInListAccount { .. } => RuleSetError::OwnerInListAccountCheckFailed.into(),
         }
     }
 }
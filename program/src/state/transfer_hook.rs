@@ -0,0 +1,110 @@
+use solana_program::{
+    msg,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use super::try_from_bytes;
+use crate::{error::RuleSetError, instruction::EXECUTE_DISCRIMINATOR};
+
+/// The size of the fixed section at the beginning of an extra account metas account.
+pub const EXTRA_ACCOUNT_METAS_HEADER_LEN: usize = EXECUTE_DISCRIMINATOR.len() + 4 + 4;
+
+/// The size of each extra account meta defined by the transfer hook interface.
+pub const EXTRA_ACCOUNT_META_LEN: usize = 1 + PUBKEY_BYTES + 1 + 1;
+
+/// A zero-copy view of the extra account metas account of a Token-2022 mint, which lists the
+/// accounts Token-2022 passes to the `Execute` instruction of the transfer hook.
+///
+/// Extra account metas account data layout
+/// ```text
+/// | Execute discriminator | Length  | Count   | Meta 0   | Meta 1   | ... |
+/// |-----------------------|---------|---------|----------|----------|-----|
+/// | 8 bytes               | 4 bytes | 4 bytes | 35 bytes | 35 bytes | ... |
+/// ```
+///
+/// Each meta is a discriminator byte, an address, and the signer and writable flags.  Only
+/// fixed addresses (discriminator 0) are written by this program, all of them read-only, with
+/// the `RuleSet` PDA first.
+pub struct ExtraAccountMetasAccount<'a> {
+    /// The addresses of the extra accounts, in the order Token-2022 passes them.
+    pub pubkeys: Vec<&'a Pubkey>,
+}
+
+impl<'a> ExtraAccountMetasAccount<'a> {
+    /// Deserialize an extra account metas account from its data without copying the addresses.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        if bytes.len() < EXTRA_ACCOUNT_METAS_HEADER_LEN || bytes[..8] != EXECUTE_DISCRIMINATOR {
+            return Err(RuleSetError::DataTypeMismatch);
+        }
+
+        let mut count = [0; 4];
+        count.copy_from_slice(&bytes[12..EXTRA_ACCOUNT_METAS_HEADER_LEN]);
+        let count = u32::from_le_bytes(count) as usize;
+
+        if bytes.len() != Self::account_len(count) {
+            msg!("The extra account metas do not match the account length");
+            return Err(RuleSetError::DataTypeMismatch);
+        }
+
+        let pubkeys = (0..count)
+            .map(|index| {
+                let start = EXTRA_ACCOUNT_METAS_HEADER_LEN + index * EXTRA_ACCOUNT_META_LEN;
+                if bytes[start] != 0 {
+                    msg!("Only fixed extra account metas are supported");
+                    return Err(RuleSetError::DataTypeMismatch);
+                }
+
+                try_from_bytes::<Pubkey>(start + 1, PUBKEY_BYTES, bytes)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { pubkeys })
+    }
+
+    /// Returns the length of an account holding `count` extra account metas.
+    pub fn account_len(count: usize) -> usize {
+        EXTRA_ACCOUNT_METAS_HEADER_LEN + count * EXTRA_ACCOUNT_META_LEN
+    }
+
+    /// Serialize an extra account metas account listing the `Pubkey`s as read-only accounts.
+    pub fn serialize(pubkeys: &[Pubkey]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(Self::account_len(pubkeys.len()));
+        data.extend_from_slice(&EXECUTE_DISCRIMINATOR);
+        data.extend_from_slice(
+            &((4 + pubkeys.len() * EXTRA_ACCOUNT_META_LEN) as u32).to_le_bytes(),
+        );
+        data.extend_from_slice(&(pubkeys.len() as u32).to_le_bytes());
+        pubkeys.iter().for_each(|pubkey| {
+            data.push(0);
+            data.extend_from_slice(pubkey.as_ref());
+            data.extend_from_slice(&[0, 0]);
+        });
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extra_account_metas_account() {
+        let pubkeys = (0..3).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let data = ExtraAccountMetasAccount::serialize(&pubkeys);
+        assert_eq!(data.len(), ExtraAccountMetasAccount::account_len(3));
+        assert_eq!(data[8..12], (4 + 3 * 35u32).to_le_bytes());
+
+        let metas = ExtraAccountMetasAccount::from_bytes(&data).unwrap();
+        assert_eq!(
+            metas.pubkeys.into_iter().cloned().collect::<Vec<_>>(),
+            pubkeys
+        );
+
+        // Truncated account, and a meta derived from seeds.
+        assert!(ExtraAccountMetasAccount::from_bytes(&data[..data.len() - 1]).is_err());
+        let mut seeded = data.clone();
+        seeded[EXTRA_ACCOUNT_METAS_HEADER_LEN] = 1;
+        assert!(ExtraAccountMetasAccount::from_bytes(&seeded).is_err());
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     error::RuleSetError,
     state::{try_from_bytes, RuleResult},
     state::{
-        v2::{Constraint, ConstraintType, RuleV2, HEADER_SECTION, U64_BYTES},
+        v2::{Constraint, ConstraintType, RuleList, HEADER_SECTION, U64_BYTES},
         Header,
     },
 };
@@ -13,8 +13,8 @@ use crate::{
 pub struct All<'a> {
     /// The number of rules contained under All.
     pub size: &'a u64,
-    /// The vector of 'RuleV2' contained under All.
-    pub rules: Vec<RuleV2<'a>>,
+    /// The 'RuleV2's contained under All.
+    pub rules: RuleList<'a>,
 }
 
 impl<'a> All<'a> {
@@ -22,14 +22,7 @@ impl<'a> All<'a> {
     pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
         let size = try_from_bytes::<u64>(0, U64_BYTES, bytes)?;
 
-        let mut rules = Vec::with_capacity(*size as usize);
-        let mut offset = U64_BYTES;
-
-        for _ in 0..*size {
-            let rule = RuleV2::from_bytes(&bytes[offset..])?;
-            offset += rule.length();
-            rules.push(rule);
-        }
+        let rules = RuleList::new(*size, &bytes[U64_BYTES..])?;
 
         Ok(Self { size, rules })
     }
@@ -42,8 +35,7 @@ impl<'a> All<'a> {
                 .iter()
                 .map(|v| v.len())
                 .reduce(|accum, item| accum + item)
-                .ok_or(RuleSetError::DataIsEmpty)
-                .unwrap()) as u32;
+                .ok_or(RuleSetError::DataIsEmpty)?) as u32;
 
         let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
 
@@ -66,6 +58,14 @@ impl<'a> Constraint<'a> for All<'a> {
         ConstraintType::All
     }
 
+    fn references(&self) -> Vec<&'a crate::state::v2::Str32> {
+        self.rules.references()
+    }
+
+    fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        self.rules.assert_well_formed()
+    }
+
     fn validate(
         &self,
         accounts: &std::collections::HashMap<
@@ -76,18 +76,46 @@ impl<'a> Constraint<'a> for All<'a> {
         update_rule_state: bool,
         rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
         rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        self.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            &crate::state::v2::RuleSetV2::empty(),
+        )
+    }
+
+    fn validate_with_definitions(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+        rule_set: &crate::state::v2::RuleSetV2<'a>,
     ) -> RuleResult {
         msg!("Validating All");
 
         let mut last: Option<ProgramError> = None;
 
-        for rule in &self.rules {
-            let result = rule.validate(
+        for rule in self.rules.iter() {
+            let rule = match rule {
+                Ok(rule) => rule,
+                Err(err) => return RuleResult::Error(err.into()),
+            };
+
+            let result = rule.validate_with_definitions(
                 accounts,
                 payload,
                 update_rule_state,
                 rule_set_state_pda,
                 rule_authority,
+                rule_set,
             );
             // Return failure on the first failing rule.
             match result {
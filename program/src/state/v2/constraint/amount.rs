@@ -98,7 +98,7 @@ impl<'a> Constraint<'a> for Amount<'a> {
                 Ok(Operator::Gt) => PartialOrd::gt,
                 Ok(Operator::GtEq) => PartialOrd::ge,
                 // sanity check: the value is checked at creation
-                Err(_) => return RuleResult::Failure(condition_type.to_error()),
+                Err(error) => return RuleResult::Error(error.into()),
             };
 
             if operator_fn(payload_amount, self.amount) {
@@ -0,0 +1,91 @@
+use solana_program::msg;
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION, U64_BYTES},
+        Header,
+    },
+};
+
+/// Constraint representing a test where the amount must be within an inclusive range.
+///
+/// This constraint requires a `PayloadType` value of `PayloadType::Number`. The `field`
+/// value in the rule is used to locate the numerical amount in the payload.
+pub struct AmountRange<'a> {
+    /// The minimum amount (inclusive).
+    pub min: &'a u64,
+    /// The maximum amount (inclusive).
+    pub max: &'a u64,
+    /// The field the amount is stored in.
+    pub field: &'a Str32,
+}
+
+impl<'a> AmountRange<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        // min
+        let min = try_from_bytes::<u64>(0, U64_BYTES, bytes)?;
+        let mut cursor = U64_BYTES;
+
+        // max
+        let max = try_from_bytes::<u64>(cursor, U64_BYTES, bytes)?;
+        cursor += U64_BYTES;
+
+        // field
+        let field = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+
+        Ok(Self { min, max, field })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(field: String, min: u64, max: u64) -> Result<Vec<u8>, RuleSetError> {
+        // length of the assert
+        let length = (U64_BYTES + U64_BYTES + Str32::SIZE) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::AmountRange, length, &mut data);
+
+        // Constraint
+        // - min
+        data.extend(u64::to_le_bytes(min));
+        // - max
+        data.extend(u64::to_le_bytes(max));
+        // - field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..field.len()].copy_from_slice(field.as_bytes());
+        data.extend(field_bytes);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for AmountRange<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::AmountRange
+    }
+
+    fn validate(
+        &self,
+        _accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating AmountRange");
+
+        match payload.get_amount(&self.field.to_string()) {
+            Some(amount) if (*self.min..=*self.max).contains(&amount) => {
+                RuleResult::Success(self.constraint_type().to_error())
+            }
+            Some(_) => RuleResult::Failure(self.constraint_type().to_error()),
+            None => RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use solana_program::msg;
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION, U64_BYTES},
+        Header,
+    },
+};
+
+/// Constraint representing a test where the ratio between two amounts must be at least
+/// `num / denom`.
+///
+/// This constraint requires `PayloadType` values of either `PayloadType::Number` or
+/// `PayloadType::U128`. The ratio is checked as `numerator * denom >= denominator * num`
+/// using 128-bit math, and a multiplication overflow is an error rather than a failure.
+pub struct AmountRatio<'a> {
+    /// The numerator of the minimum ratio.
+    pub num: &'a u64,
+    /// The denominator of the minimum ratio.
+    pub denom: &'a u64,
+    /// The field the numerator amount is stored in.
+    pub numerator_field: &'a Str32,
+    /// The field the denominator amount is stored in.
+    pub denominator_field: &'a Str32,
+}
+
+impl<'a> AmountRatio<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        // num
+        let num = try_from_bytes::<u64>(0, U64_BYTES, bytes)?;
+        let mut cursor = U64_BYTES;
+
+        // denom
+        let denom = try_from_bytes::<u64>(cursor, U64_BYTES, bytes)?;
+        cursor += U64_BYTES;
+
+        // numerator field
+        let numerator_field = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+        cursor += Str32::SIZE;
+
+        // denominator field
+        let denominator_field = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+
+        Ok(Self {
+            num,
+            denom,
+            numerator_field,
+            denominator_field,
+        })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(
+        numerator_field: String,
+        denominator_field: String,
+        num: u64,
+        denom: u64,
+    ) -> Result<Vec<u8>, RuleSetError> {
+        // length of the assert
+        let length = (U64_BYTES + U64_BYTES + Str32::SIZE + Str32::SIZE) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::AmountRatio, length, &mut data);
+
+        // Constraint
+        // - num
+        data.extend(u64::to_le_bytes(num));
+        // - denom
+        data.extend(u64::to_le_bytes(denom));
+        // - numerator field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..numerator_field.len()].copy_from_slice(numerator_field.as_bytes());
+        data.extend(field_bytes);
+        // - denominator field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..denominator_field.len()].copy_from_slice(denominator_field.as_bytes());
+        data.extend(field_bytes);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for AmountRatio<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::AmountRatio
+    }
+
+    fn validate(
+        &self,
+        _accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating AmountRatio");
+
+        let (numerator, denominator) = match (
+            payload.get_amount_u128(&self.numerator_field.to_string()),
+            payload.get_amount_u128(&self.denominator_field.to_string()),
+        ) {
+            (Some(numerator), Some(denominator)) => (numerator, denominator),
+            _ => return RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        };
+
+        let (lhs, rhs) = match (
+            numerator.checked_mul(*self.denom as u128),
+            denominator.checked_mul(*self.num as u128),
+        ) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            _ => return RuleResult::Error(RuleSetError::NumericalOverflow.into()),
+        };
+
+        if lhs >= rhs {
+            RuleResult::Success(self.constraint_type().to_error())
+        } else {
+            RuleResult::Failure(self.constraint_type().to_error())
+        }
+    }
+}
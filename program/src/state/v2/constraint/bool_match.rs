@@ -0,0 +1,79 @@
+use solana_program::msg;
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION, U64_BYTES},
+        Header,
+    },
+};
+
+/// Constraint representing a match against a boolean flag.
+///
+/// This constraint requires a `PayloadType` value of `PayloadType::Bool`. The `field`
+/// value in the rule is used to locate the flag in the payload that must be equal to the
+/// value stored in the rule.
+pub struct BoolMatch<'a> {
+    /// The expected value (`0` or `1`).
+    pub value: &'a u64,
+    /// The field the flag is stored in.
+    pub field: &'a Str32,
+}
+
+impl<'a> BoolMatch<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let value = try_from_bytes::<u64>(0, U64_BYTES, bytes)?;
+        let field = try_from_bytes::<Str32>(U64_BYTES, Str32::SIZE, bytes)?;
+
+        Ok(Self { value, field })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(field: String, value: bool) -> Result<Vec<u8>, RuleSetError> {
+        let length = (U64_BYTES + Str32::SIZE) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::BoolMatch, length, &mut data);
+
+        // Constraint
+        // - value
+        data.extend(u64::to_le_bytes(value as u64));
+        // - field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..field.len()].copy_from_slice(field.as_bytes());
+        data.extend(field_bytes);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for BoolMatch<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::BoolMatch
+    }
+
+    fn validate(
+        &self,
+        _accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating BoolMatch");
+
+        match payload.get_bool(&self.field.to_string()) {
+            Some(flag) if flag as u64 == *self.value => {
+                RuleResult::Success(self.constraint_type().to_error())
+            }
+            Some(_) => RuleResult::Failure(self.constraint_type().to_error()),
+            None => RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        }
+    }
+}
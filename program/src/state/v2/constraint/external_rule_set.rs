@@ -0,0 +1,138 @@
+use solana_program::{
+    msg,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION, U64_BYTES},
+        Header,
+    },
+    utils::validate_external_rule_set,
+};
+
+/// Value of the `revision` field that indicates that the latest revision should be used.
+pub const LATEST_REVISION: u64 = u64::MAX;
+
+/// Constraint representing that the rule of an operation in another `RuleSet` must pass.
+///
+/// The `RuleSet` PDA must be provided to `Validate` via the `additional_rule_accounts`
+/// argument, and the same `Payload` is used to validate the operation. The number of nested
+/// `RuleSet`s is limited by `MAX_RULE_SET_DEPTH`.
+pub struct ExternalRuleSet<'a> {
+    /// The address of the `RuleSet` PDA.
+    pub rule_set: &'a Pubkey,
+    /// The operation to validate in the `RuleSet`.
+    pub operation: &'a Str32,
+    /// The revision of the `RuleSet` to use, or `LATEST_REVISION`.
+    pub revision: &'a u64,
+}
+
+impl<'a> ExternalRuleSet<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let mut cursor = 0;
+
+        let rule_set = try_from_bytes::<Pubkey>(cursor, PUBKEY_BYTES, bytes)?;
+        cursor += PUBKEY_BYTES;
+
+        let operation = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+        cursor += Str32::SIZE;
+
+        let revision = try_from_bytes::<u64>(cursor, U64_BYTES, bytes)?;
+
+        Ok(Self {
+            rule_set,
+            operation,
+            revision,
+        })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(
+        rule_set: Pubkey,
+        operation: String,
+        revision: Option<usize>,
+    ) -> Result<Vec<u8>, RuleSetError> {
+        let length = (PUBKEY_BYTES + Str32::SIZE + U64_BYTES) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::ExternalRuleSet, length, &mut data);
+
+        // Constraint
+        // - rule_set
+        data.extend(rule_set.as_ref());
+        // - operation
+        let mut operation_bytes = [0u8; Str32::SIZE];
+        operation_bytes[..operation.len()].copy_from_slice(operation.as_bytes());
+        data.extend(operation_bytes);
+        // - revision
+        let revision = match revision {
+            Some(revision) => revision as u64,
+            None => LATEST_REVISION,
+        };
+        data.extend(u64::to_le_bytes(revision));
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for ExternalRuleSet<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::ExternalRuleSet
+    }
+
+    fn validate(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        self.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            &crate::state::v2::RuleSetV2::empty(),
+        )
+    }
+
+    fn validate_with_definitions(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+        rule_set: &crate::state::v2::RuleSetV2<'a>,
+    ) -> RuleResult {
+        msg!("Validating ExternalRuleSet");
+
+        let revision = match *self.revision {
+            LATEST_REVISION => None,
+            revision => Some(revision as usize),
+        };
+
+        validate_external_rule_set(
+            self.rule_set,
+            self.operation.to_string(),
+            revision,
+            rule_set.depth(),
+            accounts,
+            payload,
+            rule_authority,
+        )
+    }
+}
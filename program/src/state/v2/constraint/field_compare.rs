@@ -0,0 +1,134 @@
+use solana_program::msg;
+
+use crate::{
+    error::RuleSetError,
+    payload::PayloadType,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, Operator, Str32, HEADER_SECTION, U64_BYTES},
+        Header,
+    },
+};
+
+/// Constraint representing a comparison between two values in the payload.
+///
+/// This constraint requires both fields to be present in the payload with the same
+/// `PayloadType`, either `PayloadType::Number` or `PayloadType::Pubkey`. The value of the
+/// `left_field` is compared to the value of the `right_field` using the comparison operator
+/// stored in the rule. `Pubkey`s can only be compared using `Operator::Eq`.
+pub struct FieldCompare<'a> {
+    /// The operator to be used in the comparison.
+    pub operator: &'a u64,
+    /// The field of the left-hand side value.
+    pub left_field: &'a Str32,
+    /// The field of the right-hand side value.
+    pub right_field: &'a Str32,
+}
+
+impl<'a> FieldCompare<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        // operator
+        let operator = try_from_bytes::<u64>(0, U64_BYTES, bytes)?;
+        let mut cursor = U64_BYTES;
+
+        // left field
+        let left_field = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+        cursor += Str32::SIZE;
+
+        // right field
+        let right_field = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+
+        Ok(Self {
+            operator,
+            left_field,
+            right_field,
+        })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(
+        left_field: String,
+        operator: Operator,
+        right_field: String,
+    ) -> Result<Vec<u8>, RuleSetError> {
+        // length of the assert
+        let length = (U64_BYTES + Str32::SIZE + Str32::SIZE) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::FieldCompare, length, &mut data);
+
+        // Constraint
+        // - operator
+        data.extend(u64::to_le_bytes(operator as u64));
+        // - left field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..left_field.len()].copy_from_slice(left_field.as_bytes());
+        data.extend(field_bytes);
+        // - right field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..right_field.len()].copy_from_slice(right_field.as_bytes());
+        data.extend(field_bytes);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for FieldCompare<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::FieldCompare
+    }
+
+    fn validate(
+        &self,
+        _accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating FieldCompare");
+        let condition_type = self.constraint_type();
+
+        let (left, right) = match (
+            payload.get(&self.left_field.to_string()),
+            payload.get(&self.right_field.to_string()),
+        ) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        };
+
+        let operator = match Operator::try_from(*self.operator) {
+            Ok(operator) => operator,
+            // sanity check: the value is checked at creation
+            Err(error) => return RuleResult::Error(error.into()),
+        };
+
+        let result = match (left, right) {
+            (PayloadType::Number(left), PayloadType::Number(right)) => match operator {
+                Operator::Lt => left < right,
+                Operator::LtEq => left <= right,
+                Operator::Eq => left == right,
+                Operator::Gt => left > right,
+                Operator::GtEq => left >= right,
+            },
+            (PayloadType::Pubkey(left), PayloadType::Pubkey(right)) => {
+                if operator != Operator::Eq {
+                    return RuleResult::Error(RuleSetError::InvalidCompareOp.into());
+                }
+                left == right
+            }
+            _ => return RuleResult::Error(RuleSetError::FieldTypeMismatch.into()),
+        };
+
+        if result {
+            RuleResult::Success(condition_type.to_error())
+        } else {
+            RuleResult::Failure(condition_type.to_error())
+        }
+    }
+}
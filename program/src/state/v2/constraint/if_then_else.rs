@@ -0,0 +1,149 @@
+use solana_program::msg;
+
+use crate::{
+    error::RuleSetError,
+    state::v2::{Constraint, ConstraintType, RuleList, RuleV2, HEADER_SECTION},
+    state::{Header, RuleResult},
+};
+
+/// Constraint representing a conditional, where the `then` rule must pass if the `condition`
+/// rule passes, and the `otherwise` rule must pass if the `condition` rule fails.
+///
+/// The condition is evaluated only once.  If the condition returns an error, that error is
+/// returned without evaluating either branch.
+pub struct IfThenElse<'a> {
+    /// The condition, `then` and `otherwise` Rules, in that order.
+    pub rules: RuleList<'a>,
+}
+
+impl<'a> IfThenElse<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let rules = RuleList::new(3, bytes)?;
+        Ok(Self { rules })
+    }
+
+    /// Deserializes the Rule used as the condition.
+    pub fn condition(&self) -> Result<RuleV2<'a>, RuleSetError> {
+        self.rules.get(0)
+    }
+
+    /// Deserializes the Rule evaluated when the condition passes.
+    pub fn then(&self) -> Result<RuleV2<'a>, RuleSetError> {
+        self.rules.get(1)
+    }
+
+    /// Deserializes the Rule evaluated when the condition fails.
+    pub fn otherwise(&self) -> Result<RuleV2<'a>, RuleSetError> {
+        self.rules.get(2)
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(
+        condition: &[u8],
+        then: &[u8],
+        otherwise: &[u8],
+    ) -> Result<Vec<u8>, RuleSetError> {
+        let length = (condition.len() + then.len() + otherwise.len()) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::IfThenElse, length, &mut data);
+
+        // Constraint
+        // - condition
+        data.extend(condition);
+        // - then
+        data.extend(then);
+        // - otherwise
+        data.extend(otherwise);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for IfThenElse<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::IfThenElse
+    }
+
+    fn references(&self) -> Vec<&'a crate::state::v2::Str32> {
+        self.rules.references()
+    }
+
+    fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        self.rules.assert_well_formed()
+    }
+
+    fn validate(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        self.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            &crate::state::v2::RuleSetV2::empty(),
+        )
+    }
+
+    fn validate_with_definitions(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+        rule_set: &crate::state::v2::RuleSetV2<'a>,
+    ) -> RuleResult {
+        msg!("Validating IfThenElse");
+
+        let condition = match self.condition() {
+            Ok(condition) => condition,
+            Err(err) => return RuleResult::Error(err.into()),
+        };
+
+        let result = condition.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            rule_set,
+        );
+
+        // Select the branch based on the condition, an error is not a failure so
+        // neither branch is evaluated.
+        let branch = match result {
+            RuleResult::Success(_) => self.then(),
+            RuleResult::Failure(_) => self.otherwise(),
+            RuleResult::Error(err) => return RuleResult::Error(err),
+        };
+
+        let branch = match branch {
+            Ok(branch) => branch,
+            Err(err) => return RuleResult::Error(err.into()),
+        };
+
+        branch.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            rule_set,
+        )
+    }
+}
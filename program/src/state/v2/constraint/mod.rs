@@ -6,33 +6,69 @@
 mod additional_signer;
 mod all;
 mod amount;
+mod amount_i64;
+mod amount_range;
+mod amount_ratio;
+mod amount_u128;
 mod any;
+mod bool_match;
+mod bytes_match;
+mod external_rule_set;
+mod field_compare;
 mod frequency;
+mod if_then_else;
 mod is_wallet;
 mod namespace;
 mod not;
+mod owner_in_list_account;
 mod pass;
 mod pda_match;
 mod program_owned;
 mod program_owned_list;
 mod program_owned_tree;
+mod program_owned_tree_registry;
+mod pubkey_in_list_account;
+mod pubkey_list_contains;
 mod pubkey_list_match;
 mod pubkey_match;
+mod pubkey_tree_exclusion;
 mod pubkey_tree_match;
+mod pubkey_tree_match_registry;
+mod rule_ref;
+mod str_match;
+mod threshold;
 
 pub use additional_signer::*;
 pub use all::*;
 pub use amount::*;
+pub use amount_i64::*;
+pub use amount_range::*;
+pub use amount_ratio::*;
+pub use amount_u128::*;
 pub use any::*;
+pub use bool_match::*;
+pub use bytes_match::*;
+pub use external_rule_set::*;
+pub use field_compare::*;
 pub use frequency::*;
+pub use if_then_else::*;
 pub use is_wallet::*;
 pub use namespace::*;
 pub use not::*;
+pub use owner_in_list_account::*;
 pub use pass::*;
 pub use pda_match::*;
 pub use program_owned::*;
 pub use program_owned_list::*;
 pub use program_owned_tree::*;
+pub use program_owned_tree_registry::*;
+pub use pubkey_in_list_account::*;
+pub use pubkey_list_contains::*;
 pub use pubkey_list_match::*;
 pub use pubkey_match::*;
+pub use pubkey_tree_exclusion::*;
 pub use pubkey_tree_match::*;
+pub use pubkey_tree_match_registry::*;
+pub use rule_ref::*;
+pub use str_match::*;
+pub use threshold::*;
@@ -2,20 +2,20 @@ use solana_program::msg;
 
 use crate::{
     error::RuleSetError,
-    state::v2::{Constraint, ConstraintType, RuleV2, HEADER_SECTION},
+    state::v2::{Constraint, ConstraintType, RuleList, HEADER_SECTION},
     state::{Header, RuleResult},
 };
 
 /// Constraint representing a negation, where the contained rule must fail.
 pub struct Not<'a> {
     /// The Rule contained under Not.
-    pub rule: RuleV2<'a>,
+    pub rule: RuleList<'a>,
 }
 
 impl<'a> Not<'a> {
     /// Deserialize a constraint from a byte array.
     pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
-        let rule = RuleV2::from_bytes(bytes)?;
+        let rule = RuleList::new(1, bytes)?;
         Ok(Self { rule })
     }
 
@@ -39,6 +39,14 @@ impl<'a> Constraint<'a> for Not<'a> {
         ConstraintType::Not
     }
 
+    fn references(&self) -> Vec<&'a crate::state::v2::Str32> {
+        self.rule.references()
+    }
+
+    fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        self.rule.assert_well_formed()
+    }
+
     fn validate(
         &self,
         accounts: &std::collections::HashMap<
@@ -49,15 +57,43 @@ impl<'a> Constraint<'a> for Not<'a> {
         update_rule_state: bool,
         rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
         rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        self.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            &crate::state::v2::RuleSetV2::empty(),
+        )
+    }
+
+    fn validate_with_definitions(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+        rule_set: &crate::state::v2::RuleSetV2<'a>,
     ) -> RuleResult {
         msg!("Validating Not");
 
-        let result = self.rule.validate(
+        let rule = match self.rule.get(0) {
+            Ok(rule) => rule,
+            Err(err) => return RuleResult::Error(err.into()),
+        };
+
+        let result = rule.validate_with_definitions(
             accounts,
             payload,
             update_rule_state,
             rule_set_state_pda,
             rule_authority,
+            rule_set,
         );
 
         // Negate the result.
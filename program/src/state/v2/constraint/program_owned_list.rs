@@ -8,7 +8,7 @@ use crate::{
     error::RuleSetError,
     state::RuleResult,
     state::{
-        try_cast_slice, try_from_bytes,
+        assert_sorted, try_cast_slice, try_from_bytes,
         v2::{Constraint, ConstraintType, Str32, HEADER_SECTION},
         Header,
     },
@@ -27,6 +27,8 @@ pub struct ProgramOwnedList<'a> {
     pub field: &'a Str32,
     /// The program that must own the `Pubkey`.
     pub programs: &'a [Pubkey],
+    /// Whether the list is sorted, which allows it to be binary searched.
+    pub sorted: bool,
 }
 
 impl<'a> ProgramOwnedList<'a> {
@@ -35,16 +37,49 @@ impl<'a> ProgramOwnedList<'a> {
         let field = try_from_bytes::<Str32>(0, Str32::SIZE, bytes)?;
         let programs = try_cast_slice(&bytes[Str32::SIZE..])?;
 
-        Ok(Self { field, programs })
+        Ok(Self {
+            field,
+            programs,
+            sorted: false,
+        })
+    }
+
+    /// Deserialize a constraint with a sorted list from a byte array.  The list must be in
+    /// ascending order without duplicates to be binary searched, which is checked on every read
+    /// since the bytes of a `RuleSet` are not necessarily written by `serialize_sorted`.
+    pub fn from_sorted_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let mut constraint = Self::from_bytes(bytes)?;
+        assert_sorted(constraint.programs)?;
+        constraint.sorted = true;
+
+        Ok(constraint)
     }
 
     /// Serialize a constraint into a byte array.
     pub fn serialize(field: String, programs: &[Pubkey]) -> Result<Vec<u8>, RuleSetError> {
+        Self::serialize_with_type(ConstraintType::ProgramOwnedList, field, programs)
+    }
+
+    /// Serialize a constraint into a byte array, sorting and deduplicating the list so that it
+    /// can be binary searched during validation.
+    pub fn serialize_sorted(field: String, programs: &[Pubkey]) -> Result<Vec<u8>, RuleSetError> {
+        let mut programs = programs.to_vec();
+        programs.sort();
+        programs.dedup();
+
+        Self::serialize_with_type(ConstraintType::ProgramOwnedListSorted, field, &programs)
+    }
+
+    fn serialize_with_type(
+        constraint_type: ConstraintType,
+        field: String,
+        programs: &[Pubkey],
+    ) -> Result<Vec<u8>, RuleSetError> {
         let length = (Str32::SIZE + (programs.len() * PUBKEY_BYTES)) as u32;
         let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
 
         // Header
-        Header::serialize(ConstraintType::ProgramOwnedList, length, &mut data);
+        Header::serialize(constraint_type, length, &mut data);
 
         // Constraint
         // - field
@@ -62,7 +97,11 @@ impl<'a> ProgramOwnedList<'a> {
 
 impl<'a> Constraint<'a> for ProgramOwnedList<'a> {
     fn constraint_type(&self) -> ConstraintType {
-        ConstraintType::ProgramOwnedList
+        if self.sorted {
+            ConstraintType::ProgramOwnedListSorted
+        } else {
+            ConstraintType::ProgramOwnedList
+        }
     }
 
     fn validate(
@@ -108,6 +147,16 @@ impl<'a> Constraint<'a> for ProgramOwnedList<'a> {
 }
 
 impl<'a> ProgramOwnedList<'a> {
+    /// Returns whether the list contains the `Pubkey`, using a binary search when the list is
+    /// sorted.
+    fn contains(&self, pubkey: &Pubkey) -> bool {
+        if self.sorted {
+            self.programs.binary_search(pubkey).is_ok()
+        } else {
+            self.programs.contains(pubkey)
+        }
+    }
+
     fn validate_field(
         &self,
         accounts: &std::collections::HashMap<
@@ -141,7 +190,7 @@ impl<'a> ProgramOwnedList<'a> {
             });
 
             return RuleResult::Error(RuleSetError::DataIsEmpty.into());
-        } else if self.programs.contains(account.owner) {
+        } else if self.contains(account.owner) {
             // Account owner must be in the set.
             return RuleResult::Success(self.constraint_type().to_error());
         }
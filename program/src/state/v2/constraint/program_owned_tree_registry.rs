@@ -0,0 +1,152 @@
+use solana_program::{
+    msg,
+    program_error::ProgramError,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use crate::{
+    error::RuleSetError,
+    state::RuleResult,
+    state::{
+        try_from_bytes,
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION},
+        Header,
+    },
+    utils::{compute_merkle_root, get_registry_root, is_zeroed},
+};
+
+/// Constraint representing a test where the `Pubkey` must be owned by a member of the Merkle
+/// tree whose root is stored in a `RootRegistryAccount`.
+///
+/// This constraint requires `PayloadType` values of `PayloadType::Pubkey` and
+/// `PayloadType::MerkleProof`. The `field` values in the Rule are used to locate them in the
+/// `Payload`. Both the registry account and the `Pubkey` account must be provided to `Validate`
+/// via the `additional_rule_accounts` argument. The owner of the `Pubkey` account and the proof
+/// are then used to calculate a Merkle root, which is compared against the root stored in the
+/// registry.
+pub struct ProgramOwnedTreeRegistry<'a> {
+    /// The field in the `Payload` to be compared when looking for the `Pubkey`.
+    pub pubkey_field: &'a Str32,
+    /// The field in the `Payload` to be compared when looking for the Merkle proof.
+    pub proof_field: &'a Str32,
+    /// The registry account holding the root of the Merkle tree.
+    pub registry: &'a Pubkey,
+}
+
+impl<'a> ProgramOwnedTreeRegistry<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let pubkey_field = try_from_bytes::<Str32>(0, Str32::SIZE, bytes)?;
+        let mut cursor = Str32::SIZE;
+
+        let proof_field = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+        cursor += Str32::SIZE;
+
+        let registry = try_from_bytes::<Pubkey>(cursor, PUBKEY_BYTES, bytes)?;
+
+        Ok(Self {
+            pubkey_field,
+            proof_field,
+            registry,
+        })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(
+        pubkey_field: String,
+        proof_field: String,
+        registry: &Pubkey,
+    ) -> Result<Vec<u8>, RuleSetError> {
+        let length = (Str32::SIZE + Str32::SIZE + PUBKEY_BYTES) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::ProgramOwnedTreeRegistry, length, &mut data);
+
+        // Constraint
+        // - pubkey_field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..pubkey_field.len()].copy_from_slice(pubkey_field.as_bytes());
+        data.extend(field_bytes);
+        // - proof_field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..proof_field.len()].copy_from_slice(proof_field.as_bytes());
+        data.extend(field_bytes);
+        // - registry
+        data.extend_from_slice(registry.as_ref());
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for ProgramOwnedTreeRegistry<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::ProgramOwnedTreeRegistry
+    }
+
+    fn validate(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating ProgramOwnedTreeRegistry");
+
+        // Get the current root from the registry account.
+        let root = match get_registry_root(self.registry, accounts) {
+            Ok(root) => root,
+            Err(error) => return RuleResult::Error(error),
+        };
+
+        // Get the `Pubkey` we are checking from the payload.
+        let key = match payload.get_pubkey(&self.pubkey_field.to_string()) {
+            Some(pubkey) => pubkey,
+            _ => return RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        };
+
+        // Get the `AccountInfo` struct for the `Pubkey`.
+        let account = match accounts.get(key) {
+            Some(account) => account,
+            _ => return RuleResult::Error(RuleSetError::MissingAccount.into()),
+        };
+
+        let data = match account.data.try_borrow() {
+            Ok(data) => data,
+            Err(_) => return RuleResult::Error(ProgramError::AccountBorrowFailed),
+        };
+
+        // Account must have nonzero data to count as program-owned.
+        if is_zeroed(&data) {
+            // Print helpful errors.
+            if data.len() == 0 {
+                msg!("Account data is empty");
+            } else {
+                msg!("Account data is zeroed");
+            }
+
+            return RuleResult::Error(RuleSetError::DataIsEmpty.into());
+        }
+
+        // The account owner is the leaf.
+        let leaf = account.owner;
+
+        // Get the Merkle proof from the payload.
+        let merkle_proof = match payload.get_merkle_proof(&self.proof_field.to_string()) {
+            Some(merkle_proof) => merkle_proof,
+            _ => return RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        };
+
+        // Check if the computed hash (root) is equal to the root in the registry.
+        let computed_root = compute_merkle_root(leaf, merkle_proof);
+        if computed_root == root {
+            RuleResult::Success(self.constraint_type().to_error())
+        } else {
+            RuleResult::Failure(self.constraint_type().to_error())
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use solana_program::{
+    msg,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION},
+        Header,
+    },
+    utils::pubkey_list_contains,
+};
+
+/// Constraint representing a test where a `Pubkey` must be in the list stored in a
+/// `PubkeyListAccount`.
+///
+/// This constraint requires a `PayloadType` value of `PayloadType::Pubkey`. The `field` value
+/// in the rule is used to locate the `Pubkey` in the payload. The list account must be provided
+/// to `Validate` via the `additional_rule_accounts` argument, and is binary searched in place.
+pub struct PubkeyInListAccount<'a> {
+    /// The account holding the list of FIELD/// Constraint representing a test where a `Pubkey` must be in the list stored in a
+    /// `PubkeyListAccount`.
+    ///
+    /// This constraint requires a `PayloadType` value of `PayloadType::Pubkey`. The `field` value
+    /// in the rule is used to locate the `Pubkey` in the payload. The list account must be provided
+    /// to `Validate` via the `additional_rule_accounts` argument, and is binary searched in place..
+    pub list: &'a Pubkey,
+    /// The field in the `Payload` to be compared.
+    pub field: &'a Str32,
+}
+
+impl<'a> PubkeyInListAccount<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let list = try_from_bytes::<Pubkey>(0, PUBKEY_BYTES, bytes)?;
+        let field = try_from_bytes::<Str32>(PUBKEY_BYTES, Str32::SIZE, bytes)?;
+
+        Ok(Self { list, field })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(field: String, list: Pubkey) -> Result<Vec<u8>, RuleSetError> {
+        let length = (PUBKEY_BYTES + Str32::SIZE) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::PubkeyInListAccount, length, &mut data);
+
+        // Constraint
+        // - list
+        data.extend(list.as_ref());
+        // - field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..field.len()].copy_from_slice(field.as_bytes());
+        data.extend(field_bytes);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for PubkeyInListAccount<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::PubkeyInListAccount
+    }
+
+    fn validate(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating PubkeyInListAccount");
+
+        let key = match payload.get_pubkey(&self.field.to_string()) {
+            Some(pubkey) => pubkey,
+            _ => return RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        };
+
+        match pubkey_list_contains(self.list, key, accounts) {
+            Ok(true) => RuleResult::Success(self.constraint_type().to_error()),
+            Ok(false) => RuleResult::Failure(self.constraint_type().to_error()),
+            Err(error) => RuleResult::Error(error),
+        }
+    }
+}
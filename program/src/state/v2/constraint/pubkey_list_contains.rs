@@ -0,0 +1,82 @@
+use solana_program::{
+    msg,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION},
+        Header,
+    },
+};
+
+/// Constraint representing a test where the `Pubkey` in the rule must be contained in a list
+/// of `Pubkey`s.
+///
+/// This constraint requires a `PayloadType` value of `PayloadType::PubkeyList`. The `field`
+/// value in the rule is used to locate the list in the payload.
+pub struct PubkeyListContains<'a> {
+    /// The public key that must be in the list.
+    pub pubkey: &'a Pubkey,
+    /// The field the list is stored in.
+    pub field: &'a Str32,
+}
+
+impl<'a> PubkeyListContains<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let pubkey = try_from_bytes::<Pubkey>(0, PUBKEY_BYTES, bytes)?;
+        let field = try_from_bytes::<Str32>(PUBKEY_BYTES, Str32::SIZE, bytes)?;
+
+        Ok(Self { pubkey, field })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(field: String, pubkey: Pubkey) -> Result<Vec<u8>, RuleSetError> {
+        let length = (PUBKEY_BYTES + Str32::SIZE) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::PubkeyListContains, length, &mut data);
+
+        // Constraint
+        // - pubkey
+        data.extend(pubkey.as_ref());
+        // - field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..field.len()].copy_from_slice(field.as_bytes());
+        data.extend(field_bytes);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for PubkeyListContains<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::PubkeyListContains
+    }
+
+    fn validate(
+        &self,
+        _accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating PubkeyListContains");
+
+        match payload.get_pubkey_list(&self.field.to_string()) {
+            Some(pubkeys) if pubkeys.contains(self.pubkey) => {
+                RuleResult::Success(self.constraint_type().to_error())
+            }
+            Some(_) => RuleResult::Failure(self.constraint_type().to_error()),
+            None => RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        }
+    }
+}
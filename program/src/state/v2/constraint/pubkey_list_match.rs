@@ -6,7 +6,7 @@ use solana_program::{
 use crate::{
     error::RuleSetError,
     state::{
-        try_cast_slice,
+        assert_sorted, try_cast_slice,
         v2::{Constraint, ConstraintType, Str32, HEADER_SECTION},
         Header,
     },
@@ -23,6 +23,8 @@ pub struct PubkeyListMatch<'a> {
     pub field: &'a Str32,
     /// The list of public keys to be compared against.
     pub pubkeys: &'a [Pubkey],
+    /// Whether the list is sorted, which allows it to be binary searched.
+    pub sorted: bool,
 }
 
 impl<'a> PubkeyListMatch<'a> {
@@ -31,16 +33,49 @@ impl<'a> PubkeyListMatch<'a> {
         let field = try_from_bytes::<Str32>(0, Str32::SIZE, bytes)?;
         let pubkeys = try_cast_slice(&bytes[Str32::SIZE..])?;
 
-        Ok(Self { field, pubkeys })
+        Ok(Self {
+            field,
+            pubkeys,
+            sorted: false,
+        })
+    }
+
+    /// Deserialize a constraint with a sorted list from a byte array.  The list must be in
+    /// ascending order without duplicates to be binary searched, which is checked on every read
+    /// since the bytes of a `RuleSet` are not necessarily written by `serialize_sorted`.
+    pub fn from_sorted_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let mut constraint = Self::from_bytes(bytes)?;
+        assert_sorted(constraint.pubkeys)?;
+        constraint.sorted = true;
+
+        Ok(constraint)
     }
 
     /// Serialize a constraint into a byte array.
     pub fn serialize(field: String, pubkeys: &[Pubkey]) -> Result<Vec<u8>, RuleSetError> {
+        Self::serialize_with_type(ConstraintType::PubkeyListMatch, field, pubkeys)
+    }
+
+    /// Serialize a constraint into a byte array, sorting and deduplicating the list so that it
+    /// can be binary searched during validation.
+    pub fn serialize_sorted(field: String, pubkeys: &[Pubkey]) -> Result<Vec<u8>, RuleSetError> {
+        let mut pubkeys = pubkeys.to_vec();
+        pubkeys.sort();
+        pubkeys.dedup();
+
+        Self::serialize_with_type(ConstraintType::PubkeyListMatchSorted, field, &pubkeys)
+    }
+
+    fn serialize_with_type(
+        constraint_type: ConstraintType,
+        field: String,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<u8>, RuleSetError> {
         let length = (Str32::SIZE + (pubkeys.len() * PUBKEY_BYTES)) as u32;
         let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
 
         // Header
-        Header::serialize(ConstraintType::PubkeyListMatch, length, &mut data);
+        Header::serialize(constraint_type, length, &mut data);
 
         // Constraint
         // - field
@@ -58,7 +93,11 @@ impl<'a> PubkeyListMatch<'a> {
 
 impl<'a> Constraint<'a> for PubkeyListMatch<'a> {
     fn constraint_type(&self) -> ConstraintType {
-        ConstraintType::PubkeyListMatch
+        if self.sorted {
+            ConstraintType::PubkeyListMatchSorted
+        } else {
+            ConstraintType::PubkeyListMatch
+        }
     }
 
     fn validate(
@@ -104,13 +143,23 @@ impl<'a> Constraint<'a> for PubkeyListMatch<'a> {
 }
 
 impl<'a> PubkeyListMatch<'a> {
+    /// Returns whether the list contains the `Pubkey`, using a binary search when the list is
+    /// sorted.
+    fn contains(&self, pubkey: &Pubkey) -> bool {
+        if self.sorted {
+            self.pubkeys.binary_search(pubkey).is_ok()
+        } else {
+            self.pubkeys.contains(pubkey)
+        }
+    }
+
     fn validate_field(&self, payload: &crate::payload::Payload, field: String) -> RuleResult {
         let key = match payload.get_pubkey(&field) {
             Some(pubkey) => pubkey,
             _ => return RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
         };
 
-        if self.pubkeys.contains(key) {
+        if self.contains(key) {
             // Account owner must be in the set.
             return RuleResult::Success(self.constraint_type().to_error());
         }
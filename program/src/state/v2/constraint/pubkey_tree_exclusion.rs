@@ -0,0 +1,112 @@
+use solana_program::{msg, pubkey::PUBKEY_BYTES};
+
+use crate::{
+    error::RuleSetError,
+    state::RuleResult,
+    state::{
+        try_from_bytes,
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION},
+        Header,
+    },
+    utils::is_excluded,
+};
+
+/// Constraint representing a test where a `Pubkey` must not be a member of the exclusion tree
+/// in the rule.
+///
+/// This constraint requires `PayloadType` values of `PayloadType::Pubkey` and
+/// `PayloadType::ExclusionProof`. The `field` values in the Rule are used to locate them in the
+/// `Payload`. The proof must contain two adjacent keys of the tree that bracket the `Pubkey`,
+/// and lead to the root stored in the rule.
+pub struct PubkeyTreeExclusion<'a> {
+    /// The field in the `Payload` to be compared when looking for the `Pubkey`.
+    pub pubkey_field: &'a Str32,
+    /// The field in the `Payload` to be compared when looking for the exclusion proof.
+    pub proof_field: &'a Str32,
+    /// The root of the exclusion tree.
+    pub root: &'a [u8; PUBKEY_BYTES],
+}
+
+impl<'a> PubkeyTreeExclusion<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let pubkey_field = try_from_bytes::<Str32>(0, Str32::SIZE, bytes)?;
+        let mut cursor = Str32::SIZE;
+
+        let proof_field = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+        cursor += Str32::SIZE;
+
+        let root = try_from_bytes::<[u8; 32]>(cursor, PUBKEY_BYTES, bytes)?;
+
+        Ok(Self {
+            pubkey_field,
+            proof_field,
+            root,
+        })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(
+        pubkey_field: String,
+        proof_field: String,
+        root: &[u8; PUBKEY_BYTES],
+    ) -> Result<Vec<u8>, RuleSetError> {
+        let length = (Str32::SIZE + Str32::SIZE + PUBKEY_BYTES) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::PubkeyTreeExclusion, length, &mut data);
+
+        // Constraint
+        // - pubkey_field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..pubkey_field.len()].copy_from_slice(pubkey_field.as_bytes());
+        data.extend(field_bytes);
+        // - proof_field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..proof_field.len()].copy_from_slice(proof_field.as_bytes());
+        data.extend(field_bytes);
+        // - root
+        data.extend_from_slice(root);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for PubkeyTreeExclusion<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::PubkeyTreeExclusion
+    }
+
+    fn validate(
+        &self,
+        _accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating PubkeyTreeExclusion");
+
+        // Get the `Pubkey` we are checking from the payload.
+        let key = match payload.get_pubkey(&self.pubkey_field.to_string()) {
+            Some(pubkey) => pubkey,
+            _ => return RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        };
+
+        // Get the exclusion proof from the payload.
+        let exclusion_proof = match payload.get_exclusion_proof(&self.proof_field.to_string()) {
+            Some(exclusion_proof) => exclusion_proof,
+            _ => return RuleResult::Error(RuleSetError::MissingPayloadValue.into()),
+        };
+
+        if is_excluded(key, exclusion_proof, self.root) {
+            RuleResult::Success(self.constraint_type().to_error())
+        } else {
+            RuleResult::Failure(self.constraint_type().to_error())
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use solana_program::msg;
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, Str32, HEADER_SECTION},
+        Header,
+    },
+};
+
+/// Constraint representing a reference to a named rule definition stored in the `RuleSet`,
+/// which allows the same rule to be shared by several operations.
+///
+/// The definition is resolved against the `RuleSet` that contains the constraint at validation
+/// time.
+pub struct RuleRef<'a> {
+    /// The name of the rule definition.
+    pub name: &'a Str32,
+}
+
+impl<'a> RuleRef<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let name = try_from_bytes::<Str32>(0, Str32::SIZE, bytes)?;
+        Ok(Self { name })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(name: String) -> Result<Vec<u8>, RuleSetError> {
+        let length = Str32::SIZE as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::RuleRef, length, &mut data);
+
+        // Constraint
+        // - name
+        let mut name_bytes = [0u8; Str32::SIZE];
+        name_bytes[..name.len()].copy_from_slice(name.as_bytes());
+        data.extend(name_bytes);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for RuleRef<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::RuleRef
+    }
+
+    fn references(&self) -> Vec<&'a Str32> {
+        vec![self.name]
+    }
+
+    fn validate(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        self.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            &crate::state::v2::RuleSetV2::empty(),
+        )
+    }
+
+    fn validate_with_definitions(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+        rule_set: &crate::state::v2::RuleSetV2<'a>,
+    ) -> RuleResult {
+        msg!("Validating RuleRef");
+
+        match rule_set.get_definition(self.name) {
+            Ok(Some(rule)) => rule.validate_with_definitions(
+                accounts,
+                payload,
+                update_rule_state,
+                rule_set_state_pda,
+                rule_authority,
+                rule_set,
+            ),
+            Ok(None) => RuleResult::Error(RuleSetError::RuleDefinitionNotFound.into()),
+            Err(error) => RuleResult::Error(error.into()),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use solana_program::msg;
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, MatchOperator, Str32, HEADER_SECTION, U64_BYTES},
+        Header,
+    },
+};
+
+/// Constraint representing a match against a string.
+///
+/// This constraint requires a `PayloadType` value of `PayloadType::Str`. The `field`
+/// value in the rule is used to locate the string in the payload to match against the value
+/// stored in the rule, using the match operator stored in the rule.
+pub struct StrMatch<'a> {
+    /// The operator to be used in the match.
+    pub operator: &'a u64,
+    /// The field the string is stored in.
+    pub field: &'a Str32,
+    /// The string to be matched against.
+    pub value: &'a [u8],
+}
+
+impl<'a> StrMatch<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        // operator
+        let operator = try_from_bytes::<u64>(0, U64_BYTES, bytes)?;
+        let mut cursor = U64_BYTES;
+
+        // field
+        let field = try_from_bytes::<Str32>(cursor, Str32::SIZE, bytes)?;
+        cursor += Str32::SIZE;
+
+        // value length
+        let length = try_from_bytes::<u64>(cursor, U64_BYTES, bytes)?;
+        cursor += U64_BYTES;
+
+        // value
+        let value = usize::try_from(*length)
+            .ok()
+            .and_then(|length| bytes.get(cursor..cursor.checked_add(length)?))
+            .ok_or(RuleSetError::RuleSetReadFailed)?;
+
+        Ok(Self {
+            operator,
+            field,
+            value,
+        })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(
+        field: String,
+        operator: MatchOperator,
+        value: String,
+    ) -> Result<Vec<u8>, RuleSetError> {
+        let value = value.into_bytes();
+        // the value is padded to keep the following rules aligned
+        let padding = (U64_BYTES - value.len() % U64_BYTES) % U64_BYTES;
+
+        // length of the assert
+        let length = (U64_BYTES + Str32::SIZE + U64_BYTES + value.len() + padding) as u32;
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::StrMatch, length, &mut data);
+
+        // Constraint
+        // - operator
+        data.extend(u64::to_le_bytes(operator as u64));
+        // - field
+        let mut field_bytes = [0u8; Str32::SIZE];
+        field_bytes[..field.len()].copy_from_slice(field.as_bytes());
+        data.extend(field_bytes);
+        // - value
+        data.extend(u64::to_le_bytes(value.len() as u64));
+        data.extend(value);
+        data.extend(vec![0u8; padding]);
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for StrMatch<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::StrMatch
+    }
+
+    fn validate(
+        &self,
+        _accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        _update_rule_state: bool,
+        _rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        _rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        msg!("Validating StrMatch");
+        let condition_type = self.constraint_type();
+
+        if let Some(payload_value) = payload.get_str(&self.field.to_string()) {
+            let operator = match MatchOperator::try_from(*self.operator) {
+                Ok(operator) => operator,
+                // sanity check: the value is checked at creation
+                Err(error) => return RuleResult::Error(error.into()),
+            };
+
+            if operator.apply(payload_value.as_bytes(), self.value) {
+                RuleResult::Success(condition_type.to_error())
+            } else {
+                RuleResult::Failure(condition_type.to_error())
+            }
+        } else {
+            RuleResult::Error(RuleSetError::MissingPayloadValue.into())
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use solana_program::{msg, program_error::ProgramError};
+
+use crate::{
+    error::RuleSetError,
+    state::{try_from_bytes, RuleResult},
+    state::{
+        v2::{Constraint, ConstraintType, RuleList, HEADER_SECTION, U64_BYTES},
+        Header,
+    },
+};
+
+/// Constraint representing a group threshold (k-of-n), where at least `k` of the rules
+/// contained must pass.
+///
+/// Rules are evaluated in order and the evaluation stops as soon as `k` rules have passed. When
+/// fewer than `k` rules pass, the last failure is returned if there was at least one failure,
+/// otherwise the last error is returned.
+pub struct Threshold<'a> {
+    /// The minimum number of rules that must pass.
+    pub k: &'a u64,
+    /// The number of rules contained under Threshold.
+    pub size: &'a u64,
+    /// The 'RuleV2's contained under Threshold.
+    pub rules: RuleList<'a>,
+}
+
+impl<'a> Threshold<'a> {
+    /// Deserialize a constraint from a byte array.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        let k = try_from_bytes::<u64>(0, U64_BYTES, bytes)?;
+        let size = try_from_bytes::<u64>(U64_BYTES, U64_BYTES, bytes)?;
+
+        let rules = RuleList::new(*size, &bytes[U64_BYTES + U64_BYTES..])?;
+
+        Ok(Self { k, size, rules })
+    }
+
+    /// Serialize a constraint into a byte array.
+    pub fn serialize(k: u64, rules: &[&[u8]]) -> Result<Vec<u8>, RuleSetError> {
+        // length of the assert
+        let length = (U64_BYTES
+            + U64_BYTES
+            + rules
+                .iter()
+                .map(|v| v.len())
+                .reduce(|accum, item| accum + item)
+                .ok_or(RuleSetError::DataIsEmpty)?) as u32;
+
+        let mut data = Vec::with_capacity(HEADER_SECTION + length as usize);
+
+        // Header
+        Header::serialize(ConstraintType::Threshold, length, &mut data);
+
+        // Constraint
+        // - k
+        data.extend(u64::to_le_bytes(k));
+        // - size
+        data.extend(u64::to_le_bytes(rules.len() as u64));
+        // - rules
+        rules.iter().for_each(|x| data.extend(x.iter()));
+
+        Ok(data)
+    }
+}
+
+impl<'a> Constraint<'a> for Threshold<'a> {
+    fn constraint_type(&self) -> ConstraintType {
+        ConstraintType::Threshold
+    }
+
+    fn references(&self) -> Vec<&'a crate::state::v2::Str32> {
+        self.rules.references()
+    }
+
+    fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        // A threshold larger than the number of rules can never be met.
+        if *self.k > *self.size {
+            msg!(
+                "Threshold {} is larger than the number of rules {}",
+                self.k,
+                self.size
+            );
+            return Err(RuleSetError::InvalidThreshold);
+        }
+
+        self.rules.assert_well_formed()
+    }
+
+    fn validate(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+    ) -> RuleResult {
+        self.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            &crate::state::v2::RuleSetV2::empty(),
+        )
+    }
+
+    fn validate_with_definitions(
+        &self,
+        accounts: &std::collections::HashMap<
+            solana_program::pubkey::Pubkey,
+            &solana_program::account_info::AccountInfo,
+        >,
+        payload: &crate::payload::Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
+        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+        rule_set: &crate::state::v2::RuleSetV2<'a>,
+    ) -> RuleResult {
+        msg!("Validating Threshold");
+
+        // A threshold of zero is always met.
+        if *self.k == 0 {
+            return RuleResult::Success(self.constraint_type().to_error());
+        }
+
+        let mut passed: u64 = 0;
+        let mut last_failure: Option<ProgramError> = None;
+        let mut last_error: Option<ProgramError> = None;
+
+        for rule in self.rules.iter() {
+            let rule = match rule {
+                Ok(rule) => rule,
+                Err(err) => return RuleResult::Error(err.into()),
+            };
+
+            let result = rule.validate_with_definitions(
+                accounts,
+                payload,
+                update_rule_state,
+                rule_set_state_pda,
+                rule_authority,
+                rule_set,
+            );
+
+            match result {
+                RuleResult::Success(err) => {
+                    // Return pass as soon as `k` rules passed.
+                    passed += 1;
+                    if passed >= *self.k {
+                        return RuleResult::Success(err);
+                    }
+                }
+                RuleResult::Failure(err) => last_failure = Some(err),
+                RuleResult::Error(err) => last_error = Some(err),
+            }
+        }
+
+        // Return the last failure if and only if fewer than `k` rules passed and there was
+        // at least one failure, otherwise return the last error.
+        if let Some(err) = last_failure {
+            RuleResult::Failure(err)
+        } else if let Some(err) = last_error {
+            RuleResult::Error(err)
+        } else {
+            RuleResult::Error(RuleSetError::UnexpectedRuleSetFailure.into())
+        }
+    }
+}
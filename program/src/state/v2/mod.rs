@@ -10,7 +10,12 @@ use bytemuck::{AnyBitPattern, NoUninit, Pod, Zeroable};
 use solana_program::{account_info::AccountInfo, msg, program_error::ProgramError, pubkey::Pubkey};
 use std::{collections::HashMap, fmt::Display};
 
-use crate::{error::RuleSetError, payload::Payload, state::RuleResult, types::MAX_NAME_LENGTH};
+use crate::{
+    error::RuleSetError,
+    payload::Payload,
+    state::{CompareOp, MatchOp, RuleResult},
+    types::MAX_NAME_LENGTH,
+};
 
 /// Size (in bytes) of a u64 value.
 pub const U64_BYTES: usize = std::mem::size_of::<u64>();
@@ -21,17 +26,20 @@ pub(crate) fn try_from_bytes<T: AnyBitPattern>(
     length: usize,
     bytes: &[u8],
 ) -> Result<&T, RuleSetError> {
-    if start + length > bytes.len() {
-        msg!(
-            "Invalid range: start + length > bytes.len() ({} + {} > {})",
-            start,
-            length,
-            bytes.len()
-        );
-        return Err(RuleSetError::RuleSetReadFailed);
-    }
+    let end = match start.checked_add(length) {
+        Some(end) if end <= bytes.len() => end,
+        _ => {
+            msg!(
+                "Invalid range: start + length > bytes.len() ({} + {} > {})",
+                start,
+                length,
+                bytes.len()
+            );
+            return Err(RuleSetError::RuleSetReadFailed);
+        }
+    };
 
-    bytemuck::try_from_bytes::<T>(&bytes[start..start + length]).map_err(|error| {
+    bytemuck::try_from_bytes::<T>(&bytes[start..end]).map_err(|error| {
         msg!("{}", error);
         RuleSetError::RuleSetReadFailed
     })
@@ -48,6 +56,17 @@ pub(crate) fn try_cast_slice<A: NoUninit, B: AnyBitPattern>(
     })
 }
 
+/// Checks that a list of `Pubkey`s is in ascending order without duplicates, so that it can be
+/// binary searched.
+pub(crate) fn assert_sorted(pubkeys: &[Pubkey]) -> Result<(), RuleSetError> {
+    if pubkeys.windows(2).all(|pair| pair[0] < pair[1]) {
+        Ok(())
+    } else {
+        msg!("Pubkey list is not sorted or contains duplicates");
+        Err(RuleSetError::UnsortedPubkeyList)
+    }
+}
+
 /// Struct representing a 32 byte string.
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
@@ -86,8 +105,41 @@ pub trait Constraint<'a> {
         rule_authority: &Option<&AccountInfo>,
     ) -> RuleResult;
 
+    /// Validates the constraint condition, resolving any `RuleRef` nested in the constraint
+    /// against the definitions of the `RuleSet` that contains it.  Constraints without nested
+    /// rules do not use the definitions and default to `validate`.
+    fn validate_with_definitions(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+        _rule_set: &RuleSetV2<'a>,
+    ) -> RuleResult {
+        self.validate(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+        )
+    }
+
     /// Returns the type of the constraint.
     fn constraint_type(&self) -> ConstraintType;
+
+    /// Returns the names of the rule definitions referenced by the constraint.  References made
+    /// by the definitions themselves are not followed.
+    fn references(&self) -> Vec<&'a Str32> {
+        Vec::new()
+    }
+
+    /// Checks that the rules nested under the constraint can be deserialized.  Nested rules are
+    /// only parsed when they are evaluated, so this is used to reject malformed data up front.
+    fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        Ok(())
+    }
 }
 
 #[repr(u32)]
@@ -130,6 +182,48 @@ pub enum ConstraintType {
     PubkeyMatch,
     /// The comparing `Pubkey` must be a member of the Merkle tree in the rule.
     PubkeyTreeMatch,
+    /// Group threshold (k-of-n), where at least `k` of the rules contained must pass.
+    Threshold,
+    /// Conditional, where the rule to evaluate is selected by the result of a condition rule.
+    IfThenElse,
+    /// A reference to a named rule definition stored in the `RuleSet`.
+    RuleRef,
+    /// The rule of an operation in another `RuleSet` must pass.
+    ExternalRuleSet,
+    /// Comparison against bytes.
+    BytesMatch,
+    /// Comparison against a string.
+    StrMatch,
+    /// Comparison against a boolean flag.
+    BoolMatch,
+    /// Comparison against a signed amount.
+    AmountI64,
+    /// Comparison against a 128-bit amount.
+    AmountU128,
+    /// The `Pubkey` in the rule must be contained in a list of `Pubkey`s.
+    PubkeyListContains,
+    /// Comparison between two values in the payload.
+    FieldCompare,
+    /// The amount must be within an inclusive range.
+    AmountRange,
+    /// The ratio between two amounts must be at least a given ratio.
+    AmountRatio,
+    /// The comparing `Pubkey` must not be a member of the exclusion tree in the rule.
+    PubkeyTreeExclusion,
+    /// The comparing `Pubkey` must be in the Merkle tree whose root is stored in a registry account.
+    PubkeyTreeMatchRegistry,
+    /// The comparing `Pubkey` must be owned by a member of the Merkle tree whose root is stored
+    /// in a registry account.
+    ProgramOwnedTreeRegistry,
+    /// The comparing `Pubkey` must be in the list stored in a Pubkey List account.
+    PubkeyInListAccount,
+    /// The comparing `Pubkey` must be owned by a program in the list stored in a Pubkey List
+    /// account.
+    OwnerInListAccount,
+    /// The comparing `Pubkey` must be in the sorted list of `Pubkey`s.
+    PubkeyListMatchSorted,
+    /// The `Pubkey` must be owned by a program in the sorted list of `Pubkey`s.
+    ProgramOwnedListSorted,
 }
 
 impl ConstraintType {
@@ -144,23 +238,49 @@ impl ConstraintType {
             | ConstraintType::Any
             | ConstraintType::Namespace
             | ConstraintType::Not
-            | ConstraintType::Pass => RuleSetError::UnexpectedRuleSetFailure.into(),
-            ConstraintType::Amount => RuleSetError::AmountCheckFailed.into(),
+            | ConstraintType::Pass
+            | ConstraintType::Threshold
+            | ConstraintType::IfThenElse
+            | ConstraintType::RuleRef
+            | ConstraintType::ExternalRuleSet => RuleSetError::UnexpectedRuleSetFailure.into(),
+            ConstraintType::Amount | ConstraintType::AmountI64 | ConstraintType::AmountU128 => {
+                RuleSetError::AmountCheckFailed.into()
+            }
             ConstraintType::Frequency { .. } => RuleSetError::FrequencyCheckFailed.into(),
             ConstraintType::IsWallet { .. } => RuleSetError::IsWalletCheckFailed.into(),
             ConstraintType::PDAMatch { .. } => RuleSetError::PDAMatchCheckFailed.into(),
             ConstraintType::ProgramOwned { .. } => RuleSetError::ProgramOwnedCheckFailed.into(),
-            ConstraintType::ProgramOwnedList => RuleSetError::ProgramOwnedListCheckFailed.into(),
-            ConstraintType::ProgramOwnedTree { .. } => {
+            ConstraintType::ProgramOwnedList | ConstraintType::ProgramOwnedListSorted => {
+                RuleSetError::ProgramOwnedListCheckFailed.into()
+            }
+            ConstraintType::ProgramOwnedTree { .. } | ConstraintType::ProgramOwnedTreeRegistry => {
                 RuleSetError::ProgramOwnedTreeCheckFailed.into()
             }
-            ConstraintType::PubkeyListMatch { .. } => {
+            ConstraintType::PubkeyListMatch { .. } | ConstraintType::PubkeyListMatchSorted => {
                 RuleSetError::PubkeyListMatchCheckFailed.into()
             }
             ConstraintType::PubkeyMatch { .. } => RuleSetError::PubkeyMatchCheckFailed.into(),
-            ConstraintType::PubkeyTreeMatch { .. } => {
+            ConstraintType::PubkeyTreeMatch { .. } | ConstraintType::PubkeyTreeMatchRegistry => {
                 RuleSetError::PubkeyTreeMatchCheckFailed.into()
             }
+            ConstraintType::BytesMatch => RuleSetError::BytesMatchCheckFailed.into(),
+            ConstraintType::StrMatch => RuleSetError::StrMatchCheckFailed.into(),
+            ConstraintType::BoolMatch => RuleSetError::BoolMatchCheckFailed.into(),
+            ConstraintType::PubkeyListContains => {
+                RuleSetError::PubkeyListContainsCheckFailed.into()
+            }
+            ConstraintType::FieldCompare => RuleSetError::FieldCompareCheckFailed.into(),
+            ConstraintType::AmountRange => RuleSetError::AmountRangeCheckFailed.into(),
+            ConstraintType::AmountRatio => RuleSetError::AmountRatioCheckFailed.into(),
+            ConstraintType::PubkeyTreeExclusion => {
+                RuleSetError::PubkeyTreeExclusionCheckFailed.into()
+            }
+            ConstraintType::PubkeyInListAccount => {
+                RuleSetError::PubkeyInListAccountCheckFailed.into()
+            }
+            ConstraintType::OwnerInListAccount => {
+                RuleSetError::OwnerInListAccountCheckFailed.into()
+            }
         }
     }
 }
@@ -188,6 +308,26 @@ impl TryFrom<u32> for ConstraintType {
             14 => Ok(ConstraintType::PubkeyListMatch),
             15 => Ok(ConstraintType::PubkeyMatch),
             16 => Ok(ConstraintType::PubkeyTreeMatch),
+            17 => Ok(ConstraintType::Threshold),
+            18 => Ok(ConstraintType::IfThenElse),
+            19 => Ok(ConstraintType::RuleRef),
+            20 => Ok(ConstraintType::ExternalRuleSet),
+            21 => Ok(ConstraintType::BytesMatch),
+            22 => Ok(ConstraintType::StrMatch),
+            23 => Ok(ConstraintType::BoolMatch),
+            24 => Ok(ConstraintType::AmountI64),
+            25 => Ok(ConstraintType::AmountU128),
+            26 => Ok(ConstraintType::PubkeyListContains),
+            27 => Ok(ConstraintType::FieldCompare),
+            28 => Ok(ConstraintType::AmountRange),
+            29 => Ok(ConstraintType::AmountRatio),
+            30 => Ok(ConstraintType::PubkeyTreeExclusion),
+            31 => Ok(ConstraintType::PubkeyTreeMatchRegistry),
+            32 => Ok(ConstraintType::ProgramOwnedTreeRegistry),
+            33 => Ok(ConstraintType::PubkeyInListAccount),
+            34 => Ok(ConstraintType::OwnerInListAccount),
+            35 => Ok(ConstraintType::PubkeyListMatchSorted),
+            36 => Ok(ConstraintType::ProgramOwnedListSorted),
             _ => Err(RuleSetError::InvalidConstraintType),
         }
     }
@@ -224,3 +364,78 @@ impl TryFrom<u64> for Operator {
         }
     }
 }
+
+impl From<&CompareOp> for Operator {
+    fn from(operator: &CompareOp) -> Self {
+        match operator {
+            CompareOp::Lt => Operator::Lt,
+            CompareOp::LtEq => Operator::LtEq,
+            CompareOp::Eq => Operator::Eq,
+            CompareOp::GtEq => Operator::GtEq,
+            CompareOp::Gt => Operator::Gt,
+        }
+    }
+}
+
+impl From<&Operator> for CompareOp {
+    fn from(operator: &Operator) -> Self {
+        match operator {
+            Operator::Lt => CompareOp::Lt,
+            Operator::LtEq => CompareOp::LtEq,
+            Operator::Eq => CompareOp::Eq,
+            Operator::GtEq => CompareOp::GtEq,
+            Operator::Gt => CompareOp::Gt,
+        }
+    }
+}
+
+#[repr(u64)]
+#[derive(PartialEq, Eq, Debug, Clone)]
+/// Operators that can be used to match against a `BytesMatch` or `StrMatch` rule.
+pub enum MatchOperator {
+    /// Equal To
+    Eq,
+    /// Starts With
+    Prefix,
+}
+
+impl MatchOperator {
+    /// Returns whether `value` matches `expected` using the operator.
+    pub fn apply(&self, value: &[u8], expected: &[u8]) -> bool {
+        match self {
+            MatchOperator::Eq => value == expected,
+            MatchOperator::Prefix => value.starts_with(expected),
+        }
+    }
+}
+
+impl TryFrom<u64> for MatchOperator {
+    // Type of the error generated.
+    type Error = RuleSetError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MatchOperator::Eq),
+            1 => Ok(MatchOperator::Prefix),
+            _ => Err(RuleSetError::InvalidMatchOp),
+        }
+    }
+}
+
+impl From<&MatchOp> for MatchOperator {
+    fn from(operator: &MatchOp) -> Self {
+        match operator {
+            MatchOp::Eq => MatchOperator::Eq,
+            MatchOp::Prefix => MatchOperator::Prefix,
+        }
+    }
+}
+
+impl From<&MatchOperator> for MatchOp {
+    fn from(operator: &MatchOperator) -> Self {
+        match operator {
+            MatchOperator::Eq => MatchOp::Eq,
+            MatchOperator::Prefix => MatchOp::Prefix,
+        }
+    }
+}
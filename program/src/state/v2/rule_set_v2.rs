@@ -5,20 +5,46 @@ use solana_program::{
     pubkey::{Pubkey, PUBKEY_BYTES},
 };
 
-use super::{try_cast_slice, try_from_bytes, Constraint, ConstraintType, RuleV2, Str32, U64_BYTES};
+use std::{cell::OnceCell, collections::HashMap};
+
+use super::{
+    try_cast_slice, try_from_bytes, Constraint, ConstraintType, Header, RuleV2, ScopedRuleV2,
+    Str32, HEADER_SECTION, U64_BYTES,
+};
 use crate::{
     error::RuleSetError,
-    types::{Assertable, LibVersion, RuleSet},
+    types::{Assertable, LibVersion, RuleSet, DEFAULT_OPERATION},
+    utils::assert_valid_references,
 };
 
 // Length of a empty array.
 const EMPTY: usize = 0;
 
+/// Size (in bytes) of a u32 value.
+const U32_BYTES: usize = std::mem::size_of::<u32>();
+
+/// Flag stored in the second byte of the header when the operations and definition names are
+/// sorted and followed by an index of rule offsets.
+pub const RULE_SET_INDEXED: u8 = 1;
+
 /// The struct containing all Rule Set data, most importantly the map of operations to `Rules`.
 ///  See top-level module for description of PDA memory layout.
+///
+/// Indexed RuleSetV2 data layout
+/// ```text
+/// | Header  | Owner    | Name     | Operations | Definition names | Rule offsets            | Rules          | Definitions    |
+/// |---------|----------|----------|------------|------------------|-------------------------|----------------|----------------|
+/// | 8 bytes | 32 bytes | 32 bytes | 32 * n     | 32 * m           | 4 * (n + m), 8 aligned  | variable bytes | variable bytes |
+/// ```
+///
+/// Operations and definition names are sorted, so they can be binary searched, and the rule
+/// offsets locate each rule relative to the start of the rules section.  RuleSets serialized
+/// before the index was introduced have no offsets and are searched linearly.  In both cases a
+/// rule is only parsed the first time it is accessed.
 pub struct RuleSetV2<'a> {
-    /// Header information. The first byte holds the lib_version of the rule set
-    /// and the last 4 bytes (u32) represent the number of rules.
+    /// Header information. The first byte holds the lib_version of the rule set, the second
+    /// byte holds the layout flags, the third and fourth bytes (u16) hold the number of rule
+    /// definitions and the last 4 bytes (u32) represent the number of rules.
     header: &'a [u32; 2],
 
     /// Owner (creator) of the RuleSet.
@@ -30,8 +56,20 @@ pub struct RuleSetV2<'a> {
     /// Operations available.
     pub operations: &'a [Str32],
 
-    /// Rules for each operation.
-    pub rules: Vec<RuleV2<'a>>,
+    /// Names of the rule definitions.
+    pub definition_names: &'a [Str32],
+
+    /// Serialized rules followed by the serialized definitions.
+    rules_data: &'a [u8],
+
+    /// Offset in `rules_data` of each rule followed by each definition.
+    offsets: Vec<usize>,
+
+    /// Rules followed by definitions, parsed on first access.
+    rules: Vec<OnceCell<RuleV2<'a>>>,
+
+    /// Number of `ExternalRuleSet` rules followed to load the RuleSet.  This is not stored.
+    depth: u8,
 }
 
 impl<'a> RuleSetV2<'a> {
@@ -40,7 +78,49 @@ impl<'a> RuleSetV2<'a> {
         self.header[1]
     }
 
-    /// Deserialize a `RuleSetV2` from a byte array.
+    /// Returns the number of rule definitions in the rule set.
+    pub fn definitions_size(&self) -> u16 {
+        (self.header[0] >> 16) as u16
+    }
+
+    /// Returns whether the operations and definition names are sorted and indexed.
+    pub fn is_indexed(&self) -> bool {
+        ((self.header[0] >> 8) as u8 & RULE_SET_INDEXED) != 0
+    }
+
+    /// Returns the number of `ExternalRuleSet` rules followed to load the `RuleSet`.
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Set the number of `ExternalRuleSet` rules followed to load the `RuleSet`.
+    pub(crate) fn set_depth(&mut self, depth: u8) {
+        self.depth = depth;
+    }
+
+    /// Returns a `RuleSetV2` without rules or definitions, used to validate a rule outside of
+    /// the `RuleSet` that contains it.
+    pub(crate) fn empty() -> Self {
+        static OWNER: Pubkey = Pubkey::new_from_array([0; PUBKEY_BYTES]);
+        static NAME: Str32 = Str32 {
+            value: [0; Str32::SIZE],
+        };
+
+        Self {
+            header: &[LibVersion::V2 as u32, 0],
+            owner: &OWNER,
+            rule_set_name: &NAME,
+            operations: &[],
+            definition_names: &[],
+            rules_data: &[],
+            offsets: Vec::new(),
+            rules: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Deserialize a `RuleSetV2` from a byte array.  Only the fixed sections are read, rules
+    /// are parsed when they are first accessed.
     pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
         // header
         let header = try_from_bytes::<[u32; 2]>(0, U64_BYTES, bytes)?;
@@ -58,35 +138,73 @@ impl<'a> RuleSetV2<'a> {
         let size = header[1] as usize;
 
         // operations
-        let slice_end = cursor
-            + Str32::SIZE
-                .checked_mul(size)
-                .ok_or(RuleSetError::NumericalOverflow)?;
+        let operations = Self::slice_at::<Str32>(bytes, cursor, size)?;
+        cursor += std::mem::size_of_val(operations);
+
+        // number of rule definitions
+        let definitions_size = (header[0] >> 16) as usize;
+
+        // definition names
+        let definition_names = Self::slice_at::<Str32>(bytes, cursor, definitions_size)?;
+        cursor += std::mem::size_of_val(definition_names);
+
+        let count = size
+            .checked_add(definitions_size)
+            .ok_or(RuleSetError::NumericalOverflow)?;
+        let indexed = ((header[0] >> 8) as u8 & RULE_SET_INDEXED) != 0;
+
+        let (offsets, rules_data) = if indexed {
+            // sanity check: names must be sorted to be binary searched
+            if !Self::is_sorted(operations) || !Self::is_sorted(definition_names) {
+                msg!("Operations or definition names are not sorted");
+                return Err(RuleSetError::RuleSetReadFailed);
+            }
 
-        // sanity check: make sure we got the correct slice size
-        if size > 0 && (slice_end + 1) > bytes.len() {
-            msg!("Invalid slice end: {} > {}", slice_end, bytes.len());
-            return Err(RuleSetError::RuleSetReadFailed);
-        }
+            // rule offsets
+            let index = Self::slice_at::<u32>(bytes, cursor, count)?;
+            cursor += Self::index_length(count);
 
-        let operations = try_cast_slice(&bytes[cursor..slice_end])?;
-        cursor = slice_end;
+            let rules_data = bytes.get(cursor..).ok_or(RuleSetError::RuleSetReadFailed)?;
+            let offsets = index
+                .iter()
+                .map(|offset| *offset as usize)
+                .collect::<Vec<usize>>();
+
+            (offsets, rules_data)
+        } else {
+            // without an index, the offsets are found by walking the rule headers
+            let rules_data = bytes.get(cursor..).ok_or(RuleSetError::RuleSetReadFailed)?;
+
+            // sanity check: every rule takes at least the header section
+            if count.saturating_mul(HEADER_SECTION) > rules_data.len() {
+                msg!("Invalid number of rules: {}", count);
+                return Err(RuleSetError::RuleSetReadFailed);
+            }
 
-        // rules
-        let mut rules = Vec::with_capacity(size);
+            let mut offsets = Vec::with_capacity(count);
+            let mut offset = 0;
 
-        for _ in 0..size {
-            let rule = RuleV2::from_bytes(&bytes[cursor..]).unwrap();
-            cursor += rule.length();
-            rules.push(rule);
-        }
+            for _ in 0..count {
+                let header = try_from_bytes::<Header>(offset, HEADER_SECTION, rules_data)?;
+                offsets.push(offset);
+                offset = offset
+                    .checked_add(HEADER_SECTION + header.length())
+                    .ok_or(RuleSetError::NumericalOverflow)?;
+            }
+
+            (offsets, rules_data)
+        };
 
         Ok(Self {
             header,
             owner,
             rule_set_name,
             operations,
-            rules,
+            definition_names,
+            rules_data,
+            offsets,
+            rules: (0..count).map(|_| OnceCell::new()).collect(),
+            depth: 0,
         })
     }
 
@@ -97,22 +215,45 @@ impl<'a> RuleSetV2<'a> {
         operations: &[String],
         rules: &[&[u8]],
     ) -> Result<Vec<u8>, RuleSetError> {
+        Self::serialize_with_definitions(owner, name, operations, rules, &[], &[])
+    }
+
+    /// Serialize a `RuleSetV2` with named rule definitions into a byte array.  Operations and
+    /// definitions are sorted by name and indexed.
+    pub fn serialize_with_definitions(
+        owner: Pubkey,
+        name: &str,
+        operations: &[String],
+        rules: &[&[u8]],
+        definition_names: &[String],
+        definitions: &[&[u8]],
+    ) -> Result<Vec<u8>, RuleSetError> {
+        let count = operations.len() + definition_names.len();
+
         // length of the rule set
         let length = U64_BYTES
             + PUBKEY_BYTES
             + Str32::SIZE
             + (operations.len() * Str32::SIZE)
+            + (definition_names.len() * Str32::SIZE)
+            + Self::index_length(count)
             + rules
                 .iter()
+                .chain(definitions.iter())
                 .map(|v| v.len())
                 .reduce(|accum, item| accum + item)
                 .unwrap_or(EMPTY);
 
+        // sanity check: the number of definitions must fit in the header
+        let definitions_size =
+            u16::try_from(definition_names.len()).map_err(|_| RuleSetError::NumericalOverflow)?;
+
         let mut data = Vec::with_capacity(length);
 
         // header section
-        // - lib version
-        data.extend([LibVersion::V2 as u8, 0, 0, 0]);
+        // - lib version, flags and number of definitions
+        data.extend([LibVersion::V2 as u8, RULE_SET_INDEXED]);
+        data.extend(u16::to_le_bytes(definitions_size));
         // - size
         data.extend(u32::to_le_bytes(operations.len() as u32));
 
@@ -120,9 +261,7 @@ impl<'a> RuleSetV2<'a> {
         data.extend(owner.as_ref());
 
         // name
-        let mut field_bytes = [0u8; Str32::SIZE];
-        field_bytes[..name.len()].copy_from_slice(name.as_bytes());
-        data.extend(field_bytes);
+        data.extend(Self::to_str32(name));
 
         // operations
 
@@ -131,30 +270,276 @@ impl<'a> RuleSetV2<'a> {
             return Err(RuleSetError::DuplicatedOperationName);
         }
 
-        operations.iter().for_each(|x| {
-            let mut field_bytes = [0u8; Str32::SIZE];
-            field_bytes[..x.len()].copy_from_slice(x.as_bytes());
-            data.extend(field_bytes);
-        });
+        // sanity check: every operation must have a rule
+        if operations.len() != rules.len() {
+            return Err(RuleSetError::OperationNotFound);
+        }
+
+        let mut operations = operations
+            .iter()
+            .map(|x| Self::to_str32(x))
+            .zip(rules.iter())
+            .collect::<Vec<_>>();
+        operations.sort_by_key(|(name, _)| *name);
+
+        operations.iter().for_each(|(x, _)| data.extend(x));
+
+        // definition names
+
+        // sanity check: checks whether we have duplicated definition names
+        if (1..definition_names.len())
+            .any(|i| definition_names[i..].contains(&definition_names[i - 1]))
+        {
+            return Err(RuleSetError::DuplicatedDefinitionName);
+        }
+
+        // sanity check: every definition must have a rule
+        if definition_names.len() != definitions.len() {
+            return Err(RuleSetError::RuleDefinitionNotFound);
+        }
+
+        let mut definitions = definition_names
+            .iter()
+            .map(|x| Self::to_str32(x))
+            .zip(definitions.iter())
+            .collect::<Vec<_>>();
+        definitions.sort_by_key(|(name, _)| *name);
+
+        definitions.iter().for_each(|(x, _)| data.extend(x));
+
+        // rule offsets
+        let mut offset = 0;
+
+        for (_, rule) in operations.iter().chain(definitions.iter()) {
+            let value = u32::try_from(offset).map_err(|_| RuleSetError::NumericalOverflow)?;
+            data.extend(u32::to_le_bytes(value));
+            offset += rule.len();
+        }
+
+        // - padding to keep the rules aligned
+        data.resize(
+            data.len() + Self::index_length(count) - (count * U32_BYTES),
+            0,
+        );
 
-        // rules
-        rules.iter().for_each(|x| data.extend(x.iter()));
+        // rules and definitions
+        operations
+            .iter()
+            .chain(definitions.iter())
+            .for_each(|(_, x)| data.extend(x.iter()));
 
         Ok(data)
     }
 
+    /// Returns the rule for the operation at `index`, parsing it on first access.
+    pub fn rule(&self, index: usize) -> Result<&RuleV2<'a>, RuleSetError> {
+        if index >= self.operations.len() {
+            return Err(RuleSetError::RuleSetReadFailed);
+        }
+
+        self.load(index)
+    }
+
+    /// Returns the rule for the definition at `index`, parsing it on first access.
+    pub fn definition(&self, index: usize) -> Result<&RuleV2<'a>, RuleSetError> {
+        if index >= self.definition_names.len() {
+            return Err(RuleSetError::RuleSetReadFailed);
+        }
+
+        self.load(self.operations.len() + index)
+    }
+
     /// Retrieve the `Rule` tree for a given `Operation`.
-    pub fn get(&self, operation: String) -> Option<&RuleV2<'a>> {
-        let mut bytes = [0u8; Str32::SIZE];
-        bytes[..operation.len()].copy_from_slice(operation.as_bytes());
+    pub fn get(&self, operation: String) -> Result<Option<&RuleV2<'a>>, RuleSetError> {
+        // Operation names longer than the maximum length can't be stored.
+        if operation.len() > Str32::SIZE {
+            return Ok(None);
+        }
+
+        match self.find(self.operations, &Self::to_str32(&operation)) {
+            Some(index) => self.rule(index).map(Some),
+            None => Ok(None),
+        }
+    }
 
-        for (i, operation) in self.operations.iter().enumerate() {
-            if sol_memcmp(&operation.value, &bytes, bytes.len()) == 0 {
-                return Some(&self.rules[i]);
+    /// Retrieve the `Rule` tree for a given `Operation`, resolving any `RuleRef` to its
+    /// definition.
+    fn get_resolved(&self, operation: &str) -> Result<Option<&RuleV2<'a>>, ProgramError> {
+        let mut rule = self.get(operation.to_string())?;
+
+        // Resolve the rule definition when the operation rule is a reference.
+        while let Some(current) = rule {
+            if !matches!(current.constraint_type(), ConstraintType::RuleRef) {
+                break;
             }
+
+            let definition = match current.references().first() {
+                Some(name) => self.get_definition(name)?,
+                None => None,
+            };
+
+            rule = Some(definition.ok_or(RuleSetError::RuleDefinitionNotFound)?);
+        }
+
+        Ok(rule)
+    }
+
+    /// Retrieve the `Rule` tree for a given definition name.
+    pub fn get_definition(&self, name: &Str32) -> Result<Option<&RuleV2<'a>>, RuleSetError> {
+        match self.find(self.definition_names, &name.value) {
+            Some(index) => self.definition(index).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Check that every `RuleRef` in the `RuleSet` resolves to a definition and that the
+    /// definitions do not reference each other in a cycle.  This parses every rule.
+    pub fn assert_valid_references(&self) -> Result<(), RuleSetError> {
+        let to_string = |rule: &RuleV2<'a>| {
+            rule.references()
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let mut roots = Vec::new();
+
+        for index in 0..self.operations.len() {
+            roots.extend(to_string(self.rule(index)?));
+        }
+
+        let mut definitions = HashMap::with_capacity(self.definition_names.len());
+
+        for (index, name) in self.definition_names.iter().enumerate() {
+            definitions.insert(name.to_string(), to_string(self.definition(index)?));
+        }
+
+        // sanity check: definition names are unique
+        if definitions.len() != self.definition_names.len() {
+            return Err(RuleSetError::DuplicatedDefinitionName);
+        }
+
+        assert_valid_references(&roots, &definitions)
+    }
+
+    /// Check that every rule and definition, including the rules nested under them, can be
+    /// deserialized.  This parses every rule.
+    pub fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        for index in 0..self.rules.len() {
+            self.load(index)?.assert_well_formed()?;
+        }
+
+        Ok(())
+    }
+
+    /// Parse the rule at `index` of the rules followed by definitions, unless it was already
+    /// parsed.
+    fn load(&self, index: usize) -> Result<&RuleV2<'a>, RuleSetError> {
+        let cell = self
+            .rules
+            .get(index)
+            .ok_or(RuleSetError::RuleSetReadFailed)?;
+
+        if let Some(rule) = cell.get() {
+            return Ok(rule);
+        }
+
+        let bytes = self
+            .offsets
+            .get(index)
+            .and_then(|offset| self.rules_data.get(*offset..))
+            .ok_or(RuleSetError::RuleSetReadFailed)?;
+
+        let _ = cell.set(RuleV2::from_bytes(bytes)?);
+        cell.get().ok_or(RuleSetError::RuleSetReadFailed)
+    }
+
+    /// Find the position of `name` in `names`, using a binary search when the `RuleSet` is
+    /// indexed.
+    fn find(&self, names: &[Str32], name: &[u8; Str32::SIZE]) -> Option<usize> {
+        if self.is_indexed() {
+            names.binary_search_by(|x| x.value.cmp(name)).ok()
+        } else {
+            names
+                .iter()
+                .position(|x| sol_memcmp(&x.value, name, Str32::SIZE) == 0)
+        }
+    }
+
+    /// Re-interprets `count` elements starting at `start` as a slice of `T`.
+    fn slice_at<T: bytemuck::AnyBitPattern>(
+        bytes: &'a [u8],
+        start: usize,
+        count: usize,
+    ) -> Result<&'a [T], RuleSetError> {
+        let end = std::mem::size_of::<T>()
+            .checked_mul(count)
+            .and_then(|length| start.checked_add(length))
+            .ok_or(RuleSetError::NumericalOverflow)?;
+
+        // sanity check: make sure we got the correct slice size
+        if end > bytes.len() {
+            msg!("Invalid slice end: {} > {}", end, bytes.len());
+            return Err(RuleSetError::RuleSetReadFailed);
         }
 
-        None
+        try_cast_slice(&bytes[start..end])
+    }
+
+    /// Length (in bytes) of the rule offsets index, padded to keep the rules 8-byte aligned.
+    fn index_length(count: usize) -> usize {
+        (count * U32_BYTES).next_multiple_of(U64_BYTES)
+    }
+
+    /// Returns whether the names are in strictly ascending order.
+    fn is_sorted(names: &[Str32]) -> bool {
+        names.windows(2).all(|pair| pair[0].value < pair[1].value)
+    }
+
+    /// Pads a name with zeros to `Str32::SIZE` bytes.
+    fn to_str32(value: &str) -> [u8; Str32::SIZE] {
+        let mut bytes = [0u8; Str32::SIZE];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        bytes
+    }
+
+    /// Returns the rule for an operation by searching through fallbacks.
+    fn find_rule(&self, operation: &str) -> Result<&RuleV2<'a>, ProgramError> {
+        let is_namespace =
+            |rule: &RuleV2| matches!(rule.constraint_type(), ConstraintType::Namespace);
+
+        let rule = match self.get_resolved(operation)? {
+            Some(rule) if is_namespace(rule) => {
+                // Check for ':' namespace separators. If they exist try the longest operation
+                // namespace that has a rule. E.g. 'transfer:owner:escrow' will check for a
+                // fallback for 'transfer:owner' and then for 'transfer'.
+                let mut fallback = None;
+
+                for (index, _) in operation.rmatch_indices(':') {
+                    match self.get_resolved(&operation[..index])? {
+                        Some(rule) if !is_namespace(rule) => {
+                            fallback = Some(rule);
+                            break;
+                        }
+                        _ => continue,
+                    }
+                }
+
+                fallback
+            }
+            rule => rule,
+        };
+
+        // If no rule was found, use the default operation rule if there is one.
+        let rule = match rule {
+            Some(rule) => rule,
+            None => match self.get_resolved(DEFAULT_OPERATION)? {
+                Some(rule) if !is_namespace(rule) => rule,
+                _ => return Err(RuleSetError::OperationNotFound.into()),
+            },
+        };
+
+        Ok(rule)
     }
 }
 
@@ -171,37 +556,36 @@ impl<'a> RuleSet<'a> for RuleSetV2<'a> {
         (self.header[0] & 0x000000ff) as u8
     }
 
-    /// This function returns the rule for an operation by recursively searching through fallbacks
+    /// This function returns the rule for an operation by searching through fallbacks.
     fn get_rule(&self, operation: String) -> Result<&dyn Assertable<'a>, ProgramError> {
-        let rule = self.get(operation.to_string());
-
-        match rule {
-            Some(rule) => {
-                match rule.constraint_type() {
-                    ConstraintType::Namespace => {
-                        // Check for a ':' namespace separator. If it exists try to operation namespace to see if
-                        // a fallback exists. E.g. 'transfer:owner' will check for a fallback for 'transfer'.
-                        // If it doesn't exist then fail.
-                        let split = operation.split(':').collect::<Vec<&str>>();
-                        if split.len() > 1 {
-                            self.get_rule(split[0].to_owned())
-                        } else {
-                            Err(RuleSetError::OperationNotFound.into())
-                        }
-                    }
-                    _ => Ok(rule),
-                }
-            }
-            None => Err(RuleSetError::OperationNotFound.into()),
-        }
+        Ok(self.find_rule(&operation)?)
+    }
+
+    fn get_rule_with_definitions<'s>(
+        &'s self,
+        operation: String,
+    ) -> Result<Box<dyn Assertable<'a> + 's>, ProgramError>
+    where
+        'a: 's,
+    {
+        Ok(Box::new(ScopedRuleV2 {
+            rule: self.find_rule(&operation)?,
+            rule_set: self,
+        }))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use crate::{
         error::RuleSetError,
-        state::v2::{Amount, Operator, ProgramOwnedList, RuleSetV2},
+        payload::{Payload, PayloadType},
+        state::v2::{
+            All, Amount, Any, Constraint, ConstraintType, IfThenElse, Not, Operator, Pass,
+            ProgramOwnedList, RuleRef, RuleSetV2, Threshold,
+        },
         types::{LibVersion, RuleSet},
     };
     use solana_program::pubkey::Pubkey;
@@ -232,7 +616,7 @@ mod tests {
         let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
 
         assert_eq!(rule_set.operations.len(), 2);
-        assert_eq!(rule_set.rules.len(), 2);
+        assert_eq!(rule_set.size(), 2);
         assert_eq!(rule_set.lib_version(), LibVersion::V2 as u8);
     }
 
@@ -260,4 +644,277 @@ mod tests {
 
         assert_eq!(error, RuleSetError::DuplicatedOperationName);
     }
+
+    #[test]
+    fn test_create_with_definitions() {
+        // definition rules
+        let pass = Pass::serialize().unwrap();
+        let amount = Amount::serialize(String::from("Amount"), Operator::Lt, 5).unwrap();
+
+        // rule referencing a definition
+        let reference = RuleRef::serialize(String::from("small")).unwrap();
+
+        // rule set
+
+        let serialized = RuleSetV2::serialize_with_definitions(
+            Pubkey::default(),
+            "Royalties",
+            &["transfer".to_string()],
+            &[&reference],
+            &["small".to_string(), "unused".to_string()],
+            &[&amount, &pass],
+        )
+        .unwrap();
+
+        // loads a rule set object
+
+        let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+
+        assert_eq!(rule_set.size(), 1);
+        assert_eq!(rule_set.definition_names.len(), 2);
+        assert_eq!(rule_set.definitions_size(), 2);
+        assert_eq!(rule_set.lib_version(), LibVersion::V2 as u8);
+        assert!(rule_set
+            .get_definition(&rule_set.definition_names[0])
+            .unwrap()
+            .is_some());
+        assert!(rule_set.assert_valid_references().is_ok());
+    }
+
+    #[test]
+    fn test_get_rule_with_definitions() {
+        // rule nesting a reference to a definition
+        let amount = Amount::serialize(String::from("Amount"), Operator::Lt, 5).unwrap();
+        let reference = RuleRef::serialize(String::from("small")).unwrap();
+        let all = All::serialize(&[&reference]).unwrap();
+
+        let serialized = RuleSetV2::serialize_with_definitions(
+            Pubkey::default(),
+            "Royalties",
+            &["transfer".to_string()],
+            &[&all],
+            &["small".to_string()],
+            &[&amount],
+        )
+        .unwrap();
+
+        let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+        let payload = Payload::from([("Amount".to_string(), PayloadType::Number(1))]);
+
+        // the rule alone cannot resolve the nested reference
+        let error = rule_set
+            .get_rule("transfer".to_string())
+            .unwrap()
+            .validate(&HashMap::new(), &payload, false, &None, &None)
+            .unwrap_err();
+        assert_eq!(error, RuleSetError::RuleDefinitionNotFound.into());
+
+        // the rule bound to the rule set resolves it
+        assert!(rule_set
+            .get_rule_with_definitions("transfer".to_string())
+            .unwrap()
+            .validate(&HashMap::new(), &payload, false, &None, &None)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_unsorted_list_is_rejected_when_read() {
+        // unsorted list stored with the sorted type, bypassing `serialize_sorted`
+        let programs = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut unsorted = ProgramOwnedList::serialize(
+            String::from("Destination"),
+            &[programs[0].max(programs[1]), programs[0].min(programs[1])],
+        )
+        .unwrap();
+        unsorted[..4]
+            .copy_from_slice(&(ConstraintType::ProgramOwnedListSorted as u32).to_le_bytes());
+        let any = Any::serialize(&[&unsorted]).unwrap();
+
+        let serialized = RuleSetV2::serialize(
+            Pubkey::default(),
+            "Royalties",
+            &["transfer".to_string()],
+            &[&any],
+        )
+        .unwrap();
+
+        // the rule set data is not checked on creation when read from an account
+        let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+
+        let error = rule_set
+            .get_rule_with_definitions("transfer".to_string())
+            .and_then(|rule| rule.validate(&HashMap::new(), &Payload::new(), false, &None, &None))
+            .unwrap_err();
+        assert_eq!(error, RuleSetError::UnsortedPubkeyList.into());
+    }
+
+    #[test]
+    fn test_definition_cycle() {
+        // definitions referencing each other
+        let first = Not::serialize(&RuleRef::serialize(String::from("second")).unwrap()).unwrap();
+        let second = RuleRef::serialize(String::from("first")).unwrap();
+        let pass = Pass::serialize().unwrap();
+
+        let serialized = RuleSetV2::serialize_with_definitions(
+            Pubkey::default(),
+            "Royalties",
+            &["transfer".to_string()],
+            &[&pass],
+            &["first".to_string(), "second".to_string()],
+            &[&first, &second],
+        )
+        .unwrap();
+
+        let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+
+        // asserts that we got the expected error
+
+        assert_eq!(
+            rule_set.assert_valid_references().unwrap_err(),
+            RuleSetError::RuleDefinitionCycle
+        );
+    }
+
+    #[test]
+    fn test_indexed_lookup() {
+        // one rule per operation, in reverse order
+        let operations = (0..50)
+            .rev()
+            .map(|i| format!("transfer:{:02}", i))
+            .collect::<Vec<String>>();
+        let rules = (0..50)
+            .rev()
+            .map(|i| Amount::serialize(String::from("Amount"), Operator::Eq, i).unwrap())
+            .collect::<Vec<Vec<u8>>>();
+
+        let serialized = RuleSetV2::serialize(
+            Pubkey::default(),
+            "Royalties",
+            &operations,
+            &rules.iter().map(|x| x.as_slice()).collect::<Vec<&[u8]>>(),
+        )
+        .unwrap();
+
+        let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+
+        assert!(rule_set.is_indexed());
+        assert!(RuleSetV2::is_sorted(rule_set.operations));
+
+        // only the selected rule is parsed
+        let rule = rule_set.get("transfer:07".to_string()).unwrap().unwrap();
+        assert!(matches!(rule.constraint_type(), ConstraintType::Amount));
+        assert_eq!(
+            rule_set.rules.iter().filter(|x| x.get().is_some()).count(),
+            1
+        );
+
+        // each operation resolves to its own rule
+        for (operation, rule) in operations.iter().zip(rules.iter()) {
+            let found = rule_set.get(operation.clone()).unwrap().unwrap();
+            assert!(matches!(found.constraint_type(), ConstraintType::Amount));
+
+            let index = rule_set
+                .find(rule_set.operations, &RuleSetV2::to_str32(operation))
+                .unwrap();
+            let offset = rule_set.offsets[index];
+            assert_eq!(
+                &rule_set.rules_data[offset..offset + rule.len()],
+                rule.as_slice()
+            );
+        }
+
+        assert!(rule_set.get("transfer:50".to_string()).unwrap().is_none());
+        assert!(rule_set.get("transfer".to_string()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_legacy_layout() {
+        let amount = Amount::serialize(String::from("Amount"), Operator::Eq, 1).unwrap();
+        let pass = Pass::serialize().unwrap();
+
+        // rule set serialized without the index and with unsorted operations
+        let mut serialized = vec![LibVersion::V2 as u8, 0, 0, 0];
+        serialized.extend(u32::to_le_bytes(2));
+        serialized.extend(Pubkey::default().as_ref());
+        serialized.extend(RuleSetV2::to_str32("Royalties"));
+        serialized.extend(RuleSetV2::to_str32("transfer"));
+        serialized.extend(RuleSetV2::to_str32("delegate"));
+        serialized.extend(&amount);
+        serialized.extend(&pass);
+
+        let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+
+        assert!(!rule_set.is_indexed());
+        assert_eq!(rule_set.offsets, vec![0, amount.len()]);
+
+        let rule = rule_set.get("delegate".to_string()).unwrap().unwrap();
+        assert!(matches!(rule.constraint_type(), ConstraintType::Pass));
+
+        let rule = rule_set.get("transfer".to_string()).unwrap().unwrap();
+        assert!(matches!(rule.constraint_type(), ConstraintType::Amount));
+
+        // the same operations out of order are rejected when the index flag is set
+        drop(rule_set);
+        serialized[1] = super::RULE_SET_INDEXED;
+        assert!(matches!(
+            RuleSetV2::from_bytes(&serialized),
+            Err(RuleSetError::RuleSetReadFailed)
+        ));
+    }
+
+    #[test]
+    fn test_malformed_rule_set() {
+        let amount = Amount::serialize(String::from("Amount"), Operator::Lt, 5).unwrap();
+        let pass = Pass::serialize().unwrap();
+        let reference = RuleRef::serialize(String::from("small")).unwrap();
+        let not = Not::serialize(&reference).unwrap();
+        let any = Any::serialize(&[&not, &pass]).unwrap();
+        let threshold = Threshold::serialize(1, &[&amount, &any]).unwrap();
+        let if_then_else = IfThenElse::serialize(&amount, &threshold, &pass).unwrap();
+        let all = All::serialize(&[&if_then_else, &reference]).unwrap();
+
+        let serialized = RuleSetV2::serialize_with_definitions(
+            Pubkey::default(),
+            "Royalties",
+            &["transfer".to_string(), "delegate".to_string()],
+            &[&all, &pass],
+            &["small".to_string()],
+            &[&amount],
+        )
+        .unwrap();
+
+        let check = |bytes: &[u8]| -> Result<(), RuleSetError> {
+            let rule_set = RuleSetV2::from_bytes(bytes)?;
+            rule_set.assert_well_formed()?;
+            rule_set.assert_valid_references()?;
+
+            for operation in ["transfer", "delegate", "unknown"] {
+                rule_set.get(operation.to_string())?;
+            }
+
+            Ok(())
+        };
+
+        assert!(check(&serialized).is_ok());
+
+        // every truncation is rejected without panicking
+        for length in 0..serialized.len() {
+            assert!(check(&serialized[..length]).is_err());
+        }
+
+        // corrupting any byte or any u32 word does not panic
+        for position in 0..serialized.len() {
+            let mut corrupted = serialized.clone();
+            corrupted[position] ^= 0xff;
+            let _ = check(&corrupted);
+
+            if position % 4 == 0 {
+                for value in [0, 1, 0x8000_0000, u32::MAX] {
+                    let mut corrupted = serialized.clone();
+                    corrupted[position..position + 4].copy_from_slice(&value.to_le_bytes());
+                    let _ = check(&corrupted);
+                }
+            }
+        }
+    }
 }
@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use crate::{
     error::RuleSetError,
     payload::Payload,
-    state::{constraint::*, Constraint, ConstraintType, RuleResult, U64_BYTES},
+    state::{constraint::*, Constraint, ConstraintType, RuleResult, RuleSetV2, Str32, U64_BYTES},
     types::Assertable,
 };
 
@@ -15,9 +15,21 @@ use super::try_from_bytes;
 pub const HEADER_SECTION: usize = U64_BYTES;
 
 /// Macro to automate the code required to deserialize a constraint from a byte array.
+/// Sorted variants are deserialized by the constraint of the unsorted variant, which checks the
+/// ordering of the list.
 macro_rules! constraint_from_bytes {
-    ( $constraint_type:ident, $slice:expr, $( $available:ident ),+ $(,)? ) => {
+    (
+        $constraint_type:ident,
+        $slice:expr,
+        sorted: { $( $sorted:ident => $unsorted:ident ),+ $(,)? },
+        $( $available:ident ),+ $(,)?
+    ) => {
         match $constraint_type {
+            $(
+                $crate::state::ConstraintType::$sorted => {
+                    Box::new($unsorted::from_sorted_bytes($slice)?) as Box<dyn Constraint>
+                }
+            )+
             $(
                 $crate::state::ConstraintType::$available => {
                     Box::new($available::from_bytes($slice)?) as Box<dyn Constraint>
@@ -36,39 +48,69 @@ pub struct RuleV2<'a> {
     pub header: &'a Header,
     /// Constraint represented by the rule.
     pub constraint: Box<dyn Constraint<'a> + 'a>,
+    /// Serialized constraint data following the header.
+    pub data: &'a [u8],
 }
 
 impl<'a> RuleV2<'a> {
     /// Deserialize a constraint from a byte array.
     pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, RuleSetError> {
-        let (header, data) = bytes.split_at(HEADER_SECTION);
-        let header = try_from_bytes::<Header>(0, HEADER_SECTION, header)?;
+        let header = try_from_bytes::<Header>(0, HEADER_SECTION, bytes)?;
+        let constraint_type = header.constraint_type()?;
 
-        let constraint_type = header.constraint_type();
-        let length = header.length();
+        let data = bytes
+            .get(HEADER_SECTION..)
+            .and_then(|data| data.get(..header.length()))
+            .ok_or(RuleSetError::RuleSetReadFailed)?;
 
         let constraint = constraint_from_bytes!(
             constraint_type,
-            &data[..length],
+            data,
+            sorted: {
+                ProgramOwnedListSorted => ProgramOwnedList,
+                PubkeyListMatchSorted => PubkeyListMatch,
+            },
             AdditionalSigner,
             All,
             Amount,
+            AmountI64,
+            AmountRange,
+            AmountRatio,
+            AmountU128,
             Any,
+            BoolMatch,
+            BytesMatch,
+            ExternalRuleSet,
+            FieldCompare,
             Frequency,
+            IfThenElse,
             IsWallet,
             Namespace,
             Not,
+            OwnerInListAccount,
             Pass,
             PDAMatch,
             ProgramOwnedList,
             ProgramOwnedTree,
+            ProgramOwnedTreeRegistry,
             ProgramOwned,
+            PubkeyInListAccount,
+            PubkeyListContains,
             PubkeyListMatch,
             PubkeyMatch,
-            PubkeyTreeMatch
+            PubkeyTreeExclusion,
+            PubkeyTreeMatch,
+            PubkeyTreeMatchRegistry,
+            RuleRef,
+            StrMatch,
+            Threshold
         );
 
-        Ok(Self { header, constraint })
+        Ok(Self {
+            header,
+            constraint,
+            data,
+        })
     }
 
     /// Length (in bytes) of the serialized rule.
@@ -77,6 +119,184 @@ impl<'a> RuleV2<'a> {
     }
 }
 
+/// A sequence of serialized `RuleV2`s nested under a constraint.
+///
+/// Rules are deserialized from their byte offsets when they are accessed, so a rule that is
+/// never evaluated is never parsed.
+#[derive(Clone, Copy)]
+pub struct RuleList<'a> {
+    /// The number of rules in the list.
+    size: usize,
+    /// The serialized rules.
+    bytes: &'a [u8],
+}
+
+impl<'a> RuleList<'a> {
+    /// Creates a list of `size` rules serialized back-to-back in `bytes`.
+    pub fn new(size: u64, bytes: &'a [u8]) -> Result<Self, RuleSetError> {
+        // Every rule takes at least the header section, so larger sizes cannot be valid.
+        let size = usize::try_from(size)
+            .ok()
+            .filter(|size| {
+                size.checked_mul(HEADER_SECTION)
+                    .is_some_and(|length| length <= bytes.len())
+            })
+            .ok_or(RuleSetError::RuleSetReadFailed)?;
+
+        Ok(Self { size, bytes })
+    }
+
+    /// Returns the number of rules in the list.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the list contains no rules.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Deserializes the rule at `index`.  Only the headers of the preceding rules are read.
+    pub fn get(&self, index: usize) -> Result<RuleV2<'a>, RuleSetError> {
+        if index >= self.size {
+            return Err(RuleSetError::RuleSetReadFailed);
+        }
+
+        let mut offset = 0;
+
+        for _ in 0..index {
+            let header = try_from_bytes::<Header>(offset, HEADER_SECTION, self.bytes)?;
+            offset = (offset + HEADER_SECTION)
+                .checked_add(header.length())
+                .ok_or(RuleSetError::RuleSetReadFailed)?;
+        }
+
+        RuleV2::from_bytes(
+            self.bytes
+                .get(offset..)
+                .ok_or(RuleSetError::RuleSetReadFailed)?,
+        )
+    }
+
+    /// Returns an iterator deserializing the rules in order.
+    pub fn iter(&self) -> RuleListIter<'a> {
+        RuleListIter {
+            remaining: self.size,
+            bytes: self.bytes,
+        }
+    }
+
+    /// Returns the names of the rule definitions referenced by the rules in the list.  Rules
+    /// that cannot be deserialized are skipped.
+    pub fn references(&self) -> Vec<&'a Str32> {
+        self.iter()
+            .filter_map(Result::ok)
+            .flat_map(|rule| rule.references())
+            .collect()
+    }
+
+    /// Checks that every rule in the list, and every rule nested under them, can be
+    /// deserialized and that the rules take up the whole byte array.
+    pub fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        let mut length = 0;
+
+        for rule in self.iter() {
+            let rule = rule?;
+            rule.assert_well_formed()?;
+            length += rule.length();
+        }
+
+        if length != self.bytes.len() {
+            return Err(RuleSetError::RuleSetReadFailed);
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterator over the rules of a `RuleList`.
+pub struct RuleListIter<'a> {
+    /// The number of rules left to deserialize.
+    remaining: usize,
+    /// The bytes of the remaining rules.
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for RuleListIter<'a> {
+    type Item = Result<RuleV2<'a>, RuleSetError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        match RuleV2::from_bytes(self.bytes) {
+            Ok(rule) => {
+                self.remaining -= 1;
+                self.bytes = self.bytes.get(rule.length()..).unwrap_or_default();
+                Some(Ok(rule))
+            }
+            Err(error) => {
+                // Following rules cannot be located once a rule fails to deserialize.
+                self.remaining = 0;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// A `RuleV2` bound to the `RuleSetV2` that contains it, so that any `RuleRef` in the rule
+/// tree can be resolved against the `RuleSet` definitions.
+pub struct ScopedRuleV2<'r, 'a> {
+    /// The top level rule.
+    pub rule: &'r RuleV2<'a>,
+    /// The `RuleSet` that owns the rule.
+    pub rule_set: &'r RuleSetV2<'a>,
+}
+
+impl<'r, 'a> Assertable<'a> for ScopedRuleV2<'r, 'a> {
+    fn validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> ProgramResult {
+        let result = self.low_level_validate(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+        );
+
+        match result {
+            RuleResult::Success(_) => Ok(()),
+            RuleResult::Failure(err) => Err(err),
+            RuleResult::Error(err) => Err(err),
+        }
+    }
+
+    fn low_level_validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> RuleResult {
+        self.rule.constraint.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            self.rule_set,
+        )
+    }
+}
+
 impl<'a> Assertable<'a> for RuleV2<'a> {
     fn validate(
         &self,
@@ -100,6 +320,23 @@ impl<'a> Assertable<'a> for RuleV2<'a> {
             RuleResult::Error(err) => Err(err),
         }
     }
+
+    fn low_level_validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> RuleResult {
+        self.constraint.validate(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+        )
+    }
 }
 
 impl<'a> Constraint<'a> for RuleV2<'a> {
@@ -107,16 +344,21 @@ impl<'a> Constraint<'a> for RuleV2<'a> {
         self.constraint.constraint_type()
     }
 
+    fn references(&self) -> Vec<&'a Str32> {
+        self.constraint.references()
+    }
+
+    fn assert_well_formed(&self) -> Result<(), RuleSetError> {
+        self.constraint.assert_well_formed()
+    }
+
     fn validate(
         &self,
-        accounts: &std::collections::HashMap<
-            solana_program::pubkey::Pubkey,
-            &solana_program::account_info::AccountInfo,
-        >,
-        payload: &crate::payload::Payload,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
         update_rule_state: bool,
-        rule_set_state_pda: &Option<&solana_program::account_info::AccountInfo>,
-        rule_authority: &Option<&solana_program::account_info::AccountInfo>,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
     ) -> RuleResult {
         self.constraint.validate(
             accounts,
@@ -126,6 +368,25 @@ impl<'a> Constraint<'a> for RuleV2<'a> {
             rule_authority,
         )
     }
+
+    fn validate_with_definitions(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+        rule_set: &RuleSetV2<'a>,
+    ) -> RuleResult {
+        self.constraint.validate_with_definitions(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+            rule_set,
+        )
+    }
 }
 
 /// Header for the rule.
@@ -138,8 +399,8 @@ pub struct Header {
 
 impl Header {
     /// Returns the type of the constraint.
-    pub fn constraint_type(&self) -> ConstraintType {
-        ConstraintType::try_from(self.data[0]).unwrap()
+    pub fn constraint_type(&self) -> Result<ConstraintType, RuleSetError> {
+        ConstraintType::try_from(self.data[0])
     }
 
     /// Returns the length of the data section.
@@ -159,7 +420,13 @@ impl Header {
 #[cfg(test)]
 mod tests {
     use super::RuleV2;
-    use crate::state::v2::{Amount, Any, Operator, ProgramOwnedList, Str32};
+    use crate::{
+        error::RuleSetError,
+        state::v2::{
+            All, Amount, AmountU128, Any, BytesMatch, Constraint, ConstraintType, ExternalRuleSet,
+            IfThenElse, MatchOperator, Not, Operator, Pass, ProgramOwnedList, Str32, Threshold,
+        },
+    };
     use solana_program::pubkey::Pubkey;
 
     #[test]
@@ -208,6 +475,36 @@ mod tests {
         assert_eq!(rule.header.length(), Str32::SIZE + (SIZE * 32));
     }
 
+    #[test]
+    fn test_create_sorted_program_owned_list() {
+        let mut programs = (0..100).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        programs.reverse();
+        programs.push(programs[0]);
+
+        let program_owned =
+            ProgramOwnedList::serialize_sorted(String::from("Destination"), &programs).unwrap();
+
+        // loads the data using bytemuck
+        let rule = RuleV2::from_bytes(&program_owned).unwrap();
+
+        assert_eq!(rule.header.length(), Str32::SIZE + (100 * 32));
+        assert!(matches!(
+            rule.constraint.constraint_type(),
+            ConstraintType::ProgramOwnedListSorted
+        ));
+
+        // An unsorted list stored with the sorted type is rejected.
+        let mut unsorted =
+            ProgramOwnedList::serialize(String::from("Destination"), &programs[..100]).unwrap();
+        unsorted[..4]
+            .copy_from_slice(&(ConstraintType::ProgramOwnedListSorted as u32).to_le_bytes());
+
+        assert!(matches!(
+            RuleV2::from_bytes(&unsorted),
+            Err(RuleSetError::UnsortedPubkeyList)
+        ));
+    }
+
     #[test]
     fn test_create_any() {
         let programs_list1 = &[Pubkey::default()];
@@ -228,4 +525,151 @@ mod tests {
             8 + program_owned1.len() + program_owned2.len()
         );
     }
+
+    #[test]
+    fn test_create_threshold() {
+        let programs_list1 = &[Pubkey::default()];
+        let program_owned1 =
+            ProgramOwnedList::serialize(String::from("Destination"), programs_list1).unwrap();
+
+        let programs_list2 = &[Pubkey::default(), Pubkey::default(), Pubkey::default()];
+        let program_owned2 =
+            ProgramOwnedList::serialize(String::from("Destination"), programs_list2).unwrap();
+
+        let threshold = Threshold::serialize(1, &[&program_owned1, &program_owned2]).unwrap();
+
+        // loads the data using bytemuck
+        let rule = RuleV2::from_bytes(&threshold).unwrap();
+
+        assert_eq!(
+            rule.header.length(),
+            16 + program_owned1.len() + program_owned2.len()
+        );
+    }
+
+    #[test]
+    fn test_create_if_then_else() {
+        let amount = Amount::serialize(String::from("Amount"), Operator::Eq, 1).unwrap();
+
+        let programs = &[Pubkey::default(), Pubkey::default()];
+        let program_owned =
+            ProgramOwnedList::serialize(String::from("Destination"), programs).unwrap();
+
+        let pass = Pass::serialize().unwrap();
+
+        let if_then_else = IfThenElse::serialize(&amount, &program_owned, &pass).unwrap();
+
+        // loads the data using bytemuck
+        let rule = RuleV2::from_bytes(&if_then_else).unwrap();
+
+        assert_eq!(
+            rule.header.length(),
+            amount.len() + program_owned.len() + pass.len()
+        );
+    }
+
+    #[test]
+    fn test_create_external_rule_set() {
+        let external =
+            ExternalRuleSet::serialize(Pubkey::default(), String::from("transfer"), None).unwrap();
+
+        // loads the data using bytemuck
+        let rule = RuleV2::from_bytes(&external).unwrap();
+
+        assert_eq!(rule.header.length(), 72);
+    }
+
+    #[test]
+    fn test_create_bytes_match() {
+        let bytes_match =
+            BytesMatch::serialize(String::from("Data"), MatchOperator::Prefix, vec![1, 2, 3])
+                .unwrap();
+
+        // the value is padded to 8 bytes
+        let rule = RuleV2::from_bytes(&bytes_match).unwrap();
+        assert_eq!(rule.header.length(), 8 + Str32::SIZE + 8 + 8);
+
+        let constraint = BytesMatch::from_bytes(&bytes_match[8..]).unwrap();
+        assert_eq!(constraint.value, &[1, 2, 3]);
+
+        // rules following an unaligned value can still be loaded
+        let amount = Amount::serialize(String::from("Amount"), Operator::Eq, 1).unwrap();
+        let all = All::serialize(&[&bytes_match, &amount]).unwrap();
+        let rule = RuleV2::from_bytes(&all).unwrap();
+        assert_eq!(rule.header.length(), 8 + bytes_match.len() + amount.len());
+    }
+
+    #[test]
+    fn test_create_amount_u128() {
+        let amount_u128 =
+            AmountU128::serialize(String::from("Amount"), Operator::GtEq, u128::MAX - 1).unwrap();
+
+        let rule = RuleV2::from_bytes(&amount_u128).unwrap();
+        assert_eq!(rule.header.length(), 16 + 8 + Str32::SIZE);
+
+        let constraint = AmountU128::from_bytes(&amount_u128[8..]).unwrap();
+        assert_eq!(u128::from_le_bytes(*constraint.amount), u128::MAX - 1);
+    }
+
+    #[test]
+    fn test_lazy_nested_rules() {
+        let amount = Amount::serialize(String::from("Amount"), Operator::Eq, 1).unwrap();
+        let pass = Pass::serialize().unwrap();
+        let mut not = Not::serialize(&pass).unwrap();
+        let all = All::serialize(&[&amount, &not]).unwrap();
+
+        let rule = RuleV2::from_bytes(&all).unwrap();
+        assert!(rule.assert_well_formed().is_ok());
+
+        // an invalid constraint type nested under Not is only found when the rules are checked
+        not[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let all = All::serialize(&[&amount, &not]).unwrap();
+
+        let rule = RuleV2::from_bytes(&all).unwrap();
+        assert!(matches!(
+            rule.assert_well_formed(),
+            Err(RuleSetError::InvalidConstraintType)
+        ));
+
+        // a size larger than the number of rules that can fit is rejected
+        let mut all = All::serialize(&[&amount, &pass]).unwrap();
+        all[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            RuleV2::from_bytes(&all),
+            Err(RuleSetError::RuleSetReadFailed)
+        ));
+
+        // a rule length past the end of the data is rejected
+        let mut amount = amount;
+        amount[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            RuleV2::from_bytes(&amount),
+            Err(RuleSetError::RuleSetReadFailed)
+        ));
+    }
+
+    #[test]
+    fn test_threshold_larger_than_rules() {
+        let amount = Amount::serialize(String::from("Amount"), Operator::Eq, 1).unwrap();
+        let pass = Pass::serialize().unwrap();
+
+        let threshold = Threshold::serialize(2, &[&amount, &pass]).unwrap();
+        let rule = RuleV2::from_bytes(&threshold).unwrap();
+        assert!(rule.assert_well_formed().is_ok());
+
+        // a threshold that can never be met is rejected, also when nested
+        let threshold = Threshold::serialize(3, &[&amount, &pass]).unwrap();
+        let rule = RuleV2::from_bytes(&threshold).unwrap();
+        assert!(matches!(
+            rule.assert_well_formed(),
+            Err(RuleSetError::InvalidThreshold)
+        ));
+
+        let not = Not::serialize(&threshold).unwrap();
+        let rule = RuleV2::from_bytes(&not).unwrap();
+        assert!(matches!(
+            rule.assert_well_formed(),
+            Err(RuleSetError::InvalidThreshold)
+        ));
+    }
 }
@@ -5,11 +5,20 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
-use crate::{error::RuleSetError, payload::Payload};
+use crate::{error::RuleSetError, payload::Payload, state::RuleResult};
 
 /// Max name length for any of the names used in this crate.
 pub const MAX_NAME_LENGTH: usize = 32;
 
+/// Max size (in bytes) of a serialized `Payload`.
+pub const MAX_PAYLOAD_SIZE: usize = 2048;
+
+/// Reserved operation name of the rule used when no rule is found for an operation.
+pub const DEFAULT_OPERATION: &str = "*";
+
+/// Max number of `ExternalRuleSet` rules that can be followed from the validated `RuleSet`.
+pub const MAX_RULE_SET_DEPTH: u8 = 3;
+
 /// Versioning for `RuleSet` structs.
 pub enum LibVersion {
     V1 = 1,
@@ -29,6 +38,7 @@ impl TryFrom<u8> for LibVersion {
 }
 
 pub trait Assertable<'a> {
+    /// The top level validation function which parses an entire rule tree.
     fn validate(
         &self,
         accounts: &HashMap<Pubkey, &AccountInfo>,
@@ -37,6 +47,66 @@ pub trait Assertable<'a> {
         rule_set_state_pda: &Option<&AccountInfo>,
         rule_authority: &Option<&AccountInfo>,
     ) -> ProgramResult;
+
+    /// Validates the rule, keeping the distinction between a rule failure and an error.
+    ///
+    /// The default implementation cannot tell them apart, so any error of `validate` is
+    /// reported as a `RuleResult::Error`.
+    fn low_level_validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> RuleResult {
+        match self.validate(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+        ) {
+            Ok(()) => RuleResult::Success(RuleSetError::UnexpectedRuleSetFailure.into()),
+            Err(err) => RuleResult::Error(err),
+        }
+    }
+}
+
+impl<'a, T: Assertable<'a> + ?Sized> Assertable<'a> for &T {
+    fn validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> ProgramResult {
+        (**self).validate(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+        )
+    }
+
+    fn low_level_validate(
+        &self,
+        accounts: &HashMap<Pubkey, &AccountInfo>,
+        payload: &Payload,
+        update_rule_state: bool,
+        rule_set_state_pda: &Option<&AccountInfo>,
+        rule_authority: &Option<&AccountInfo>,
+    ) -> RuleResult {
+        (**self).low_level_validate(
+            accounts,
+            payload,
+            update_rule_state,
+            rule_set_state_pda,
+            rule_authority,
+        )
+    }
 }
 
 pub trait RuleSet<'a> {
@@ -50,5 +120,21 @@ pub trait RuleSet<'a> {
     fn lib_version(&self) -> u8;
 
     /// Returns the rule associated with an operation.
+    ///
+    /// Any `RuleRef` nested in the returned rule is not resolved, use `get_rule_with_definitions`
+    /// to validate rules that refer to the `RuleSet` definitions.
     fn get_rule(&self, operation: String) -> Result<&dyn Assertable<'a>, ProgramError>;
+
+    /// Returns the rule associated with an operation, bound to the `RuleSet` so that any
+    /// `RuleRef` in the rule tree is resolved against its definitions.
+    fn get_rule_with_definitions<'s>(
+        &'s self,
+        operation: String,
+    ) -> Result<Box<dyn Assertable<'a> + 's>, ProgramError>
+    where
+        'a: 's,
+    {
+        let rule: &dyn Assertable<'a> = self.get_rule(operation)?;
+        Ok(Box::new(rule))
+    }
 }
@@ -0,0 +1,397 @@
+//! Planning the upload of a `RuleSet` to its PDA.
+//!
+//! A `RuleSet` that fits in a transaction is passed directly to `CreateOrUpdate`.  A larger one
+//! is written to the buffer PDA of the payer with `WriteToBuffer` V2 in chunks as large as a
+//! transaction allows, and then stored with a `CreateOrUpdate` reading the buffer.  Since a
+//! `RuleSet` PDA can only grow by `MAX_PERMITTED_DATA_INCREASE` bytes in an instruction, the
+//! `CreateOrUpdate` of an existing `RuleSet` PDA is preceded by as many `PuffRuleSet`
+//! instructions as the new revision needs, packed in as few transactions as fit.
+//!
+//! Every step is a transaction that can be sent on its own.  The chunks are written at their
+//! offset, so they can be sent in any order, or in parallel, as long as the buffer PDA is new or
+//! already large enough; an existing smaller buffer grows as chunks are written in order.  The
+//! `PuffRuleSet` steps can also be sent in any order, but must land before the `CreateOrUpdate`.
+//! If a step fails, or it is unknown whether it landed, `UploadPlanner::resume` returns the steps
+//! left from the chunk bitmap of the buffer PDA, so chunks already written are not written again.
+use crate::{
+    instruction::{
+        builders::{CreateOrUpdateBuilder, PuffRuleSetBuilder, WriteToBufferBuilder},
+        CreateOrUpdateArgs, InstructionBuilder, PuffRuleSetArgs, WriteToBufferArgs,
+    },
+    pda::{find_buffer_address, find_rule_set_address},
+    state::{
+        BufferAccount, RuleSetRevisionMapV2, BUFFER_BLOCK_LEN, CHUNK_SIZE,
+        RULE_SET_SERIALIZED_HEADER_LEN, U64_BYTES,
+    },
+    types::LibVersion,
+    utils::{get_revision_map_from_data, is_zeroed},
+};
+use solana_program::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE, instruction::Instruction, keccak, message::Message,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+use thiserror::Error;
+
+/// Largest serialized transaction, which is the size of the data of a packet.
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Size of a transaction signature.
+const SIGNATURE_SIZE: usize = 64;
+
+/// Errors returned when planning an upload.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum UploadError {
+    /// The data of the `RuleSet` PDA could not be read.
+    #[error("invalid RuleSet PDA data: {0}")]
+    InvalidRuleSetData(ProgramError),
+
+    /// The instructions could not be built.
+    #[error("cannot build the instruction: {0}")]
+    Instruction(String),
+
+    /// The `RuleSet` is larger than a buffer can hold.
+    #[error("a RuleSet of {0} bytes is too large for the buffer")]
+    RuleSetTooLarge(usize),
+
+    /// A step does not fit in a transaction.
+    #[error("a transaction of {0} bytes exceeds the limit of {MAX_TRANSACTION_SIZE} bytes")]
+    TransactionTooLarge(usize),
+}
+
+/// What a step of an upload does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UploadStepKind {
+    /// Stores the `RuleSet` passed in the instruction.
+    CreateOrUpdate,
+    /// Writes `len` bytes of the `RuleSet` at `offset` of the buffer.
+    WriteToBuffer {
+        /// Offset of the chunk in the serialized `RuleSet`.
+        offset: usize,
+        /// Length of the chunk.
+        len: usize,
+    },
+    /// Grows the `RuleSet` PDA with `puffs` `PuffRuleSet` instructions.
+    PuffRuleSet {
+        /// Number of `PuffRuleSet` instructions.
+        puffs: usize,
+    },
+    /// Stores the `RuleSet` in the buffer.
+    CreateOrUpdateFromBuffer,
+}
+
+/// A step of an upload, sent as one transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadStep {
+    /// What the step does.
+    pub kind: UploadStepKind,
+    /// Instructions of the transaction.
+    pub instructions: Vec<Instruction>,
+}
+
+/// Plans the transactions storing a serialized `RuleSet` as a new revision of its PDA.
+#[derive(Clone, Debug)]
+pub struct UploadPlanner {
+    payer: Pubkey,
+    rule_set_name: String,
+    rule_set_pda: Pubkey,
+    buffer_pda: Pubkey,
+    serialized_rule_set: Vec<u8>,
+    total_len: u32,
+    hash: [u8; 32],
+    growth: usize,
+    instructions: Vec<Instruction>,
+}
+
+impl UploadPlanner {
+    /// Create a planner for the upload of `serialized_rule_set` by its owner, the `payer`.  The
+    /// `existing_data` is the current data of the `RuleSet` PDA, empty if it does not exist.
+    pub fn new(
+        payer: Pubkey,
+        rule_set_name: &str,
+        serialized_rule_set: Vec<u8>,
+        existing_data: &[u8],
+    ) -> Result<Self, UploadError> {
+        let total_len = u32::try_from(serialized_rule_set.len())
+            .map_err(|_| UploadError::RuleSetTooLarge(serialized_rule_set.len()))?;
+
+        // The instruction can grow an empty account to any size.
+        let growth = if existing_data.is_empty() {
+            0
+        } else {
+            new_data_len(existing_data, &serialized_rule_set)?.saturating_sub(existing_data.len())
+        };
+
+        Ok(Self {
+            payer,
+            rule_set_name: rule_set_name.to_string(),
+            rule_set_pda: find_rule_set_address(payer, rule_set_name.to_string()).0,
+            buffer_pda: find_buffer_address(payer).0,
+            hash: keccak::hash(&serialized_rule_set).to_bytes(),
+            serialized_rule_set,
+            total_len,
+            growth,
+            instructions: vec![],
+        })
+    }
+
+    /// Add instructions, such as compute budget instructions, to the transaction storing the
+    /// `RuleSet`.  They come before the program instructions.
+    pub fn with_instructions(mut self, instructions: Vec<Instruction>) -> Self {
+        self.instructions = instructions;
+        self
+    }
+
+    /// Returns the address of the `RuleSet` PDA.
+    pub fn rule_set_pda(&self) -> Pubkey {
+        self.rule_set_pda
+    }
+
+    /// Returns the address of the buffer PDA.
+    pub fn buffer_pda(&self) -> Pubkey {
+        self.buffer_pda
+    }
+
+    /// Returns the steps of the upload, in the order they are sent.
+    pub fn plan(&self) -> Result<Vec<UploadStep>, UploadError> {
+        let mut instructions = self.instructions.clone();
+        instructions.push(self.create_or_update(None)?);
+
+        // Pass the `RuleSet` directly when it fits and the PDA does not need to be puffed.
+        if self.puffs() == 0 && self.transaction_size(&instructions) <= MAX_TRANSACTION_SIZE {
+            return Ok(vec![UploadStep {
+                kind: UploadStepKind::CreateOrUpdate,
+                instructions,
+            }]);
+        }
+
+        self.steps_from(None)
+    }
+
+    /// Returns the steps left to upload the `RuleSet`, given the current data of the buffer
+    /// PDA.  Chunks already marked in the chunk bitmap of the buffer are not written again if
+    /// the buffer is being written for this `RuleSet`, otherwise every chunk is written.
+    /// Resuming a plan that stored the `RuleSet` directly writes it to the buffer.  Once the last
+    /// step has landed the upload is complete, and resuming it would store the `RuleSet` again.
+    ///
+    /// The `PuffRuleSet` steps follow from the data of the `RuleSet` PDA the planner was created
+    /// with.  If some of them landed, create a new planner from the current data of the PDA so
+    /// that it is not grown again.
+    pub fn resume(&self, buffer_data: &[u8]) -> Result<Vec<UploadStep>, UploadError> {
+        let buffer = BufferAccount::from_bytes(buffer_data)
+            .ok()
+            .filter(|buffer| {
+                buffer.total_len == self.serialized_rule_set.len() && buffer.hash == self.hash
+            });
+
+        self.steps_from(buffer)
+    }
+
+    /// Returns the steps writing the chunks of the `RuleSet` missing from the buffer, if any,
+    /// growing the `RuleSet` PDA and storing it.
+    fn steps_from(&self, buffer: Option<BufferAccount>) -> Result<Vec<UploadStep>, UploadError> {
+        let chunk_size = self.chunk_size()?;
+        let mut steps = vec![];
+
+        for start in (0..self.serialized_rule_set.len()).step_by(chunk_size) {
+            let end = start
+                .saturating_add(chunk_size)
+                .min(self.serialized_rule_set.len());
+
+            let is_written = buffer.as_ref().is_some_and(|buffer| {
+                (start / BUFFER_BLOCK_LEN..end.div_ceil(BUFFER_BLOCK_LEN))
+                    .all(|block| buffer.is_written(block))
+            });
+
+            if is_written {
+                continue;
+            }
+
+            steps.push(UploadStep {
+                kind: UploadStepKind::WriteToBuffer {
+                    offset: start,
+                    len: end - start,
+                },
+                instructions: vec![self.write_to_buffer(start, end)?],
+            });
+        }
+
+        let mut puffs = self.puffs();
+
+        if puffs > 0 {
+            let puff = self.puff_rule_set()?;
+            let per_transaction = self.puffs_per_transaction(&puff)?;
+
+            while puffs > 0 {
+                let count = puffs.min(per_transaction);
+
+                steps.push(UploadStep {
+                    kind: UploadStepKind::PuffRuleSet { puffs: count },
+                    instructions: vec![puff.clone(); count],
+                });
+
+                puffs -= count;
+            }
+        }
+
+        let mut instructions = self.instructions.clone();
+        instructions.push(self.create_or_update(Some(self.buffer_pda))?);
+
+        let size = self.transaction_size(&instructions);
+        if size > MAX_TRANSACTION_SIZE {
+            return Err(UploadError::TransactionTooLarge(size));
+        }
+
+        steps.push(UploadStep {
+            kind: UploadStepKind::CreateOrUpdateFromBuffer,
+            instructions,
+        });
+
+        Ok(steps)
+    }
+
+    /// Returns the `PuffRuleSet` instruction.
+    fn puff_rule_set(&self) -> Result<Instruction, UploadError> {
+        Ok(PuffRuleSetBuilder::new()
+            .payer(self.payer)
+            .rule_set_pda(self.rule_set_pda)
+            .build(PuffRuleSetArgs::V1 {
+                rule_set_name: self.rule_set_name.clone(),
+            })
+            .map_err(|error| UploadError::Instruction(error.to_string()))?
+            .instruction())
+    }
+
+    /// Returns the largest number of `PuffRuleSet` instructions that fit in a transaction, up to
+    /// the number the upload needs.
+    fn puffs_per_transaction(&self, puff: &Instruction) -> Result<usize, UploadError> {
+        let mut count = 1;
+
+        let size = self.transaction_size(&vec![puff.clone(); count]);
+        if size > MAX_TRANSACTION_SIZE {
+            return Err(UploadError::TransactionTooLarge(size));
+        }
+
+        while count < self.puffs()
+            && self.transaction_size(&vec![puff.clone(); count + 1]) <= MAX_TRANSACTION_SIZE
+        {
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the `CreateOrUpdate` instruction, reading the `RuleSet` from the buffer if one
+    /// is given.
+    fn create_or_update(&self, buffer_pda: Option<Pubkey>) -> Result<Instruction, UploadError> {
+        let mut builder = CreateOrUpdateBuilder::new();
+        builder.payer(self.payer).rule_set_pda(self.rule_set_pda);
+
+        let serialized_rule_set = match buffer_pda {
+            Some(buffer_pda) => {
+                builder.buffer_pda(buffer_pda);
+                vec![]
+            }
+            None => self.serialized_rule_set.clone(),
+        };
+
+        Ok(builder
+            .build(CreateOrUpdateArgs::V1 {
+                serialized_rule_set,
+            })
+            .map_err(|error| UploadError::Instruction(error.to_string()))?
+            .instruction())
+    }
+
+    /// Returns the number of `PuffRuleSet` instructions growing the PDA beyond what
+    /// `CreateOrUpdate` can add.
+    fn puffs(&self) -> usize {
+        self.growth
+            .saturating_sub(MAX_PERMITTED_DATA_INCREASE)
+            .div_ceil(CHUNK_SIZE)
+    }
+
+    /// Returns the `WriteToBuffer` instruction writing the bytes of the `RuleSet` from `start`
+    /// to `end`.
+    fn write_to_buffer(&self, start: usize, end: usize) -> Result<Instruction, UploadError> {
+        Ok(WriteToBufferBuilder::new()
+            .payer(self.payer)
+            .buffer_pda(self.buffer_pda)
+            .build(WriteToBufferArgs::V2 {
+                offset: start as u32,
+                data: self.serialized_rule_set[start..end].to_vec(),
+                total_len: self.total_len,
+                hash: Some(self.hash),
+            })
+            .map_err(|error| UploadError::Instruction(error.to_string()))?
+            .instruction())
+    }
+
+    /// Returns the largest chunk of a `WriteToBuffer` transaction, in whole blocks of the
+    /// buffer.
+    fn chunk_size(&self) -> Result<usize, UploadError> {
+        let empty_ix = self.write_to_buffer(0, 0)?;
+
+        // The length of the instruction data takes one more byte once it exceeds 127 bytes.
+        let size = self.transaction_size(&[empty_ix]) + 1;
+
+        match MAX_TRANSACTION_SIZE.checked_sub(size) {
+            Some(chunk_size) if chunk_size >= BUFFER_BLOCK_LEN => {
+                Ok(chunk_size / BUFFER_BLOCK_LEN * BUFFER_BLOCK_LEN)
+            }
+            _ => Err(UploadError::TransactionTooLarge(size)),
+        }
+    }
+
+    /// Returns the size of the serialized transaction with the instructions.
+    pub fn transaction_size(&self, instructions: &[Instruction]) -> usize {
+        transaction_size(instructions, &self.payer)
+    }
+}
+
+/// Returns the size of a serialized transaction with the instructions, paid by `payer`.
+pub fn transaction_size(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    let signatures = usize::from(message.header.num_required_signatures);
+
+    // The signatures are prefixed with their number, as a compact `u16`.
+    let prefix = match signatures {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    };
+
+    prefix + signatures * SIGNATURE_SIZE + message.serialize().len()
+}
+
+/// Returns the length of the `RuleSet` PDA data once `CreateOrUpdate` has added the revision,
+/// following the layout described in the `state` module.
+fn new_data_len(existing_data: &[u8], serialized_rule_set: &[u8]) -> Result<usize, UploadError> {
+    let is_v2 = serialized_rule_set.first() == Some(&(LibVersion::V2 as u8));
+
+    let (mut revision_map, rev_map_location) = if is_zeroed(existing_data) {
+        (
+            RuleSetRevisionMapV2::default(),
+            RULE_SET_SERIALIZED_HEADER_LEN,
+        )
+    } else {
+        get_revision_map_from_data(existing_data).map_err(UploadError::InvalidRuleSetData)?
+    };
+
+    // V2 revisions are aligned and carry their own lib version, V1 revisions are prefixed with
+    // it.
+    let (start, lib_version_len) = if is_v2 {
+        (rev_map_location.next_multiple_of(U64_BYTES), 0)
+    } else {
+        (rev_map_location, 1)
+    };
+
+    revision_map.rule_set_revisions.push(start);
+    revision_map.rule_set_hashes.push([0; 32]);
+
+    let rev_map_len = borsh::to_vec(&revision_map)
+        .map_err(|error| UploadError::InvalidRuleSetData(error.into()))?
+        .len();
+
+    // The revision is followed by the revision map version and the revision map.
+    Ok(start + lib_version_len + serialized_rule_set.len() + 1 + rev_map_len)
+}
@@ -1,11 +1,13 @@
 //! Utilities for the program
 use crate::{
     error::RuleSetError,
-    payload::ProofInfo,
+    payload::{ExclusionProofInfo, Payload, ProofInfo},
     state::{
-        RuleSetHeader, RuleSetRevisionMapV1, RULE_SET_REV_MAP_VERSION,
+        PubkeyListAccount, RootRegistryAccount, RuleResult, RuleSetHeader, RuleSetRevisionMapV1,
+        RuleSetRevisionMapV2, RuleSetV1, RuleSetV2, SolanaAccount, RULE_SET_REV_MAP_VERSION,
         RULE_SET_SERIALIZED_HEADER_LEN,
     },
+    types::{LibVersion, RuleSet, MAX_RULE_SET_DEPTH},
 };
 use borsh::BorshDeserialize;
 use solana_program::{
@@ -20,6 +22,7 @@ use solana_program::{
     system_instruction,
     sysvar::Sysvar,
 };
+use std::collections::HashMap;
 // TODO: Uncomment this when the syscall is available.
 //use solana_zk_token_sdk::curve25519::curve_syscall_traits::CURVE25519_EDWARDS;
 
@@ -127,8 +130,15 @@ pub fn cmp_pubkeys(a: &Pubkey, b: &Pubkey) -> bool {
 /// Compute the root of a Merkle tree given a leaf and a proof.  Uses a constant value
 /// of 0x01 as an input to the hashing function along with the values to be hashed.
 pub fn compute_merkle_root(leaf: &Pubkey, merkle_proof: &ProofInfo) -> [u8; 32] {
-    let mut computed_hash = leaf.to_bytes();
-    for proof_element in merkle_proof.proof.iter() {
+    compute_merkle_root_from_node(leaf.to_bytes(), &merkle_proof.proof)
+}
+
+/// Compute the root of a Merkle tree given a node and the proof from that node.  Each pair of
+/// nodes is sorted before being hashed, so the proof does not need to include the position of
+/// the node.
+pub fn compute_merkle_root_from_node(node: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    let mut computed_hash = node;
+    for proof_element in proof.iter() {
         if computed_hash <= *proof_element {
             // Hash(current computed hash + current element of the proof).
             computed_hash =
@@ -143,16 +153,91 @@ pub fn compute_merkle_root(leaf: &Pubkey, merkle_proof: &ProofInfo) -> [u8; 32]
     computed_hash
 }
 
-/// Get a revision map by looking at the header, finding its location, and deserializing it.
+/// Hash of an exclusion tree leaf, which commits to two adjacent keys of the sorted set.  A
+/// missing key is the open-ended bound below the smallest key or above the largest key of the
+/// set, and is flagged apart from the key bytes so that every key can be excluded.  Uses a
+/// constant value of 0x02 as an input to the hashing function so that a leaf can never be
+/// confused with an inner node of the tree.
+pub fn hash_exclusion_leaf(low: Option<&Pubkey>, high: Option<&Pubkey>) -> [u8; 32] {
+    let bound = |key: Option<&Pubkey>| match key {
+        Some(key) => ([0x01], key.to_bytes()),
+        None => ([0x00], [0; PUBKEY_BYTES]),
+    };
+    let (low_flag, low) = bound(low);
+    let (high_flag, high) = bound(high);
+
+    solana_program::keccak::hashv(&[&[0x02], &low_flag, &low, &high_flag, &high]).0
+}
+
+/// Compute the root of an exclusion tree given an exclusion proof.
+pub fn compute_exclusion_root(exclusion_proof: &ExclusionProofInfo) -> [u8; 32] {
+    compute_merkle_root_from_node(
+        hash_exclusion_leaf(exclusion_proof.low.as_ref(), exclusion_proof.high.as_ref()),
+        &exclusion_proof.proof,
+    )
+}
+
+/// Returns whether the exclusion proof shows that `key` is not a member of the exclusion tree
+/// with the given root: the adjacent keys in the proof must bracket `key` and the proof must
+/// lead to the root.
+pub fn is_excluded(key: &Pubkey, exclusion_proof: &ExclusionProofInfo, root: &[u8; 32]) -> bool {
+    exclusion_proof.low.iter().all(|low| low < key)
+        && exclusion_proof.high.iter().all(|high| key < high)
+        && compute_exclusion_root(exclusion_proof) == *root
+}
+
+/// Read the Merkle root stored in a `RootRegistryAccount`.  The registry account must be
+/// provided in `accounts` and be owned by this program.
+pub fn get_registry_root(
+    registry: &Pubkey,
+    accounts: &HashMap<Pubkey, &AccountInfo>,
+) -> Result<[u8; 32], ProgramError> {
+    let account = accounts.get(registry).ok_or(RuleSetError::MissingAccount)?;
+
+    if account.data_is_empty() {
+        return Err(RuleSetError::DataIsEmpty.into());
+    }
+
+    RootRegistryAccount::from_account_info(account).map(|registry| registry.root)
+}
+
+/// Binary search a `PubkeyListAccount` for `key` without deserializing the list.  The list
+/// account must be provided in `accounts` and be owned by this program.
+pub fn pubkey_list_contains(
+    list: &Pubkey,
+    key: &Pubkey,
+    accounts: &HashMap<Pubkey, &AccountInfo>,
+) -> Result<bool, ProgramError> {
+    let account = accounts.get(list).ok_or(RuleSetError::MissingAccount)?;
+    assert_owned_by(account, &crate::ID)?;
+
+    let data = account
+        .data
+        .try_borrow()
+        .map_err(|_| ProgramError::AccountBorrowFailed)?;
+
+    Ok(PubkeyListAccount::from_bytes(&data)?.contains(key))
+}
+
+/// Get a revision map by looking at the header, finding its location, and deserializing it.  A
+/// `RuleSetRevisionMapV1` without hashes is upgraded to a `RuleSetRevisionMapV2` with hashes of
+/// zeros.
 pub fn get_existing_revision_map(
     rule_set_pda_info: &AccountInfo,
-) -> Result<(RuleSetRevisionMapV1, usize), ProgramError> {
-    // Mutably borrow the existing `RuleSet` PDA data.
+) -> Result<(RuleSetRevisionMapV2, usize), ProgramError> {
+    // Borrow the existing `RuleSet` PDA data.
     let data = rule_set_pda_info
         .data
         .try_borrow()
         .map_err(|_| ProgramError::AccountBorrowFailed)?;
 
+    get_revision_map_from_data(&data)
+}
+
+/// Get the revision map and the location of its version from the data of a `RuleSet` PDA.
+pub fn get_revision_map_from_data(
+    data: &[u8],
+) -> Result<(RuleSetRevisionMapV2, usize), ProgramError> {
     // Deserialize header.
     let header = if data.len() >= RULE_SET_SERIALIZED_HEADER_LEN {
         RuleSetHeader::try_from_slice(&data[..RULE_SET_SERIALIZED_HEADER_LEN])?
@@ -161,29 +246,168 @@ pub fn get_existing_revision_map(
     };
 
     // Get revision map version location from header and use it check revision map version.
-    match data.get(header.rev_map_version_location) {
-        Some(&RULE_SET_REV_MAP_VERSION) => {
-            // Increment starting location by size of the revision map version.
-            let start = header
-                .rev_map_version_location
+    let version = *data
+        .get(header.rev_map_version_location)
+        .ok_or(RuleSetError::DataTypeMismatch)?;
+
+    // Increment starting location by size of the revision map version.
+    let start = header
+        .rev_map_version_location
+        .checked_add(1)
+        .ok_or(RuleSetError::NumericalOverflow)?;
+
+    if start >= data.len() {
+        return Err(RuleSetError::DataTypeMismatch.into());
+    }
+
+    // Deserialize revision map.  The hashes follow the `RuleSetRevisionMapV1`, unless the PDA was
+    // last written by a program that does not record them, in which case the map ends the data
+    // or is followed by the zeros of a `PuffRuleSet`.
+    let mut location = &data[start..];
+    let revision_map = match version {
+        RULE_SET_REV_MAP_VERSION => {
+            let revision_map = RuleSetRevisionMapV1::deserialize(&mut location)?;
+
+            match Vec::<[u8; 32]>::deserialize(&mut location) {
+                Ok(rule_set_hashes)
+                    if rule_set_hashes.len() == revision_map.rule_set_revisions.len() =>
+                {
+                    RuleSetRevisionMapV2 {
+                        rule_set_revisions: revision_map.rule_set_revisions,
+                        rule_set_hashes,
+                    }
+                }
+                _ => revision_map.into(),
+            }
+        }
+        _ => return Err(RuleSetError::UnsupportedRuleSetRevMapVersion.into()),
+    };
+
+    Ok((revision_map, header.rev_map_version_location))
+}
+
+/// Get the location of a `RuleSet` revision in the PDA, returned as the start and end offsets
+/// of the serialized `RuleSet`.  If `rule_set_revision` is `None`, the latest revision is used.
+pub fn get_rule_set_revision_location(
+    rule_set_pda_info: &AccountInfo,
+    rule_set_revision: Option<usize>,
+) -> Result<(usize, usize), ProgramError> {
+    let data = rule_set_pda_info
+        .data
+        .try_borrow()
+        .map_err(|_| ProgramError::AccountBorrowFailed)?;
+
+    get_rule_set_revision_location_from_data(&data, rule_set_revision)
+}
+
+/// Get the location of a `RuleSet` revision from the data of a `RuleSet` PDA.  See
+/// `get_rule_set_revision_location`.
+pub fn get_rule_set_revision_location_from_data(
+    data: &[u8],
+    rule_set_revision: Option<usize>,
+) -> Result<(usize, usize), ProgramError> {
+    // Get existing revision map and its serialized length.
+    let (revision_map, rev_map_location) = get_revision_map_from_data(data)?;
+
+    // Use the user-provided revision number to look up the `RuleSet` revision location in the PDA.
+    match rule_set_revision {
+        Some(revision) => {
+            let start = revision_map
+                .rule_set_revisions
+                .get(revision)
+                .ok_or(RuleSetError::RuleSetRevisionNotAvailable)?;
+
+            let end_index = revision
                 .checked_add(1)
                 .ok_or(RuleSetError::NumericalOverflow)?;
 
-            // Deserialize revision map.
-            if start < data.len() {
-                let mut location = &data[start..];
-                let revision_map = RuleSetRevisionMapV1::deserialize(&mut location)?;
+            let end = revision_map
+                .rule_set_revisions
+                .get(end_index)
+                .unwrap_or(&rev_map_location);
+            Ok((*start, *end))
+        }
+        None => {
+            let start = revision_map
+                .rule_set_revisions
+                .last()
+                .ok_or(RuleSetError::RuleSetRevisionNotAvailable)?;
+            Ok((*start, rev_map_location))
+        }
+    }
+}
 
-                Ok((revision_map, header.rev_map_version_location))
-            } else {
-                Err(RuleSetError::DataTypeMismatch.into())
+/// Deserialize the `RuleSet` revision stored between `start` and `end` of the PDA data.  The
+/// `depth` is the number of `ExternalRuleSet` rules that were followed to reach the `RuleSet`.
+pub fn deserialize_rule_set<'d>(
+    data: &'d [u8],
+    start: usize,
+    end: usize,
+    depth: u8,
+) -> Result<Box<dyn RuleSet<'d> + 'd>, ProgramError> {
+    // Check `RuleSet` lib version.
+    let lib_version = match data.get(start) {
+        Some(lib_version) => LibVersion::try_from(*lib_version)?,
+        None => return Err(RuleSetError::DataTypeMismatch.into()),
+    };
+
+    match lib_version {
+        LibVersion::V1 => {
+            // Increment starting location by size of lib version.
+            let start = start
+                .checked_add(1)
+                .ok_or(RuleSetError::NumericalOverflow)?;
+
+            // Deserialize `RuleSet`.
+            match data.get(start..end) {
+                Some(rule_set_data) if end < data.len() => {
+                    let mut rule_set = rmp_serde::from_slice::<RuleSetV1>(rule_set_data)
+                        .map_err(|_| RuleSetError::MessagePackDeserializationError)?;
+                    rule_set.set_depth(depth);
+
+                    Ok(Box::new(rule_set))
+                }
+                _ => Err(RuleSetError::DataTypeMismatch.into()),
             }
         }
-        Some(_) => Err(RuleSetError::UnsupportedRuleSetRevMapVersion.into()),
-        None => Err(RuleSetError::DataTypeMismatch.into()),
+        LibVersion::V2 => match data.get(start..end) {
+            Some(rule_set_data) if end < data.len() => {
+                let mut rule_set = RuleSetV2::from_bytes(rule_set_data)?;
+                rule_set.set_depth(depth);
+
+                Ok(Box::new(rule_set))
+            }
+            _ => Err(RuleSetError::DataTypeMismatch.into()),
+        },
     }
 }
 
+/// Validate an operation of a deserialized `RuleSet`.  This is shared by every instruction that
+/// validates a `Payload`, so that the key length and size limits of the `Payload` are enforced
+/// whichever instruction built it.
+pub fn validate_operation<'a>(
+    rule_set: &dyn RuleSet<'a>,
+    operation: String,
+    accounts: &HashMap<Pubkey, &AccountInfo>,
+    payload: &Payload,
+    update_rule_state: bool,
+    rule_set_state_pda: &Option<&AccountInfo>,
+    rule_authority: &Option<&AccountInfo>,
+) -> ProgramResult {
+    // The `Payload` must be within the key length and size limits.
+    payload.assert_within_limits()?;
+
+    let rule = rule_set.get_rule_with_definitions(operation)?;
+
+    rule.validate(
+        accounts,
+        payload,
+        update_rule_state,
+        rule_set_state_pda,
+        rule_authority,
+    )
+}
+
 /// Get the latest revision number stored on the revision map.
 ///
 /// This will first deserialize the header to find the map location and then deserialize the
@@ -230,3 +454,109 @@ pub fn is_zeroed(buf: &[u8]) -> bool {
             && chunks.remainder() == &ZEROS[..chunks.remainder().len()]
     }
 }
+
+/// Check the rule definition references of a `RuleSet`.  `roots` holds the names referenced by
+/// the operation rules and `definitions` maps each definition name to the names it references.
+/// Every referenced name must be defined and definitions must not reference each other in a
+/// cycle.
+pub fn assert_valid_references(
+    roots: &[String],
+    definitions: &HashMap<String, Vec<String>>,
+) -> Result<(), RuleSetError> {
+    // Visit state of each definition: `false` while on the current path, `true` once done.
+    let mut visited: HashMap<&str, bool> = HashMap::with_capacity(definitions.len());
+
+    fn visit<'d>(
+        name: &str,
+        definitions: &'d HashMap<String, Vec<String>>,
+        visited: &mut HashMap<&'d str, bool>,
+    ) -> Result<(), RuleSetError> {
+        match visited.get(name) {
+            Some(true) => return Ok(()),
+            Some(false) => {
+                msg!("Rule definition {} references itself", name);
+                return Err(RuleSetError::RuleDefinitionCycle);
+            }
+            None => (),
+        }
+
+        let (name, references) = match definitions.get_key_value(name) {
+            Some((name, references)) => (name.as_str(), references),
+            None => {
+                msg!("Rule definition {} not found", name);
+                return Err(RuleSetError::RuleDefinitionNotFound);
+            }
+        };
+
+        visited.insert(name, false);
+
+        for reference in references {
+            visit(reference, definitions, visited)?;
+        }
+
+        visited.insert(name, true);
+
+        Ok(())
+    }
+
+    // Unreferenced definitions are checked as well so that a stored `RuleSet` never contains
+    // a definition that cannot be evaluated.
+    for name in roots.iter().chain(definitions.keys()) {
+        visit(name, definitions, &mut visited)?;
+    }
+
+    Ok(())
+}
+
+/// Validate an operation of a `RuleSet` stored in another PDA.  The PDA is looked up in the
+/// `accounts` and must be owned by this program.  The `depth` is the depth of the `RuleSet`
+/// containing the rule that references the external `RuleSet`.
+///
+/// Rule state updates are not supported in the external `RuleSet`, since the `RuleSet` state
+/// PDA is derived from the `RuleSet` being validated.
+pub fn validate_external_rule_set(
+    rule_set: &Pubkey,
+    operation: String,
+    revision: Option<usize>,
+    depth: u8,
+    accounts: &HashMap<Pubkey, &AccountInfo>,
+    payload: &Payload,
+    rule_authority: &Option<&AccountInfo>,
+) -> RuleResult {
+    // Bound the number of `RuleSet`s loaded in a single validation.
+    if depth >= MAX_RULE_SET_DEPTH {
+        return RuleResult::Error(RuleSetError::RuleSetDepthExceeded.into());
+    }
+
+    let rule_set_pda_info = match accounts.get(rule_set) {
+        Some(account) => account,
+        _ => return RuleResult::Error(RuleSetError::MissingAccount.into()),
+    };
+
+    // `RuleSet` must be owned by this program.
+    if *rule_set_pda_info.owner != crate::ID {
+        return RuleResult::Error(RuleSetError::IncorrectOwner.into());
+    }
+
+    let (start, end) = match get_rule_set_revision_location(rule_set_pda_info, revision) {
+        Ok(location) => location,
+        Err(err) => return RuleResult::Error(err),
+    };
+
+    let data = match rule_set_pda_info.data.try_borrow() {
+        Ok(data) => data,
+        Err(_) => return RuleResult::Error(ProgramError::AccountBorrowFailed),
+    };
+
+    let external = match deserialize_rule_set(&data, start, end, depth + 1) {
+        Ok(external) => external,
+        Err(err) => return RuleResult::Error(err),
+    };
+
+    let rule = match external.get_rule_with_definitions(operation) {
+        Ok(rule) => rule,
+        Err(err) => return RuleResult::Error(err),
+    };
+
+    rule.low_level_validate(accounts, payload, false, &None, rule_authority)
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    payload::{Payload, PayloadType},
+    state::{AmountRange, AmountRatio, RuleSetV2},
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use utils::program_test;
+
+const SALE: &str = "Sale";
+const ROYALTY: &str = "Royalty";
+
+const PRICE: &str = "Price";
+
+// Create a `validate` instruction for an operation with a payload.
+fn validate_ix(rule_set_addr: Pubkey, operation: &str, payload: Payload) -> Instruction {
+    ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(Keypair::new().pubkey())
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: operation.to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction()
+}
+
+// Create a payload with a royalty and a price.
+fn royalty_payload(royalty: PayloadType, price: u64) -> Payload {
+    Payload::from([
+        (ROYALTY.to_string(), royalty),
+        (PRICE.to_string(), PayloadType::Number(price)),
+    ])
+}
+
+#[tokio::test]
+async fn amount_range_and_ratio_v2() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create some rules.
+    let sale_rule = AmountRange::serialize(PRICE.to_string(), 10, 20).unwrap();
+    // At least 5% royalties must be paid.
+    let royalty_rule =
+        AmountRatio::serialize(ROYALTY.to_string(), PRICE.to_string(), 5, 100).unwrap();
+
+    // Create a RuleSet.
+    let rule_set = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[SALE.to_string(), ROYALTY.to_string()],
+        &[&sale_rule, &royalty_rule],
+    )
+    .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain_serialized!(&mut context, rule_set, "test rule_set".to_string())
+            .await;
+
+    // --------------------------------
+    // Validate AmountRange
+    // --------------------------------
+    // Both bounds are inclusive.
+    for price in [10, 20] {
+        let payload = Payload::from([(PRICE.to_string(), PayloadType::Number(price))]);
+        let ix = validate_ix(rule_set_addr, SALE, payload);
+        process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+    }
+
+    for price in [9, 21] {
+        let payload = Payload::from([(PRICE.to_string(), PayloadType::Number(price))]);
+        let ix = validate_ix(rule_set_addr, SALE, payload);
+        let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+        assert_custom_error!(err, RuleSetError::AmountRangeCheckFailed);
+    }
+
+    // --------------------------------
+    // Validate AmountRatio
+    // --------------------------------
+    // Exactly 5% of the price is paid.
+    let payload = royalty_payload(PayloadType::Number(50), 1_000);
+    let ix = validate_ix(rule_set_addr, ROYALTY, payload);
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // Less than 5% of the price is paid.
+    let payload = royalty_payload(PayloadType::Number(49), 1_000);
+    let ix = validate_ix(rule_set_addr, ROYALTY, payload);
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::AmountRatioCheckFailed);
+
+    // The values do not overflow with the largest `u64` amounts.
+    let payload = royalty_payload(PayloadType::Number(u64::MAX), u64::MAX);
+    let ix = validate_ix(rule_set_addr, ROYALTY, payload);
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // A `u128` amount can overflow, which is an error rather than a failure.
+    let payload = royalty_payload(PayloadType::U128(u128::MAX), 1_000);
+    let ix = validate_ix(rule_set_addr, ROYALTY, payload);
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::NumericalOverflow);
+}
@@ -0,0 +1,654 @@
+#![cfg(feature = "client")]
+
+// Property-based differential tests checking that equivalent V1 and V2 rules agree on the
+// outcome of a validation, including the error codes.
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    payload::{Payload, PayloadType, ProofInfo, SeedsVec},
+    state::{
+        AdditionalSigner, All, Amount, AmountI64, AmountRange, AmountRatio, AmountU128, Any,
+        BoolMatch, BytesMatch, CompareOp, FieldCompare, IfThenElse, IsWallet, MatchOp,
+        MatchOperator, Not, Operator, PDAMatch, Pass, ProgramOwned, ProgramOwnedList,
+        ProgramOwnedTree, PubkeyListContains, PubkeyListMatch, PubkeyMatch, PubkeyTreeMatch, Rule,
+        RuleResult, RuleSetV1, RuleSetV2, StrMatch, Threshold,
+    },
+    types::RuleSet,
+    utils::compute_merkle_root,
+};
+use proptest::{collection::vec, prelude::*, sample::select};
+use solana_program::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, system_program,
+};
+use std::collections::HashMap;
+
+// Pubkeys shared by the rules, payloads and accounts, so that they refer to each other.
+const KEYS: usize = 6;
+
+// Payload fields, grouped by the type of their value.  Rules on a `Pubkey` accept a list of
+// fields separated by `|`.
+const PUBKEY_FIELDS: [&str; 4] = ["Destination", "Source", "Authority", "Destination|Source"];
+const NUMBER_FIELDS: [&str; 2] = ["Amount", "Limit"];
+const I64_FIELDS: [&str; 1] = ["Delta"];
+const U128_FIELDS: [&str; 1] = ["Supply"];
+const BOOL_FIELDS: [&str; 1] = ["Flag"];
+const BYTES_FIELDS: [&str; 1] = ["Memo"];
+const STR_FIELDS: [&str; 1] = ["Name"];
+const PUBKEY_LIST_FIELDS: [&str; 1] = ["Signers"];
+const SEEDS_FIELDS: [&str; 1] = ["Seeds"];
+const PROOF_FIELDS: [&str; 1] = ["Proof"];
+
+const FIELDS: [&str; 14] = [
+    "Destination",
+    "Source",
+    "Authority",
+    "Destination|Source",
+    "Amount",
+    "Limit",
+    "Delta",
+    "Supply",
+    "Flag",
+    "Memo",
+    "Name",
+    "Signers",
+    "Seeds",
+    "Proof",
+];
+
+// Number of merkle proofs that can be passed in the payload.
+const PROOFS: usize = 3;
+
+const OPERATION: &str = "transfer";
+
+fn key(index: usize) -> Pubkey {
+    match index {
+        0 => system_program::ID,
+        1 => mpl_token_auth_rules::ID,
+        _ => Pubkey::new_from_array([index as u8; 32]),
+    }
+}
+
+fn proof(index: usize) -> ProofInfo {
+    ProofInfo::new((0..index).map(|node| [node as u8 + 1; 32]).collect())
+}
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    (0..KEYS).prop_map(key)
+}
+
+// Rules mostly read a field of the type they expect, and sometimes any other field.
+fn field(fields: &'static [&'static str]) -> impl Strategy<Value = String> {
+    prop_oneof![7 => select(fields), 1 => select(FIELDS.as_slice())].prop_map(String::from)
+}
+
+// Amounts are drawn from a small range so that comparisons between fields can succeed.
+fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![0..4u64, any::<u64>()]
+}
+
+fn u128_amount() -> impl Strategy<Value = u128> {
+    prop_oneof![0..4u128, any::<u128>()]
+}
+
+fn operator() -> impl Strategy<Value = CompareOp> {
+    prop_oneof![
+        Just(CompareOp::Lt),
+        Just(CompareOp::LtEq),
+        Just(CompareOp::Eq),
+        Just(CompareOp::GtEq),
+        Just(CompareOp::Gt),
+    ]
+}
+
+fn match_operator() -> impl Strategy<Value = MatchOp> {
+    prop_oneof![Just(MatchOp::Eq), Just(MatchOp::Prefix)]
+}
+
+// The root of a tree containing a key from the pool, committed with a proof from the pool.
+fn root() -> impl Strategy<Value = [u8; 32]> {
+    prop_oneof![
+        (pubkey(), 0..PROOFS).prop_map(|(leaf, index)| compute_merkle_root(&leaf, &proof(index))),
+        any::<[u8; 32]>(),
+    ]
+}
+
+fn leaf_rule() -> impl Strategy<Value = Rule> {
+    prop_oneof![
+        Just(Rule::Pass),
+        pubkey().prop_map(|account| Rule::AdditionalSigner { account }),
+        (pubkey(), field(&PUBKEY_FIELDS))
+            .prop_map(|(pubkey, field)| Rule::PubkeyMatch { pubkey, field }),
+        (vec(pubkey(), 0..4), field(&PUBKEY_FIELDS))
+            .prop_map(|(pubkeys, field)| Rule::PubkeyListMatch { pubkeys, field }),
+        (pubkey(), field(&PUBKEY_FIELDS))
+            .prop_map(|(program, field)| Rule::ProgramOwned { program, field }),
+        (vec(pubkey(), 0..4), field(&PUBKEY_FIELDS))
+            .prop_map(|(programs, field)| Rule::ProgramOwnedList { programs, field }),
+        (vec(pubkey(), 0..4), field(&PUBKEY_FIELDS)).prop_map(|(programs, field)| {
+            Rule::ProgramOwnedSet {
+                programs: programs.into_iter().collect(),
+                field,
+            }
+        }),
+        (root(), field(&PUBKEY_FIELDS), field(&PROOF_FIELDS)).prop_map(
+            |(root, pubkey_field, proof_field)| {
+                Rule::PubkeyTreeMatch {
+                    root,
+                    pubkey_field,
+                    proof_field,
+                }
+            }
+        ),
+        (root(), field(&PUBKEY_FIELDS), field(&PROOF_FIELDS)).prop_map(
+            |(root, pubkey_field, proof_field)| {
+                Rule::ProgramOwnedTree {
+                    root,
+                    pubkey_field,
+                    proof_field,
+                }
+            }
+        ),
+        // V2 stores a missing program as the default pubkey, so it is not used as a program.
+        (
+            prop::option::of(1..KEYS),
+            field(&PUBKEY_FIELDS),
+            field(&SEEDS_FIELDS)
+        )
+            .prop_map(|(program, pda_field, seeds_field)| Rule::PDAMatch {
+                program: program.map(key),
+                pda_field,
+                seeds_field,
+            }),
+        (amount(), operator(), field(&NUMBER_FIELDS)).prop_map(|(amount, operator, field)| {
+            Rule::Amount {
+                amount,
+                operator,
+                field,
+            }
+        }),
+        (any::<i64>(), operator(), field(&I64_FIELDS)).prop_map(|(amount, operator, field)| {
+            Rule::AmountI64 {
+                amount,
+                operator,
+                field,
+            }
+        }),
+        (u128_amount(), operator(), field(&U128_FIELDS)).prop_map(|(amount, operator, field)| {
+            Rule::AmountU128 {
+                amount,
+                operator,
+                field,
+            }
+        }),
+        (amount(), amount(), field(&NUMBER_FIELDS))
+            .prop_map(|(min, max, field)| Rule::AmountRange { min, max, field }),
+        (
+            amount(),
+            amount(),
+            field(&NUMBER_FIELDS),
+            field(&NUMBER_FIELDS)
+        )
+            .prop_map(|(num, denom, numerator_field, denominator_field)| {
+                Rule::AmountRatio {
+                    num,
+                    denom,
+                    numerator_field,
+                    denominator_field,
+                }
+            }),
+        prop_oneof![
+            (field(&NUMBER_FIELDS), operator(), field(&NUMBER_FIELDS)),
+            (field(&PUBKEY_FIELDS), operator(), field(&PUBKEY_FIELDS)),
+        ]
+        .prop_map(|(left_field, operator, right_field)| {
+            Rule::FieldCompare {
+                left_field,
+                operator,
+                right_field,
+            }
+        }),
+        field(&PUBKEY_FIELDS).prop_map(|field| Rule::IsWallet { field }),
+        (
+            vec(any::<u8>(), 0..4),
+            match_operator(),
+            field(&BYTES_FIELDS)
+        )
+            .prop_map(|(value, operator, field)| {
+                Rule::BytesMatch {
+                    value,
+                    operator,
+                    field,
+                }
+            }),
+        ("[ab]{0,3}", match_operator(), field(&STR_FIELDS)).prop_map(|(value, operator, field)| {
+            Rule::StrMatch {
+                value,
+                operator,
+                field,
+            }
+        }),
+        (any::<bool>(), field(&BOOL_FIELDS))
+            .prop_map(|(value, field)| Rule::BoolMatch { value, field }),
+        (pubkey(), field(&PUBKEY_LIST_FIELDS))
+            .prop_map(|(pubkey, field)| Rule::PubkeyListContains { pubkey, field }),
+    ]
+}
+
+fn rule() -> impl Strategy<Value = Rule> {
+    leaf_rule().prop_recursive(3, 24, 3, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 1..=3).prop_map(|rules| Rule::All { rules }),
+            vec(inner.clone(), 1..=3).prop_map(|rules| Rule::Any { rules }),
+            inner.clone().prop_map(|rule| Rule::Not {
+                rule: Box::new(rule)
+            }),
+            (0..=4u64, vec(inner.clone(), 1..=3))
+                .prop_map(|(k, rules)| Rule::Threshold { k, rules }),
+            (inner.clone(), inner.clone(), inner).prop_map(|(condition, then, otherwise)| {
+                Rule::IfThenElse {
+                    condition: Box::new(condition),
+                    then: Box::new(then),
+                    otherwise: Box::new(otherwise),
+                }
+            }),
+        ]
+    })
+}
+
+fn seeds() -> impl Strategy<Value = SeedsVec> {
+    vec(vec(any::<u8>(), 0..4), 0..3).prop_map(SeedsVec::new)
+}
+
+fn proof_info() -> impl Strategy<Value = ProofInfo> {
+    (0..PROOFS).prop_map(proof)
+}
+
+fn value() -> impl Strategy<Value = PayloadType> {
+    prop_oneof![
+        pubkey().prop_map(PayloadType::Pubkey),
+        amount().prop_map(PayloadType::Number),
+        any::<i64>().prop_map(PayloadType::I64),
+        u128_amount().prop_map(PayloadType::U128),
+        any::<bool>().prop_map(PayloadType::Bool),
+        vec(any::<u8>(), 0..4).prop_map(PayloadType::Bytes),
+        "[ab]{0,3}".prop_map(PayloadType::Str),
+        vec(pubkey(), 0..4).prop_map(PayloadType::PubkeyList),
+        seeds().prop_map(PayloadType::Seeds),
+        proof_info().prop_map(PayloadType::MerkleProof),
+    ]
+}
+
+// A payload entry that is usually of the type of the field, and is sometimes missing or of
+// another type.
+fn entry(
+    field: &'static str,
+    value: impl Strategy<Value = PayloadType> + 'static,
+) -> BoxedStrategy<Option<(String, PayloadType)>> {
+    prop_oneof![
+        8 => value.prop_map(Some),
+        1 => self::value().prop_map(Some),
+        1 => Just(None),
+    ]
+    .prop_map(move |value| value.map(|value| (field.to_string(), value)))
+    .boxed()
+}
+
+fn payload() -> impl Strategy<Value = Payload> {
+    vec![
+        entry("Destination", pubkey().prop_map(PayloadType::Pubkey)),
+        entry("Source", pubkey().prop_map(PayloadType::Pubkey)),
+        entry("Authority", pubkey().prop_map(PayloadType::Pubkey)),
+        entry("Amount", amount().prop_map(PayloadType::Number)),
+        entry("Limit", amount().prop_map(PayloadType::Number)),
+        entry("Delta", any::<i64>().prop_map(PayloadType::I64)),
+        entry("Supply", u128_amount().prop_map(PayloadType::U128)),
+        entry("Flag", any::<bool>().prop_map(PayloadType::Bool)),
+        entry("Memo", vec(any::<u8>(), 0..4).prop_map(PayloadType::Bytes)),
+        entry("Name", "[ab]{0,3}".prop_map(PayloadType::Str)),
+        entry(
+            "Signers",
+            vec(pubkey(), 0..4).prop_map(PayloadType::PubkeyList),
+        ),
+        entry("Seeds", seeds().prop_map(PayloadType::Seeds)),
+        entry("Proof", proof_info().prop_map(PayloadType::MerkleProof)),
+    ]
+    .prop_map(|entries| {
+        let mut payload = Payload::new();
+
+        for (field, value) in entries.into_iter().flatten() {
+            payload.insert(field, value);
+        }
+
+        payload
+    })
+}
+
+// Accounts as (key, owner, data, is_signer).
+fn accounts() -> impl Strategy<Value = Vec<(Pubkey, Pubkey, Vec<u8>, bool)>> {
+    let data = prop_oneof![Just(Vec::new()), Just(vec![0; 8]), vec(any::<u8>(), 1..8),];
+
+    vec((pubkey(), pubkey(), data, any::<bool>()), 0..6)
+}
+
+fn to_operator(operator: &CompareOp) -> Operator {
+    match operator {
+        CompareOp::Lt => Operator::Lt,
+        CompareOp::LtEq => Operator::LtEq,
+        CompareOp::Eq => Operator::Eq,
+        CompareOp::GtEq => Operator::GtEq,
+        CompareOp::Gt => Operator::Gt,
+    }
+}
+
+fn to_match_operator(operator: &MatchOp) -> MatchOperator {
+    match operator {
+        MatchOp::Eq => MatchOperator::Eq,
+        MatchOp::Prefix => MatchOperator::Prefix,
+    }
+}
+
+fn slices(rules: &[Vec<u8>]) -> Vec<&[u8]> {
+    rules.iter().map(|rule| rule.as_slice()).collect()
+}
+
+// Serializes the V2 rule equivalent to a V1 rule.
+fn to_v2(rule: &Rule) -> Vec<u8> {
+    let rules = |rules: &[Rule]| rules.iter().map(to_v2).collect::<Vec<_>>();
+
+    match rule {
+        Rule::Pass => Pass::serialize(),
+        Rule::AdditionalSigner { account } => AdditionalSigner::serialize(*account),
+        Rule::PubkeyMatch { pubkey, field } => PubkeyMatch::serialize(field.clone(), *pubkey),
+        // V1 validates a `PubkeyListMatch` on several fields as a `ProgramOwnedList` on each
+        // field, unlike V2 which checks that one of the fields is in the list.  V1 is kept as it
+        // is for the `RuleSet`s already deployed, so its V2 equivalent is the list of programs.
+        Rule::PubkeyListMatch { pubkeys, field } if field.contains('|') => {
+            let rules = field
+                .split('|')
+                .map(|field| ProgramOwnedList::serialize(field.to_string(), pubkeys).unwrap())
+                .collect::<Vec<_>>();
+            Any::serialize(&slices(&rules))
+        }
+        Rule::PubkeyListMatch { pubkeys, field } => {
+            PubkeyListMatch::serialize(field.clone(), pubkeys)
+        }
+        Rule::ProgramOwned { program, field } => ProgramOwned::serialize(field.clone(), *program),
+        Rule::ProgramOwnedList { programs, field } => {
+            ProgramOwnedList::serialize(field.clone(), programs)
+        }
+        // The sorted list is the V2 counterpart of the set, failing with the error of the list.
+        Rule::ProgramOwnedSet { programs, field } => ProgramOwnedList::serialize_sorted(
+            field.clone(),
+            &programs.iter().copied().collect::<Vec<_>>(),
+        ),
+        Rule::PubkeyTreeMatch {
+            root,
+            pubkey_field,
+            proof_field,
+        } => PubkeyTreeMatch::serialize(pubkey_field.clone(), proof_field.clone(), root),
+        Rule::ProgramOwnedTree {
+            root,
+            pubkey_field,
+            proof_field,
+        } => ProgramOwnedTree::serialize(pubkey_field.clone(), proof_field.clone(), root),
+        Rule::PDAMatch {
+            program,
+            pda_field,
+            seeds_field,
+        } => PDAMatch::serialize(pda_field.clone(), *program, seeds_field.clone()),
+        Rule::Amount {
+            amount,
+            operator,
+            field,
+        } => Amount::serialize(field.clone(), to_operator(operator), *amount),
+        Rule::AmountI64 {
+            amount,
+            operator,
+            field,
+        } => AmountI64::serialize(field.clone(), to_operator(operator), *amount),
+        Rule::AmountU128 {
+            amount,
+            operator,
+            field,
+        } => AmountU128::serialize(field.clone(), to_operator(operator), *amount),
+        Rule::AmountRange { min, max, field } => AmountRange::serialize(field.clone(), *min, *max),
+        Rule::AmountRatio {
+            num,
+            denom,
+            numerator_field,
+            denominator_field,
+        } => AmountRatio::serialize(
+            numerator_field.clone(),
+            denominator_field.clone(),
+            *num,
+            *denom,
+        ),
+        Rule::FieldCompare {
+            left_field,
+            operator,
+            right_field,
+        } => FieldCompare::serialize(
+            left_field.clone(),
+            to_operator(operator),
+            right_field.clone(),
+        ),
+        Rule::IsWallet { field } => IsWallet::serialize(field.clone()),
+        Rule::BytesMatch {
+            value,
+            operator,
+            field,
+        } => BytesMatch::serialize(field.clone(), to_match_operator(operator), value.clone()),
+        Rule::StrMatch {
+            value,
+            operator,
+            field,
+        } => StrMatch::serialize(field.clone(), to_match_operator(operator), value.clone()),
+        Rule::BoolMatch { value, field } => BoolMatch::serialize(field.clone(), *value),
+        Rule::PubkeyListContains { pubkey, field } => {
+            PubkeyListContains::serialize(field.clone(), *pubkey)
+        }
+        Rule::All { rules: children } => All::serialize(&slices(&rules(children))),
+        Rule::Any { rules: children } => Any::serialize(&slices(&rules(children))),
+        Rule::Not { rule } => Not::serialize(&to_v2(rule)),
+        Rule::Threshold { k, rules: children } => {
+            Threshold::serialize(*k, &slices(&rules(children)))
+        }
+        Rule::IfThenElse {
+            condition,
+            then,
+            otherwise,
+        } => IfThenElse::serialize(&to_v2(condition), &to_v2(then), &to_v2(otherwise)),
+        _ => unreachable!("rule is not generated"),
+    }
+    .unwrap()
+}
+
+// Outcome of a validation.  The error returned with a success is not compared.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Success,
+    Failure(ProgramError),
+    Error(ProgramError),
+}
+
+fn validate(
+    rule_set: &dyn RuleSet,
+    payload: &Payload,
+    accounts: &HashMap<Pubkey, &AccountInfo>,
+) -> Outcome {
+    let rule = match rule_set.get_rule_with_definitions(OPERATION.to_string()) {
+        Ok(rule) => rule,
+        Err(error) => return Outcome::Error(error),
+    };
+
+    match rule.low_level_validate(accounts, payload, false, &None, &None) {
+        RuleResult::Success(_) => Outcome::Success,
+        RuleResult::Failure(error) => Outcome::Failure(error),
+        RuleResult::Error(error) => Outcome::Error(error),
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1024))]
+
+    #[test]
+    fn v1_and_v2_agree(rule in rule(), payload in payload(), accounts in accounts()) {
+        let owner = Pubkey::new_unique();
+
+        let mut v1 = RuleSetV1::new("Differential".to_string(), owner);
+        v1.add(OPERATION.to_string(), rule.clone()).unwrap();
+
+        let serialized = RuleSetV2::serialize(
+            owner,
+            "Differential",
+            &[OPERATION.to_string()],
+            &[&to_v2(&rule)],
+        )
+        .unwrap();
+        let v2 = RuleSetV2::from_bytes(&serialized).unwrap();
+
+        let mut accounts = accounts
+            .into_iter()
+            .map(|(key, owner, data, is_signer)| (key, owner, 0u64, data, is_signer))
+            .collect::<Vec<_>>();
+        let infos = accounts
+            .iter_mut()
+            .map(|(key, owner, lamports, data, is_signer)| {
+                AccountInfo::new(key, *is_signer, false, lamports, data, owner, false, 0)
+            })
+            .collect::<Vec<_>>();
+        let accounts = infos
+            .iter()
+            .map(|info| (*info.key, info))
+            .collect::<HashMap<Pubkey, &AccountInfo>>();
+
+        // A V1 `ProgramOwnedSet` fails with its own error, while its V2 counterpart fails with
+        // the error of `ProgramOwnedList`.
+        let v1_outcome = match validate(&v1, &payload, &accounts) {
+            Outcome::Failure(error) if error == RuleSetError::ProgramOwnedSetCheckFailed.into() => {
+                Outcome::Failure(RuleSetError::ProgramOwnedListCheckFailed.into())
+            }
+            outcome => outcome,
+        };
+
+        prop_assert_eq!(
+            v1_outcome,
+            validate(&v2, &payload, &accounts),
+            "{:?}",
+            rule
+        );
+    }
+}
+
+#[test]
+fn invalid_operator_is_an_error() {
+    // An operator outside of the range of `Operator` cannot be deserialized in V1, so V2
+    // returns an error rather than a failure.
+    let mut amount = Amount::serialize("Amount".to_string(), Operator::Eq, 1).unwrap();
+    amount[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+
+    let serialized = RuleSetV2::serialize(
+        Pubkey::default(),
+        "Invalid",
+        &[OPERATION.to_string()],
+        &[&amount],
+    )
+    .unwrap();
+    let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+    let payload = Payload::from([("Amount".to_string(), PayloadType::Number(1))]);
+
+    assert_eq!(
+        validate(&rule_set, &payload, &HashMap::new()),
+        Outcome::Error(RuleSetError::InvalidCompareOp.into())
+    );
+}
+
+#[test]
+fn v1_multi_field_pubkey_list_match_checks_program_owners() {
+    // V1 validates a `PubkeyListMatch` on several fields as a `ProgramOwnedList` on each field,
+    // so a listed key passes only when it is an account owned by a listed program.
+    let listed = key(2);
+    let rule = Rule::PubkeyListMatch {
+        pubkeys: vec![listed],
+        field: "Destination|Source".to_string(),
+    };
+
+    let mut v1 = RuleSetV1::new("Differential".to_string(), Pubkey::default());
+    v1.add(OPERATION.to_string(), rule).unwrap();
+
+    // The listed key itself is not an account owned by a listed program.
+    let payload = Payload::from([
+        ("Destination".to_string(), PayloadType::Pubkey(listed)),
+        ("Source".to_string(), PayloadType::Pubkey(listed)),
+    ]);
+    assert_eq!(
+        validate(&v1, &payload, &HashMap::new()),
+        Outcome::Error(RuleSetError::MissingAccount.into())
+    );
+
+    // Both fields are an account owned by the listed program.
+    let destination = key(3);
+    let mut lamports = 0;
+    let mut data = vec![1; 8];
+    let info = AccountInfo::new(
+        &destination,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &listed,
+        false,
+        0,
+    );
+    let accounts = HashMap::from([(destination, &info)]);
+
+    let payload = Payload::from([
+        ("Destination".to_string(), PayloadType::Pubkey(destination)),
+        ("Source".to_string(), PayloadType::Pubkey(destination)),
+    ]);
+    assert_eq!(validate(&v1, &payload, &accounts), Outcome::Success);
+}
+
+#[test]
+fn sorted_program_owned_list_fails_with_the_list_error() {
+    // The sorted layout of a `ProgramOwnedList` fails with the error of the V1 list.
+    let programs = vec![key(2), key(1)];
+    let account = key(3);
+    let owner = key(4);
+    let rule = Rule::ProgramOwnedList {
+        programs: programs.clone(),
+        field: "Destination".to_string(),
+    };
+
+    let mut v1 = RuleSetV1::new("Differential".to_string(), Pubkey::default());
+    v1.add(OPERATION.to_string(), rule).unwrap();
+
+    let sorted = ProgramOwnedList::serialize_sorted("Destination".to_string(), &programs).unwrap();
+    let serialized = RuleSetV2::serialize(
+        Pubkey::default(),
+        "Differential",
+        &[OPERATION.to_string()],
+        &[&sorted],
+    )
+    .unwrap();
+    let v2 = RuleSetV2::from_bytes(&serialized).unwrap();
+
+    // The account is owned by a program that is not listed.
+    let mut lamports = 0;
+    let mut data = vec![1; 8];
+    let info = AccountInfo::new(
+        &account,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+    let accounts = HashMap::from([(account, &info)]);
+    let payload = Payload::from([("Destination".to_string(), PayloadType::Pubkey(account))]);
+
+    let failure = Outcome::Failure(RuleSetError::ProgramOwnedListCheckFailed.into());
+    assert_eq!(validate(&v1, &payload, &accounts), failure);
+    assert_eq!(validate(&v2, &payload, &accounts), failure);
+}
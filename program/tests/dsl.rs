@@ -0,0 +1,269 @@
+#![cfg(feature = "client")]
+
+use mpl_token_auth_rules::{
+    dsl::{compile_v1, compile_v2, DslError, Expr, Policy, Span},
+    state::{CompareOp, Rule, RuleSetV2},
+    types::RuleSet,
+};
+use solana_program::pubkey;
+
+const EXAMPLE: &str = r#"
+rule_set "Marketplace Rules"
+owner AuthxYNhPnnrGBo1wdzeUdukrsFpHvR42wghx8ZPNEo4
+
+// Constants hold pubkeys or lists of pubkeys.
+const TOKEN = TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA
+const MARKETS = [TOKEN, M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K]
+
+// Definitions can be shared by several operations with `rule_ref`.
+define allowed => any(program_owned_list(Destination, MARKETS), is_wallet(Destination))
+
+transfer:owner => all(rule_ref(allowed), amount(Amount, <=, 1))
+"Delegate:Sale" => pass
+"#;
+
+// Policy using every rule of the language.
+const EVERY_RULE: &str = r#"
+rule_set "Every Rule"
+owner AuthxYNhPnnrGBo1wdzeUdukrsFpHvR42wghx8ZPNEo4
+
+const SYSTEM = 11111111111111111111111111111111
+const TOKEN = TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA
+
+define wallet => is_wallet(Destination | Source)
+
+Composite => all(
+    any(rule_ref(wallet), not(namespace)),
+    threshold(1, pass, additional_signer(SYSTEM)),
+    if_then_else(bool_match(Flag, true), frequency(TOKEN), pass),
+)
+External => external_rule_set(TOKEN, "Transfer:Owner")
+ExternalRevision => external_rule_set(TOKEN, Transfer, 3)
+Pubkeys => any(
+    pubkey_match(Destination, SYSTEM),
+    pubkey_list_match(Destination, [TOKEN, SYSTEM]),
+    pubkey_list_match_sorted(Destination, [TOKEN, SYSTEM, TOKEN]),
+    pubkey_list_contains(Authorities, TOKEN),
+)
+Programs => any(
+    program_owned(Destination, TOKEN),
+    program_owned_list(Destination, [TOKEN, SYSTEM]),
+    program_owned_set(Destination, [TOKEN, SYSTEM]),
+)
+Trees => any(
+    pubkey_tree_match(Destination, Proof, 0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20),
+    program_owned_tree(Destination, Proof, 0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20),
+    pubkey_tree_exclusion(Destination, Proof, 0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20),
+    pubkey_tree_match_registry(Destination, Proof, SYSTEM),
+    program_owned_tree_registry(Destination, Proof, SYSTEM),
+)
+Lists => any(pubkey_in_list_account(Destination, SYSTEM), owner_in_list_account(Destination, TOKEN))
+Pdas => any(pda_match(Destination, Seeds), pda_match(Destination, Seeds, TOKEN))
+Amounts => all(
+    amount(Amount, <, 10),
+    amount_i64(Delta, >=, -5),
+    amount_u128(Total, ==, 340282366920938463463374607431768211455),
+    amount_range(Amount, 1, 100),
+    amount_ratio(Fee, Amount, 1, 20),
+    field_compare(Amount, >, Fee),
+)
+Matches => any(bytes_match(Memo, prefix, 0x01ff), str_match("Memo Text", ==, "a \"quoted\" value"))
+"#;
+
+// Compiles `source` and returns the error, which must be located at `text` in the source.
+fn error_at(source: &str, text: &str) -> DslError {
+    let error = compile_v2(source).unwrap_err();
+    let start = source.find(text).unwrap();
+
+    assert_eq!(
+        error.span,
+        Span::new(start, start + text.len()),
+        "{}",
+        error
+    );
+
+    error
+}
+
+#[test]
+fn example_compiles() {
+    let rule_set = compile_v1(EXAMPLE).unwrap();
+
+    assert_eq!(rule_set.name(), "Marketplace Rules");
+    assert_eq!(
+        rule_set.owner(),
+        &pubkey!("AuthxYNhPnnrGBo1wdzeUdukrsFpHvR42wghx8ZPNEo4")
+    );
+    assert_eq!(rule_set.operations.len(), 2);
+    assert_eq!(rule_set.operations["Delegate:Sale"], Rule::Pass);
+    assert_eq!(
+        rule_set.operations["transfer:owner"],
+        Rule::All {
+            rules: vec![
+                Rule::RuleRef {
+                    name: "allowed".to_string()
+                },
+                Rule::Amount {
+                    amount: 1,
+                    operator: CompareOp::LtEq,
+                    field: "Amount".to_string(),
+                },
+            ]
+        }
+    );
+    assert_eq!(
+        rule_set.definitions["allowed"],
+        Rule::Any {
+            rules: vec![
+                Rule::ProgramOwnedList {
+                    programs: vec![
+                        pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+                        pubkey!("M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K"),
+                    ],
+                    field: "Destination".to_string(),
+                },
+                Rule::IsWallet {
+                    field: "Destination".to_string(),
+                },
+            ]
+        }
+    );
+
+    let serialized = compile_v2(EXAMPLE).unwrap();
+    let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+
+    assert_eq!(rule_set.name(), "Marketplace Rules");
+    assert!(rule_set
+        .get("transfer:owner".to_string())
+        .unwrap()
+        .is_some());
+    assert!(rule_set.get("Delegate:Sale".to_string()).unwrap().is_some());
+    rule_set.assert_valid_references().unwrap();
+}
+
+#[test]
+fn v1_round_trips() {
+    for source in [EXAMPLE, EVERY_RULE] {
+        let rule_set = compile_v1(source).unwrap();
+
+        // The decompiled policy prints to a source compiling to the same rule set.
+        let printed = Policy::from_v1(&rule_set).to_string();
+        assert_eq!(compile_v1(&printed).unwrap(), rule_set, "{}", printed);
+
+        // Printing is stable.
+        let policy = Policy::parse(&printed).unwrap();
+        assert_eq!(policy.to_string(), printed);
+    }
+}
+
+#[test]
+fn v2_round_trips() {
+    for source in [EXAMPLE, EVERY_RULE] {
+        let serialized = compile_v2(source).unwrap();
+        let rule_set = RuleSetV2::from_bytes(&serialized).unwrap();
+
+        let printed = Policy::from_v2(&rule_set).unwrap().to_string();
+        assert_eq!(compile_v2(&printed).unwrap(), serialized, "{}", printed);
+    }
+}
+
+#[test]
+fn sorted_lists_are_deduplicated() {
+    let source = r#"
+        rule_set "Sorted"
+        owner 11111111111111111111111111111111
+        Transfer => pubkey_list_match_sorted(Destination, [
+            TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA,
+            11111111111111111111111111111111,
+            TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA,
+        ])
+    "#;
+
+    assert_eq!(
+        compile_v1(source).unwrap().operations["Transfer"],
+        Rule::PubkeyListMatch {
+            pubkeys: vec![
+                pubkey!("11111111111111111111111111111111"),
+                pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+            ],
+            field: "Destination".to_string(),
+        }
+    );
+}
+
+#[test]
+fn errors_are_located() {
+    let header = "rule_set \"Errors\"\nowner 11111111111111111111111111111111\n";
+
+    let error = error_at(&format!("{}Transfer => al(pass)", header), "al");
+    assert_eq!(error.message, "unknown rule `al`");
+
+    let source = format!("{}Transfer => not(pass, pass)", header);
+    let error = error_at(&source, "not(pass, pass)");
+    assert_eq!(error.message, "`not` expects 1 argument, found 2");
+
+    let error = error_at(
+        &format!("{}Transfer => additional_signer(Wallet0)", header),
+        "Wallet0",
+    );
+    assert_eq!(error.message, "`Wallet0` is not a pubkey");
+
+    let error = error_at(
+        &format!("{}Transfer => rule_ref(missing)", header),
+        "missing",
+    );
+    assert_eq!(error.message, "the definition `missing` is not declared");
+
+    let error = error_at(
+        &format!("{}Transfer => amount(Amount, <=, -1)", header),
+        "-1",
+    );
+    assert_eq!(error.message, "expected an unsigned 64-bit integer");
+
+    let source = format!("{}Transfer => all(pass", header);
+    let error = compile_v2(&source).unwrap_err();
+    assert_eq!(
+        error.message,
+        "expected `,` or `)`, found the end of the input"
+    );
+    assert_eq!(error.span, Span::new(source.len(), source.len()));
+
+    let source = format!("{}Transfer => pass\nTransfer => pass", header);
+    let error = compile_v2(&source).unwrap_err();
+    assert_eq!(error.message, "the operation `Transfer` is declared twice");
+    assert_eq!(error.line_column(&source), (4, 1));
+
+    let error = error_at(&format!("{}Transfer => \"pass", header), "\"pass");
+    assert_eq!(error.message, "unterminated string");
+}
+
+#[test]
+fn errors_render_the_source_line() {
+    let source = "rule_set \"Errors\"\nowner 11111111111111111111111111111111\n\
+                  Transfer => all(pass, nope)";
+    let error = compile_v1(source).unwrap_err();
+
+    assert_eq!(
+        error.render(source),
+        "error: unknown rule `nope`\n \
+         --> 3:23\n  \
+         |\n\
+         3 | Transfer => all(pass, nope)\n  \
+         |                       ^^^^"
+    );
+}
+
+#[test]
+fn long_rules_are_broken() {
+    let policy = Policy::parse(EVERY_RULE).unwrap();
+    let printed = policy.to_string();
+
+    assert!(printed.lines().all(|line| line.len() <= 100
+        || line.trim_start().starts_with("pubkey_tree")
+        || line.trim_start().starts_with("program_owned_tree(")));
+    assert!(printed.contains("Composite => all(\n    any(rule_ref(wallet), not(namespace)),\n"));
+
+    // Short rules stay on one line.
+    let rule = Expr::call("not", vec![Expr::word("pass")]);
+    assert_eq!(rule.pretty(0), "not(pass)");
+}
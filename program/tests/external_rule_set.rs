@@ -0,0 +1,290 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    payload::{Payload, PayloadType},
+    state::{Rule, RuleSetV1},
+};
+use solana_program::instruction::AccountMeta;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use utils::{program_test, Operation, PayloadKey};
+
+#[tokio::test]
+async fn test_external_rule_set() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSets
+    // --------------------------------
+    // Create a Rule for the shared allowlist.
+    let marketplace = Keypair::new().pubkey();
+    let allowlist = Rule::PubkeyListMatch {
+        pubkeys: vec![marketplace],
+        field: PayloadKey::Authority.to_string(),
+    };
+
+    // Create the shared RuleSet.
+    let mut rule_set = RuleSetV1::new("global allowlist".to_string(), context.payer.pubkey());
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            allowlist,
+        )
+        .unwrap();
+
+    // Put the RuleSet on chain.
+    let global_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "global allowlist".to_string()).await;
+
+    // Create a Rule that includes the shared RuleSet.
+    let external = Rule::ExternalRuleSet {
+        rule_set: global_addr,
+        operation: Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string(),
+        revision: None,
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            external,
+        )
+        .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // Create a Keypair to simulate a token mint address.
+    let mint = Keypair::new().pubkey();
+
+    // --------------------------------
+    // Validate fail (missing account)
+    // --------------------------------
+    // Create a `validate` instruction without the shared RuleSet PDA.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::from([(
+                PayloadKey::Authority.to_string(),
+                PayloadType::Pubkey(marketplace),
+            )]),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::MissingAccount);
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // Create a `validate` instruction with an authority that is not in the shared allowlist.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(global_addr, false)])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::from([(
+                PayloadKey::Authority.to_string(),
+                PayloadType::Pubkey(Keypair::new().pubkey()),
+            )]),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::PubkeyListMatchCheckFailed);
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    // Create a `validate` instruction with an authority that is in the shared allowlist.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(global_addr, false)])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::from([(
+                PayloadKey::Authority.to_string(),
+                PayloadType::Pubkey(marketplace),
+            )]),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Transfer operation since the shared RuleSet passed.
+    process_passing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+}
+
+#[tokio::test]
+async fn test_external_rule_set_incorrect_owner() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create a Rule referencing an account that is not a RuleSet.
+    let not_a_rule_set = Keypair::new().pubkey();
+    let external = Rule::ExternalRuleSet {
+        rule_set: not_a_rule_set,
+        operation: Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string(),
+        revision: None,
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            external,
+        )
+        .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // Create a Keypair to simulate a token mint address.
+    let mint = Keypair::new().pubkey();
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // Create a `validate` instruction with the account that is not a RuleSet.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(not_a_rule_set, false)])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::IncorrectOwner);
+}
+
+#[tokio::test]
+async fn test_external_rule_set_depth() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Find the RuleSet PDA so that the RuleSet can reference itself.
+    let (self_addr, _rule_set_bump) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        "test rule_set".to_string(),
+    );
+
+    let external = Rule::ExternalRuleSet {
+        rule_set: self_addr,
+        operation: Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string(),
+        revision: None,
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            external,
+        )
+        .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // Create a Keypair to simulate a token mint address.
+    let mint = Keypair::new().pubkey();
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // Create a `validate` instruction with the RuleSet PDA as the external RuleSet.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(rule_set_addr, false)])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::RuleSetDepthExceeded);
+}
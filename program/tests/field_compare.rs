@@ -0,0 +1,145 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    payload::{Payload, PayloadType},
+    state::{CompareOp, Rule, RuleSetV1},
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use utils::program_test;
+
+const SALE: &str = "Sale";
+const TRANSFER: &str = "Transfer";
+const ORDERED: &str = "Ordered";
+
+const PAYMENT: &str = "Payment";
+const FLOOR: &str = "Floor";
+const DESTINATION: &str = "Destination";
+const AUTHORITY: &str = "Authority";
+
+// Create a `validate` instruction for an operation with two payload values.
+fn validate_ix(
+    rule_set_addr: Pubkey,
+    operation: &str,
+    left: (&str, PayloadType),
+    right: (&str, PayloadType),
+) -> Instruction {
+    ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(Keypair::new().pubkey())
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: operation.to_string(),
+            payload: Payload::from([(left.0.to_string(), left.1), (right.0.to_string(), right.1)]),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction()
+}
+
+#[tokio::test]
+async fn field_compare() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create some rules.
+    let sale_rule = Rule::FieldCompare {
+        left_field: PAYMENT.to_string(),
+        operator: CompareOp::GtEq,
+        right_field: FLOOR.to_string(),
+    };
+    let transfer_rule = Rule::FieldCompare {
+        left_field: DESTINATION.to_string(),
+        operator: CompareOp::Eq,
+        right_field: AUTHORITY.to_string(),
+    };
+    let ordered_rule = Rule::FieldCompare {
+        left_field: DESTINATION.to_string(),
+        operator: CompareOp::Lt,
+        right_field: AUTHORITY.to_string(),
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set.add(SALE.to_string(), sale_rule).unwrap();
+    rule_set.add(TRANSFER.to_string(), transfer_rule).unwrap();
+    rule_set.add(ORDERED.to_string(), ordered_rule).unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate Number
+    // --------------------------------
+    // The payment is at least the floor.
+    let ix = validate_ix(
+        rule_set_addr,
+        SALE,
+        (PAYMENT, PayloadType::Number(100)),
+        (FLOOR, PayloadType::Number(100)),
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // The payment is below the floor.
+    let ix = validate_ix(
+        rule_set_addr,
+        SALE,
+        (PAYMENT, PayloadType::Number(99)),
+        (FLOOR, PayloadType::Number(100)),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::FieldCompareCheckFailed);
+
+    // The fields have different types.
+    let ix = validate_ix(
+        rule_set_addr,
+        SALE,
+        (PAYMENT, PayloadType::Number(100)),
+        (FLOOR, PayloadType::Pubkey(Keypair::new().pubkey())),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::FieldTypeMismatch);
+
+    // --------------------------------
+    // Validate Pubkey
+    // --------------------------------
+    let authority = Keypair::new().pubkey();
+
+    // The destination is the authority.
+    let ix = validate_ix(
+        rule_set_addr,
+        TRANSFER,
+        (DESTINATION, PayloadType::Pubkey(authority)),
+        (AUTHORITY, PayloadType::Pubkey(authority)),
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // The destination is a different account.
+    let ix = validate_ix(
+        rule_set_addr,
+        TRANSFER,
+        (DESTINATION, PayloadType::Pubkey(Keypair::new().pubkey())),
+        (AUTHORITY, PayloadType::Pubkey(authority)),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::FieldCompareCheckFailed);
+
+    // `Pubkey`s cannot be ordered.
+    let ix = validate_ix(
+        rule_set_addr,
+        ORDERED,
+        (DESTINATION, PayloadType::Pubkey(authority)),
+        (AUTHORITY, PayloadType::Pubkey(authority)),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::InvalidCompareOp);
+}
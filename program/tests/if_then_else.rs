@@ -0,0 +1,230 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    payload::{Payload, PayloadType},
+    state::{CompareOp, Rule, RuleSetV1},
+};
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use utils::{program_test, Operation, PayloadKey};
+
+#[tokio::test]
+async fn test_if_then_else() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create some rules.
+    let marketplace = Keypair::new().pubkey();
+    let second_signer = Keypair::new();
+
+    let condition = Rule::PubkeyMatch {
+        pubkey: marketplace,
+        field: PayloadKey::Destination.to_string(),
+    };
+
+    let then = Rule::AdditionalSigner {
+        account: second_signer.pubkey(),
+    };
+
+    let otherwise = Rule::Amount {
+        amount: 1,
+        operator: CompareOp::LtEq,
+        field: PayloadKey::Amount.to_string(),
+    };
+
+    let overall_rule = Rule::IfThenElse {
+        condition: Box::new(condition),
+        then: Box::new(then),
+        otherwise: Box::new(otherwise),
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            overall_rule,
+        )
+        .unwrap();
+
+    println!("{:#?}", rule_set);
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // Create a Keypair to simulate a token mint address.
+    let mint = Keypair::new().pubkey();
+
+    // --------------------------------
+    // Validate fail (then branch)
+    // --------------------------------
+    // Store a payload of data with the marketplace as the destination.
+    let payload = Payload::from([
+        (
+            PayloadKey::Destination.to_string(),
+            PayloadType::Pubkey(marketplace),
+        ),
+        (PayloadKey::Amount.to_string(), PayloadType::Number(1)),
+    ]);
+
+    // Create a `validate` instruction WITHOUT the additional signer signing.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(
+            second_signer.pubkey(),
+            false,
+        )])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: payload.clone(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.  In this case we expect the `then` failure to roll up.
+    assert_custom_error!(err, RuleSetError::AdditionalSignerCheckFailed);
+
+    // --------------------------------
+    // Validate pass (then branch)
+    // --------------------------------
+    // Create a `validate` instruction WITH the additional signer signing.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(
+            second_signer.pubkey(),
+            true,
+        )])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Transfer operation since the `then` rule passed.
+    process_passing_validate_ix!(&mut context, validate_ix, vec![&second_signer], None).await;
+
+    // --------------------------------
+    // Validate fail (otherwise branch)
+    // --------------------------------
+    // Store a payload of data with a different destination and the WRONG amount.
+    let payload = Payload::from([
+        (
+            PayloadKey::Destination.to_string(),
+            PayloadType::Pubkey(Pubkey::new_unique()),
+        ),
+        (PayloadKey::Amount.to_string(), PayloadType::Number(2)),
+    ]);
+
+    // Create a `validate` instruction.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.  In this case we expect the `otherwise` failure to
+    // roll up.
+    assert_custom_error!(err, RuleSetError::AmountCheckFailed);
+
+    // --------------------------------
+    // Validate pass (otherwise branch)
+    // --------------------------------
+    // Store a payload of data with a different destination and the correct amount.
+    let payload = Payload::from([
+        (
+            PayloadKey::Destination.to_string(),
+            PayloadType::Pubkey(Pubkey::new_unique()),
+        ),
+        (PayloadKey::Amount.to_string(), PayloadType::Number(1)),
+    ]);
+
+    // Create a `validate` instruction.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Transfer operation since the `otherwise` rule passed.
+    process_passing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // --------------------------------
+    // Validate fail (condition error)
+    // --------------------------------
+    // Store a payload of data WITHOUT the destination.
+    let payload = Payload::from([(PayloadKey::Amount.to_string(), PayloadType::Number(1))]);
+
+    // Create a `validate` instruction.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.  In this case the condition error is returned even
+    // though the `otherwise` rule would have passed.
+    assert_custom_error!(err, RuleSetError::MissingPayloadValue);
+}
@@ -0,0 +1,215 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    payload::{Payload, PayloadType},
+    state::{AdditionalSigner, Amount, IfThenElse, Operator, PubkeyMatch, RuleSetV2},
+};
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use utils::{program_test, Operation, PayloadKey};
+
+#[tokio::test]
+async fn test_if_then_else_v2() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create some rules.
+    let marketplace = Keypair::new().pubkey();
+    let second_signer = Keypair::new();
+
+    let condition =
+        PubkeyMatch::serialize(PayloadKey::Destination.to_string(), marketplace).unwrap();
+    let then = AdditionalSigner::serialize(second_signer.pubkey()).unwrap();
+    let otherwise = Amount::serialize(PayloadKey::Amount.to_string(), Operator::LtEq, 1).unwrap();
+
+    let overall_rule = IfThenElse::serialize(&condition, &then, &otherwise).unwrap();
+
+    // Create a RuleSet.
+    let rule_set = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&overall_rule],
+    )
+    .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain_serialized!(&mut context, rule_set, "test rule_set".to_string())
+            .await;
+
+    // Create a Keypair to simulate a token mint address.
+    let mint = Keypair::new().pubkey();
+
+    // --------------------------------
+    // Validate fail (then branch)
+    // --------------------------------
+    // Store a payload of data with the marketplace as the destination.
+    let payload = Payload::from([
+        (
+            PayloadKey::Destination.to_string(),
+            PayloadType::Pubkey(marketplace),
+        ),
+        (PayloadKey::Amount.to_string(), PayloadType::Number(1)),
+    ]);
+
+    // Create a `validate` instruction WITHOUT the additional signer signing.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(
+            second_signer.pubkey(),
+            false,
+        )])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: payload.clone(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.  In this case we expect the `then` failure to roll up.
+    assert_custom_error!(err, RuleSetError::AdditionalSignerCheckFailed);
+
+    // --------------------------------
+    // Validate pass (then branch)
+    // --------------------------------
+    // Create a `validate` instruction WITH the additional signer signing.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(
+            second_signer.pubkey(),
+            true,
+        )])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Transfer operation since the `then` rule passed.
+    process_passing_validate_ix!(&mut context, validate_ix, vec![&second_signer], None).await;
+
+    // --------------------------------
+    // Validate fail (otherwise branch)
+    // --------------------------------
+    // Store a payload of data with a different destination and the WRONG amount.
+    let payload = Payload::from([
+        (
+            PayloadKey::Destination.to_string(),
+            PayloadType::Pubkey(Pubkey::new_unique()),
+        ),
+        (PayloadKey::Amount.to_string(), PayloadType::Number(2)),
+    ]);
+
+    // Create a `validate` instruction.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.  In this case we expect the `otherwise` failure to
+    // roll up.
+    assert_custom_error!(err, RuleSetError::AmountCheckFailed);
+
+    // --------------------------------
+    // Validate pass (otherwise branch)
+    // --------------------------------
+    // Store a payload of data with a different destination and the correct amount.
+    let payload = Payload::from([
+        (
+            PayloadKey::Destination.to_string(),
+            PayloadType::Pubkey(Pubkey::new_unique()),
+        ),
+        (PayloadKey::Amount.to_string(), PayloadType::Number(1)),
+    ]);
+
+    // Create a `validate` instruction.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Transfer operation since the `otherwise` rule passed.
+    process_passing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // --------------------------------
+    // Validate fail (condition error)
+    // --------------------------------
+    // Store a payload of data WITHOUT the destination.
+    let payload = Payload::from([(PayloadKey::Amount.to_string(), PayloadType::Number(1))]);
+
+    // Create a `validate` instruction.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.  In this case the condition error is returned even
+    // though the `otherwise` rule would have passed.
+    assert_custom_error!(err, RuleSetError::MissingPayloadValue);
+}
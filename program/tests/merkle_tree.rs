@@ -0,0 +1,77 @@
+#![cfg(feature = "client")]
+
+use mpl_token_auth_rules::{
+    merkle::{ExclusionTree, MerkleTree},
+    utils::{compute_merkle_root, is_excluded},
+};
+use proptest::{collection::vec, prelude::*};
+use solana_program::pubkey::Pubkey;
+
+// Strategy generating a `Pubkey` from random bytes.
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+proptest! {
+    #[test]
+    fn proofs_match_on_chain_verifier(leaves in vec(pubkey(), 1..64), index in any::<usize>()) {
+        let tree = MerkleTree::new(&leaves);
+        let index = index % leaves.len();
+
+        // The proof of any leaf is accepted by the on-chain verifier.
+        let proof = tree.proof(index).unwrap();
+        prop_assert_eq!(compute_merkle_root(&leaves[index], &proof), tree.root());
+    }
+
+    #[test]
+    fn proofs_reject_other_leaves(leaves in vec(pubkey(), 1..64), other in pubkey()) {
+        prop_assume!(!leaves.contains(&other));
+        let tree = MerkleTree::new(&leaves);
+
+        // A key that is not a leaf cannot reuse the proof of a leaf.
+        for index in 0..leaves.len() {
+            let proof = tree.proof(index).unwrap();
+            prop_assert_ne!(compute_merkle_root(&other, &proof), tree.root());
+        }
+    }
+
+    #[test]
+    fn multi_proofs_compute_root(
+        leaves in vec(pubkey(), 1..64),
+        indices in vec(any::<usize>(), 1..16),
+    ) {
+        let tree = MerkleTree::new(&leaves);
+        let indices = indices.iter().map(|index| index % leaves.len()).collect::<Vec<_>>();
+
+        // The proven leaves are given in the order of the sorted indices.
+        let proof = tree.multi_proof(&indices).unwrap();
+        let proven = proof.indices.iter().map(|index| leaves[*index]).collect::<Vec<_>>();
+        prop_assert_eq!(proof.compute_root(&proven), Some(tree.root()));
+
+        // Each proven leaf also has a single proof accepted by the on-chain verifier.
+        for index in &proof.indices {
+            let single = tree.proof(*index).unwrap();
+            prop_assert_eq!(compute_merkle_root(&leaves[*index], &single), tree.root());
+        }
+    }
+
+    #[test]
+    fn exclusion_proofs(keys in vec(pubkey(), 0..64), key in pubkey()) {
+        let tree = ExclusionTree::new(&keys);
+
+        match tree.exclusion_proof(&key) {
+            // A key that is not a member can be proven excluded.
+            Some(proof) => {
+                prop_assert!(!keys.contains(&key));
+                prop_assert!(is_excluded(&key, &proof, &tree.root()));
+            }
+            // A member cannot be proven excluded.
+            None => prop_assert!(tree.contains(&key)),
+        }
+
+        // The proof of another key does not exclude a member.
+        if let (Some(member), Some(proof)) = (keys.first(), tree.exclusion_proof(&key)) {
+            prop_assert!(!is_excluded(member, &proof, &tree.root()));
+        }
+    }
+}
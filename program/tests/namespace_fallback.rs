@@ -0,0 +1,210 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    payload::{Payload, PayloadType},
+    state::{CompareOp, Rule, RuleSetV1},
+    types::DEFAULT_OPERATION,
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use utils::{program_test, PayloadKey};
+
+const TRANSFER: &str = "Transfer";
+const TRANSFER_OWNER: &str = "Transfer:Owner";
+const TRANSFER_OWNER_ESCROW: &str = "Transfer:Owner:Escrow";
+const TRANSFER_DELEGATE_ESCROW: &str = "Transfer:Delegate:Escrow";
+const DELEGATE_SALE: &str = "Delegate:Sale";
+
+// Create a `validate` instruction for an operation with an amount in the payload.
+fn validate_ix(rule_set_addr: Pubkey, operation: &str, amount: u64) -> Instruction {
+    ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(Keypair::new().pubkey())
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: operation.to_string(),
+            payload: Payload::from([(PayloadKey::Amount.to_string(), PayloadType::Number(amount))]),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction()
+}
+
+#[tokio::test]
+async fn multi_level_namespace() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create some rules.
+    let transfer_rule = Rule::Amount {
+        amount: 1,
+        operator: CompareOp::Eq,
+        field: PayloadKey::Amount.to_string(),
+    };
+    let transfer_owner_rule = Rule::Amount {
+        amount: 2,
+        operator: CompareOp::Eq,
+        field: PayloadKey::Amount.to_string(),
+    };
+    let namespace_rule = Rule::Namespace;
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set.add(TRANSFER.to_string(), transfer_rule).unwrap();
+    rule_set
+        .add(TRANSFER_OWNER.to_string(), transfer_owner_rule)
+        .unwrap();
+    rule_set
+        .add(TRANSFER_OWNER_ESCROW.to_string(), namespace_rule.clone())
+        .unwrap();
+    rule_set
+        .add(TRANSFER_DELEGATE_ESCROW.to_string(), namespace_rule)
+        .unwrap();
+
+    println!("{:#?}", rule_set);
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate longest prefix
+    // --------------------------------
+    // 'Transfer:Owner:Escrow' falls back to 'Transfer:Owner', which requires an amount of 2.
+    let err = process_failing_validate_ix!(
+        &mut context,
+        validate_ix(rule_set_addr, TRANSFER_OWNER_ESCROW, 1),
+        vec![],
+        None
+    )
+    .await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::AmountCheckFailed);
+
+    process_passing_validate_ix!(
+        &mut context,
+        validate_ix(rule_set_addr, TRANSFER_OWNER_ESCROW, 2),
+        vec![],
+        None
+    )
+    .await;
+
+    // --------------------------------
+    // Validate skipped level
+    // --------------------------------
+    // 'Transfer:Delegate' has no rule, so 'Transfer:Delegate:Escrow' falls back to 'Transfer'.
+    process_passing_validate_ix!(
+        &mut context,
+        validate_ix(rule_set_addr, TRANSFER_DELEGATE_ESCROW, 1),
+        vec![],
+        None
+    )
+    .await;
+
+    // --------------------------------
+    // Validate no default
+    // --------------------------------
+    // There is no rule for 'Delegate:Sale' and no default rule.
+    let err = process_failing_validate_ix!(
+        &mut context,
+        validate_ix(rule_set_addr, DELEGATE_SALE, 1),
+        vec![],
+        None
+    )
+    .await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::OperationNotFound);
+}
+
+#[tokio::test]
+async fn default_operation() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create some rules.
+    let transfer_rule = Rule::Amount {
+        amount: 1,
+        operator: CompareOp::Eq,
+        field: PayloadKey::Amount.to_string(),
+    };
+    let transfer_owner_rule = Rule::Amount {
+        amount: 2,
+        operator: CompareOp::Eq,
+        field: PayloadKey::Amount.to_string(),
+    };
+    let namespace_rule = Rule::Namespace;
+    let default_rule = Rule::Amount {
+        amount: 3,
+        operator: CompareOp::Eq,
+        field: PayloadKey::Amount.to_string(),
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set.add(TRANSFER.to_string(), transfer_rule).unwrap();
+    rule_set
+        .add(TRANSFER_OWNER.to_string(), transfer_owner_rule)
+        .unwrap();
+    rule_set
+        .add(TRANSFER_OWNER_ESCROW.to_string(), namespace_rule.clone())
+        .unwrap();
+    rule_set
+        .add(TRANSFER_DELEGATE_ESCROW.to_string(), namespace_rule)
+        .unwrap();
+    rule_set
+        .add(DEFAULT_OPERATION.to_string(), default_rule)
+        .unwrap();
+
+    println!("{:#?}", rule_set);
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate default
+    // --------------------------------
+    // There is no rule for 'Delegate:Sale', so the default rule is used.
+    let err = process_failing_validate_ix!(
+        &mut context,
+        validate_ix(rule_set_addr, DELEGATE_SALE, 1),
+        vec![],
+        None
+    )
+    .await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::AmountCheckFailed);
+
+    process_passing_validate_ix!(
+        &mut context,
+        validate_ix(rule_set_addr, DELEGATE_SALE, 3),
+        vec![],
+        None
+    )
+    .await;
+
+    // --------------------------------
+    // Validate namespace before default
+    // --------------------------------
+    // Namespace fallbacks have precedence over the default rule.
+    process_passing_validate_ix!(
+        &mut context,
+        validate_ix(rule_set_addr, TRANSFER_OWNER_ESCROW, 2),
+        vec![],
+        None
+    )
+    .await;
+}
@@ -0,0 +1,242 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    payload::{Payload, PayloadType},
+    state::{CompareOp, MatchOp, Rule, RuleSetV1},
+    types::MAX_NAME_LENGTH,
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use utils::program_test;
+
+const DATA: &str = "Data";
+const NAME: &str = "Name";
+const FLAG: &str = "Flag";
+const DELTA: &str = "Delta";
+const AMOUNT: &str = "Amount";
+const CREATORS: &str = "Creators";
+
+// An amount that does not fit in a `u64`.
+const LARGE: u128 = u64::MAX as u128 + 1;
+
+// Create a `validate` instruction for an operation with a single payload value.
+fn validate_ix(
+    rule_set_addr: Pubkey,
+    operation: &str,
+    field: &str,
+    value: PayloadType,
+) -> Instruction {
+    ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(Keypair::new().pubkey())
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: operation.to_string(),
+            payload: Payload::from([(field.to_string(), value)]),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction()
+}
+
+#[tokio::test]
+async fn payload_types() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    let creator = Keypair::new().pubkey();
+
+    // Create some rules.
+    let rules = [
+        (
+            "Bytes",
+            Rule::BytesMatch {
+                value: vec![1, 2],
+                operator: MatchOp::Prefix,
+                field: DATA.to_string(),
+            },
+        ),
+        (
+            "Str",
+            Rule::StrMatch {
+                value: "mpl".to_string(),
+                operator: MatchOp::Eq,
+                field: NAME.to_string(),
+            },
+        ),
+        (
+            "Bool",
+            Rule::BoolMatch {
+                value: true,
+                field: FLAG.to_string(),
+            },
+        ),
+        (
+            "I64",
+            Rule::AmountI64 {
+                amount: -10,
+                operator: CompareOp::Gt,
+                field: DELTA.to_string(),
+            },
+        ),
+        (
+            "U128",
+            Rule::AmountU128 {
+                amount: LARGE,
+                operator: CompareOp::GtEq,
+                field: AMOUNT.to_string(),
+            },
+        ),
+        (
+            "List",
+            Rule::PubkeyListContains {
+                pubkey: creator,
+                field: CREATORS.to_string(),
+            },
+        ),
+    ];
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    for (operation, rule) in rules {
+        rule_set.add(operation.to_string(), rule).unwrap();
+    }
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate Bytes
+    // --------------------------------
+    // The payload bytes start with the prefix in the rule.
+    let ix = validate_ix(
+        rule_set_addr,
+        "Bytes",
+        DATA,
+        PayloadType::Bytes(vec![1, 2, 3]),
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // The payload bytes do not start with the prefix in the rule.
+    let ix = validate_ix(rule_set_addr, "Bytes", DATA, PayloadType::Bytes(vec![1]));
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::BytesMatchCheckFailed);
+
+    // --------------------------------
+    // Validate Str
+    // --------------------------------
+    let ix = validate_ix(
+        rule_set_addr,
+        "Str",
+        NAME,
+        PayloadType::Str("mpl".to_string()),
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // Equality does not match prefixes.
+    let ix = validate_ix(
+        rule_set_addr,
+        "Str",
+        NAME,
+        PayloadType::Str("mplx".to_string()),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::StrMatchCheckFailed);
+
+    // --------------------------------
+    // Validate Bool
+    // --------------------------------
+    let ix = validate_ix(rule_set_addr, "Bool", FLAG, PayloadType::Bool(true));
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    let ix = validate_ix(rule_set_addr, "Bool", FLAG, PayloadType::Bool(false));
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::BoolMatchCheckFailed);
+
+    // --------------------------------
+    // Validate I64
+    // --------------------------------
+    let ix = validate_ix(rule_set_addr, "I64", DELTA, PayloadType::I64(-9));
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    let ix = validate_ix(rule_set_addr, "I64", DELTA, PayloadType::I64(-10));
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::AmountCheckFailed);
+
+    // --------------------------------
+    // Validate U128
+    // --------------------------------
+    let ix = validate_ix(rule_set_addr, "U128", AMOUNT, PayloadType::U128(LARGE));
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    let ix = validate_ix(rule_set_addr, "U128", AMOUNT, PayloadType::U128(LARGE - 1));
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::AmountCheckFailed);
+
+    // A `Number` is not accepted in place of a `U128`.
+    let ix = validate_ix(rule_set_addr, "U128", AMOUNT, PayloadType::Number(u64::MAX));
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::MissingPayloadValue);
+
+    // --------------------------------
+    // Validate PubkeyList
+    // --------------------------------
+    let list = vec![Keypair::new().pubkey(), creator];
+    let ix = validate_ix(
+        rule_set_addr,
+        "List",
+        CREATORS,
+        PayloadType::PubkeyList(list),
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    let list = vec![Keypair::new().pubkey()];
+    let ix = validate_ix(
+        rule_set_addr,
+        "List",
+        CREATORS,
+        PayloadType::PubkeyList(list),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::PubkeyListContainsCheckFailed);
+}
+
+#[tokio::test]
+async fn payload_limits() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set.add("Bool".to_string(), Rule::Pass).unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // The payload key is longer than the max name length.
+    let field = "k".repeat(MAX_NAME_LENGTH + 1);
+    let ix = validate_ix(rule_set_addr, "Bool", &field, PayloadType::Bool(true));
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::PayloadKeyTooLong);
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    let field = "k".repeat(MAX_NAME_LENGTH);
+    let ix = validate_ix(rule_set_addr, "Bool", &field, PayloadType::Bool(true));
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+}
@@ -0,0 +1,299 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{
+        builders::{AddToPubkeyListBuilder, RemoveFromPubkeyListBuilder, ValidateBuilder},
+        InstructionBuilder, ModifyPubkeyListArgs, ValidateArgs,
+    },
+    payload::{Payload, PayloadType},
+    pda::find_pubkey_list_address,
+    state::{Rule, RuleSetV1},
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_program_test::{tokio, BanksClientError, ProgramTestContext};
+use solana_sdk::{signature::Signer, signer::keypair::Keypair, transaction::Transaction};
+use utils::{program_test, Operation, PayloadKey};
+
+// Add `Pubkey`s to, or remove them from, a list owned by `authority`.
+async fn modify_list(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    list_addr: Pubkey,
+    pubkeys: Vec<Pubkey>,
+    add: bool,
+) -> Result<(), BanksClientError> {
+    let args = ModifyPubkeyListArgs::V1 {
+        list_name: "marketplaces".to_string(),
+        pubkeys,
+    };
+
+    let ix = if add {
+        AddToPubkeyListBuilder::new()
+            .payer(context.payer.pubkey())
+            .authority(authority.pubkey())
+            .pubkey_list_pda(list_addr)
+            .build(args)
+            .unwrap()
+            .instruction()
+    } else {
+        RemoveFromPubkeyListBuilder::new()
+            .payer(context.payer.pubkey())
+            .authority(authority.pubkey())
+            .pubkey_list_pda(list_addr)
+            .build(args)
+            .unwrap()
+            .instruction()
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+// Create a `validate` instruction for a transfer to a destination.
+fn validate_ix(
+    rule_set_addr: Pubkey,
+    operation: &str,
+    destination: Pubkey,
+    additional_rule_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let payload = Payload::from([(
+        PayloadKey::Destination.to_string(),
+        PayloadType::Pubkey(destination),
+    )]);
+
+    ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(Keypair::new().pubkey())
+        .additional_rule_accounts(additional_rule_accounts)
+        .build(ValidateArgs::V1 {
+            operation: operation.to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction()
+}
+
+#[tokio::test]
+async fn pubkey_in_list_account() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create Pubkey List
+    // --------------------------------
+    let authority = Keypair::new();
+    let (list_addr, _) = find_pubkey_list_address(authority.pubkey(), "marketplaces".to_string());
+
+    let marketplaces = (0..20).map(|_| Keypair::new().pubkey()).collect::<Vec<_>>();
+    modify_list(
+        &mut context,
+        &authority,
+        list_addr,
+        marketplaces.clone(),
+        true,
+    )
+    .await
+    .unwrap();
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    let transfer = Operation::Transfer {
+        scenario: utils::TransferScenario::Holder,
+    }
+    .to_string();
+
+    // Create a Rule: The destination must be in the list account.
+    let rule = Rule::PubkeyInListAccount {
+        list: list_addr,
+        field: PayloadKey::Destination.to_string(),
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set.add(transfer.clone(), rule).unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    let list_account = vec![AccountMeta::new_readonly(list_addr, false)];
+
+    for marketplace in [marketplaces[0], marketplaces[7], marketplaces[19]] {
+        let ix = validate_ix(rule_set_addr, &transfer, marketplace, list_account.clone());
+        process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+    }
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // The destination is not in the list.
+    let new_marketplace = Keypair::new().pubkey();
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        new_marketplace,
+        list_account.clone(),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::PubkeyInListAccountCheckFailed);
+
+    // The list account is not provided.
+    let ix = validate_ix(rule_set_addr, &transfer, marketplaces[0], vec![]);
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::MissingAccount);
+
+    // --------------------------------
+    // Update the list
+    // --------------------------------
+    // Another signer cannot modify the list.
+    let other_authority = Keypair::new();
+    let err = modify_list(
+        &mut context,
+        &other_authority,
+        list_addr,
+        vec![new_marketplace],
+        true,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error!(err, RuleSetError::DerivedKeyInvalid);
+
+    // Add a marketplace without a new RuleSet revision.
+    modify_list(
+        &mut context,
+        &authority,
+        list_addr,
+        vec![new_marketplace],
+        true,
+    )
+    .await
+    .unwrap();
+
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        new_marketplace,
+        list_account.clone(),
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // Remove a marketplace.
+    modify_list(
+        &mut context,
+        &authority,
+        list_addr,
+        vec![marketplaces[7]],
+        false,
+    )
+    .await
+    .unwrap();
+
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        marketplaces[7],
+        list_account.clone(),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::PubkeyInListAccountCheckFailed);
+
+    let ix = validate_ix(rule_set_addr, &transfer, marketplaces[19], list_account);
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+}
+
+#[tokio::test]
+async fn owner_in_list_account() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create Pubkey List
+    // --------------------------------
+    let authority = Keypair::new();
+    let (list_addr, _) = find_pubkey_list_address(authority.pubkey(), "marketplaces".to_string());
+
+    let mut programs = (0..5).map(|_| Keypair::new().pubkey()).collect::<Vec<_>>();
+    programs.push(mpl_token_auth_rules::ID);
+    modify_list(&mut context, &authority, list_addr, programs, true)
+        .await
+        .unwrap();
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    let transfer = Operation::Transfer {
+        scenario: utils::TransferScenario::Holder,
+    }
+    .to_string();
+
+    // Create a Rule: The destination must be owned by a program in the list account.
+    let rule = Rule::OwnerInListAccount {
+        list: list_addr,
+        field: PayloadKey::Destination.to_string(),
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set.add(transfer.clone(), rule).unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    // The RuleSet account is owned by mpl-token-auth-rules, which is in the list.
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        rule_set_addr,
+        vec![
+            AccountMeta::new_readonly(list_addr, false),
+            AccountMeta::new_readonly(rule_set_addr, false),
+        ],
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // Remove mpl-token-auth-rules from the list.
+    modify_list(
+        &mut context,
+        &authority,
+        list_addr,
+        vec![mpl_token_auth_rules::ID],
+        false,
+    )
+    .await
+    .unwrap();
+
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        rule_set_addr,
+        vec![
+            AccountMeta::new_readonly(list_addr, false),
+            AccountMeta::new_readonly(rule_set_addr, false),
+        ],
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::OwnerInListAccountCheckFailed);
+}
@@ -4,12 +4,15 @@ pub mod utils;
 
 use mpl_token_auth_rules::{
     error::RuleSetError,
-    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    instruction::{
+        builders::{CreateOrUpdateBuilder, ValidateBuilder},
+        CreateOrUpdateArgs, InstructionBuilder, ValidateArgs,
+    },
     payload::{Payload, PayloadType},
-    state::{PubkeyListMatch, RuleSetV2},
+    state::{ConstraintType, PubkeyListMatch, RuleSetV2},
 };
 use solana_program_test::tokio;
-use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use solana_sdk::{signature::Signer, signer::keypair::Keypair, transaction::Transaction};
 use utils::{program_test, Operation, PayloadKey};
 
 #[tokio::test]
@@ -114,3 +117,148 @@ async fn test_pubkey_list_match_v2() {
     // Validate Transfer operation.
     process_passing_validate_ix!(&mut context, validate_ix, vec![], None).await;
 }
+
+#[tokio::test]
+async fn test_pubkey_list_match_sorted_v2() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSets
+    // --------------------------------
+    // A large marketplace allowlist.
+    let marketplaces = (0..300)
+        .map(|_| Keypair::new().pubkey())
+        .collect::<Vec<_>>();
+    let transfer = Operation::Transfer {
+        scenario: utils::TransferScenario::Holder,
+    }
+    .to_string();
+
+    let unsorted_rule =
+        PubkeyListMatch::serialize(PayloadKey::Authority.to_string(), &marketplaces).unwrap();
+    let sorted_rule =
+        PubkeyListMatch::serialize_sorted(PayloadKey::Authority.to_string(), &marketplaces)
+            .unwrap();
+
+    let mut rule_set_addrs = Vec::new();
+    for (name, rule) in [("unsorted", &unsorted_rule), ("sorted", &sorted_rule)] {
+        let rule_set =
+            RuleSetV2::serialize(context.payer.pubkey(), name, &[transfer.clone()], &[rule])
+                .unwrap();
+
+        rule_set_addrs.push(
+            create_big_rule_set_on_chain!(&mut context, rule_set, name.to_string(), None).await,
+        );
+    }
+
+    // --------------------------------
+    // Validate
+    // --------------------------------
+    // Use the last key of the unsorted list, which is the worst case for a linear scan.
+    let payload = Payload::from([(
+        PayloadKey::Authority.to_string(),
+        PayloadType::Pubkey(marketplaces[marketplaces.len() - 1]),
+    )]);
+
+    let mut units_consumed = Vec::new();
+    for rule_set_addr in rule_set_addrs {
+        let validate_ix = ValidateBuilder::new()
+            .rule_set_pda(rule_set_addr)
+            .mint(Keypair::new().pubkey())
+            .additional_rule_accounts(vec![])
+            .build(ValidateArgs::V1 {
+                operation: transfer.clone(),
+                payload: payload.clone(),
+                update_rule_state: false,
+                rule_set_revision: None,
+            })
+            .unwrap()
+            .instruction();
+
+        let tx = Transaction::new_signed_with_payer(
+            &[validate_ix.clone()],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+
+        let simulation = context.banks_client.simulate_transaction(tx).await.unwrap();
+        units_consumed.push(simulation.simulation_details.unwrap().units_consumed);
+
+        process_passing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+    }
+
+    // The binary search is cheaper than the linear scan.
+    assert!(
+        units_consumed[1] < units_consumed[0],
+        "sorted: {}, unsorted: {}",
+        units_consumed[1],
+        units_consumed[0]
+    );
+}
+
+#[tokio::test]
+async fn create_unsorted_pubkey_list_match_sorted_v2_fails() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // An unsorted list stored with the sorted type.
+    let mut marketplaces = (0..10).map(|_| Keypair::new().pubkey()).collect::<Vec<_>>();
+    marketplaces.sort();
+    marketplaces.reverse();
+
+    let mut unsorted_rule =
+        PubkeyListMatch::serialize(PayloadKey::Authority.to_string(), &marketplaces).unwrap();
+    unsorted_rule[..4]
+        .copy_from_slice(&(ConstraintType::PubkeyListMatchSorted as u32).to_le_bytes());
+
+    let serialized_rule_set = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&unsorted_rule],
+    )
+    .unwrap();
+
+    // --------------------------------
+    // Fail on-chain creation
+    // --------------------------------
+    // Find RuleSet PDA.
+    let (rule_set_addr, _rule_set_bump) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        "test rule_set".to_string(),
+    );
+
+    // Create a `create` instruction.
+    let create_ix = CreateOrUpdateBuilder::new()
+        .payer(context.payer.pubkey())
+        .rule_set_pda(rule_set_addr)
+        .build(CreateOrUpdateArgs::V1 {
+            serialized_rule_set,
+        })
+        .unwrap()
+        .instruction();
+
+    // Add it to a transaction.
+    let create_tx = Transaction::new_signed_with_payer(
+        &[create_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    // Process the transaction.
+    let err = context
+        .banks_client
+        .process_transaction(create_tx)
+        .await
+        .expect_err("Creation should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::UnsortedPubkeyList);
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    merkle::ExclusionTree,
+    payload::{ExclusionProofInfo, Payload, PayloadType},
+    state::{Rule, RuleSetV1},
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair};
+use utils::{program_test, Operation, PayloadKey};
+
+// Create a `validate` instruction for a transfer to a destination.
+fn validate_ix(
+    rule_set_addr: Pubkey,
+    operation: &str,
+    destination: Pubkey,
+    proof: Option<ExclusionProofInfo>,
+) -> Instruction {
+    let mut payload = Payload::from([(
+        PayloadKey::Destination.to_string(),
+        PayloadType::Pubkey(destination),
+    )]);
+    if let Some(proof) = proof {
+        payload.insert(
+            PayloadKey::DestinationProof.to_string(),
+            PayloadType::ExclusionProof(proof),
+        );
+    }
+
+    ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(Keypair::new().pubkey())
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V1 {
+            operation: operation.to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction()
+}
+
+#[tokio::test]
+async fn pubkey_tree_exclusion() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create a denylist tree.
+    let denylist = (0..10).map(|_| Keypair::new().pubkey()).collect::<Vec<_>>();
+    let tree = ExclusionTree::new(&denylist);
+
+    let transfer = Operation::Transfer {
+        scenario: utils::TransferScenario::Holder,
+    }
+    .to_string();
+
+    // Create a Rule: The destination must not be a member of the denylist.
+    let rule = Rule::PubkeyTreeExclusion {
+        root: tree.root(),
+        pubkey_field: PayloadKey::Destination.to_string(),
+        proof_field: PayloadKey::DestinationProof.to_string(),
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set.add(transfer.clone(), rule).unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    // The destination is not in the denylist.
+    let destination = Keypair::new().pubkey();
+    let proof = tree.exclusion_proof(&destination);
+    let ix = validate_ix(rule_set_addr, &transfer, destination, proof.clone());
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // A denylisted destination cannot reuse the proof of another key.
+    let ix = validate_ix(rule_set_addr, &transfer, denylist[0], proof);
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::PubkeyTreeExclusionCheckFailed);
+
+    // The proof is missing.
+    let ix = validate_ix(rule_set_addr, &transfer, destination, None);
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::MissingPayloadValue);
+}
@@ -0,0 +1,254 @@
+#![cfg(feature = "client")]
+
+use borsh::BorshSerialize;
+use mpl_token_auth_rules::{
+    diff::{decode_revision, diff_revisions, Change, RevisionDiff, RulePath, Scope, Step},
+    dsl::{compile_v1, compile_v2, Policy},
+    state::{
+        RuleSetHeader, RuleSetRevisionMapV1, RULE_SET_REV_MAP_VERSION,
+        RULE_SET_SERIALIZED_HEADER_LEN,
+    },
+    types::LibVersion,
+};
+use rmp_serde::Serializer;
+use serde::Serialize;
+
+const REVISION_0: &str = r#"
+rule_set "Marketplace"
+owner AuthxYNhPnnrGBo1wdzeUdukrsFpHvR42wghx8ZPNEo4
+
+define allowed => any(
+    program_owned_list(Destination, [
+        TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA,
+        M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K,
+    ]),
+    is_wallet(Destination),
+)
+
+Transfer => all(rule_ref(allowed), amount(Amount, <=, 1))
+Delegate => pass
+Legacy => namespace
+"#;
+
+const REVISION_1: &str = r#"
+rule_set "Marketplace"
+owner AuthxYNhPnnrGBo1wdzeUdukrsFpHvR42wghx8ZPNEo4
+
+define allowed => any(
+    program_owned_list(Destination, [
+        TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA,
+        11111111111111111111111111111111,
+    ]),
+    is_wallet(Destination),
+)
+
+Transfer => all(rule_ref(allowed), is_wallet(Source), amount(Amount, <, 2))
+Delegate => pass
+Sale => not(pass)
+"#;
+
+// Lays out the revisions in the data of a `RuleSet` PDA the way `CreateOrUpdate` does: V1
+// revisions are prefixed with their lib version and V2 revisions are aligned to 8 bytes.
+fn rule_set_pda(revisions: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = vec![0; RULE_SET_SERIALIZED_HEADER_LEN];
+    let mut revision_map = RuleSetRevisionMapV1::default();
+
+    for revision in revisions {
+        if revision[0] == LibVersion::V2 as u8 {
+            data.resize(data.len().next_multiple_of(8), 0);
+        }
+
+        revision_map.rule_set_revisions.push(data.len());
+        data.extend_from_slice(revision);
+    }
+
+    let header = RuleSetHeader::new(data.len());
+    data[..RULE_SET_SERIALIZED_HEADER_LEN].copy_from_slice(&borsh::to_vec(&header).unwrap());
+
+    data.push(RULE_SET_REV_MAP_VERSION);
+    revision_map.serialize(&mut data).unwrap();
+
+    data
+}
+
+fn v1_revision(source: &str) -> Vec<u8> {
+    let mut serialized = vec![LibVersion::V1 as u8];
+    compile_v1(source)
+        .unwrap()
+        .serialize(&mut Serializer::new(&mut serialized))
+        .unwrap();
+    serialized
+}
+
+fn path(scope: Scope, name: &str, steps: &[(&str, usize)]) -> RulePath {
+    RulePath {
+        scope,
+        name: name.to_string(),
+        steps: steps
+            .iter()
+            .map(|(rule, index)| Step {
+                rule: rule.to_string(),
+                index: *index,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn revisions_are_decoded() {
+    let data = rule_set_pda(&[v1_revision(REVISION_0), compile_v2(REVISION_1).unwrap()]);
+
+    let revision_0 = Policy::parse(REVISION_0).unwrap().to_v1().unwrap();
+    assert_eq!(
+        decode_revision(&data, Some(0)).unwrap().to_v1().unwrap(),
+        revision_0
+    );
+
+    // The latest revision is decoded by default.
+    let revision_1 = compile_v2(REVISION_1).unwrap();
+    assert_eq!(
+        decode_revision(&data, None).unwrap().to_v2().unwrap(),
+        revision_1
+    );
+    assert!(decode_revision(&data, Some(2)).is_err());
+}
+
+#[test]
+fn v1_and_v2_revisions_are_compared() {
+    let data = rule_set_pda(&[v1_revision(REVISION_0), compile_v2(REVISION_1).unwrap()]);
+    let diff = diff_revisions(&data, 0, 1).unwrap();
+
+    assert_eq!(
+        diff.changes,
+        vec![
+            Change::ListChanged {
+                path: path(Scope::Definition, "allowed", &[("any", 0)]),
+                added: vec!["11111111111111111111111111111111".to_string()],
+                removed: vec!["M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K".to_string()],
+            },
+            Change::Removed {
+                path: path(Scope::Operation, "Legacy", &[]),
+                rule: "namespace".to_string(),
+            },
+            Change::Added {
+                path: path(Scope::Operation, "Sale", &[]),
+                rule: "not(pass)".to_string(),
+            },
+            Change::Changed {
+                path: path(Scope::Operation, "Transfer", &[("all", 2)]),
+                old: "amount(Amount, <=, 1)".to_string(),
+                new: "amount(Amount, <, 2)".to_string(),
+            },
+            Change::Added {
+                path: path(Scope::Operation, "Transfer", &[("all", 1)]),
+                rule: "is_wallet(Source)".to_string(),
+            },
+        ]
+    );
+    assert_eq!(diff.added_operations(), vec!["Sale"]);
+    assert_eq!(diff.removed_operations(), vec!["Legacy"]);
+
+    assert_eq!(
+        diff.to_string(),
+        "~ definition allowed > any[0] list\n    \
+         - M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K\n    \
+         + 11111111111111111111111111111111\n\
+         - operation Legacy => namespace\n\
+         + operation Sale => not(pass)\n\
+         ~ operation Transfer > all[2]\n    \
+         - amount(Amount, <=, 1)\n    \
+         + amount(Amount, <, 2)\n\
+         + operation Transfer > all[1] => is_wallet(Source)\n"
+    );
+
+    // The same revision compiled to V1 and V2 has no changes.
+    let data = rule_set_pda(&[v1_revision(REVISION_1), compile_v2(REVISION_1).unwrap()]);
+    assert!(diff_revisions(&data, 0, 1).unwrap().is_empty());
+}
+
+#[test]
+fn diff_is_machine_readable() {
+    let old = Policy::parse(REVISION_0).unwrap();
+    let new = Policy::parse(&REVISION_0.replace("Marketplace", "Market")).unwrap();
+    let diff = RevisionDiff::new(&old, &new);
+
+    assert_eq!(
+        serde_json::to_value(&diff).unwrap(),
+        serde_json::json!({
+            "changes": [{ "change": "renamed", "old": "Marketplace", "new": "Market" }]
+        })
+    );
+
+    let new =
+        Policy::parse(&REVISION_0.replace("Legacy => namespace", "Legacy => not(pass)")).unwrap();
+    let diff = RevisionDiff::new(&old, &new);
+
+    assert_eq!(
+        serde_json::to_value(&diff).unwrap(),
+        serde_json::json!({
+            "changes": [{
+                "change": "changed",
+                "path": { "scope": "operation", "name": "Legacy", "steps": [] },
+                "old": "namespace",
+                "new": "not(pass)",
+            }]
+        })
+    );
+}
+
+#[test]
+fn subtrees_are_compared() {
+    let old = Policy::parse(
+        r#"
+        Transfer => any(
+            threshold(1, pass, if_then_else(is_wallet(Source), pass, not(namespace))),
+            rule_ref(allowed),
+        )
+        "#,
+    )
+    .unwrap();
+    let new = Policy::parse(
+        r#"
+        Transfer => any(
+            threshold(1, pass, if_then_else(is_wallet(Source), pass, not(pass))),
+            is_wallet(Destination),
+        )
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        RevisionDiff::new(&old, &new).changes,
+        vec![
+            Change::Changed {
+                path: path(
+                    Scope::Operation,
+                    "Transfer",
+                    &[
+                        ("any", 0),
+                        ("threshold", 2),
+                        ("if_then_else", 2),
+                        ("not", 0)
+                    ]
+                ),
+                old: "namespace".to_string(),
+                new: "pass".to_string(),
+            },
+            Change::Removed {
+                path: path(Scope::Operation, "Transfer", &[("any", 1)]),
+                rule: "rule_ref(allowed)".to_string(),
+            },
+            Change::Added {
+                path: path(Scope::Operation, "Transfer", &[("any", 1)]),
+                rule: "is_wallet(Destination)".to_string(),
+            },
+        ]
+    );
+
+    // A different count replaces the whole threshold.
+    let new = Policy::parse(&old.to_string().replace("threshold(1", "threshold(2")).unwrap();
+    assert!(matches!(
+        RevisionDiff::new(&old, &new).changes.as_slice(),
+        [Change::Changed { path, .. }] if path.steps.len() == 1
+    ));
+}
@@ -0,0 +1,355 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
+    payload::Payload,
+    state::{
+        Pass, Rule, RuleSetHeader, RuleSetRevisionMapV1, RuleSetV1, RuleSetV2, CHUNK_SIZE,
+        RULE_SET_REV_MAP_VERSION, RULE_SET_SERIALIZED_HEADER_LEN,
+    },
+    types::LibVersion,
+    utils::get_revision_map_from_data,
+};
+use rmp_serde::Serializer;
+use serde::Serialize;
+use solana_program::{keccak, pubkey::Pubkey, rent::Rent};
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{account::Account, signature::Signer, signer::keypair::Keypair};
+use utils::{program_test, Operation};
+
+const RULE_SET_NAME: &str = "test rule_set";
+
+// Returns a `RuleSetV1` with a `Pass` rule for the operation and its MessagePack serialization.
+fn pass_rule_set_v1(owner: Pubkey) -> Vec<u8> {
+    let mut rule_set = RuleSetV1::new(RULE_SET_NAME.to_string(), owner);
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            Rule::Pass,
+        )
+        .unwrap();
+
+    let mut serialized_rule_set = Vec::new();
+    rule_set
+        .serialize(&mut Serializer::new(&mut serialized_rule_set))
+        .unwrap();
+    serialized_rule_set
+}
+
+// Returns a serialized `RuleSetV2` with a `Pass` rule for the operation.
+fn pass_rule_set_v2(owner: Pubkey) -> Vec<u8> {
+    RuleSetV2::serialize(
+        owner,
+        RULE_SET_NAME,
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&Pass::serialize().unwrap()],
+    )
+    .unwrap()
+}
+
+fn validate_ix(
+    rule_set_addr: Pubkey,
+    rule_set_revision: Option<usize>,
+    expected_hash: Option<[u8; 32]>,
+) -> solana_program::instruction::Instruction {
+    ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(Keypair::new().pubkey())
+        .additional_rule_accounts(vec![])
+        .build(ValidateArgs::V2 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision,
+            expected_hash,
+        })
+        .unwrap()
+        .instruction()
+}
+
+async fn rule_set_data(context: &mut ProgramTestContext, rule_set_addr: Pubkey) -> Vec<u8> {
+    context
+        .banks_client
+        .get_account(rule_set_addr)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+// Reads the revision map the way a program that only knows `RuleSetRevisionMapV1` does.
+fn v1_revision_map(data: &[u8]) -> (RuleSetRevisionMapV1, usize) {
+    let header = RuleSetHeader::try_from_slice(&data[..RULE_SET_SERIALIZED_HEADER_LEN]).unwrap();
+    let rev_map_version_loc = header.rev_map_version_location;
+
+    assert_eq!(data[rev_map_version_loc], RULE_SET_REV_MAP_VERSION);
+    let revision_map =
+        RuleSetRevisionMapV1::deserialize(&mut &data[rev_map_version_loc + 1..]).unwrap();
+
+    (revision_map, rev_map_version_loc)
+}
+
+#[tokio::test]
+async fn test_revision_hashes_are_recorded() {
+    let mut context = program_test().start_with_context().await;
+
+    // Put a V1 and a V2 revision on chain.
+    let rule_set_v1 = pass_rule_set_v1(context.payer.pubkey());
+    let rule_set_v2 = pass_rule_set_v2(context.payer.pubkey());
+
+    create_rule_set_on_chain_serialized!(
+        &mut context,
+        rule_set_v1.clone(),
+        RULE_SET_NAME.to_string()
+    )
+    .await;
+    let rule_set_addr = create_rule_set_on_chain_serialized!(
+        &mut context,
+        rule_set_v2.clone(),
+        RULE_SET_NAME.to_string()
+    )
+    .await;
+
+    // The revision map is a V2 map holding the hash of each serialized `RuleSet`.
+    let data = rule_set_data(&mut context, rule_set_addr).await;
+    let (revision_map, rev_map_version_loc) = get_revision_map_from_data(&data).unwrap();
+
+    assert_eq!(data[rev_map_version_loc], RULE_SET_REV_MAP_VERSION);
+    assert_eq!(
+        revision_map.rule_set_hashes,
+        vec![
+            keccak::hash(&rule_set_v1).to_bytes(),
+            keccak::hash(&rule_set_v2).to_bytes(),
+        ]
+    );
+
+    // The hashes are still read once `PuffRuleSet` has grown the PDA with zeros.
+    let mut puffed = data.clone();
+    puffed.resize(data.len() + CHUNK_SIZE, 0);
+    assert_eq!(get_revision_map_from_data(&puffed).unwrap().0, revision_map);
+}
+
+#[tokio::test]
+async fn test_validate_with_expected_hash() {
+    let mut context = program_test().start_with_context().await;
+
+    let rule_set_v1 = pass_rule_set_v1(context.payer.pubkey());
+    let rule_set_v2 = pass_rule_set_v2(context.payer.pubkey());
+
+    create_rule_set_on_chain_serialized!(
+        &mut context,
+        rule_set_v1.clone(),
+        RULE_SET_NAME.to_string()
+    )
+    .await;
+    let rule_set_addr = create_rule_set_on_chain_serialized!(
+        &mut context,
+        rule_set_v2.clone(),
+        RULE_SET_NAME.to_string()
+    )
+    .await;
+
+    let hash_v1 = keccak::hash(&rule_set_v1).to_bytes();
+    let hash_v2 = keccak::hash(&rule_set_v2).to_bytes();
+
+    // The latest revision matches its own hash.
+    let ix = validate_ix(rule_set_addr, None, Some(hash_v2));
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // A pinned revision matches its own hash.
+    let ix = validate_ix(rule_set_addr, Some(0), Some(hash_v1));
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // Without an expected hash any revision is used.
+    let ix = validate_ix(rule_set_addr, Some(0), None);
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // The hash of another revision fails.
+    let ix = validate_ix(rule_set_addr, None, Some(hash_v1));
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::RuleSetHashMismatch);
+}
+
+#[tokio::test]
+async fn test_legacy_revision_map_is_upgraded() {
+    let mut context = program_test().start_with_context().await;
+
+    // Lay out a `RuleSet` PDA written before hashes were recorded: a V1 revision followed by a
+    // `RuleSetRevisionMapV1`.
+    let rule_set_v1 = pass_rule_set_v1(context.payer.pubkey());
+    let rev_map_version_loc = RULE_SET_SERIALIZED_HEADER_LEN + 1 + rule_set_v1.len();
+
+    let mut data = Vec::new();
+    RuleSetHeader::new(rev_map_version_loc)
+        .serialize(&mut data)
+        .unwrap();
+    data.push(LibVersion::V1 as u8);
+    data.extend_from_slice(&rule_set_v1);
+    data.push(RULE_SET_REV_MAP_VERSION);
+    RuleSetRevisionMapV1 {
+        rule_set_revisions: vec![RULE_SET_SERIALIZED_HEADER_LEN],
+    }
+    .serialize(&mut data)
+    .unwrap();
+
+    let (rule_set_addr, _) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        RULE_SET_NAME.to_string(),
+    );
+    context.set_account(
+        &rule_set_addr,
+        &Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: mpl_token_auth_rules::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+
+    // The legacy revision can be validated but not pinned.
+    let ix = validate_ix(rule_set_addr, None, None);
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    let ix = validate_ix(rule_set_addr, None, Some([0; 32]));
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::RuleSetHashMismatch);
+
+    // A new revision upgrades the revision map.
+    let rule_set_v2 = pass_rule_set_v2(context.payer.pubkey());
+    create_rule_set_on_chain_serialized!(
+        &mut context,
+        rule_set_v2.clone(),
+        RULE_SET_NAME.to_string()
+    )
+    .await;
+
+    let data = rule_set_data(&mut context, rule_set_addr).await;
+    let (revision_map, rev_map_version_loc) = get_revision_map_from_data(&data).unwrap();
+
+    assert_eq!(data[rev_map_version_loc], RULE_SET_REV_MAP_VERSION);
+    assert_eq!(revision_map.rule_set_revisions.len(), 2);
+    assert_eq!(
+        revision_map.rule_set_hashes,
+        vec![[0; 32], keccak::hash(&rule_set_v2).to_bytes()]
+    );
+
+    let ix = validate_ix(
+        rule_set_addr,
+        Some(1),
+        Some(keccak::hash(&rule_set_v2).to_bytes()),
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+}
+
+#[tokio::test]
+async fn test_revision_map_is_read_by_v1_readers() {
+    let mut context = program_test().start_with_context().await;
+
+    let rule_set_v1 = pass_rule_set_v1(context.payer.pubkey());
+    let rule_set_v2 = pass_rule_set_v2(context.payer.pubkey());
+
+    create_rule_set_on_chain_serialized!(
+        &mut context,
+        rule_set_v1.clone(),
+        RULE_SET_NAME.to_string()
+    )
+    .await;
+    let rule_set_addr = create_rule_set_on_chain_serialized!(
+        &mut context,
+        rule_set_v2.clone(),
+        RULE_SET_NAME.to_string()
+    )
+    .await;
+
+    // A program reading only `RuleSetRevisionMapV1`s finds the same revisions.
+    let mut data = rule_set_data(&mut context, rule_set_addr).await;
+    let (revision_map, rev_map_version_loc) = get_revision_map_from_data(&data).unwrap();
+    let (v1_revision_map, v1_rev_map_version_loc) = v1_revision_map(&data);
+
+    assert_eq!(v1_rev_map_version_loc, rev_map_version_loc);
+    assert_eq!(
+        v1_revision_map.rule_set_revisions,
+        revision_map.rule_set_revisions
+    );
+    assert_eq!(
+        &data[v1_revision_map.rule_set_revisions[1]..rev_map_version_loc],
+        rule_set_v2.as_slice()
+    );
+
+    // Such a program writes its new revision over the revision map, without the hashes.
+    let mut rule_set_revisions = v1_revision_map.rule_set_revisions;
+    rule_set_revisions.push(rev_map_version_loc);
+
+    data.truncate(rev_map_version_loc);
+    data.push(LibVersion::V1 as u8);
+    data.extend_from_slice(&rule_set_v1);
+
+    let rev_map_version_loc = data.len();
+    data.push(RULE_SET_REV_MAP_VERSION);
+    RuleSetRevisionMapV1 { rule_set_revisions }
+        .serialize(&mut data)
+        .unwrap();
+    data[..RULE_SET_SERIALIZED_HEADER_LEN]
+        .copy_from_slice(&borsh::to_vec(&RuleSetHeader::new(rev_map_version_loc)).unwrap());
+
+    context.set_account(
+        &rule_set_addr,
+        &Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: mpl_token_auth_rules::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+
+    // The revisions written without hashes can be validated but not pinned.
+    let ix = validate_ix(rule_set_addr, Some(2), None);
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    let ix = validate_ix(
+        rule_set_addr,
+        Some(1),
+        Some(keccak::hash(&rule_set_v2).to_bytes()),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::RuleSetHashMismatch);
+
+    // A new revision records its hash again.
+    create_rule_set_on_chain_serialized!(
+        &mut context,
+        rule_set_v2.clone(),
+        RULE_SET_NAME.to_string()
+    )
+    .await;
+
+    let data = rule_set_data(&mut context, rule_set_addr).await;
+    let (revision_map, _) = get_revision_map_from_data(&data).unwrap();
+
+    assert_eq!(
+        revision_map.rule_set_hashes,
+        vec![
+            [0; 32],
+            [0; 32],
+            [0; 32],
+            keccak::hash(&rule_set_v2).to_bytes()
+        ]
+    );
+    assert_eq!(v1_revision_map(&data).0.rule_set_revisions.len(), 4);
+}
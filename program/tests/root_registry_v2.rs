@@ -0,0 +1,212 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{
+        builders::{CreateOrUpdateRootRegistryBuilder, ValidateBuilder},
+        CreateOrUpdateRootRegistryArgs, InstructionBuilder, ValidateArgs,
+    },
+    merkle::MerkleTree,
+    payload::{Payload, PayloadType, ProofInfo},
+    pda::find_root_registry_address,
+    state::{PubkeyTreeMatchRegistry, RuleSetV2},
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_program_test::{tokio, BanksClientError, ProgramTestContext};
+use solana_sdk::{signature::Signer, signer::keypair::Keypair, transaction::Transaction};
+use utils::{program_test, Operation, PayloadKey};
+
+// Create or update a root registry owned by `authority`.
+async fn set_registry_root(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    registry_addr: Pubkey,
+    root: [u8; 32],
+) -> Result<(), BanksClientError> {
+    let ix = CreateOrUpdateRootRegistryBuilder::new()
+        .payer(context.payer.pubkey())
+        .authority(authority.pubkey())
+        .root_registry_pda(registry_addr)
+        .build(CreateOrUpdateRootRegistryArgs::V1 {
+            registry_name: "allowlist".to_string(),
+            root,
+        })
+        .unwrap()
+        .instruction();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+// Create a `validate` instruction for a transfer to a destination.
+fn validate_ix(
+    rule_set_addr: Pubkey,
+    operation: &str,
+    destination: Pubkey,
+    proof: ProofInfo,
+    additional_rule_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let payload = Payload::from([
+        (
+            PayloadKey::Destination.to_string(),
+            PayloadType::Pubkey(destination),
+        ),
+        (
+            PayloadKey::DestinationProof.to_string(),
+            PayloadType::MerkleProof(proof),
+        ),
+    ]);
+
+    ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(Keypair::new().pubkey())
+        .additional_rule_accounts(additional_rule_accounts)
+        .build(ValidateArgs::V1 {
+            operation: operation.to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction()
+}
+
+#[tokio::test]
+async fn root_registry_v2() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create root registry
+    // --------------------------------
+    let authority = Keypair::new();
+    let (registry_addr, _) =
+        find_root_registry_address(authority.pubkey(), "allowlist".to_string());
+
+    let allowlist_a = (0..8).map(|_| Keypair::new().pubkey()).collect::<Vec<_>>();
+    let tree_a = MerkleTree::new(&allowlist_a);
+
+    set_registry_root(&mut context, &authority, registry_addr, tree_a.root())
+        .await
+        .unwrap();
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    let transfer = Operation::Transfer {
+        scenario: utils::TransferScenario::Holder,
+    }
+    .to_string();
+
+    // Create a Rule: The destination must be in the tree whose root is stored in the registry.
+    let rule = PubkeyTreeMatchRegistry::serialize(
+        PayloadKey::Destination.to_string(),
+        PayloadKey::DestinationProof.to_string(),
+        &registry_addr,
+    )
+    .unwrap();
+
+    // Create a RuleSet.
+    let rule_set = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[transfer.clone()],
+        &[&rule],
+    )
+    .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain_serialized!(&mut context, rule_set, "test rule_set".to_string())
+            .await;
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    let registry_account = vec![AccountMeta::new_readonly(registry_addr, false)];
+
+    // The destination is in the allowlist stored in the registry.
+    let proof_a = tree_a.proof_for(&allowlist_a[3]).unwrap();
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        allowlist_a[3],
+        proof_a.clone(),
+        registry_account.clone(),
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // The destination is not in the allowlist.
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        Keypair::new().pubkey(),
+        proof_a.clone(),
+        registry_account.clone(),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::PubkeyTreeMatchCheckFailed);
+
+    // The registry account is not provided.
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        allowlist_a[3],
+        proof_a.clone(),
+        vec![],
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::MissingAccount);
+
+    // --------------------------------
+    // Rotate the root
+    // --------------------------------
+    let allowlist_b = (0..8).map(|_| Keypair::new().pubkey()).collect::<Vec<_>>();
+    let tree_b = MerkleTree::new(&allowlist_b);
+
+    // Another signer cannot update the registry.
+    let other_authority = Keypair::new();
+    let err = set_registry_root(&mut context, &other_authority, registry_addr, tree_b.root())
+        .await
+        .unwrap_err();
+    assert_custom_error!(err, RuleSetError::DerivedKeyInvalid);
+
+    set_registry_root(&mut context, &authority, registry_addr, tree_b.root())
+        .await
+        .unwrap();
+
+    // The previous allowlist no longer passes.
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        allowlist_a[3],
+        proof_a,
+        registry_account.clone(),
+    );
+    let err = process_failing_validate_ix!(&mut context, ix, vec![], None).await;
+    assert_custom_error!(err, RuleSetError::PubkeyTreeMatchCheckFailed);
+
+    // The new allowlist passes without a new RuleSet revision.
+    let proof_b = tree_b.proof_for(&allowlist_b[5]).unwrap();
+    let ix = validate_ix(
+        rule_set_addr,
+        &transfer,
+        allowlist_b[5],
+        proof_b,
+        registry_account,
+    );
+    process_passing_validate_ix!(&mut context, ix, vec![], None).await;
+}
@@ -0,0 +1,294 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{
+        builders::{CreateOrUpdateBuilder, ValidateBuilder},
+        CreateOrUpdateArgs, InstructionBuilder, ValidateArgs,
+    },
+    payload::{Payload, PayloadType},
+    state::{AdditionalSigner, All, Amount, Any, Not, Operator, Pass, RuleRef, RuleSetV2},
+};
+use solana_program::instruction::AccountMeta;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair, transaction::Transaction};
+use utils::{program_test, DelegateScenario, Operation, PayloadKey, TokenDelegateRole};
+
+#[tokio::test]
+async fn test_rule_ref_v2() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create a shared rule definition.
+    let cosigner = Keypair::new();
+    let adtl_signer = AdditionalSigner::serialize(cosigner.pubkey()).unwrap();
+
+    // Create some rules referencing the definition.
+    let transfer_rule = RuleRef::serialize("cosigner".to_string()).unwrap();
+
+    let cosigner_ref = RuleRef::serialize("cosigner".to_string()).unwrap();
+    let amount_check = Amount::serialize(PayloadKey::Amount.to_string(), Operator::Lt, 5).unwrap();
+    let delegate_rule = All::serialize(&[&cosigner_ref, &amount_check]).unwrap();
+
+    // Create a RuleSet.
+    let rule_set = RuleSetV2::serialize_with_definitions(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            Operation::Delegate {
+                scenario: DelegateScenario::Token(TokenDelegateRole::Sale),
+            }
+            .to_string(),
+        ],
+        &[&transfer_rule, &delegate_rule],
+        &["cosigner".to_string()],
+        &[&adtl_signer],
+    )
+    .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain_serialized!(&mut context, rule_set, "test rule_set".to_string())
+            .await;
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // Create a Keypair to simulate a token mint address.
+    let mint = Keypair::new().pubkey();
+
+    // Create a `validate` instruction without the cosigner signing.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(cosigner.pubkey(), false)])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![], None).await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::AdditionalSignerCheckFailed);
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    // Create a `validate` instruction with the cosigner signing.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(cosigner.pubkey(), true)])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Transfer operation since the referenced Rule condition was true.
+    process_passing_validate_ix!(&mut context, validate_ix, vec![&cosigner], None).await;
+
+    // --------------------------------
+    // Validate fail (nested reference)
+    // --------------------------------
+    // Store a payload of data with an amount that is too large.
+    let payload = Payload::from([(PayloadKey::Amount.to_string(), PayloadType::Number(5))]);
+
+    // Create a `validate` instruction with the cosigner signing.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(cosigner.pubkey(), true)])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Delegate {
+                scenario: DelegateScenario::Token(TokenDelegateRole::Sale),
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Delegate operation.
+    let err = process_failing_validate_ix!(&mut context, validate_ix, vec![&cosigner], None).await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::AmountCheckFailed);
+
+    // --------------------------------
+    // Validate pass (nested reference)
+    // --------------------------------
+    // Store a payload of data with an amount that is allowed.
+    let payload = Payload::from([(PayloadKey::Amount.to_string(), PayloadType::Number(4))]);
+
+    // Create a `validate` instruction with the cosigner signing.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![AccountMeta::new_readonly(cosigner.pubkey(), true)])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Delegate {
+                scenario: DelegateScenario::Token(TokenDelegateRole::Sale),
+            }
+            .to_string(),
+            payload,
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Delegate operation since both Rule conditions were true.
+    process_passing_validate_ix!(&mut context, validate_ix, vec![&cosigner], None).await;
+}
+
+#[tokio::test]
+async fn create_rule_ref_cycle_v2_fails() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create definitions that reference each other.
+    let first = Not::serialize(&RuleRef::serialize("second".to_string()).unwrap()).unwrap();
+    let second = RuleRef::serialize("first".to_string()).unwrap();
+    let transfer_rule = RuleRef::serialize("first".to_string()).unwrap();
+
+    // Create a RuleSet.
+    let serialized_rule_set = RuleSetV2::serialize_with_definitions(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&transfer_rule],
+        &["first".to_string(), "second".to_string()],
+        &[&first, &second],
+    )
+    .unwrap();
+
+    // --------------------------------
+    // Fail on-chain creation
+    // --------------------------------
+    // Find RuleSet PDA.
+    let (rule_set_addr, _rule_set_bump) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        "test rule_set".to_string(),
+    );
+
+    // Create a `create` instruction.
+    let create_ix = CreateOrUpdateBuilder::new()
+        .payer(context.payer.pubkey())
+        .rule_set_pda(rule_set_addr)
+        .build(CreateOrUpdateArgs::V1 {
+            serialized_rule_set,
+        })
+        .unwrap()
+        .instruction();
+
+    // Add it to a transaction.
+    let create_tx = Transaction::new_signed_with_payer(
+        &[create_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    // Process the transaction.
+    let err = context
+        .banks_client
+        .process_transaction(create_tx)
+        .await
+        .expect_err("Creation should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::RuleDefinitionCycle);
+}
+
+#[tokio::test]
+async fn create_rule_ref_missing_definition_v2_fails() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create a rule referencing a definition that does not exist.
+    let pass = Pass::serialize().unwrap();
+    let cosigner_ref = RuleRef::serialize("cosigner".to_string()).unwrap();
+    let transfer_rule = Any::serialize(&[&pass, &cosigner_ref]).unwrap();
+
+    // Create a RuleSet.
+    let serialized_rule_set = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&transfer_rule],
+    )
+    .unwrap();
+
+    // --------------------------------
+    // Fail on-chain creation
+    // --------------------------------
+    // Find RuleSet PDA.
+    let (rule_set_addr, _rule_set_bump) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        "test rule_set".to_string(),
+    );
+
+    // Create a `create` instruction.
+    let create_ix = CreateOrUpdateBuilder::new()
+        .payer(context.payer.pubkey())
+        .rule_set_pda(rule_set_addr)
+        .build(CreateOrUpdateArgs::V1 {
+            serialized_rule_set,
+        })
+        .unwrap()
+        .instruction();
+
+    // Add it to a transaction.
+    let create_tx = Transaction::new_signed_with_payer(
+        &[create_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    // Process the transaction.
+    let err = context
+        .banks_client
+        .process_transaction(create_tx)
+        .await
+        .expect_err("Creation should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::RuleDefinitionNotFound);
+}
@@ -0,0 +1,239 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{
+        builders::{CreateOrUpdateBuilder, ValidateBuilder},
+        CreateOrUpdateArgs, InstructionBuilder, ValidateArgs,
+    },
+    payload::Payload,
+    state::{Rule, RuleSetV1},
+};
+use rmp_serde::Serializer;
+use serde::Serialize;
+use solana_program::instruction::AccountMeta;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair, transaction::Transaction};
+use utils::{program_test, Operation};
+
+#[tokio::test]
+async fn test_threshold() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create some rules.
+    let first_signer = Keypair::new();
+    let second_signer = Keypair::new();
+    let third_signer = Keypair::new();
+
+    let overall_rule = Rule::Threshold {
+        k: 2,
+        rules: vec![
+            Rule::AdditionalSigner {
+                account: first_signer.pubkey(),
+            },
+            Rule::AdditionalSigner {
+                account: second_signer.pubkey(),
+            },
+            Rule::AdditionalSigner {
+                account: third_signer.pubkey(),
+            },
+        ],
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            overall_rule,
+        )
+        .unwrap();
+
+    println!("{:#?}", rule_set);
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain!(&mut context, rule_set, "test rule_set".to_string()).await;
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // Create a Keypair to simulate a token mint address.
+    let mint = Keypair::new().pubkey();
+
+    // Create a `validate` instruction with only one of the three signers.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![
+            AccountMeta::new_readonly(first_signer.pubkey(), true),
+            AccountMeta::new_readonly(second_signer.pubkey(), false),
+            AccountMeta::new_readonly(third_signer.pubkey(), false),
+        ])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err =
+        process_failing_validate_ix!(&mut context, validate_ix, vec![&first_signer], None).await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::AdditionalSignerCheckFailed);
+
+    // --------------------------------
+    // Validate fail (failure over error)
+    // --------------------------------
+    // Create a `validate` instruction with one signer, one missing account and one non-signer.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![
+            AccountMeta::new_readonly(first_signer.pubkey(), true),
+            AccountMeta::new_readonly(third_signer.pubkey(), false),
+        ])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err =
+        process_failing_validate_ix!(&mut context, validate_ix, vec![&first_signer], None).await;
+
+    // Check that error is what we expect.  In this case we expect the failure to have
+    // precedence over the missing account error.
+    assert_custom_error!(err, RuleSetError::AdditionalSignerCheckFailed);
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    // Create a `validate` instruction with two of the three signers.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![
+            AccountMeta::new_readonly(first_signer.pubkey(), true),
+            AccountMeta::new_readonly(second_signer.pubkey(), false),
+            AccountMeta::new_readonly(third_signer.pubkey(), true),
+        ])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Transfer operation since two of the Rule conditions were true.
+    process_passing_validate_ix!(
+        &mut context,
+        validate_ix,
+        vec![&first_signer, &third_signer],
+        None
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn create_threshold_larger_than_rules_fails() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create a threshold that can never be met.
+    let overall_rule = Rule::Threshold {
+        k: 3,
+        rules: vec![
+            Rule::AdditionalSigner {
+                account: Keypair::new().pubkey(),
+            },
+            Rule::AdditionalSigner {
+                account: Keypair::new().pubkey(),
+            },
+        ],
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), context.payer.pubkey());
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            overall_rule,
+        )
+        .unwrap();
+
+    // Serialize the RuleSet using RMP serde.
+    let mut serialized_rule_set = Vec::new();
+    rule_set
+        .serialize(&mut Serializer::new(&mut serialized_rule_set))
+        .unwrap();
+
+    // --------------------------------
+    // Fail on-chain creation
+    // --------------------------------
+    // Find RuleSet PDA.
+    let (rule_set_addr, _rule_set_bump) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        "test rule_set".to_string(),
+    );
+
+    // Create a `create` instruction.
+    let create_ix = CreateOrUpdateBuilder::new()
+        .payer(context.payer.pubkey())
+        .rule_set_pda(rule_set_addr)
+        .build(CreateOrUpdateArgs::V1 {
+            serialized_rule_set,
+        })
+        .unwrap()
+        .instruction();
+
+    // Add it to a transaction.
+    let create_tx = Transaction::new_signed_with_payer(
+        &[create_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    // Process the transaction.
+    let err = context
+        .banks_client
+        .process_transaction(create_tx)
+        .await
+        .expect_err("Creation should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::InvalidThreshold);
+}
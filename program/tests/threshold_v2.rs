@@ -0,0 +1,214 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{
+        builders::{CreateOrUpdateBuilder, ValidateBuilder},
+        CreateOrUpdateArgs, InstructionBuilder, ValidateArgs,
+    },
+    payload::Payload,
+    state::{AdditionalSigner, RuleSetV2, Threshold},
+};
+use solana_program::instruction::AccountMeta;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Signer, signer::keypair::Keypair, transaction::Transaction};
+use utils::{program_test, Operation};
+
+#[tokio::test]
+async fn test_threshold_v2() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create some rules.
+    let first_signer = Keypair::new();
+    let second_signer = Keypair::new();
+    let third_signer = Keypair::new();
+
+    let first = AdditionalSigner::serialize(first_signer.pubkey()).unwrap();
+    let second = AdditionalSigner::serialize(second_signer.pubkey()).unwrap();
+    let third = AdditionalSigner::serialize(third_signer.pubkey()).unwrap();
+
+    let overall_rule = Threshold::serialize(2, &[&first, &second, &third]).unwrap();
+
+    // Create a RuleSet.
+    let rule_set = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&overall_rule],
+    )
+    .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain_serialized!(&mut context, rule_set, "test rule_set".to_string())
+            .await;
+
+    // --------------------------------
+    // Validate fail
+    // --------------------------------
+    // Create a Keypair to simulate a token mint address.
+    let mint = Keypair::new().pubkey();
+
+    // Create a `validate` instruction with only one of the three signers.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![
+            AccountMeta::new_readonly(first_signer.pubkey(), true),
+            AccountMeta::new_readonly(second_signer.pubkey(), false),
+            AccountMeta::new_readonly(third_signer.pubkey(), false),
+        ])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err =
+        process_failing_validate_ix!(&mut context, validate_ix, vec![&first_signer], None).await;
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::AdditionalSignerCheckFailed);
+
+    // --------------------------------
+    // Validate fail (failure over error)
+    // --------------------------------
+    // Create a `validate` instruction with one signer, one missing account and one non-signer.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![
+            AccountMeta::new_readonly(first_signer.pubkey(), true),
+            AccountMeta::new_readonly(third_signer.pubkey(), false),
+        ])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Fail to validate Transfer operation.
+    let err =
+        process_failing_validate_ix!(&mut context, validate_ix, vec![&first_signer], None).await;
+
+    // Check that error is what we expect.  In this case we expect the failure to have
+    // precedence over the missing account error.
+    assert_custom_error!(err, RuleSetError::AdditionalSignerCheckFailed);
+
+    // --------------------------------
+    // Validate pass
+    // --------------------------------
+    // Create a `validate` instruction with two of the three signers.
+    let validate_ix = ValidateBuilder::new()
+        .rule_set_pda(rule_set_addr)
+        .mint(mint)
+        .additional_rule_accounts(vec![
+            AccountMeta::new_readonly(first_signer.pubkey(), true),
+            AccountMeta::new_readonly(second_signer.pubkey(), false),
+            AccountMeta::new_readonly(third_signer.pubkey(), true),
+        ])
+        .build(ValidateArgs::V1 {
+            operation: Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            payload: Payload::default(),
+            update_rule_state: false,
+            rule_set_revision: None,
+        })
+        .unwrap()
+        .instruction();
+
+    // Validate Transfer operation since two of the Rule conditions were true.
+    process_passing_validate_ix!(
+        &mut context,
+        validate_ix,
+        vec![&first_signer, &third_signer],
+        None
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn create_threshold_larger_than_rules_v2_fails() {
+    let mut context = program_test().start_with_context().await;
+
+    // --------------------------------
+    // Create RuleSet
+    // --------------------------------
+    // Create a threshold that can never be met.
+    let first = AdditionalSigner::serialize(Keypair::new().pubkey()).unwrap();
+    let second = AdditionalSigner::serialize(Keypair::new().pubkey()).unwrap();
+
+    let overall_rule = Threshold::serialize(3, &[&first, &second]).unwrap();
+
+    // Create a RuleSet.
+    let serialized_rule_set = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        "test rule_set",
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&overall_rule],
+    )
+    .unwrap();
+
+    // --------------------------------
+    // Fail on-chain creation
+    // --------------------------------
+    // Find RuleSet PDA.
+    let (rule_set_addr, _rule_set_bump) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        "test rule_set".to_string(),
+    );
+
+    // Create a `create` instruction.
+    let create_ix = CreateOrUpdateBuilder::new()
+        .payer(context.payer.pubkey())
+        .rule_set_pda(rule_set_addr)
+        .build(CreateOrUpdateArgs::V1 {
+            serialized_rule_set,
+        })
+        .unwrap()
+        .instruction();
+
+    // Add it to a transaction.
+    let create_tx = Transaction::new_signed_with_payer(
+        &[create_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    // Process the transaction.
+    let err = context
+        .banks_client
+        .process_transaction(create_tx)
+        .await
+        .expect_err("Creation should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::InvalidThreshold);
+}
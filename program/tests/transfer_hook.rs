@@ -0,0 +1,408 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use std::collections::HashMap;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{
+        builders::CreateOrUpdateTransferHookBuilder, transfer_hook_execute,
+        CreateOrUpdateTransferHookArgs, InstructionBuilder,
+    },
+    pda::find_extra_account_metas_address,
+    state::{All, Amount, ExtraAccountMetasAccount, Operator, PubkeyMatch, RuleSetV2},
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_program_test::{tokio, BanksClientError, ProgramTestContext};
+use solana_sdk::{
+    signature::Signer, signer::keypair::Keypair, system_instruction, transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{transfer_hook, ExtensionType, StateWithExtensions},
+    instruction::AuthorityType,
+    offchain::resolve_extra_transfer_account_metas,
+    state::{Account, Mint},
+};
+use utils::{create_mint, get_account_data, program_test, PayloadKey};
+
+struct TransferHook {
+    mint: Pubkey,
+    authority: Keypair,
+    source: Pubkey,
+    destination: Pubkey,
+    owner: Keypair,
+    rule_set_addr: Pubkey,
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    // The payer signs once, even when it is also the authority.
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend(
+        signers
+            .iter()
+            .filter(|signer| signer.pubkey() != context.payer.pubkey()),
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+async fn create_or_update_transfer_hook(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    mint: Pubkey,
+    rule_set_addr: Pubkey,
+) -> Result<(), BanksClientError> {
+    let (extra_account_metas_pda, _bump) = find_extra_account_metas_address(mint);
+
+    let instruction = CreateOrUpdateTransferHookBuilder::new()
+        .payer(context.payer.pubkey())
+        .authority(authority.pubkey())
+        .mint(mint)
+        .extra_account_metas_pda(extra_account_metas_pda)
+        .rule_set_pda(rule_set_addr)
+        .build(CreateOrUpdateTransferHookArgs::V1 {
+            additional_rule_accounts: vec![],
+        })
+        .unwrap()
+        .instruction();
+
+    process(context, &[instruction], &[authority]).await
+}
+
+// Creates a Token-2022 mint using this program as its transfer hook, configured by `authority`,
+// with the payer as its mint authority.
+async fn create_transfer_hook_mint(
+    context: &mut ProgramTestContext,
+    mint: &Keypair,
+    authority: &Pubkey,
+) {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let space =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook]).unwrap();
+    let payer = context.payer.pubkey();
+
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        transfer_hook::instruction::initialize(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            Some(*authority),
+            Some(mpl_token_auth_rules::id()),
+        )
+        .unwrap(),
+        spl_token_2022::instruction::initialize_mint2(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            &payer,
+            None,
+            0,
+        )
+        .unwrap(),
+    ];
+
+    process(context, &instructions, &[mint]).await.unwrap();
+}
+
+// Creates a Token-2022 token account of the mint.
+async fn create_token_account(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let account = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let space =
+        ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferHookAccount])
+            .unwrap();
+
+    let instructions = [
+        system_instruction::create_account(
+            &context.payer.pubkey(),
+            &account.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        spl_token_2022::instruction::initialize_account3(
+            &spl_token_2022::id(),
+            &account.pubkey(),
+            mint,
+            owner,
+        )
+        .unwrap(),
+    ];
+
+    process(context, &instructions, &[&account]).await.unwrap();
+
+    account.pubkey()
+}
+
+// Creates a Token-2022 mint with token accounts for two wallets, mints ten tokens to the first
+// one, and creates a `RuleSet` allowing transfers of at most one token to the second wallet.
+async fn setup(context: &mut ProgramTestContext) -> TransferHook {
+    let mint = Keypair::new();
+    let authority = Keypair::new();
+    create_transfer_hook_mint(context, &mint, &authority.pubkey()).await;
+
+    let owner = Keypair::new();
+    let destination_owner = Keypair::new();
+    let source = create_token_account(context, &mint.pubkey(), &owner.pubkey()).await;
+    let destination =
+        create_token_account(context, &mint.pubkey(), &destination_owner.pubkey()).await;
+
+    let mint_to = spl_token_2022::instruction::mint_to(
+        &spl_token_2022::id(),
+        &mint.pubkey(),
+        &source,
+        &context.payer.pubkey(),
+        &[],
+        10,
+    )
+    .unwrap();
+    process(context, &[mint_to], &[]).await.unwrap();
+
+    // Create a RuleSet.
+    let amount_check =
+        Amount::serialize(PayloadKey::Amount.to_string(), Operator::LtEq, 1).unwrap();
+    let destination_check = PubkeyMatch::serialize(
+        PayloadKey::Destination.to_string(),
+        destination_owner.pubkey(),
+    )
+    .unwrap();
+    let transfer_rule = All::serialize(&[&amount_check, &destination_check]).unwrap();
+
+    let rule_set = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        "transfer hook",
+        &["Transfer".to_string()],
+        &[&transfer_rule],
+    )
+    .unwrap();
+
+    // Put the RuleSet on chain.
+    let rule_set_addr =
+        create_rule_set_on_chain_serialized!(context, rule_set, "transfer hook".to_string()).await;
+
+    TransferHook {
+        mint: mint.pubkey(),
+        authority,
+        source,
+        destination,
+        owner,
+        rule_set_addr,
+    }
+}
+
+// Transfers tokens with Token-2022, adding the extra accounts of the transfer hook the way
+// clients resolve them.
+async fn transfer(
+    context: &mut ProgramTestContext,
+    hook: &TransferHook,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let mut instruction = spl_token_2022::instruction::transfer_checked(
+        &spl_token_2022::id(),
+        &hook.source,
+        &hook.mint,
+        &hook.destination,
+        &hook.owner.pubkey(),
+        &[],
+        amount,
+        0,
+    )
+    .unwrap();
+
+    // The resolution reads the mint and its extra account metas.
+    let (extra_account_metas_pda, _bump) = find_extra_account_metas_address(hook.mint);
+    let mut accounts = HashMap::new();
+    for address in [hook.mint, extra_account_metas_pda] {
+        accounts.insert(address, get_account_data(context, address).await);
+    }
+
+    resolve_extra_transfer_account_metas(
+        &mut instruction,
+        |address| {
+            let data = accounts.get(&address).cloned();
+            async move { Ok(data) }
+        },
+        &hook.mint,
+    )
+    .await
+    .unwrap();
+
+    process(context, &[instruction], &[&hook.owner]).await
+}
+
+async fn token_balance(context: &mut ProgramTestContext, token_account: Pubkey) -> u64 {
+    let data = get_account_data(context, token_account).await;
+    StateWithExtensions::<Account>::unpack(&data)
+        .unwrap()
+        .base
+        .amount
+}
+
+// Stands for the `Execute` CPI made by Token-2022 during a transfer.
+fn execute(hook: &TransferHook, rule_set_addr: Pubkey, amount: u64) -> Instruction {
+    transfer_hook_execute(
+        hook.source,
+        hook.mint,
+        hook.destination,
+        hook.owner.pubkey(),
+        vec![AccountMeta::new_readonly(rule_set_addr, false)],
+        amount,
+    )
+}
+
+#[tokio::test]
+async fn transfer_hook_validates_transfers() {
+    let mut context = program_test().start_with_context().await;
+    let hook = setup(&mut context).await;
+
+    create_or_update_transfer_hook(&mut context, &hook.authority, hook.mint, hook.rule_set_addr)
+        .await
+        .unwrap();
+
+    // The extra account metas list the `RuleSet` PDA as a read-only account.
+    let (extra_account_metas_pda, _bump) = find_extra_account_metas_address(hook.mint);
+    let data = get_account_data(&mut context, extra_account_metas_pda).await;
+    assert_eq!(
+        data,
+        ExtraAccountMetasAccount::serialize(&[hook.rule_set_addr])
+    );
+
+    // A transfer of one token to the destination wallet passes.
+    transfer(&mut context, &hook, 1).await.unwrap();
+    assert_eq!(token_balance(&mut context, hook.source).await, 9);
+    assert_eq!(token_balance(&mut context, hook.destination).await, 1);
+
+    // A transfer of two tokens fails.
+    let err = transfer(&mut context, &hook, 2)
+        .await
+        .expect_err("Transfer should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::AmountCheckFailed);
+    assert_eq!(token_balance(&mut context, hook.destination).await, 1);
+}
+
+#[tokio::test]
+async fn transfer_hook_wrong_rule_set_fails() {
+    let mut context = program_test().start_with_context().await;
+    let hook = setup(&mut context).await;
+
+    create_or_update_transfer_hook(&mut context, &hook.authority, hook.mint, hook.rule_set_addr)
+        .await
+        .unwrap();
+
+    // Pass another account in place of the `RuleSet` configured for the mint.
+    let err = process(
+        &mut context,
+        &[execute(&hook, Pubkey::new_unique(), 1)],
+        &[],
+    )
+    .await
+    .expect_err("Execute should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::TransferHookAccountMismatch);
+}
+
+#[tokio::test]
+async fn transfer_hook_wrong_authority_fails() {
+    let mut context = program_test().start_with_context().await;
+    let hook = setup(&mut context).await;
+
+    // The owner of a token account is not the transfer hook authority.
+    let err =
+        create_or_update_transfer_hook(&mut context, &hook.owner, hook.mint, hook.rule_set_addr)
+            .await
+            .expect_err("CreateOrUpdateTransferHook should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::TransferHookAuthorityMismatch);
+
+    // Neither is the mint authority.
+    let payer = context.payer.insecure_clone();
+    let err = create_or_update_transfer_hook(&mut context, &payer, hook.mint, hook.rule_set_addr)
+        .await
+        .expect_err("CreateOrUpdateTransferHook should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::TransferHookAuthorityMismatch);
+}
+
+#[tokio::test]
+async fn transfer_hook_revoked_mint_authority() {
+    let mut context = program_test().start_with_context().await;
+    let hook = setup(&mut context).await;
+
+    // Revoke the mint authority, which leaves the transfer hook authority in place.
+    let revoke = spl_token_2022::instruction::set_authority(
+        &spl_token_2022::id(),
+        &hook.mint,
+        None,
+        AuthorityType::MintTokens,
+        &context.payer.pubkey(),
+        &[],
+    )
+    .unwrap();
+    process(&mut context, &[revoke], &[]).await.unwrap();
+
+    let data = get_account_data(&mut context, hook.mint).await;
+    assert!(StateWithExtensions::<Mint>::unpack(&data)
+        .unwrap()
+        .base
+        .mint_authority
+        .is_none());
+
+    // The transfer hook authority still configures the hook.
+    create_or_update_transfer_hook(&mut context, &hook.authority, hook.mint, hook.rule_set_addr)
+        .await
+        .unwrap();
+
+    transfer(&mut context, &hook, 1).await.unwrap();
+    assert_eq!(token_balance(&mut context, hook.destination).await, 1);
+}
+
+#[tokio::test]
+async fn transfer_hook_legacy_mint_fails() {
+    let mut context = program_test().start_with_context().await;
+    let hook = setup(&mut context).await;
+
+    // A mint of the legacy token program.
+    let mint = Keypair::new();
+    let payer = context.payer.pubkey();
+    create_mint(&mut context, &mint, &payer, None, 0)
+        .await
+        .unwrap();
+
+    let payer = context.payer.insecure_clone();
+    let err =
+        create_or_update_transfer_hook(&mut context, &payer, mint.pubkey(), hook.rule_set_addr)
+            .await
+            .expect_err("CreateOrUpdateTransferHook should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::IncorrectOwner);
+}
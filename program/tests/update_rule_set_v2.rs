@@ -6,7 +6,7 @@ use borsh::BorshDeserialize;
 use mpl_token_auth_rules::{
     state::{All, Amount, ProgramOwnedList, RuleSetV2},
     state::{
-        CompareOp, Rule, RuleSetHeader, RuleSetRevisionMapV1, RuleSetV1,
+        CompareOp, Rule, RuleSetHeader, RuleSetRevisionMapV2, RuleSetV1,
         RULE_SET_SERIALIZED_HEADER_LEN,
     },
     types::{LibVersion, RuleSet},
@@ -427,8 +427,8 @@ async fn create_rule_set_with_v1_and_v2() {
 
     let location = header.rev_map_version_location;
     // the revision map is stored at location + 1, since the first byte is the version
-    let revision_map = RuleSetRevisionMapV1::try_from_slice(&data[location + 1..])
-        .expect("Failed to deserialize RuleSetRevisionMapV1");
+    let revision_map = RuleSetRevisionMapV2::try_from_slice(&data[location + 1..])
+        .expect("Failed to deserialize RuleSetRevisionMapV2");
 
     let rule_set_v1 = rmp_serde::from_slice::<RuleSetV1>(
         &data[revision_map.rule_set_revisions[0] + 1..revision_map.rule_set_revisions[1]],
@@ -8,14 +8,14 @@ use mpl_token_auth_rules::{
     instruction::{builders::ValidateBuilder, InstructionBuilder, ValidateArgs},
     payload::{Payload, PayloadType},
     state::{
-        CompareOp, Rule, RuleSetHeader, RuleSetRevisionMapV1, RuleSetV1, RULE_SET_REV_MAP_VERSION,
+        CompareOp, Rule, RuleSetHeader, RuleSetRevisionMapV2, RuleSetV1, RULE_SET_REV_MAP_VERSION,
         RULE_SET_SERIALIZED_HEADER_LEN,
     },
     types::LibVersion,
 };
 use rmp_serde::Serializer;
 use serde::Serialize;
-use solana_program::instruction::AccountMeta;
+use solana_program::{instruction::AccountMeta, keccak};
 use solana_program_test::tokio;
 use solana_sdk::{signature::Signer, signer::keypair::Keypair};
 use utils::{cmp_slice, program_test, Operation, PayloadKey};
@@ -232,8 +232,9 @@ async fn test_update_ruleset_data_integrity() {
         .unwrap()
         .data;
 
-    // Check all the RuleSets, saving their start locations for later use.
+    // Check all the RuleSets, saving their start locations and hashes for later use.
     let mut offsets = vec![RULE_SET_SERIALIZED_HEADER_LEN];
+    let mut hashes = vec![];
     for n in 0..rule_sets.len() {
         // Offset n is the `RuleSet` lib version location.
         let rule_set_version_loc = offsets[n];
@@ -261,6 +262,9 @@ async fn test_update_ruleset_data_integrity() {
             n,
         );
 
+        // The hash of `RuleSet` n covers its serialized data.
+        hashes.push(keccak::hash(&serialized_rule_set).to_bytes());
+
         // The end of `RuleSet` n is the offset for the next item.
         offsets.push(rule_set_end)
     }
@@ -274,8 +278,11 @@ async fn test_update_ruleset_data_integrity() {
         "The buffer doesn't match the revision map version"
     );
 
-    // Create revision map using the known locations of the two `RuleSet`s in this test.
-    let mut revision_map = RuleSetRevisionMapV1::default();
+    // Create revision map using the known locations and hashes of the `RuleSet`s in this test.
+    let mut revision_map = RuleSetRevisionMapV2 {
+        rule_set_hashes: hashes,
+        ..Default::default()
+    };
 
     // Push the `RuleSet` locations.
     for loc in offsets.iter().take(rule_sets.len()) {
@@ -0,0 +1,271 @@
+#![cfg(feature = "client")]
+
+use mpl_token_auth_rules::{
+    instruction::{CreateOrUpdateArgs, RuleSetInstruction, WriteToBufferArgs},
+    state::{
+        BufferAccount, RuleSetHeader, RuleSetRevisionMapV2, BUFFER_BLOCK_LEN, BUFFER_HEADER_LEN,
+        CHUNK_SIZE, RULE_SET_REV_MAP_VERSION, RULE_SET_SERIALIZED_HEADER_LEN,
+    },
+    upload::{transaction_size, UploadPlanner, UploadStep, UploadStepKind, MAX_TRANSACTION_SIZE},
+};
+use solana_program::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE, instruction::Instruction, pubkey::Pubkey,
+};
+
+// The data of a `RuleSet` PDA holding one V1 revision of `len` bytes.
+fn rule_set_pda(len: usize) -> Vec<u8> {
+    let rev_map_location = RULE_SET_SERIALIZED_HEADER_LEN + 1 + len;
+
+    let mut data = vec![0; rev_map_location];
+    data[..RULE_SET_SERIALIZED_HEADER_LEN]
+        .copy_from_slice(&borsh::to_vec(&RuleSetHeader::new(rev_map_location)).unwrap());
+
+    data.push(RULE_SET_REV_MAP_VERSION);
+    data.extend(
+        borsh::to_vec(&RuleSetRevisionMapV2 {
+            rule_set_revisions: vec![RULE_SET_SERIALIZED_HEADER_LEN],
+            rule_set_hashes: vec![[1; 32]],
+        })
+        .unwrap(),
+    );
+
+    data
+}
+
+fn instructions(step: &UploadStep) -> Vec<RuleSetInstruction> {
+    step.instructions
+        .iter()
+        .filter(|ix| ix.program_id == mpl_token_auth_rules::ID)
+        .map(|ix| borsh::from_slice(&ix.data).unwrap())
+        .collect()
+}
+
+// Applies the `WriteToBuffer` instructions of the steps to the buffer, which is reset when it
+// is written for another `RuleSet`.
+fn write_to_buffer(buffer: &mut Vec<u8>, steps: &[UploadStep]) {
+    for step in steps {
+        for instruction in instructions(step) {
+            if let RuleSetInstruction::WriteToBuffer(WriteToBufferArgs::V2 {
+                offset,
+                data,
+                total_len,
+                hash,
+            }) = instruction
+            {
+                let header = BufferAccount::serialize_header(total_len, &hash.unwrap());
+                if !buffer.starts_with(&header[..BUFFER_HEADER_LEN]) {
+                    *buffer = header;
+                    buffer.resize(BufferAccount::account_len(total_len as usize), 0);
+                }
+
+                BufferAccount::write(buffer, offset as usize, &data).unwrap();
+            }
+        }
+    }
+}
+
+fn buffered_rule_set(buffer: &[u8]) -> Vec<u8> {
+    BufferAccount::from_bytes(buffer)
+        .unwrap()
+        .complete_rule_set()
+        .unwrap()
+        .to_vec()
+}
+
+// Stands for a compute budget instruction.
+fn compute_budget() -> Vec<Instruction> {
+    vec![Instruction::new_with_bytes(
+        Pubkey::new_from_array([3; 32]),
+        &[2, 128, 26, 6, 0],
+        vec![],
+    )]
+}
+
+#[test]
+fn small_rule_sets_are_passed_directly() {
+    let payer = Pubkey::new_unique();
+    let steps = UploadPlanner::new(payer, "Small", vec![1; 200], &[])
+        .unwrap()
+        .with_instructions(compute_budget())
+        .plan()
+        .unwrap();
+
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].kind, UploadStepKind::CreateOrUpdate);
+    assert_eq!(steps[0].instructions.len(), 2);
+    assert!(matches!(
+        instructions(&steps[0]).as_slice(),
+        [RuleSetInstruction::CreateOrUpdate(CreateOrUpdateArgs::V1 { serialized_rule_set })]
+            if serialized_rule_set.len() == 200
+    ));
+}
+
+#[test]
+fn new_rule_sets_are_not_puffed() {
+    let payer = Pubkey::new_unique();
+    let serialized = vec![1; 30_000];
+    let steps = UploadPlanner::new(payer, "New", serialized.clone(), &[])
+        .unwrap()
+        .plan()
+        .unwrap();
+
+    assert!(steps
+        .iter()
+        .all(|step| !matches!(step.kind, UploadStepKind::PuffRuleSet { .. })));
+    assert_eq!(
+        steps.last().unwrap().kind,
+        UploadStepKind::CreateOrUpdateFromBuffer
+    );
+
+    let mut buffer = vec![];
+    write_to_buffer(&mut buffer, &steps);
+    assert_eq!(buffered_rule_set(&buffer), serialized);
+}
+
+#[test]
+fn large_rule_sets_are_written_to_the_buffer() {
+    let payer = Pubkey::new_unique();
+    let serialized = (0..30_000).map(|i| i as u8).collect::<Vec<_>>();
+    let existing = rule_set_pda(1000);
+
+    let steps = UploadPlanner::new(payer, "Large", serialized.clone(), &existing)
+        .unwrap()
+        .with_instructions(compute_budget())
+        .plan()
+        .unwrap();
+
+    // Every step fits in a transaction, and the chunks follow each other and rebuild the
+    // `RuleSet` in the buffer.
+    let mut offset = 0;
+    for step in &steps {
+        assert!(transaction_size(&step.instructions, &payer) <= MAX_TRANSACTION_SIZE);
+
+        if let UploadStepKind::WriteToBuffer { offset: start, len } = step.kind {
+            assert_eq!(start, offset);
+            assert_eq!(start % BUFFER_BLOCK_LEN, 0);
+            assert!(matches!(
+                instructions(step).as_slice(),
+                [RuleSetInstruction::WriteToBuffer(WriteToBufferArgs::V2 { offset, data, total_len, .. })]
+                    if *offset as usize == start && data.len() == len && *total_len == 30_000
+            ));
+            offset += len;
+        }
+    }
+    assert_eq!(offset, serialized.len());
+
+    let mut buffer = vec![];
+    write_to_buffer(&mut buffer, &steps);
+    assert_eq!(buffered_rule_set(&buffer), serialized);
+
+    // The PDA is puffed enough for `CreateOrUpdate` to grow it by the rest, which is sent alone
+    // after the chunks are written.
+    let [.., puff, last] = steps.as_slice() else {
+        panic!("missing steps");
+    };
+    assert_eq!(puff.kind, UploadStepKind::PuffRuleSet { puffs: 2 });
+    assert!(2 * CHUNK_SIZE + MAX_PERMITTED_DATA_INCREASE >= serialized.len());
+    assert!(instructions(puff)
+        .iter()
+        .all(|ix| matches!(ix, RuleSetInstruction::PuffRuleSet(_))));
+
+    assert_eq!(last.kind, UploadStepKind::CreateOrUpdateFromBuffer);
+    assert_eq!(last.instructions[0], compute_budget()[0]);
+    assert!(matches!(
+        instructions(last).as_slice(),
+        [RuleSetInstruction::CreateOrUpdate(CreateOrUpdateArgs::V1 { serialized_rule_set })]
+            if serialized_rule_set.is_empty()
+    ));
+}
+
+#[test]
+fn puffs_are_split_across_transactions() {
+    let payer = Pubkey::new_unique();
+    let serialized = vec![1; 2_000_000];
+    let existing = rule_set_pda(1000);
+
+    let steps = UploadPlanner::new(payer, "Puffed", serialized.clone(), &existing)
+        .unwrap()
+        .with_instructions(compute_budget())
+        .plan()
+        .unwrap();
+
+    // The puffs follow the chunks, in several transactions that fit.
+    let first_puff = steps
+        .iter()
+        .position(|step| matches!(step.kind, UploadStepKind::PuffRuleSet { .. }))
+        .unwrap();
+    let (puffs, last) = steps[first_puff..].split_at(steps.len() - first_puff - 1);
+
+    assert!(steps[..first_puff]
+        .iter()
+        .all(|step| matches!(step.kind, UploadStepKind::WriteToBuffer { .. })));
+    assert!(puffs.len() > 1);
+
+    let mut total = 0;
+    for step in puffs {
+        assert!(transaction_size(&step.instructions, &payer) <= MAX_TRANSACTION_SIZE);
+
+        let UploadStepKind::PuffRuleSet { puffs } = step.kind else {
+            panic!("expected a PuffRuleSet step, found {:?}", step.kind);
+        };
+        assert_eq!(instructions(step).len(), puffs);
+        assert!(instructions(step)
+            .iter()
+            .all(|ix| matches!(ix, RuleSetInstruction::PuffRuleSet(_))));
+        total += puffs;
+    }
+
+    // Enough for `CreateOrUpdate` to grow the PDA by the rest, but not more.
+    assert!(total * CHUNK_SIZE + MAX_PERMITTED_DATA_INCREASE >= serialized.len());
+    assert!((total - 1) * CHUNK_SIZE + MAX_PERMITTED_DATA_INCREASE < serialized.len());
+
+    // `CreateOrUpdate` is sent alone, with the additional instructions.
+    assert_eq!(last[0].kind, UploadStepKind::CreateOrUpdateFromBuffer);
+    assert_eq!(last[0].instructions.len(), 2);
+    assert!(matches!(
+        instructions(&last[0]).as_slice(),
+        [RuleSetInstruction::CreateOrUpdate(
+            CreateOrUpdateArgs::V1 { .. }
+        )]
+    ));
+}
+
+#[test]
+fn uploads_resume_from_the_buffer() {
+    let payer = Pubkey::new_unique();
+    let serialized = (0..10_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let planner = UploadPlanner::new(payer, "Resumed", serialized.clone(), &[]).unwrap();
+    let steps = planner.plan().unwrap();
+
+    // The upload failed after the first three chunks were written.
+    let mut buffer = vec![];
+    write_to_buffer(&mut buffer, &steps[..3]);
+
+    let resumed = planner.resume(&buffer).unwrap();
+    assert_eq!(resumed, steps[3..]);
+
+    // Chunks written out of order are skipped as well.
+    write_to_buffer(&mut buffer, &steps[4..5]);
+
+    let resumed = planner.resume(&buffer).unwrap();
+    assert_eq!(resumed[0], steps[3]);
+    assert_eq!(resumed[1..], steps[5..]);
+
+    write_to_buffer(&mut buffer, &resumed);
+    assert_eq!(buffered_rule_set(&buffer), serialized);
+
+    // Only the `CreateOrUpdate` is left once every chunk is written.
+    let resumed = planner.resume(&buffer).unwrap();
+    assert_eq!(resumed.len(), 1);
+    assert_eq!(resumed[0].kind, UploadStepKind::CreateOrUpdateFromBuffer);
+
+    // A buffer written for another `RuleSet`, or by `WriteToBuffer` V1, is written again.
+    let other = UploadPlanner::new(payer, "Resumed", vec![7; 10_000], &[]).unwrap();
+    let mut buffer = vec![];
+    write_to_buffer(&mut buffer, &other.plan().unwrap());
+
+    for buffer in [buffer, vec![7; 500]] {
+        let resumed = planner.resume(&buffer).unwrap();
+        assert_eq!(resumed, steps);
+    }
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    state::{Pass, PubkeyListMatch, RuleSetV2},
+    upload::{UploadPlanner, UploadStepKind},
+    utils::{get_revision_map_from_data, get_rule_set_revision_location_from_data},
+};
+use solana_program::{keccak, pubkey::Pubkey};
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+use utils::{cmp_slice, get_account_data, program_test, Operation, PayloadKey};
+
+const RULE_SET_NAME: &str = "test rule_set";
+
+// Returns a serialized `RuleSetV2` listing `len` pubkeys for the operation.
+fn pubkey_list_rule_set(owner: Pubkey, len: usize) -> Vec<u8> {
+    let pubkeys = (0..len).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+    let rule = PubkeyListMatch::serialize(PayloadKey::Authority.to_string(), &pubkeys).unwrap();
+
+    RuleSetV2::serialize(
+        owner,
+        RULE_SET_NAME,
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&rule],
+    )
+    .unwrap()
+}
+
+// Asserts that the latest revision of the `RuleSet` PDA is the serialized `RuleSet`.
+fn assert_latest_revision(data: &[u8], serialized_rule_set: &[u8]) {
+    let (start, end) = get_rule_set_revision_location_from_data(data, None).unwrap();
+    assert!(
+        cmp_slice(&data[start..end], serialized_rule_set),
+        "The revision doesn't match the serialized rule set.",
+    );
+
+    let (revision_map, _) = get_revision_map_from_data(data).unwrap();
+    assert_eq!(
+        revision_map.rule_set_hashes.last(),
+        Some(&keccak::hash(serialized_rule_set).to_bytes())
+    );
+}
+
+#[tokio::test]
+async fn upload_new_rule_set() {
+    let mut context = program_test().start_with_context().await;
+
+    // The `RuleSet` does not fit in a transaction and is written to the buffer.
+    let serialized_rule_set = pubkey_list_rule_set(context.payer.pubkey(), 1_000);
+
+    let rule_set_addr = upload_rule_set_on_chain!(
+        &mut context,
+        serialized_rule_set.clone(),
+        RULE_SET_NAME.to_string(),
+        None
+    )
+    .await;
+
+    let data = get_account_data(&mut context, rule_set_addr).await;
+    assert_latest_revision(&data, &serialized_rule_set);
+}
+
+#[tokio::test]
+async fn upload_puffs_existing_rule_set() {
+    let mut context = program_test().start_with_context().await;
+
+    let pass = RuleSetV2::serialize(
+        context.payer.pubkey(),
+        RULE_SET_NAME,
+        &[Operation::Transfer {
+            scenario: utils::TransferScenario::Holder,
+        }
+        .to_string()],
+        &[&Pass::serialize().unwrap()],
+    )
+    .unwrap();
+
+    let rule_set_addr =
+        create_rule_set_on_chain_serialized!(&mut context, pass, RULE_SET_NAME.to_string()).await;
+
+    // The new revision grows the PDA by more than `CreateOrUpdate` can, so it is puffed in its
+    // own transaction before the `RuleSet` is stored.
+    let serialized_rule_set = pubkey_list_rule_set(context.payer.pubkey(), 2_000);
+
+    let existing_data = get_account_data(&mut context, rule_set_addr).await;
+    let steps = UploadPlanner::new(
+        context.payer.pubkey(),
+        RULE_SET_NAME,
+        serialized_rule_set.clone(),
+        &existing_data,
+    )
+    .unwrap()
+    .plan()
+    .unwrap();
+
+    assert!(matches!(
+        steps.as_slice(),
+        [.., puff, last]
+            if matches!(puff.kind, UploadStepKind::PuffRuleSet { puffs } if puffs > 0)
+                && last.kind == UploadStepKind::CreateOrUpdateFromBuffer
+                && last.instructions.len() == 1
+    ));
+
+    upload_rule_set_on_chain!(
+        &mut context,
+        serialized_rule_set.clone(),
+        RULE_SET_NAME.to_string(),
+        None
+    )
+    .await;
+
+    let data = get_account_data(&mut context, rule_set_addr).await;
+    assert_latest_revision(&data, &serialized_rule_set);
+
+    let (revision_map, _) = get_revision_map_from_data(&data).unwrap();
+    assert_eq!(revision_map.rule_set_revisions.len(), 2);
+}
@@ -4,12 +4,13 @@ use mpl_token_auth_rules::{
         CreateOrUpdateArgs, InstructionBuilder, PuffRuleSetArgs, WriteToBufferArgs,
     },
     payload::ProofInfo,
-    state::RuleSetV1,
+    state::{BufferAccount, RuleSetV1},
+    upload::{UploadPlanner, UploadStepKind, MAX_TRANSACTION_SIZE},
 };
 use rmp_serde::Serializer;
 use serde::Serialize;
 use solana_program::{instruction::Instruction, pubkey::Pubkey};
-use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
 use solana_sdk::{
     compute_budget::ComputeBudgetInstruction, program_pack::Pack, signature::Signer,
     signer::keypair::Keypair, system_instruction, transaction::Transaction,
@@ -195,7 +196,14 @@ impl ToString for PayloadKey {
 }
 
 pub fn program_test() -> ProgramTest {
-    ProgramTest::new("mpl_token_auth_rules", mpl_token_auth_rules::id(), None)
+    let mut program_test =
+        ProgramTest::new("mpl_token_auth_rules", mpl_token_auth_rules::id(), None);
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(spl_token_2022::processor::Processor::process),
+    );
+    program_test
 }
 
 #[macro_export]
@@ -479,6 +487,106 @@ pub async fn create_big_rule_set_on_chain_with_loc(
     rule_set_addr
 }
 
+#[macro_export]
+macro_rules! upload_rule_set_on_chain {
+    ($context:expr, $rule_set:expr, $rule_set_name:expr, $compute_budget:expr) => {
+        $crate::utils::upload_rule_set_on_chain_with_loc(
+            $context,
+            $rule_set,
+            $rule_set_name,
+            $compute_budget,
+            file!(),
+            line!(),
+            column!(),
+        )
+    };
+}
+
+pub async fn upload_rule_set_on_chain_with_loc(
+    context: &mut ProgramTestContext,
+    serialized_rule_set: Vec<u8>,
+    rule_set_name: String,
+    compute_budget: Option<u32>,
+    file: &str,
+    line: u32,
+    column: u32,
+) -> Pubkey {
+    // Find RuleSet PDA.
+    let (rule_set_addr, _rule_set_bump) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        rule_set_name.clone(),
+    );
+
+    // Plan the upload from the current data of the RuleSet PDA.
+    let existing_data = get_account_data(context, rule_set_addr).await;
+    let planner = UploadPlanner::new(
+        context.payer.pubkey(),
+        &rule_set_name,
+        serialized_rule_set.clone(),
+        &existing_data,
+    )
+    .unwrap()
+    // Use user-provided compute budget if one was provided.
+    .with_instructions(
+        compute_budget
+            .map(|units| vec![ComputeBudgetInstruction::set_compute_unit_limit(units)])
+            .unwrap_or_default(),
+    );
+
+    for step in planner.plan().unwrap() {
+        if matches!(step.kind, UploadStepKind::CreateOrUpdateFromBuffer) {
+            let data = get_account_data(context, planner.buffer_pda()).await;
+
+            let buffer = BufferAccount::from_bytes(&data).unwrap();
+
+            assert!(
+                buffer
+                    .complete_rule_set()
+                    .is_ok_and(|rule_set| cmp_slice(rule_set, &serialized_rule_set)),
+                "The buffer doesn't match the serialized rule set.",
+            );
+        }
+
+        // Add it to a transaction.
+        let tx = Transaction::new_signed_with_payer(
+            &step.instructions,
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+
+        assert!(
+            planner.transaction_size(&step.instructions) <= MAX_TRANSACTION_SIZE,
+            "Transaction exceeds packet limit of 1232"
+        );
+
+        // Process the transaction.
+        context
+            .banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Creation error {:?}, upload_rule_set_on_chain called at {}:{}:{}",
+                    err, file, line, column
+                )
+            });
+    }
+
+    rule_set_addr
+}
+
+/// Returns the data of an account, empty if it does not exist.
+pub async fn get_account_data(context: &mut ProgramTestContext, address: Pubkey) -> Vec<u8> {
+    context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .map(|account| account.data)
+        .unwrap_or_default()
+}
+
 #[macro_export]
 macro_rules! process_passing_validate_ix {
     ($context:expr, $validate_ix:expr, $additional_signers:expr, $compute_budget:expr) => {
@@ -0,0 +1,207 @@
+#![cfg(feature = "test-bpf")]
+
+pub mod utils;
+
+use mpl_token_auth_rules::{
+    error::RuleSetError,
+    instruction::{
+        builders::{CreateOrUpdateBuilder, WriteToBufferBuilder},
+        CreateOrUpdateArgs, InstructionBuilder, WriteToBufferArgs,
+    },
+    state::{BufferAccount, Rule, RuleSetV1, BUFFER_BLOCK_LEN},
+    utils::get_revision_map_from_data,
+};
+use rmp_serde::Serializer;
+use serde::Serialize;
+use solana_program::{keccak, pubkey::Pubkey};
+use solana_program_test::{tokio, BanksClientError, ProgramTestContext};
+use solana_sdk::{signature::Signer, transaction::Transaction};
+use utils::{get_account_data, program_test, Operation};
+
+const CHUNK_LEN: usize = 25 * BUFFER_BLOCK_LEN;
+
+fn big_rule_set(owner: Pubkey) -> Vec<u8> {
+    // Create a rule with enough `Pubkey`s to need several chunks.
+    let pubkey_list_match = Rule::PubkeyListMatch {
+        pubkeys: (0..100).map(|_| Pubkey::new_unique()).collect(),
+        field: "Destination".to_string(),
+    };
+
+    // Create a RuleSet.
+    let mut rule_set = RuleSetV1::new("test rule_set".to_string(), owner);
+    rule_set
+        .add(
+            Operation::Transfer {
+                scenario: utils::TransferScenario::Holder,
+            }
+            .to_string(),
+            pubkey_list_match,
+        )
+        .unwrap();
+
+    // Serialize the RuleSet using RMP serde.
+    let mut serialized_rule_set = Vec::new();
+    rule_set
+        .serialize(&mut Serializer::new(&mut serialized_rule_set))
+        .unwrap();
+
+    serialized_rule_set
+}
+
+async fn write_chunk(
+    context: &mut ProgramTestContext,
+    serialized_rule_set: &[u8],
+    offset: usize,
+    len: usize,
+) -> Result<(), BanksClientError> {
+    let (buffer_pda, _buffer_bump) =
+        mpl_token_auth_rules::pda::find_buffer_address(context.payer.pubkey());
+
+    let end = serialized_rule_set.len().min(offset + len);
+
+    // Create a `write_to_buffer` instruction.
+    let write_to_buffer_ix = WriteToBufferBuilder::new()
+        .payer(context.payer.pubkey())
+        .buffer_pda(buffer_pda)
+        .build(WriteToBufferArgs::V2 {
+            offset: offset as u32,
+            data: serialized_rule_set[offset..end].to_vec(),
+            total_len: serialized_rule_set.len() as u32,
+            hash: Some(keccak::hash(serialized_rule_set).to_bytes()),
+        })
+        .unwrap()
+        .instruction();
+
+    // Add it to a transaction.
+    let write_to_buffer_tx = Transaction::new_signed_with_payer(
+        &[write_to_buffer_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    // Process the transaction.
+    context
+        .banks_client
+        .process_transaction(write_to_buffer_tx)
+        .await
+}
+
+async fn create_from_buffer(context: &mut ProgramTestContext) -> Result<Pubkey, BanksClientError> {
+    let (rule_set_addr, _rule_set_bump) = mpl_token_auth_rules::pda::find_rule_set_address(
+        context.payer.pubkey(),
+        "test rule_set".to_string(),
+    );
+
+    let (buffer_pda, _buffer_bump) =
+        mpl_token_auth_rules::pda::find_buffer_address(context.payer.pubkey());
+
+    // Create a `create` instruction.
+    let create_ix = CreateOrUpdateBuilder::new()
+        .payer(context.payer.pubkey())
+        .rule_set_pda(rule_set_addr)
+        .buffer_pda(buffer_pda)
+        .build(CreateOrUpdateArgs::V1 {
+            serialized_rule_set: vec![],
+        })
+        .unwrap()
+        .instruction();
+
+    // Add it to a transaction.
+    let create_tx = Transaction::new_signed_with_payer(
+        &[create_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    // Process the transaction.
+    context
+        .banks_client
+        .process_transaction(create_tx)
+        .await
+        .map(|_| rule_set_addr)
+}
+
+#[tokio::test]
+async fn write_to_buffer_out_of_order() {
+    let mut context = program_test().start_with_context().await;
+    let serialized_rule_set = big_rule_set(context.payer.pubkey());
+    assert!(serialized_rule_set.len() > 3 * CHUNK_LEN);
+
+    // Write the chunks in reverse order.
+    let offsets = (0..serialized_rule_set.len())
+        .step_by(CHUNK_LEN)
+        .collect::<Vec<_>>();
+
+    for offset in offsets.into_iter().rev() {
+        write_chunk(&mut context, &serialized_rule_set, offset, CHUNK_LEN)
+            .await
+            .unwrap();
+    }
+
+    // The buffer holds the complete `RuleSet`.
+    let (buffer_pda, _buffer_bump) =
+        mpl_token_auth_rules::pda::find_buffer_address(context.payer.pubkey());
+    let data = get_account_data(&mut context, buffer_pda).await;
+    assert_eq!(
+        BufferAccount::from_bytes(&data)
+            .unwrap()
+            .complete_rule_set()
+            .unwrap(),
+        serialized_rule_set.as_slice()
+    );
+
+    // Store the `RuleSet` from the buffer and check the hash of the revision.
+    let rule_set_addr = create_from_buffer(&mut context).await.unwrap();
+    let data = get_account_data(&mut context, rule_set_addr).await;
+    let (revision_map, _) = get_revision_map_from_data(&data).unwrap();
+
+    assert_eq!(
+        revision_map.rule_set_hashes,
+        vec![keccak::hash(&serialized_rule_set).to_bytes()]
+    );
+}
+
+#[tokio::test]
+async fn create_from_buffer_missing_chunk_fails() {
+    let mut context = program_test().start_with_context().await;
+    let serialized_rule_set = big_rule_set(context.payer.pubkey());
+
+    // Write every chunk but the second one.
+    let offsets = (0..serialized_rule_set.len())
+        .step_by(CHUNK_LEN)
+        .filter(|offset| *offset != CHUNK_LEN)
+        .collect::<Vec<_>>();
+
+    for offset in offsets {
+        write_chunk(&mut context, &serialized_rule_set, offset, CHUNK_LEN)
+            .await
+            .unwrap();
+    }
+
+    let err = create_from_buffer(&mut context)
+        .await
+        .expect_err("Create should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::BufferIncomplete);
+}
+
+#[tokio::test]
+async fn write_to_buffer_unaligned_fails() {
+    let mut context = program_test().start_with_context().await;
+    let serialized_rule_set = big_rule_set(context.payer.pubkey());
+
+    let err = write_chunk(
+        &mut context,
+        &serialized_rule_set,
+        BUFFER_BLOCK_LEN / 2,
+        CHUNK_LEN,
+    )
+    .await
+    .expect_err("Write buffer should fail");
+
+    // Check that error is what we expect.
+    assert_custom_error!(err, RuleSetError::InvalidBufferWrite);
+}